@@ -121,9 +121,12 @@ impl Plugin for DummyManifestPlugin {
         use crate::{
             crafting::recipe::RecipeManifest,
             items::item_manifest::ItemManifest,
+            organisms::disease::disease_manifest::DiseaseManifest,
             structures::structure_manifest::{StructureData, StructureManifest},
+            technology::technology_manifest::TechnologyManifest,
             terrain::terrain_manifest::{TerrainData, TerrainManifest},
             units::basic_needs::Diet,
+            units::needs::need_manifest::NeedManifest,
             units::unit_manifest::{UnitData, UnitManifest},
         };
 
@@ -153,5 +156,14 @@ impl Plugin for DummyManifestPlugin {
 
         let recipe_manifest = RecipeManifest::default();
         app.insert_resource(recipe_manifest);
+
+        let technology_manifest = TechnologyManifest::default();
+        app.insert_resource(technology_manifest);
+
+        let disease_manifest = DiseaseManifest::default();
+        app.insert_resource(disease_manifest);
+
+        let need_manifest = NeedManifest::default();
+        app.insert_resource(need_manifest);
     }
 }
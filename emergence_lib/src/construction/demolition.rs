@@ -48,7 +48,7 @@ impl<'w, 's> DemolitionQuery<'w, 's> {
 }
 
 /// Keeps marked tiles clear by sending removal signals from structures that are marked for removal
-pub(super) fn set_emitter_for_structures_to_be_demolished(
+pub(crate) fn set_emitter_for_structures_to_be_demolished(
     mut structure_query: Query<(&mut Emitter, &Id<Structure>), With<MarkedForDemolition>>,
 ) {
     for (mut doomed_emitter, &structure_id) in structure_query.iter_mut() {
@@ -532,6 +532,16 @@ pub(super) fn ghost_structure_lifecycle(
                         },
                         StartingEnergy::Full,
                     );
+                // Upgrades replace the existing structure, rather than being built from scratch
+                } else if structure_manifest.upgrades_from(structure_id).is_some() {
+                    commands.upgrade_structure(
+                        center,
+                        ClipboardData {
+                            structure_id,
+                            facing,
+                            active_recipe: active_recipe.clone(),
+                        },
+                    );
                 } else {
                     commands.spawn_structure(
                         center,
@@ -0,0 +1,587 @@
+//! Undo and redo for planning actions: zoning ghosts, terraforming and demolition.
+//!
+//! Each planning action is recorded as a [`PlanningOperation`],
+//! which stores the state of every tile it touched both before and after the action.
+//! The later state is read back from the world once the action's commands have been applied,
+//! so actions that were rejected (such as zoning onto an occupied tile) are never recorded.
+//! Undoing an operation restores the earlier state, while redoing it reapplies the later one.
+//!
+//! Holding down a button while dragging across the map counts as a single operation.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::{query::Has, system::SystemParam},
+    prelude::*,
+};
+use hexx::Hex;
+use indexmap::IndexMap;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    geometry::{MapGeometry, VoxelPos},
+    player_interaction::{
+        clipboard::{ClipboardData, ClipboardQuery},
+        InteractionSystem, PlayerAction, PlayerModifiesWorld,
+    },
+    signals::{Emitter, SignalType},
+    structures::{commands::StructureCommandsExt, structure_manifest::StructureManifest},
+};
+
+use super::{
+    demolition::MarkedForDemolition,
+    ghosts::Ghost,
+    terraform::{TerraformingAction, TerraformingCommandsExt},
+};
+
+/// Records planning actions so that they can be undone and redone.
+pub(super) struct PlanningHistoryPlugin;
+
+impl Plugin for PlanningHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlanningHistory>().add_systems(
+            Update,
+            (
+                undo_and_redo
+                    .in_set(PlayerModifiesWorld)
+                    .before(InteractionSystem::ApplyZoning),
+                finish_planning_operation.after(InteractionSystem::ApplyZoning),
+            ),
+        );
+    }
+}
+
+/// Something on the map that can be changed by planning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PlanningTarget {
+    /// The ghost structure centered on this voxel.
+    Ghost(VoxelPos),
+    /// The terraforming action planned for this tile.
+    Terraform(Hex),
+    /// Whether the structure centered on this voxel is marked for demolition.
+    Demolition(VoxelPos),
+}
+
+/// The planned state of a [`PlanningTarget`].
+#[derive(Debug, Clone, PartialEq)]
+enum PlanningState {
+    /// The ghost structure that is zoned here, if any.
+    Ghost(Option<ClipboardData>),
+    /// The terraforming action planned for this tile.
+    Terraform(TerraformingAction),
+    /// Whether or not the structure is marked for demolition.
+    Demolition(bool),
+}
+
+/// A single reversible planning action, which may have changed any number of tiles.
+#[derive(Debug, Clone, PartialEq)]
+struct PlanningOperation {
+    /// The state of each target before and after this operation, in the order they were first changed.
+    changes: IndexMap<PlanningTarget, (PlanningState, PlanningState)>,
+}
+
+impl PlanningOperation {
+    /// The state that each target should be returned to when undoing, in the order it should be applied.
+    fn undo_states(&self) -> impl Iterator<Item = (PlanningTarget, &PlanningState)> + '_ {
+        self.changes
+            .iter()
+            .rev()
+            .map(|(&target, (before, _))| (target, before))
+    }
+
+    /// The state that each target should be set to when redoing, in the order it should be applied.
+    fn redo_states(&self) -> impl Iterator<Item = (PlanningTarget, &PlanningState)> + '_ {
+        self.changes
+            .iter()
+            .map(|(&target, (_, after))| (target, after))
+    }
+}
+
+/// The planning actions that can be undone or redone.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PlanningHistory {
+    /// Operations that can be undone, oldest first.
+    undo_stack: VecDeque<PlanningOperation>,
+    /// Operations that have been undone and can be redone, most recently undone last.
+    redo_stack: Vec<PlanningOperation>,
+    /// The original state of each target touched by the operation that is currently being performed.
+    in_progress: IndexMap<PlanningTarget, PlanningState>,
+}
+
+impl PlanningHistory {
+    /// The maximum number of operations that can be undone.
+    const MAX_OPERATIONS: usize = 100;
+
+    /// Records that the operation in progress is about to change `target`, which is currently in the `before` state.
+    ///
+    /// If the target was already touched by this operation, its original state is kept.
+    fn record(&mut self, target: PlanningTarget, before: PlanningState) {
+        self.in_progress.entry(target).or_insert(before);
+    }
+
+    /// Completes the operation in progress, allowing it to be undone.
+    ///
+    /// The `current_state` of each target touched is compared to its original state,
+    /// and only targets that actually changed are kept.
+    /// Making a new change discards anything that could have been redone.
+    fn finish_operation(&mut self, current_state: impl Fn(PlanningTarget) -> PlanningState) {
+        let changes: IndexMap<PlanningTarget, (PlanningState, PlanningState)> =
+            std::mem::take(&mut self.in_progress)
+                .into_iter()
+                .map(|(target, before)| (target, (before, current_state(target))))
+                .filter(|(_, (before, after))| before != after)
+                .collect();
+
+        if changes.is_empty() {
+            return;
+        }
+        let operation = PlanningOperation { changes };
+
+        if self.undo_stack.len() >= Self::MAX_OPERATIONS {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(operation);
+        self.redo_stack.clear();
+    }
+
+    /// Moves the most recent operation onto the redo stack, returning it so it can be reverted.
+    fn undo(&mut self) -> Option<&PlanningOperation> {
+        let operation = self.undo_stack.pop_back()?;
+        self.redo_stack.push(operation);
+        self.redo_stack.last()
+    }
+
+    /// Moves the most recently undone operation back onto the undo stack, returning it so it can be reapplied.
+    fn redo(&mut self) -> Option<&PlanningOperation> {
+        let operation = self.redo_stack.pop()?;
+        self.undo_stack.push_back(operation);
+        self.undo_stack.back()
+    }
+}
+
+/// Looks up the current planned state of each [`PlanningTarget`].
+#[derive(SystemParam)]
+pub(crate) struct PlanningLookup<'w, 's> {
+    /// The ghost structures that have been zoned.
+    ghost_query: Query<'w, 's, (&'static VoxelPos, ClipboardQuery), With<Ghost>>,
+    /// The planned terraforming of each terrain tile.
+    terraforming_query: Query<'w, 's, &'static TerraformingAction>,
+    /// The structures that could be marked for demolition.
+    structure_query: Query<'w, 's, (&'static VoxelPos, Has<MarkedForDemolition>), Without<Ghost>>,
+    /// The position of everything on the map.
+    map_geometry: Res<'w, MapGeometry>,
+}
+
+impl<'w, 's> PlanningLookup<'w, 's> {
+    /// The center and data of the ghost structure found at `voxel_pos`, if any.
+    fn ghost_at(&self, voxel_pos: VoxelPos) -> Option<(VoxelPos, ClipboardData)> {
+        let ghost_entity = self.map_geometry.get_ghost_structure(voxel_pos)?;
+        let (&center, clipboard_item) = self.ghost_query.get(ghost_entity).ok()?;
+        Some((center, clipboard_item.into()))
+    }
+
+    /// The current state of `target`.
+    fn state(&self, target: PlanningTarget) -> PlanningState {
+        match target {
+            PlanningTarget::Ghost(voxel_pos) => PlanningState::Ghost(
+                self.ghost_at(voxel_pos)
+                    .filter(|(center, _)| *center == voxel_pos)
+                    .map(|(_, data)| data),
+            ),
+            PlanningTarget::Terraform(hex) => PlanningState::Terraform(
+                self.map_geometry
+                    .get_terrain(hex)
+                    .ok()
+                    .and_then(|terrain_entity| self.terraforming_query.get(terrain_entity).ok())
+                    .copied()
+                    .unwrap_or_default(),
+            ),
+            PlanningTarget::Demolition(voxel_pos) => PlanningState::Demolition(
+                self.map_geometry
+                    .get_structure(voxel_pos)
+                    .and_then(|structure_entity| self.structure_query.get(structure_entity).ok())
+                    .is_some_and(|(_, marked)| marked),
+            ),
+        }
+    }
+}
+
+/// Performs planning actions, recording them in the [`PlanningHistory`] so that they can be undone.
+#[derive(SystemParam)]
+pub(crate) struct PlanningCommands<'w, 's> {
+    /// Used to actually perform the actions.
+    commands: Commands<'w, 's>,
+    /// The record of past actions.
+    history: ResMut<'w, PlanningHistory>,
+    /// The current state of everything that can be planned.
+    lookup: PlanningLookup<'w, 's>,
+    /// The data for each structure.
+    structure_manifest: Res<'w, StructureManifest>,
+}
+
+impl<'w, 's> PlanningCommands<'w, 's> {
+    /// Records that `target` may be changed by the current operation.
+    fn record(&mut self, target: PlanningTarget) {
+        let before = self.lookup.state(target);
+        self.history.record(target, before);
+    }
+
+    /// Zones a ghost structure at `voxel_pos`, replacing any ghosts in its way.
+    pub(crate) fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData) {
+        let footprint = self.structure_manifest.footprint(data.structure_id);
+        for tile in footprint.normalized(data.facing, voxel_pos) {
+            if let Some((center, _)) = self.lookup.ghost_at(tile) {
+                self.record(PlanningTarget::Ghost(center));
+            }
+        }
+        self.record(PlanningTarget::Ghost(voxel_pos));
+
+        self.commands.spawn_ghost_structure(voxel_pos, data);
+    }
+
+    /// Removes any ghost structure found at `voxel_pos`.
+    pub(crate) fn despawn_ghost_structure(&mut self, voxel_pos: VoxelPos) {
+        if let Some((center, _)) = self.lookup.ghost_at(voxel_pos) {
+            self.record(PlanningTarget::Ghost(center));
+        }
+
+        self.commands.despawn_ghost_structure(voxel_pos);
+    }
+
+    /// Plans a [`TerraformingAction`] at `hex`.
+    pub(crate) fn start_terraform(&mut self, hex: Hex, action: TerraformingAction) {
+        self.record(PlanningTarget::Terraform(hex));
+        self.commands.start_terraform(hex, action);
+    }
+
+    /// Cancels any [`TerraformingAction`] planned at `hex`.
+    pub(crate) fn cancel_terraform(&mut self, hex: Hex) {
+        self.record(PlanningTarget::Terraform(hex));
+        self.commands.cancel_terraform(hex);
+    }
+
+    /// Marks the structure `structure_entity` for demolition.
+    pub(crate) fn mark_for_demolition(&mut self, structure_entity: Entity) {
+        if let Ok((&center, _)) = self.lookup.structure_query.get(structure_entity) {
+            self.record(PlanningTarget::Demolition(center));
+        }
+
+        self.commands
+            .entity(structure_entity)
+            .insert(MarkedForDemolition);
+    }
+}
+
+/// Completes the current planning operation once the player lets go of the planning buttons.
+fn finish_planning_operation(
+    actions: Res<ActionState<PlayerAction>>,
+    mut history: ResMut<PlanningHistory>,
+    lookup: PlanningLookup,
+) {
+    let still_planning = actions.pressed(PlayerAction::UseTool)
+        || actions.pressed(PlayerAction::Paste)
+        || actions.pressed(PlayerAction::ClearZoning);
+
+    if !still_planning && !history.in_progress.is_empty() {
+        history.finish_operation(|target| lookup.state(target));
+    }
+}
+
+/// Undoes or redoes planning operations when prompted by player input.
+fn undo_and_redo(
+    actions: Res<ActionState<PlayerAction>>,
+    mut history: ResMut<PlanningHistory>,
+    mut emitter_query: Query<&mut Emitter>,
+    lookup: PlanningLookup,
+    mut commands: Commands,
+) {
+    let undo = actions.just_pressed(PlayerAction::Undo);
+    let redo = actions.just_pressed(PlayerAction::Redo);
+    if !undo && !redo {
+        return;
+    }
+
+    // Don't leave a half-finished operation behind
+    history.finish_operation(|target| lookup.state(target));
+
+    let operation = match undo {
+        true => history.undo(),
+        false => history.redo(),
+    };
+    let Some(operation) = operation else {
+        return;
+    };
+
+    let states: Vec<(PlanningTarget, PlanningState)> = match undo {
+        true => operation
+            .undo_states()
+            .map(|(target, state)| (target, state.clone()))
+            .collect(),
+        false => operation
+            .redo_states()
+            .map(|(target, state)| (target, state.clone()))
+            .collect(),
+    };
+
+    for (target, state) in states {
+        match (target, state) {
+            (PlanningTarget::Ghost(voxel_pos), PlanningState::Ghost(Some(data))) => {
+                commands.spawn_ghost_structure(voxel_pos, data);
+            }
+            (PlanningTarget::Ghost(voxel_pos), PlanningState::Ghost(None)) => {
+                commands.despawn_ghost_structure(voxel_pos);
+            }
+            (PlanningTarget::Terraform(hex), PlanningState::Terraform(action)) => {
+                // Clear out the old ghost first, so that it is replaced rather than duplicated
+                commands.cancel_terraform(hex);
+                if action != TerraformingAction::None {
+                    commands.start_terraform(hex, action);
+                }
+            }
+            (PlanningTarget::Demolition(voxel_pos), PlanningState::Demolition(marked)) => {
+                // The structure may have been removed in the meantime
+                let Some(structure_entity) = lookup.map_geometry.get_structure(voxel_pos) else {
+                    continue;
+                };
+
+                if marked {
+                    commands
+                        .entity(structure_entity)
+                        .insert(MarkedForDemolition);
+                } else {
+                    commands
+                        .entity(structure_entity)
+                        .remove::<MarkedForDemolition>();
+
+                    // Stop calling for workers to tear this structure down
+                    if let Ok(mut emitter) = emitter_query.get_mut(structure_entity) {
+                        emitter.signals.retain(|(signal_type, _)| {
+                            !matches!(signal_type, SignalType::Demolish(_))
+                        });
+                    }
+                }
+            }
+            (target, state) => {
+                error!("Planning state {state:?} does not match its target {target:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id,
+        construction::{ghosts::GhostHandles, zoning::set_zoning},
+        crafting::recipe::ActiveRecipe,
+        geometry::Facing,
+        organisms::domestication::Domestication,
+        player_interaction::{clipboard::Tool, picking::CursorPos, selection::CurrentSelection},
+        structures::{
+            structure_assets::StructureHandles,
+            structure_manifest::{Structure, StructureData},
+        },
+        technology::{technology_manifest::TechnologyManifest, ResearchProgress},
+    };
+
+    /// Records a single terraforming change as a complete operation.
+    fn terraform(
+        history: &mut PlanningHistory,
+        hex: Hex,
+        before: TerraformingAction,
+        after: TerraformingAction,
+    ) {
+        history.record(
+            PlanningTarget::Terraform(hex),
+            PlanningState::Terraform(before),
+        );
+        history.finish_operation(|_| PlanningState::Terraform(after));
+    }
+
+    #[test]
+    fn repeated_changes_keep_the_original_state() {
+        let mut history = PlanningHistory::default();
+        let target = PlanningTarget::Terraform(Hex::ZERO);
+
+        history.record(target, PlanningState::Terraform(TerraformingAction::None));
+        history.record(target, PlanningState::Terraform(TerraformingAction::Raise));
+        history.finish_operation(|_| PlanningState::Terraform(TerraformingAction::Lower));
+        let operation = history.undo().unwrap();
+
+        let undo_states: Vec<_> = operation.undo_states().collect();
+        assert_eq!(
+            undo_states,
+            vec![(target, &PlanningState::Terraform(TerraformingAction::None))]
+        );
+
+        let redo_states: Vec<_> = operation.redo_states().collect();
+        assert_eq!(
+            redo_states,
+            vec![(target, &PlanningState::Terraform(TerraformingAction::Lower))]
+        );
+    }
+
+    #[test]
+    fn undo_and_redo_move_between_stacks() {
+        let mut history = PlanningHistory::default();
+        terraform(
+            &mut history,
+            Hex::ZERO,
+            TerraformingAction::None,
+            TerraformingAction::Raise,
+        );
+
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+        assert!(history.redo().is_some());
+        assert!(history.redo().is_none());
+
+        // New changes discard anything that could have been redone
+        history.undo();
+        terraform(
+            &mut history,
+            Hex::ZERO,
+            TerraformingAction::None,
+            TerraformingAction::Lower,
+        );
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn history_is_bounded_and_skips_no_ops() {
+        let mut history = PlanningHistory::default();
+
+        terraform(
+            &mut history,
+            Hex::ZERO,
+            TerraformingAction::Raise,
+            TerraformingAction::Raise,
+        );
+        assert!(history.undo_stack.is_empty());
+
+        for _ in 0..PlanningHistory::MAX_OPERATIONS + 10 {
+            terraform(
+                &mut history,
+                Hex::ZERO,
+                TerraformingAction::None,
+                TerraformingAction::Raise,
+            );
+        }
+        assert_eq!(history.undo_stack.len(), PlanningHistory::MAX_OPERATIONS);
+    }
+
+    /// Creates an app that can zone, undo and redo the structures named "path" and "wall".
+    fn zoning_app() -> App {
+        let mut app = App::new();
+        app.add_systems(
+            Update,
+            (undo_and_redo, set_zoning, finish_planning_operation).chain(),
+        );
+
+        let map_geometry = MapGeometry::new(&mut app.world, 3);
+        app.insert_resource(map_geometry);
+
+        let mut structure_manifest = StructureManifest::new();
+        let mut scenes = HashMap::new();
+        for name in ["path", "wall"] {
+            structure_manifest.insert(name.to_string(), StructureData::passable());
+            scenes.insert(Id::from_name(name.to_string()), Handle::default());
+        }
+        app.insert_resource(structure_manifest);
+        app.insert_resource(StructureHandles {
+            scenes,
+            picking_mesh: Handle::default(),
+        });
+
+        app.init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<GhostHandles>()
+            .init_resource::<Domestication>()
+            .init_resource::<ResearchProgress>()
+            .insert_resource(TechnologyManifest::new())
+            .init_resource::<PlanningHistory>()
+            .init_resource::<ActionState<PlayerAction>>()
+            .init_resource::<CurrentSelection>()
+            .insert_resource(CursorPos::new(VoxelPos::ZERO));
+
+        app
+    }
+
+    /// Zones a single structure named `name` under the cursor, then lets go of the button.
+    fn zone(app: &mut App, name: &str) {
+        let data = ClipboardData {
+            structure_id: Id::from_name(name.to_string()),
+            facing: Facing::default(),
+            active_recipe: ActiveRecipe::NONE,
+        };
+        let mut structures = HashMap::new();
+        structures.insert(VoxelPos::ZERO, data);
+        app.insert_resource(Tool::Structures(structures));
+
+        press(app, PlayerAction::UseTool);
+    }
+
+    /// Presses `action` for a single frame, then releases it for a frame.
+    fn press(app: &mut App, action: PlayerAction) {
+        app.world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .press(action);
+        app.update();
+
+        app.world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .release(action);
+        app.update();
+    }
+
+    /// The structure zoned on top of the tile under the cursor, if any.
+    fn zoned_structure(app: &mut App) -> Option<Id<Structure>> {
+        let ghost_entity = app
+            .world
+            .resource::<MapGeometry>()
+            .get_ghost_structure(VoxelPos::ZERO.above())?;
+        app.world.get::<Id<Structure>>(ghost_entity).copied()
+    }
+
+    #[test]
+    fn zoning_can_be_undone_and_redone() {
+        let mut app = zoning_app();
+        let path = Some(Id::from_name("path".to_string()));
+        let wall = Some(Id::from_name("wall".to_string()));
+
+        zone(&mut app, "path");
+        zone(&mut app, "wall");
+        assert_eq!(zoned_structure(&mut app), wall);
+        assert_eq!(app.world.resource::<PlanningHistory>().undo_stack.len(), 2);
+
+        // The previously zoned ghost is restored exactly
+        press(&mut app, PlayerAction::Undo);
+        assert_eq!(zoned_structure(&mut app), path);
+
+        press(&mut app, PlayerAction::Undo);
+        assert_eq!(zoned_structure(&mut app), None);
+
+        press(&mut app, PlayerAction::Redo);
+        assert_eq!(zoned_structure(&mut app), path);
+
+        press(&mut app, PlayerAction::Redo);
+        assert_eq!(zoned_structure(&mut app), wall);
+    }
+
+    #[test]
+    fn rejected_zoning_is_not_recorded() {
+        let mut app = zoning_app();
+
+        // Ghosts cannot be zoned outside of the map
+        app.insert_resource(CursorPos::new(VoxelPos::from_xy(24, 7)));
+        zone(&mut app, "path");
+
+        assert!(app
+            .world
+            .resource::<PlanningHistory>()
+            .undo_stack
+            .is_empty());
+    }
+}
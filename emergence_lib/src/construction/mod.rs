@@ -15,6 +15,7 @@ use self::terraform::{terraforming_lifecycle, terraforming_signals};
 
 pub(crate) mod demolition;
 pub(crate) mod ghosts;
+pub(crate) mod history;
 pub(crate) mod terraform;
 pub(crate) mod zoning;
 
@@ -25,6 +26,7 @@ impl Plugin for ConstructionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ghosts::GhostPlugin)
             .add_plugins(zoning::ZoningPlugin)
+            .add_plugins(history::PlanningHistoryPlugin)
             // Must run after crafting emitters in order to wipe out their signals
             .add_systems(
                 FixedUpdate,
@@ -48,11 +50,20 @@ pub enum ConstructionStrategy {
     Seedling(Id<Structure>),
     /// This structure can be built directly.
     Direct(ConstructionData),
+    /// This structure is built by upgrading an existing structure in place.
+    ///
+    /// Any items stored in the existing structure are carried over.
+    Upgrade {
+        /// The variety of structure that can be upgraded into this one.
+        from: Id<Structure>,
+        /// The additional materials and work needed to perform the upgrade.
+        data: ConstructionData,
+    },
     /// A landmark, which cannot be built.
     Landmark,
 }
 
-/// The data contained in a [`ConstructionStrategy::Direct`] variant.
+/// The data contained in a [`ConstructionStrategy::Direct`] or [`ConstructionStrategy::Upgrade`] variant.
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConstructionData {
     /// The amount of work by units required to complete the construction of this building.
@@ -77,10 +88,36 @@ pub enum RawConstructionStrategy {
         /// The set of items needed to create a new copy of this structure
         materials: HashMap<String, u32>,
     },
+    /// This structure is built by upgrading an existing structure in place.
+    Upgrade {
+        /// The name of the structure that can be upgraded into this one.
+        from: String,
+        /// The additional amount of work (in seconds) by units required to complete the upgrade.
+        ///
+        /// If this is [`None`], no work will be needed at all.
+        work: Option<f32>,
+        /// The additional set of items needed to complete the upgrade.
+        materials: HashMap<String, u32>,
+    },
     /// A landmark, which cannot be built.
     Landmark,
 }
 
+impl ConstructionData {
+    /// Creates a new [`ConstructionData`] from the unprocessed manifest values.
+    fn from_raw(work: Option<f32>, materials: HashMap<String, u32>) -> Self {
+        let inventory = materials
+            .into_iter()
+            .map(|(item_name, count)| ItemSlot::empty(Id::from_name(item_name), count))
+            .collect();
+
+        ConstructionData {
+            work: work.map(Duration::from_secs_f32),
+            materials: InputInventory::Exact { inventory },
+        }
+    }
+}
+
 impl From<RawConstructionStrategy> for ConstructionStrategy {
     fn from(raw: RawConstructionStrategy) -> Self {
         match raw {
@@ -88,17 +125,16 @@ impl From<RawConstructionStrategy> for ConstructionStrategy {
                 ConstructionStrategy::Seedling(Id::from_name(seedling_name))
             }
             RawConstructionStrategy::Direct { work, materials } => {
-                let inventory = materials
-                    .into_iter()
-                    .map(|(item_name, count)| ItemSlot::empty(Id::from_name(item_name), count))
-                    .collect();
-
-                let materials = InputInventory::Exact { inventory };
-                ConstructionStrategy::Direct(ConstructionData {
-                    work: work.map(Duration::from_secs_f32),
-                    materials,
-                })
+                ConstructionStrategy::Direct(ConstructionData::from_raw(work, materials))
             }
+            RawConstructionStrategy::Upgrade {
+                from,
+                work,
+                materials,
+            } => ConstructionStrategy::Upgrade {
+                from: Id::from_name(from),
+                data: ConstructionData::from_raw(work, materials),
+            },
             RawConstructionStrategy::Landmark => ConstructionStrategy::Landmark,
         }
     }
@@ -5,6 +5,7 @@ use bevy::{
     prelude::*,
 };
 use hexx::Hex;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
@@ -42,7 +43,20 @@ pub(crate) enum TerraformingTool {
 /// Added as a component to terrain tiles, tracking the work needed to terraform them.
 ///
 /// When set to a non-null value, units will take action to manipulate them.
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Default,
+    Serialize,
+    Deserialize,
+)]
 pub enum TerraformingAction {
     /// No terraforming action is being performed.
     #[default]
@@ -5,7 +5,7 @@ use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
     asset_management::manifest::Id,
-    construction::{demolition::MarkedForDemolition, ghosts::Preview},
+    construction::ghosts::Preview,
     geometry::MapGeometry,
     player_interaction::{
         clipboard::Tool, picking::CursorPos, selection::CurrentSelection, InteractionSystem,
@@ -14,7 +14,7 @@ use crate::{
     structures::{commands::StructureCommandsExt, structure_manifest::Structure, Landmark},
 };
 
-use super::terraform::TerraformingCommandsExt;
+use super::{history::PlanningCommands, terraform::TerraformingCommandsExt};
 
 /// Code and data for setting zoning of areas for construction.
 pub(super) struct ZoningPlugin;
@@ -57,11 +57,12 @@ struct CleanMeUp;
 /// Applies zoning to an area, causing structures to be created (or removed) there.
 ///
 /// This system also displays previews in order to ensure perfect consistency.
-fn set_zoning(
+pub(super) fn set_zoning(
     cursor_pos: Res<CursorPos>,
     actions: Res<ActionState<PlayerAction>>,
     tool: Res<Tool>,
     current_selection: Res<CurrentSelection>,
+    mut planning_commands: PlanningCommands,
     mut commands: Commands,
 ) {
     let relevant_tiles = current_selection.relevant_tiles(&cursor_pos);
@@ -69,8 +70,8 @@ fn set_zoning(
     // Explicitly clear the selection
     if actions.pressed(PlayerAction::ClearZoning) {
         for &voxel_pos in relevant_tiles.iter() {
-            commands.despawn_ghost_structure(voxel_pos);
-            commands.cancel_terraform(voxel_pos.hex);
+            planning_commands.despawn_ghost_structure(voxel_pos);
+            planning_commands.cancel_terraform(voxel_pos.hex);
         }
 
         // Don't try to clear and zone in the same frame
@@ -86,7 +87,7 @@ fn set_zoning(
         Tool::Terraform(terraform_tool) => match actually_build {
             true => {
                 for voxel_pos in relevant_tiles.iter() {
-                    commands.start_terraform(voxel_pos.hex, (*terraform_tool).into());
+                    planning_commands.start_terraform(voxel_pos.hex, (*terraform_tool).into());
                 }
             }
             false => {
@@ -106,7 +107,7 @@ fn set_zoning(
                             for voxel_pos in relevant_tiles.iter() {
                                 // We need to build on top of the selected tile,
                                 // not inside the terrain
-                                commands.spawn_ghost_structure(
+                                planning_commands.spawn_ghost_structure(
                                     voxel_pos.above(),
                                     clipboard_item.clone(),
                                 );
@@ -132,7 +133,7 @@ fn set_zoning(
                     for (voxel_pos, clipboard_item) in tool.offset_positions(cursor_tile_pos) {
                         match actually_build {
                             true => {
-                                commands.spawn_ghost_structure(
+                                planning_commands.spawn_ghost_structure(
                                     voxel_pos.above(),
                                     clipboard_item.clone(),
                                 );
@@ -148,6 +149,28 @@ fn set_zoning(
                 }
             }
         }
+        Tool::Blueprint(blueprint) => {
+            let Some(cursor_tile_pos) = cursor_pos.maybe_voxel_pos() else {
+                return;
+            };
+
+            for (voxel_pos, clipboard_item) in blueprint.offset_structures(cursor_tile_pos) {
+                match actually_build {
+                    true => planning_commands
+                        .spawn_ghost_structure(voxel_pos.above(), clipboard_item.clone()),
+                    false => {
+                        commands.spawn_preview_structure(voxel_pos.above(), clipboard_item.clone())
+                    }
+                }
+            }
+
+            for (hex, terraforming_action) in blueprint.offset_terraforming(cursor_tile_pos.hex) {
+                match actually_build {
+                    true => planning_commands.start_terraform(hex, terraforming_action),
+                    false => commands.preview_terraform(hex, terraforming_action),
+                }
+            }
+        }
         Tool::None => (),
     }
 }
@@ -159,15 +182,13 @@ fn mark_for_demolition(
     // Landmarks can't be demolished
     structure_query: Query<&Id<Structure>, Without<Landmark>>,
     map_geometry: Res<MapGeometry>,
-    mut commands: Commands,
+    mut planning_commands: PlanningCommands,
 ) {
     if player_actions.just_pressed(PlayerAction::ClearZoning) {
         if let CurrentSelection::Voxels(ref selected_voxels) = *current_selection {
             for voxel_object in selected_voxels.voxel_objects(&map_geometry) {
                 if structure_query.contains(voxel_object.entity) {
-                    commands
-                        .entity(voxel_object.entity)
-                        .insert(MarkedForDemolition);
+                    planning_commands.mark_for_demolition(voxel_object.entity);
                 }
             }
         }
@@ -0,0 +1,407 @@
+//! Diagnoses why crafting structures are not producing, so that stalled production chains can be found quickly.
+//!
+//! Each crafting structure tracks how long it has recently spent in each [`CraftingState`],
+//! and why it was blocked while it was not making progress.
+//! These are aggregated into a ranked list of [`Bottlenecks`], worst first.
+//! Only structures that belong to the colony are ranked: wild organisms are not the player's problem.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    light::shade::ReceivedLight,
+    organisms::domestication::Strain,
+    structures::structure_manifest::Structure,
+    terrain::fertility::Fertility,
+};
+
+use super::{
+    inventories::CraftingState,
+    recipe::{ActiveRecipe, RecipeManifest},
+    workers::WorkersPresent,
+};
+
+/// The reason that a crafting structure is not making progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockingReason {
+    /// No recipe has been selected.
+    NoRecipe,
+    /// Not all of the input items have been delivered.
+    MissingInput,
+    /// The output inventory is full, and needs to be emptied.
+    OutputFull,
+    /// Fewer workers are present than the recipe requires.
+    TooFewWorkers,
+    /// The structure is receiving too much or too little light.
+    WrongLight,
+    /// The soil beneath the structure is not fertile enough.
+    InfertileSoil,
+}
+
+impl Display for BlockingReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            BlockingReason::NoRecipe => "No recipe set",
+            BlockingReason::MissingInput => "Missing input",
+            BlockingReason::OutputFull => "Output full",
+            BlockingReason::TooFewWorkers => "Too few workers",
+            BlockingReason::WrongLight => "Wrong light level",
+            BlockingReason::InfertileSoil => "Infertile soil",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// The data-less version of [`CraftingState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum CraftingStateKind {
+    /// There are resources missing for the recipe.
+    NeedsInput,
+    /// The recipe is being crafted.
+    InProgress,
+    /// Resources need to be claimed before more crafting can continue.
+    FullAndBlocked,
+    /// The recipe is complete.
+    RecipeComplete,
+    /// The output is full but production is continuing.
+    Overproduction,
+    /// No recipe is set.
+    NoRecipe,
+}
+
+impl From<&CraftingState> for CraftingStateKind {
+    fn from(value: &CraftingState) -> Self {
+        match value {
+            CraftingState::NeedsInput => CraftingStateKind::NeedsInput,
+            CraftingState::InProgress { .. } => CraftingStateKind::InProgress,
+            CraftingState::FullAndBlocked => CraftingStateKind::FullAndBlocked,
+            CraftingState::RecipeComplete => CraftingStateKind::RecipeComplete,
+            CraftingState::Overproduction => CraftingStateKind::Overproduction,
+            CraftingState::NoRecipe => CraftingStateKind::NoRecipe,
+        }
+    }
+}
+
+/// How a crafting structure has recently spent its time.
+///
+/// Older time is gradually forgotten, so that these reflect the current state of the production chain.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub(crate) struct CraftingDiagnostics {
+    /// The recent time spent in each crafting state, in seconds.
+    time_in_state: HashMap<CraftingStateKind, f32>,
+    /// The recent time spent blocked for each reason, in seconds.
+    time_blocked: HashMap<BlockingReason, f32>,
+}
+
+impl CraftingDiagnostics {
+    /// The number of seconds over which time is remembered.
+    ///
+    /// Time spent further in the past than this has a negligible effect.
+    const MEMORY: f32 = 120.;
+
+    /// Records `delta_time` seconds spent in `state`, blocked by `reason` if any.
+    fn record(
+        &mut self,
+        state: CraftingStateKind,
+        reason: Option<BlockingReason>,
+        delta_time: f32,
+    ) {
+        let retained = (1. - delta_time / Self::MEMORY).max(0.);
+        for time in self.time_in_state.values_mut() {
+            *time *= retained;
+        }
+        for time in self.time_blocked.values_mut() {
+            *time *= retained;
+        }
+
+        *self.time_in_state.entry(state).or_default() += delta_time;
+        if let Some(reason) = reason {
+            *self.time_blocked.entry(reason).or_default() += delta_time;
+        }
+    }
+
+    /// The recent time spent in the provided `state`, in seconds.
+    pub(crate) fn time_in_state(&self, state: CraftingStateKind) -> f32 {
+        self.time_in_state.get(&state).copied().unwrap_or_default()
+    }
+
+    /// The fraction of recent time spent blocked, between 0 and 1.
+    pub(crate) fn blocked_fraction(&self) -> f32 {
+        let total: f32 = self.time_in_state.values().sum();
+        if total <= 0. {
+            return 0.;
+        }
+
+        let blocked: f32 = self.time_blocked.values().sum();
+        (blocked / total).clamp(0., 1.)
+    }
+
+    /// The reason that this structure has recently been blocked for the longest, if any.
+    pub(crate) fn dominant_blocking_reason(&self) -> Option<BlockingReason> {
+        self.time_blocked
+            .iter()
+            .filter(|(_, &time)| time > 0.)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(&reason, _)| reason)
+    }
+}
+
+impl Display for CraftingDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total: f32 = self.time_in_state.values().sum();
+        let working = match total > 0. {
+            true => self.time_in_state(CraftingStateKind::InProgress) / total,
+            false => 0.,
+        };
+        let blocked = self.blocked_fraction();
+
+        write!(
+            f,
+            "Working {:.0}%, blocked {:.0}%",
+            working * 100.,
+            blocked * 100.
+        )?;
+
+        if let Some(reason) = self.dominant_blocking_reason() {
+            write!(f, " ({reason})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A crafting structure that is spending much of its time blocked.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Bottleneck {
+    /// The type of the structure.
+    pub(crate) structure_id: Id<Structure>,
+    /// The position of the structure.
+    pub(crate) voxel_pos: VoxelPos,
+    /// The fraction of recent time the structure spent blocked.
+    pub(crate) blocked_fraction: f32,
+    /// The most common reason that the structure was blocked.
+    pub(crate) reason: BlockingReason,
+}
+
+/// How often the [`Bottlenecks`] are re-ranked, in seconds.
+const REFRESH_INTERVAL: f32 = 1.0;
+
+/// Every crafting structure that has recently been blocked, ranked from worst to best.
+#[derive(Resource, Debug)]
+pub(crate) struct Bottlenecks {
+    /// The ranked bottlenecks.
+    ranked: Vec<Bottleneck>,
+    /// Controls how often the ranking is recomputed.
+    refresh_timer: Timer,
+}
+
+impl Default for Bottlenecks {
+    fn default() -> Self {
+        Bottlenecks {
+            ranked: Vec::new(),
+            refresh_timer: Timer::from_seconds(REFRESH_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Bottlenecks {
+    /// The bottlenecks, from worst to best.
+    pub(crate) fn iter(&self) -> impl ExactSizeIterator<Item = &Bottleneck> + '_ {
+        self.ranked.iter()
+    }
+
+    /// The bottleneck with the provided `rank`, where 0 is the worst.
+    pub(crate) fn get(&self, rank: usize) -> Option<&Bottleneck> {
+        self.ranked.get(rank)
+    }
+
+    /// The number of bottlenecks.
+    pub(crate) fn len(&self) -> usize {
+        self.ranked.len()
+    }
+
+    /// Are there no bottlenecks at all?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.ranked.is_empty()
+    }
+}
+
+/// Records how each crafting structure spent the last tick, and why it was blocked.
+pub(super) fn record_crafting_diagnostics(
+    mut crafting_query: Query<(
+        &CraftingState,
+        &ActiveRecipe,
+        &WorkersPresent,
+        &VoxelPos,
+        &mut CraftingDiagnostics,
+    )>,
+    terrain_query: Query<(&ReceivedLight, &Fertility)>,
+    recipe_manifest: Res<RecipeManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    for (crafting_state, active_recipe, workers_present, voxel_pos, mut diagnostics) in
+        crafting_query.iter_mut()
+    {
+        let reason = match crafting_state {
+            CraftingState::NoRecipe => Some(BlockingReason::NoRecipe),
+            CraftingState::NeedsInput => Some(BlockingReason::MissingInput),
+            CraftingState::FullAndBlocked | CraftingState::Overproduction => {
+                Some(BlockingReason::OutputFull)
+            }
+            CraftingState::RecipeComplete => None,
+            CraftingState::InProgress { .. } => {
+                (*active_recipe.recipe_id()).and_then(|recipe_id| {
+                    let conditions = &recipe_manifest.get(recipe_id).conditions;
+                    let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).ok()?;
+                    let (received_light, &fertility) = terrain_query.get(terrain_entity).ok()?;
+
+                    conditions.unmet_condition(workers_present.current(), received_light, fertility)
+                })
+            }
+        };
+
+        diagnostics.record(crafting_state.into(), reason, delta_time);
+    }
+}
+
+/// Ranks the colony's crafting structures by how much of their time they spend blocked.
+///
+/// The diagnostics change every tick, so the ranking is only recomputed every [`REFRESH_INTERVAL`] seconds.
+pub(super) fn rank_bottlenecks(
+    crafting_query: Query<(
+        &Id<Structure>,
+        &VoxelPos,
+        &CraftingDiagnostics,
+        Option<&Strain>,
+    )>,
+    mut bottlenecks: ResMut<Bottlenecks>,
+    time: Res<Time>,
+) {
+    // Ticking the timer alone should not mark the ranking as changed
+    let refresh_timer = &mut bottlenecks.bypass_change_detection().refresh_timer;
+    if !refresh_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    bottlenecks.ranked = rank(crafting_query.iter());
+}
+
+/// Ranks the provided crafting structures from worst to best, skipping wild organisms and structures that are never blocked.
+fn rank<'a>(
+    structures: impl Iterator<
+        Item = (
+            &'a Id<Structure>,
+            &'a VoxelPos,
+            &'a CraftingDiagnostics,
+            Option<&'a Strain>,
+        ),
+    >,
+) -> Vec<Bottleneck> {
+    let mut ranked: Vec<Bottleneck> = structures
+        .filter(|(.., strain)| *strain != Some(&Strain::Wild))
+        .filter_map(|(&structure_id, &voxel_pos, diagnostics, _)| {
+            Some(Bottleneck {
+                structure_id,
+                voxel_pos,
+                blocked_fraction: diagnostics.blocked_fraction(),
+                reason: diagnostics.dominant_blocking_reason()?,
+            })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.blocked_fraction.total_cmp(&a.blocked_fraction));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dominant_blocking_reason_is_tracked() {
+        let mut diagnostics = CraftingDiagnostics::default();
+        assert_eq!(diagnostics.dominant_blocking_reason(), None);
+        assert_eq!(diagnostics.blocked_fraction(), 0.);
+
+        diagnostics.record(CraftingStateKind::InProgress, None, 1.);
+        diagnostics.record(
+            CraftingStateKind::NeedsInput,
+            Some(BlockingReason::MissingInput),
+            1.,
+        );
+        diagnostics.record(
+            CraftingStateKind::FullAndBlocked,
+            Some(BlockingReason::OutputFull),
+            2.,
+        );
+
+        assert_eq!(
+            diagnostics.dominant_blocking_reason(),
+            Some(BlockingReason::OutputFull)
+        );
+        assert!(diagnostics.time_in_state(CraftingStateKind::InProgress) > 0.);
+        assert!(diagnostics.blocked_fraction() > 0.7);
+    }
+
+    #[test]
+    fn old_time_is_forgotten() {
+        let mut diagnostics = CraftingDiagnostics::default();
+        diagnostics.record(
+            CraftingStateKind::NeedsInput,
+            Some(BlockingReason::MissingInput),
+            1.,
+        );
+
+        for _ in 0..1000 {
+            diagnostics.record(CraftingStateKind::InProgress, None, 1.);
+        }
+
+        assert!(diagnostics.blocked_fraction() < 0.01);
+    }
+
+    #[test]
+    fn wild_organisms_are_not_bottlenecks() {
+        let blocked = |seconds_blocked: f32| {
+            let mut diagnostics = CraftingDiagnostics::default();
+            diagnostics.record(CraftingStateKind::InProgress, None, 1.);
+            diagnostics.record(
+                CraftingStateKind::NeedsInput,
+                Some(BlockingReason::MissingInput),
+                seconds_blocked,
+            );
+            diagnostics
+        };
+
+        let workshop = Id::from_name("workshop".to_string());
+        let acacia = Id::from_name("acacia".to_string());
+        let slightly_blocked = blocked(1.);
+        let very_blocked = blocked(5.);
+        let never_blocked = CraftingDiagnostics::default();
+
+        let structures = [
+            (&workshop, &VoxelPos::ZERO, &slightly_blocked, None),
+            (&acacia, &VoxelPos::ZERO, &very_blocked, Some(&Strain::Wild)),
+            (
+                &acacia,
+                &VoxelPos::ZERO,
+                &very_blocked,
+                Some(&Strain::Domesticated),
+            ),
+            (&workshop, &VoxelPos::ZERO, &never_blocked, None),
+        ];
+
+        let ranked = rank(structures.into_iter());
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].structure_id, acacia);
+        assert_eq!(ranked[1].structure_id, workshop);
+        assert!(ranked[0].blocked_fraction > ranked[1].blocked_fraction);
+    }
+}
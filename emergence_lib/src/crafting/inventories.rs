@@ -303,47 +303,53 @@ impl OutputInventory {
     }
 
     /// Produces the items specified by `recipe` and adds them to the inventory.
+    ///
+    /// The number of each item produced is scaled by `yield_multiplier`.
+    /// Any fractional items that result are produced stochastically.
+    ///
+    /// If all of the items fit, returns the items that were produced.
     pub(super) fn craft(
         &mut self,
         recipe: &RecipeData,
+        yield_multiplier: f32,
         item_manifest: &ItemManifest,
         rng: &mut ThreadRng,
-    ) -> Result<(), AddManyItemsError> {
+    ) -> Result<Vec<ItemCount>, AddManyItemsError> {
+        let mut produced: Vec<ItemCount> = Vec::new();
         let mut overflow: Vec<ItemCount> = Vec::new();
 
-        match &recipe.outputs {
-            RecipeOutput::Deterministic(outputs) => {
-                for output in outputs {
-                    let result = self.try_add_item(output, item_manifest);
-                    if let Err(AddOneItemError { excess_count }) = result {
-                        overflow.push(excess_count);
-                    }
-                }
-            }
-            RecipeOutput::Stochastic(outputs) => {
-                let distribution = Uniform::new(0.0, 1.0);
-                for (item_id, number) in outputs {
-                    // Always produce items equal to quotient,
-                    // and then produce one extra items with probability remainder.
-                    let (quotient, remainder) = (number / 1.0, number % 1.0);
-                    let count = if remainder == 0. || distribution.sample(rng) > remainder {
-                        quotient as u32
-                    } else {
-                        quotient as u32 + 1
-                    };
-
-                    let output = ItemCount::new(*item_id, count);
-
-                    let result = self.try_add_item(&output, item_manifest);
-                    if let Err(AddOneItemError { excess_count }) = result {
-                        overflow.push(excess_count);
-                    }
-                }
-            }
+        let expected_outputs: Vec<(Id<Item>, f32)> = match &recipe.outputs {
+            RecipeOutput::Deterministic(outputs) => outputs
+                .iter()
+                .map(|output| (output.item_id, output.count as f32))
+                .collect(),
+            RecipeOutput::Stochastic(outputs) => outputs.clone(),
         };
 
+        let distribution = Uniform::new(0.0, 1.0);
+        for (item_id, number) in expected_outputs {
+            let number = number * yield_multiplier;
+
+            // Always produce items equal to quotient,
+            // and then produce one extra items with probability remainder.
+            let (quotient, remainder) = (number / 1.0, number % 1.0);
+            let count = if remainder == 0. || distribution.sample(rng) > remainder {
+                quotient as u32
+            } else {
+                quotient as u32 + 1
+            };
+
+            let output = ItemCount::new(item_id, count);
+            produced.push(output.clone());
+
+            let result = self.try_add_item(&output, item_manifest);
+            if let Err(AddOneItemError { excess_count }) = result {
+                overflow.push(excess_count);
+            }
+        }
+
         if overflow.is_empty() {
-            Ok(())
+            Ok(produced)
         } else {
             Err(AddManyItemsError {
                 excess_counts: overflow,
@@ -10,13 +10,22 @@ use crate::{
     items::{
         inventory::Inventory,
         item_manifest::{ItemManifest, RawItemManifest},
+        ItemCount,
     },
     light::shade::ReceivedLight,
-    organisms::{energy::EnergyPool, lifecycle::Lifecycle, Organism},
+    organisms::{
+        disease::{disease_manifest::DiseaseManifest, Infections},
+        energy::EnergyPool,
+        genetics::{Gene, Genome},
+        lifecycle::Lifecycle,
+        symbiosis::Visited,
+        Organism,
+    },
     player_interaction::InteractionSystem,
     signals::{Emitter, SignalStrength, SignalType},
     simulation::SimulationSet,
     structures::structure_manifest::{Structure, StructureManifest},
+    terrain::fertility::Fertility,
 };
 
 use std::time::Duration;
@@ -24,12 +33,16 @@ use std::time::Duration;
 use bevy::{ecs::query::WorldQuery, prelude::*};
 
 use self::{
+    diagnostics::{
+        rank_bottlenecks, record_crafting_diagnostics, Bottlenecks, CraftingDiagnostics,
+    },
     inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
     item_tags::{ItemKind, ItemTag},
-    recipe::{ActiveRecipe, RecipeInput},
+    recipe::{ActiveRecipe, Recipe, RecipeInput},
     workers::WorkersPresent,
 };
 
+pub mod diagnostics;
 pub mod inventories;
 pub mod item_tags;
 pub mod recipe;
@@ -42,6 +55,8 @@ impl Plugin for CraftingPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawItemManifest>::new())
             .add_plugins(ManifestPlugin::<RawRecipeManifest>::new())
+            .add_event::<RecipeCompleted>()
+            .init_resource::<Bottlenecks>()
             .add_systems(
                 FixedUpdate,
                 (
@@ -53,12 +68,26 @@ impl Plugin for CraftingPlugin {
                         .before(InteractionSystem::ApplyZoning),
                     set_storage_emitter.before(InteractionSystem::ApplyZoning),
                     clear_empty_storage_slots,
+                    (record_crafting_diagnostics, rank_bottlenecks)
+                        .chain()
+                        .after(progress_crafting),
                 )
                     .in_set(SimulationSet),
             );
     }
 }
 
+/// Sent whenever a recipe is successfully crafted.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct RecipeCompleted {
+    /// The recipe that was crafted.
+    pub recipe_id: Id<Recipe>,
+    /// The position of the structure that crafted the recipe.
+    pub voxel_pos: VoxelPos,
+    /// The items that were produced.
+    pub produced: Vec<ItemCount>,
+}
+
 /// All components needed to craft stuff.
 #[derive(Debug, Bundle)]
 pub(crate) struct CraftingBundle {
@@ -79,6 +108,9 @@ pub(crate) struct CraftingBundle {
 
     /// The number of workers present / allowed at this structure
     workers_present: WorkersPresent,
+
+    /// Tracks why crafting has recently been blocked
+    diagnostics: CraftingDiagnostics,
 }
 
 impl CraftingBundle {
@@ -102,6 +134,7 @@ impl CraftingBundle {
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
+                diagnostics: CraftingDiagnostics::default(),
             }
         } else {
             Self {
@@ -115,6 +148,7 @@ impl CraftingBundle {
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
+                diagnostics: CraftingDiagnostics::default(),
             }
         }
     }
@@ -138,16 +172,27 @@ struct CraftingQuery {
     voxel_pos: &'static VoxelPos,
     /// Is the structure an organism?
     maybe_organism: Option<&'static Organism>,
+    /// The heritable traits of the crafter, if it is an organism.
+    maybe_genome: Option<&'static Genome>,
+    /// The diseases affecting the crafter, if it is an organism.
+    maybe_infections: Option<&'static Infections>,
+    /// The bonus granted by a recent visit from a symbiotic partner, if any.
+    maybe_visited: Option<&'static Visited>,
 }
 
 /// Progress the state of recipes that are being crafted.
-fn progress_crafting(
+///
+/// Readers of [`RecipeCompleted`] in [`FixedUpdate`] must run after this system:
+/// events are cleared once per frame, not once per tick.
+pub(crate) fn progress_crafting(
     time: Res<Time>,
     recipe_manifest: Res<RecipeManifest>,
     item_manifest: Res<ItemManifest>,
-    terrain_query: Query<&ReceivedLight>,
+    disease_manifest: Res<DiseaseManifest>,
+    mut terrain_query: Query<(&ReceivedLight, &mut Fertility)>,
     mut crafting_query: Query<CraftingQuery>,
     map_geometry: Res<MapGeometry>,
+    mut recipe_completed_events: EventWriter<RecipeCompleted>,
 ) {
     let rng = &mut rand::thread_rng();
 
@@ -185,19 +230,31 @@ fn progress_crafting(
                     let recipe = recipe_manifest.get(*recipe_id);
                     let terrain_entity = map_geometry.get_terrain(crafter.voxel_pos.hex).unwrap();
 
-                    let received_light = terrain_query.get(terrain_entity).unwrap();
+                    let (received_light, &fertility) = terrain_query.get(terrain_entity).unwrap();
 
                     // Check if we can make progress
-                    if recipe.satisfied(crafter.workers_present.current(), received_light) {
+                    if recipe.satisfied(
+                        crafter.workers_present.current(),
+                        received_light,
+                        fertility,
+                    ) {
+                        let work_speed = crafter
+                            .maybe_genome
+                            .map_or(1.0, |genome| genome.get(Gene::WorkSpeed))
+                            * crafter
+                                .maybe_infections
+                                .map_or(1.0, |infections| infections.work_speed(&disease_manifest));
+
                         // Many hands make light work!
                         if recipe.workers_required() > 0 {
                             updated_progress += Duration::from_secs_f32(
                                 time.delta().as_secs_f32()
+                                    * work_speed
                                     * crafter.workers_present.effective_workers()
                                     / recipe.workers_required() as f32,
                             );
                         } else {
-                            updated_progress += time.delta();
+                            updated_progress += time.delta().mul_f32(work_speed);
                         }
 
                         if updated_progress >= required {
@@ -216,20 +273,38 @@ fn progress_crafting(
                 }
             }
             CraftingState::RecipeComplete => {
-                if let Some(recipe_id) = crafter.active_recipe.recipe_id() {
-                    let recipe = recipe_manifest.get(*recipe_id);
+                if let Some(recipe_id) = *crafter.active_recipe.recipe_id() {
+                    let recipe = recipe_manifest.get(recipe_id);
+                    let yield_multiplier = crafter
+                        .maybe_visited
+                        .map_or(1.0, |visited| visited.yield_multiplier());
+
                     // Actually produce the items
-                    match crafter.maybe_organism {
-                        Some(_) => {
-                            match crafter.output.craft(recipe, &item_manifest, rng) {
-                                Ok(_) => CraftingState::NeedsInput,
-                                // TODO: handle the waste products somehow
-                                Err(_) => CraftingState::Overproduction,
+                    match crafter
+                        .output
+                        .craft(recipe, yield_multiplier, &item_manifest, rng)
+                    {
+                        Ok(produced) => {
+                            // Living structures draw on (or replenish) the soil that they grow in
+                            if crafter.maybe_organism.is_some() {
+                                let terrain_entity =
+                                    map_geometry.get_terrain(crafter.voxel_pos.hex).unwrap();
+                                let (_, mut fertility) =
+                                    terrain_query.get_mut(terrain_entity).unwrap();
+                                fertility.record_recipe(recipe, &produced);
                             }
+
+                            recipe_completed_events.send(RecipeCompleted {
+                                recipe_id,
+                                voxel_pos: *crafter.voxel_pos,
+                                produced,
+                            });
+                            CraftingState::NeedsInput
                         }
-                        None => match crafter.output.craft(recipe, &item_manifest, rng) {
-                            Ok(()) => CraftingState::NeedsInput,
-                            Err(_) => CraftingState::FullAndBlocked,
+                        Err(_) => match crafter.maybe_organism {
+                            // TODO: handle the waste products somehow
+                            Some(_) => CraftingState::Overproduction,
+                            None => CraftingState::FullAndBlocked,
                         },
                     }
                 } else {
@@ -6,6 +6,7 @@ use crate::items::item_manifest::{Item, ItemManifest};
 use crate::items::{inventory::Inventory, ItemCount};
 use crate::light::shade::ReceivedLight;
 use crate::light::Illuminance;
+use crate::terrain::fertility::Fertility;
 use crate::{
     crafting::inventories::{InputInventory, OutputInventory},
     organisms::energy::Energy,
@@ -17,7 +18,7 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, time::Duration};
 
-use super::item_tags::ItemTag;
+use super::{diagnostics::BlockingReason, item_tags::ItemTag};
 
 /// The marker type for [`Id<Recipe>`](super::Id).
 #[derive(Reflect, Clone, Copy, PartialEq, Eq)]
@@ -223,8 +224,14 @@ impl From<RawRecipeData> for RecipeData {
 
 impl RecipeData {
     /// Are the conditions to craft this recipe met?
-    pub(crate) fn satisfied(&self, workers: u8, received_light: &ReceivedLight) -> bool {
-        self.conditions.satisfied(workers, received_light)
+    pub(crate) fn satisfied(
+        &self,
+        workers: u8,
+        received_light: &ReceivedLight,
+        fertility: Fertility,
+    ) -> bool {
+        self.conditions
+            .satisfied(workers, received_light, fertility)
     }
 
     /// An inventory with empty slots for all of the inputs of this recipe.
@@ -302,6 +309,8 @@ pub struct RecipeConditions {
     pub workers_required: u8,
     /// The range of light levels that are acceptable for this recipe.
     pub allowable_light_range: Option<Threshold<Illuminance>>,
+    /// The minimum soil fertility needed for this recipe, if any.
+    pub min_fertility: Option<Fertility>,
 }
 
 impl Display for RecipeConditions {
@@ -312,6 +321,9 @@ impl Display for RecipeConditions {
         if let Some(range) = &self.allowable_light_range {
             write!(f, "Light: {}", *range)?;
         }
+        if let Some(min_fertility) = &self.min_fertility {
+            write!(f, "Fertility: {}+", *min_fertility)?;
+        }
         Ok(())
     }
 }
@@ -321,6 +333,7 @@ impl RecipeConditions {
     pub const NONE: RecipeConditions = RecipeConditions {
         workers_required: 0,
         allowable_light_range: None,
+        min_fertility: None,
     };
 
     /// Creates a new [`RecipeConditions`].
@@ -328,18 +341,41 @@ impl RecipeConditions {
         Self {
             workers_required,
             allowable_light_range: Some(allowable_light_range),
+            min_fertility: None,
         }
     }
 
     /// Are the conditions to craft this recipe met?
-    fn satisfied(&self, workers: u8, received_light: &ReceivedLight) -> bool {
+    fn satisfied(&self, workers: u8, received_light: &ReceivedLight, fertility: Fertility) -> bool {
+        self.unmet_condition(workers, received_light, fertility)
+            .is_none()
+    }
+
+    /// Returns the first condition that is preventing work on this recipe, if any.
+    pub(crate) fn unmet_condition(
+        &self,
+        workers: u8,
+        received_light: &ReceivedLight,
+        fertility: Fertility,
+    ) -> Option<BlockingReason> {
         let work_satisfied = self.workers_required == 0 || workers >= self.workers_required;
         let light_satisfied = self
             .allowable_light_range
             .as_ref()
             .map_or(true, |range| range.contains(received_light.0));
-
-        work_satisfied && light_satisfied
+        let fertility_satisfied = self
+            .min_fertility
+            .map_or(true, |min_fertility| fertility >= min_fertility);
+
+        if !work_satisfied {
+            Some(BlockingReason::TooFewWorkers)
+        } else if !light_satisfied {
+            Some(BlockingReason::WrongLight)
+        } else if !fertility_satisfied {
+            Some(BlockingReason::InfertileSoil)
+        } else {
+            None
+        }
     }
 }
 
@@ -27,6 +27,10 @@ pub struct MapGeometry {
     terrain_index: HashMap<Hex, Entity>,
     /// The terraforming ghost entity at each hex, if any.
     terraforming_index: HashMap<Hex, Entity>,
+    /// The ghost entities that are planned to upgrade existing structures, indexed by the voxels they cover.
+    ///
+    /// These are stored separately from the voxel index, as the structure being upgraded still occupies these voxels.
+    upgrade_index: HashMap<VoxelPos, Entity>,
     /// The height of the terrain at each tile position.
     ///
     /// The set of keys is the set of all valid [`Hex`] positions on the map.
@@ -166,6 +170,7 @@ impl MapGeometry {
             radius,
             terrain_index,
             terraforming_index: HashMap::default(),
+            upgrade_index: HashMap::default(),
             height_index,
             voxel_index,
             walkable_neighbors: HashMap::default(),
@@ -220,6 +225,31 @@ impl MapGeometry {
             .all(|voxel_pos| self.is_valid(voxel_pos.hex))
     }
 
+    /// Is the structure with the provided `footprint` located at the `center` tile resting on something solid?
+    ///
+    /// Structures can be built on terrain, or on the roof of structures that can be walked on.
+    /// Only one voxel of the footprint needs to be supported, allowing structures to overhang uneven terrain.
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_footprint_supported(
+        &self,
+        center: VoxelPos,
+        footprint: &Footprint,
+        facing: Facing,
+    ) -> bool {
+        let normalized = footprint.normalized(facing, center);
+
+        normalized.iter().any(|voxel_pos| {
+            let below = voxel_pos.below();
+            // Voxels resting on other parts of the same structure don't count
+            !normalized.contains(&below)
+                && match self.get_voxel(below) {
+                    Some(voxel_object) => voxel_object.object_kind.can_support_structures(),
+                    None => false,
+                }
+        })
+    }
+
     /// Is there enough space for a structure with the provided `footprint` located at the `center` tile?
     #[inline]
     pub(crate) fn is_space_available(
@@ -252,7 +282,7 @@ impl MapGeometry {
     /// The `existing_entity` will be ignored when checking for space.
     #[inline]
     #[must_use]
-    fn is_space_available_to_transform(
+    pub(crate) fn is_space_available_to_transform(
         &self,
         existing_entity: Entity,
         center: VoxelPos,
@@ -347,6 +377,13 @@ impl MapGeometry {
         voxel_pos: VoxelPos,
         delivery_mode: DeliveryMode,
     ) -> Option<Entity> {
+        // Materials for upgrades should be delivered to the ghost, rather than the structure being upgraded
+        if delivery_mode == DeliveryMode::DropOff {
+            if let Some(&upgrade_ghost_entity) = self.upgrade_index.get(&voxel_pos) {
+                return Some(upgrade_ghost_entity);
+            }
+        }
+
         if let Some(voxel_data) = self.get_voxel(voxel_pos) {
             match delivery_mode {
                 DeliveryMode::DropOff => {
@@ -375,6 +412,10 @@ impl MapGeometry {
     #[inline]
     #[must_use]
     pub(crate) fn get_workplace(&self, voxel_pos: VoxelPos) -> Option<Entity> {
+        if let Some(&upgrade_ghost_entity) = self.upgrade_index.get(&voxel_pos) {
+            return Some(upgrade_ghost_entity);
+        }
+
         if let Some(voxel_data) = self.get_voxel(voxel_pos) {
             if voxel_data.object_kind.can_work_at() {
                 Some(voxel_data.entity)
@@ -447,16 +488,21 @@ impl MapGeometry {
         footprint: &Footprint,
         can_walk_on_roof: bool,
         can_walk_through: bool,
+        is_ramp: bool,
         structure_entity: Entity,
     ) -> Result<(), AdditionError> {
         self.is_space_available(center, footprint, facing)?;
 
+        // Ramps always climb in the direction that they are facing
+        let ramp_direction = is_ramp.then_some(facing.direction);
+
         for voxel_pos in footprint.normalized(facing, center) {
             let voxel_data = VoxelObject {
                 entity: structure_entity,
                 object_kind: VoxelKind::Structure {
                     can_walk_on_roof,
                     can_walk_through,
+                    ramp_direction,
                 },
             };
             self.voxel_index.insert(voxel_pos, voxel_data);
@@ -489,6 +535,8 @@ impl MapGeometry {
 
         for voxel_pos in footprint.normalized(facing, center) {
             self.voxel_index.remove(&voxel_pos);
+            // Upgrades can't be built once the structure they are upgrading is gone
+            self.upgrade_index.remove(&voxel_pos);
         }
 
         self.recompute_walkable_neighbors();
@@ -609,6 +657,10 @@ impl MapGeometry {
     #[inline]
     #[must_use]
     pub(crate) fn get_ghost_structure(&self, voxel_pos: VoxelPos) -> Option<Entity> {
+        if let Some(&upgrade_ghost_entity) = self.upgrade_index.get(&voxel_pos) {
+            return Some(upgrade_ghost_entity);
+        }
+
         let voxel_data = self.get_voxel(voxel_pos)?;
         match voxel_data.object_kind {
             VoxelKind::GhostStructure => Some(voxel_data.entity),
@@ -616,6 +668,40 @@ impl MapGeometry {
         }
     }
 
+    /// Gets the upgrade ghost [`Entity`] at the provided `voxel_pos`, if any.
+    ///
+    /// Unlike [`get_ghost_structure`](MapGeometry::get_ghost_structure), ordinary ghosts are ignored.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_upgrade_ghost(&self, voxel_pos: VoxelPos) -> Option<Entity> {
+        self.upgrade_index.get(&voxel_pos).copied()
+    }
+
+    /// Adds the provided `ghost_structure_entity` to the upgrade index at the provided `center`.
+    ///
+    /// The `existing_entity` is the structure that will be upgraded, and must be the only structure in the way.
+    #[inline]
+    pub(crate) fn add_upgrade_ghost(
+        &mut self,
+        existing_entity: Entity,
+        facing: Facing,
+        center: VoxelPos,
+        footprint: &Footprint,
+        ghost_structure_entity: Entity,
+    ) -> Result<(), AdditionError> {
+        if !self.is_space_available_to_transform(existing_entity, center, footprint, facing) {
+            return Err(AdditionError::AlreadyOccupied);
+        }
+
+        for voxel_pos in footprint.normalized(facing, center) {
+            self.upgrade_index.insert(voxel_pos, ghost_structure_entity);
+        }
+
+        // We do not need to update the passable neighbors, as upgrade ghosts are not stored in the voxel index
+
+        Ok(())
+    }
+
     /// Adds the provided `ghost_structure_entity` to the voxel index at the provided `center`.
     #[inline]
     pub(crate) fn add_ghost_structure(
@@ -654,6 +740,14 @@ impl MapGeometry {
         footprint: &Footprint,
         facing: Facing,
     ) -> Option<Entity> {
+        if let Some(&upgrade_ghost_entity) = self.upgrade_index.get(&center) {
+            for voxel_pos in footprint.normalized(facing, center) {
+                self.upgrade_index.remove(&voxel_pos);
+            }
+
+            return Some(upgrade_ghost_entity);
+        }
+
         let tentative_entry = self.voxel_index.get(&center)?;
         if !matches!(tentative_entry.object_kind, VoxelKind::GhostStructure) {
             return None;
@@ -795,6 +889,9 @@ impl MapGeometry {
         // This includes solid structures, in addition to empty or walkable voxels
         for origin_voxel in &self.origin_voxels() {
             let mut local_neighbors = Neighbors::NONE;
+            let origin_ramp_direction = self
+                .get_voxel(*origin_voxel)
+                .and_then(|voxel_object| voxel_object.object_kind.ramp_direction());
 
             for (i, &direction) in hexx::Direction::ALL_DIRECTIONS.iter().enumerate() {
                 let neighbor_hex = origin_voxel.hex.neighbor(direction);
@@ -804,18 +901,34 @@ impl MapGeometry {
                 };
                 let neighbor_above = neighbor_flat.above();
                 let neighbor_below = neighbor_flat.below();
+                // Ramps allow units to climb or descend an extra step
+                let neighbor_two_above = neighbor_above.above();
+                let neighbor_two_below = neighbor_below.below();
+
+                let can_climb_ramp = origin_ramp_direction == Some(direction);
+                let can_descend_ramp = self
+                    .get_voxel(neighbor_two_below)
+                    .and_then(|voxel_object| voxel_object.object_kind.ramp_direction())
+                    .is_some_and(|ramp_direction| {
+                        neighbor_hex.neighbor(ramp_direction) == origin_voxel.hex
+                    });
 
                 // Preferentially walk up, then level, then down
                 // So far, this is an arbitrary priority system
-                local_neighbors.maybe_neighbors[i] = if walkable_voxels.contains(&neighbor_above) {
-                    Some(neighbor_above)
-                } else if walkable_voxels.contains(&neighbor_flat) {
-                    Some(neighbor_flat)
-                } else if walkable_voxels.contains(&neighbor_below) {
-                    Some(neighbor_below)
-                } else {
-                    None
-                }
+                local_neighbors.maybe_neighbors[i] =
+                    if can_climb_ramp && walkable_voxels.contains(&neighbor_two_above) {
+                        Some(neighbor_two_above)
+                    } else if walkable_voxels.contains(&neighbor_above) {
+                        Some(neighbor_above)
+                    } else if walkable_voxels.contains(&neighbor_flat) {
+                        Some(neighbor_flat)
+                    } else if walkable_voxels.contains(&neighbor_below) {
+                        Some(neighbor_below)
+                    } else if can_descend_ramp && walkable_voxels.contains(&neighbor_two_below) {
+                        Some(neighbor_two_below)
+                    } else {
+                        None
+                    }
             }
 
             self.walkable_neighbors
@@ -1046,7 +1159,7 @@ mod tests {
 
         // Ordinary structure
         map_geometry
-            .add_structure(center, facing, &footprint, false, false, entity)
+            .add_structure(center, facing, &footprint, false, false, false, entity)
             .unwrap();
 
         assert_eq!(map_geometry.walkable_voxels(), cannot_walk);
@@ -1064,7 +1177,7 @@ mod tests {
 
         // Passable structure
         map_geometry
-            .add_structure(center, facing, &footprint, false, true, entity)
+            .add_structure(center, facing, &footprint, false, true, false, entity)
             .unwrap();
 
         assert_eq!(map_geometry.walkable_voxels(), can_walk_at_height_one);
@@ -1074,7 +1187,7 @@ mod tests {
 
         // Structure with roof
         map_geometry
-            .add_structure(center, facing, &footprint, true, false, entity)
+            .add_structure(center, facing, &footprint, true, false, false, entity)
             .unwrap();
 
         assert_eq!(map_geometry.walkable_voxels(), can_walk_at_height_two);
@@ -1144,6 +1257,7 @@ mod tests {
                 &footprint,
                 false,
                 true,
+                false,
                 Entity::from_bits(42),
             )
             .unwrap();
@@ -1186,6 +1300,7 @@ mod tests {
                 &footprint,
                 false,
                 false,
+                false,
                 Entity::from_bits(42),
             )
             .unwrap();
@@ -1257,6 +1372,7 @@ mod tests {
         };
         let can_walk_on_roof = false;
         let can_walk_through = false;
+        let is_ramp = false;
 
         map_geometry
             .add_structure(
@@ -1265,6 +1381,7 @@ mod tests {
                 &footprint,
                 can_walk_on_roof,
                 can_walk_through,
+                is_ramp,
                 structure_entity,
             )
             .unwrap();
@@ -1293,6 +1410,7 @@ mod tests {
         };
         let can_walk_on_roof = false;
         let can_walk_through = false;
+        let is_ramp = false;
 
         map_geometry
             .add_structure(
@@ -1301,6 +1419,7 @@ mod tests {
                 &footprint,
                 can_walk_on_roof,
                 can_walk_through,
+                is_ramp,
                 structure_entity,
             )
             .unwrap();
@@ -1312,4 +1431,242 @@ mod tests {
             assert_eq!(None, map_geometry.get_structure(voxel_pos));
         }
     }
+
+    #[test]
+    fn structures_must_be_supported() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 0);
+        let footprint = Footprint::single();
+        let facing = Facing::default();
+
+        let on_ground = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+        let on_roof = on_ground.above();
+
+        assert!(map_geometry.is_footprint_supported(on_ground, &footprint, facing));
+        assert!(!map_geometry.is_footprint_supported(on_roof, &footprint, facing));
+
+        // Structures without a walkable roof can't support other structures
+        map_geometry
+            .add_structure(
+                on_ground,
+                facing,
+                &footprint,
+                false,
+                false,
+                false,
+                Entity::from_bits(42),
+            )
+            .unwrap();
+        assert!(!map_geometry.is_footprint_supported(on_roof, &footprint, facing));
+        map_geometry.remove_structure(on_ground, &footprint, facing);
+
+        // But structures with a walkable roof can
+        map_geometry
+            .add_structure(
+                on_ground,
+                facing,
+                &footprint,
+                true,
+                false,
+                false,
+                Entity::from_bits(42),
+            )
+            .unwrap();
+        assert!(map_geometry.is_footprint_supported(on_roof, &footprint, facing));
+    }
+
+    #[test]
+    fn ramps_connect_heights() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let footprint = Footprint::single();
+        let facing = Facing::default();
+        let uphill = facing.direction;
+
+        // Build a two-story tower with a walkable roof next to the origin
+        let tower_hex = Hex::ZERO.neighbor(uphill);
+        for height in [DiscreteHeight(1), DiscreteHeight(2)] {
+            map_geometry
+                .add_structure(
+                    VoxelPos {
+                        hex: tower_hex,
+                        height,
+                    },
+                    facing,
+                    &footprint,
+                    true,
+                    false,
+                    false,
+                    Entity::from_bits(42),
+                )
+                .unwrap();
+        }
+
+        let ground = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+        let roof = VoxelPos {
+            hex: tower_hex,
+            height: DiscreteHeight(3),
+        };
+
+        // The roof is too high to reach without a ramp
+        assert_eq!(
+            map_geometry.walkable_neighbor_in_direction(ground, uphill),
+            None
+        );
+
+        map_geometry
+            .add_structure(
+                ground,
+                facing,
+                &footprint,
+                false,
+                true,
+                true,
+                Entity::from_bits(43),
+            )
+            .unwrap();
+
+        // Ramps can be climbed in the direction they are facing
+        assert_eq!(
+            map_geometry.walkable_neighbor_in_direction(ground, uphill),
+            Some(roof)
+        );
+
+        // And descended from the top
+        let downhill = uphill.clockwise().clockwise().clockwise();
+        assert_eq!(
+            map_geometry.walkable_neighbor_in_direction(roof, downhill),
+            Some(ground)
+        );
+    }
+
+    #[test]
+    fn upgrade_ghosts_take_priority_over_existing_structures() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 0);
+        let footprint = Footprint::single();
+        let facing = Facing::default();
+        let voxel_pos = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+
+        let structure_entity = Entity::from_bits(42);
+        let ghost_entity = Entity::from_bits(43);
+
+        map_geometry
+            .add_structure(
+                voxel_pos,
+                facing,
+                &footprint,
+                false,
+                false,
+                false,
+                structure_entity,
+            )
+            .unwrap();
+
+        // Upgrades can only be placed over the structure that they are upgrading
+        assert!(map_geometry
+            .add_upgrade_ghost(
+                Entity::from_bits(44),
+                facing,
+                voxel_pos,
+                &footprint,
+                ghost_entity
+            )
+            .is_err());
+
+        map_geometry
+            .add_upgrade_ghost(
+                structure_entity,
+                facing,
+                voxel_pos,
+                &footprint,
+                ghost_entity,
+            )
+            .unwrap();
+
+        assert_eq!(
+            map_geometry.get_structure(voxel_pos),
+            Some(structure_entity)
+        );
+        assert_eq!(
+            map_geometry.get_ghost_structure(voxel_pos),
+            Some(ghost_entity)
+        );
+        assert_eq!(map_geometry.get_workplace(voxel_pos), Some(ghost_entity));
+        assert_eq!(
+            map_geometry.get_candidate(voxel_pos, DeliveryMode::DropOff),
+            Some(ghost_entity)
+        );
+
+        assert_eq!(
+            map_geometry.remove_ghost_structure(voxel_pos, &footprint, facing),
+            Some(ghost_entity)
+        );
+        assert_eq!(map_geometry.get_ghost_structure(voxel_pos), None);
+        assert_eq!(
+            map_geometry.get_structure(voxel_pos),
+            Some(structure_entity)
+        );
+    }
+
+    #[test]
+    fn removing_a_structure_removes_its_upgrade_ghost() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 0);
+        let footprint = Footprint::single();
+        let facing = Facing::default();
+        let voxel_pos = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+
+        let structure_entity = Entity::from_bits(42);
+        let ghost_entity = Entity::from_bits(43);
+
+        map_geometry
+            .add_structure(
+                voxel_pos,
+                facing,
+                &footprint,
+                false,
+                false,
+                false,
+                structure_entity,
+            )
+            .unwrap();
+        map_geometry
+            .add_upgrade_ghost(
+                structure_entity,
+                facing,
+                voxel_pos,
+                &footprint,
+                ghost_entity,
+            )
+            .unwrap();
+        assert_eq!(
+            map_geometry.get_upgrade_ghost(voxel_pos),
+            Some(ghost_entity)
+        );
+
+        assert_eq!(
+            map_geometry.remove_structure(voxel_pos, &footprint, facing),
+            Some(structure_entity)
+        );
+        assert_eq!(map_geometry.get_upgrade_ghost(voxel_pos), None);
+        assert_eq!(map_geometry.get_ghost_structure(voxel_pos), None);
+        assert_eq!(map_geometry.get_workplace(voxel_pos), None);
+        assert_eq!(
+            map_geometry.get_candidate(voxel_pos, DeliveryMode::DropOff),
+            None
+        );
+    }
 }
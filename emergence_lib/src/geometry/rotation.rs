@@ -5,13 +5,14 @@ use core::fmt::Display;
 use derive_more::Display;
 use hexx::Direction;
 use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 use super::MAP_LAYOUT;
 
 /// The hex direction that this entity is facing.
 ///
 /// Stored as a component on each entity with a grid-aligned rotation.
-#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut, Serialize, Deserialize)]
 pub(crate) struct Facing {
     /// The desired direction.
     ///
@@ -1,6 +1,7 @@
 //! Data types and logic for working with hexagonal voxels (hexels).
 
 use bevy::prelude::*;
+use hexx::Direction;
 
 use crate::items::inventory::InventoryState;
 
@@ -35,6 +36,10 @@ pub enum VoxelKind {
         can_walk_on_roof: bool,
         /// Can units walk through this structure's voxel?
         can_walk_through: bool,
+        /// If this structure is a ramp, the direction in which it climbs.
+        ///
+        /// Units standing on a ramp can climb one extra step in this direction.
+        ramp_direction: Option<Direction>,
     },
     /// A structure that is slated to be built.
     GhostStructure,
@@ -87,6 +92,26 @@ impl VoxelKind {
         }
     }
 
+    /// Can structures be built in the voxel on top of this object?
+    pub(super) fn can_support_structures(&self) -> bool {
+        match self {
+            VoxelKind::Litter { .. } => false,
+            VoxelKind::Terrain => true,
+            VoxelKind::Structure {
+                can_walk_on_roof, ..
+            } => *can_walk_on_roof,
+            VoxelKind::GhostStructure => false,
+        }
+    }
+
+    /// The direction in which this object climbs, if it is a ramp.
+    pub(super) fn ramp_direction(&self) -> Option<Direction> {
+        match self {
+            VoxelKind::Structure { ramp_direction, .. } => *ramp_direction,
+            _ => None,
+        }
+    }
+
     /// Can objects be dropped off at this voxel?
     pub(crate) fn can_drop_off(&self) -> bool {
         match self {
@@ -11,6 +11,7 @@ use crate::{
     water::FlowVelocity,
 };
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
     render::render_resource::{Extent3d, TextureDimension, TextureFormat},
     utils::HashMap,
@@ -21,10 +22,12 @@ use crate::{
     asset_management::manifest::Id,
     enum_iter::IterableEnum,
     geometry::{Height, MapGeometry, VoxelPos},
-    graphics::palette::infovis::{WATER_TABLE_COLOR_HIGH, WATER_TABLE_COLOR_LOW},
+    graphics::palette::infovis::{
+        FERTILITY_COLOR_HIGH, FERTILITY_COLOR_LOW, WATER_TABLE_COLOR_HIGH, WATER_TABLE_COLOR_LOW,
+    },
     player_interaction::{selection::ObjectInteraction, InteractionSystem},
     signals::{SignalKind, SignalStrength, SignalType, Signals},
-    terrain::{terrain_assets::TerrainHandles, terrain_manifest::Terrain},
+    terrain::{fertility::Fertility, terrain_assets::TerrainHandles, terrain_manifest::Terrain},
     water::{PreviousWaterVolume, WaterDepth, WaterVolume},
 };
 
@@ -70,6 +73,8 @@ pub(crate) struct TileOverlay {
     light_level_color_ramp: HashMap<Illuminance, Handle<StandardMaterial>>,
     /// The materials used to visualize the net change in water volume.
     flux_color_ramp: Vec<Handle<StandardMaterial>>,
+    /// The materials used to visualize soil fertility.
+    fertility_color_ramp: Vec<Handle<StandardMaterial>>,
     /// The materials used to visualize vector fields.
     vector_field_materials: HashMap<DiscretizedVector, Handle<StandardMaterial>>,
     /// The images to be used to display the gradient in order to create a legend.
@@ -78,6 +83,8 @@ pub(crate) struct TileOverlay {
     water_table_legend: Handle<Image>,
     /// The image used to display the gradient for the net change in water volume.
     flux_legend: Handle<Image>,
+    /// The image used to display the gradient for soil fertility.
+    fertility_legend: Handle<Image>,
 }
 
 /// The type of information that is being visualized by the overlay.
@@ -100,6 +107,8 @@ pub(crate) enum OverlayType {
     NetWater,
     /// Shows the current light level of each tile.
     LightLevel,
+    /// Shows the fertility of the soil in each tile.
+    Fertility,
 }
 
 impl OverlayType {
@@ -166,6 +175,16 @@ impl FromWorld for TileOverlay {
         let mut image_assets = world.resource_mut::<Assets<Image>>();
         let flux_legend = image_assets.add(flux_legend_image);
 
+        // Fertility
+        let fertility_colors =
+            generate_color_gradient(FERTILITY_COLOR_LOW, FERTILITY_COLOR_HIGH, Self::N_COLORS);
+        let material_assets: &mut Assets<StandardMaterial> =
+            &mut world.resource_mut::<Assets<StandardMaterial>>();
+        let fertility_color_ramp = generate_color_ramp(&fertility_colors, material_assets);
+        let fertility_legend_image = generate_legend(&fertility_colors, Self::LEGEND_WIDTH);
+        let mut image_assets = world.resource_mut::<Assets<Image>>();
+        let fertility_legend = image_assets.add(fertility_legend_image);
+
         let material_assets: &mut Assets<StandardMaterial> =
             &mut world.resource_mut::<Assets<StandardMaterial>>();
 
@@ -192,11 +211,13 @@ impl FromWorld for TileOverlay {
             signal_color_ramps: color_ramps,
             water_table_color_ramp,
             flux_color_ramp,
+            fertility_color_ramp,
             light_level_color_ramp,
             vector_field_materials,
             signal_legends: legends,
             water_table_legend,
             flux_legend,
+            fertility_legend,
         }
     }
 }
@@ -429,6 +450,15 @@ impl TileOverlay {
         self.flux_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak()
     }
 
+    /// Gets the material that should be used to visualize the provided soil `fertility`.
+    fn get_fertility_material(&self, fertility: Fertility) -> Handle<StandardMaterial> {
+        let normalized_fertility = (fertility.0 / Fertility::MAX.0).clamp(0., 1.);
+
+        // Avoid indexing out of bounds by clamping to the maximum value for perfectly fertile soil
+        let color_index: usize = (normalized_fertility * Self::N_COLORS as f32) as usize;
+        self.fertility_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak()
+    }
+
     /// Gets the material that should be used to visualize the flow of water with the provided `flow_velocity`.
     pub(crate) fn get_flow_velocity_material(
         &self,
@@ -477,38 +507,61 @@ impl TileOverlay {
     pub(crate) fn flux_legend_image_handle(&self) -> Handle<Image> {
         self.flux_legend.clone_weak()
     }
-}
 
-/// Sets the material for the currently visualized map overlay.
-fn set_overlay_material(
-    mut overlay_query: Query<
-        (&VoxelPos, &mut Handle<StandardMaterial>, &mut Visibility),
-        With<Overlay>,
-    >,
-    terrain_query: Query<&ReceivedLight, With<Id<Terrain>>>,
-    water_depth_query: Query<&WaterDepth>,
-    water_volume_query: Query<(&WaterVolume, &PreviousWaterVolume)>,
-    terrain_pos_query: Query<&VoxelPos, With<Id<Terrain>>>,
-    flow_velocity_query: Query<&FlowVelocity>,
-    signals: Res<Signals>,
-    map_geometry: Res<MapGeometry>,
-    tile_overlay: Res<TileOverlay>,
-    time: Res<Time>,
-) {
-    if tile_overlay.overlay_type == OverlayType::None {
-        return;
+    /// Gets the handle to the material that should be used to display the legend for soil fertility.
+    pub(crate) fn fertility_legend_image_handle(&self) -> Handle<Image> {
+        self.fertility_legend.clone_weak()
     }
+}
 
-    for (&voxel_pos, mut overlay_material, mut overlay_visibility) in overlay_query.iter_mut() {
-        let maybe_material = match tile_overlay.overlay_type {
+/// The data needed to choose the overlay material for each tile.
+///
+/// This is shared between the overlay on the map and the minimap, which can show a different [`OverlayType`].
+#[derive(SystemParam)]
+pub(crate) struct OverlayMaterials<'w, 's> {
+    /// The light received by each tile of terrain.
+    terrain_query: Query<'w, 's, &'static ReceivedLight, With<Id<Terrain>>>,
+    /// The depth to the water table of each tile of terrain.
+    water_depth_query: Query<'w, 's, &'static WaterDepth>,
+    /// The current and previous water volume of each tile of terrain.
+    water_volume_query: Query<'w, 's, (&'static WaterVolume, &'static PreviousWaterVolume)>,
+    /// The position of each tile of terrain.
+    terrain_pos_query: Query<'w, 's, &'static VoxelPos, With<Id<Terrain>>>,
+    /// The flow velocity of water beneath each tile of terrain.
+    flow_velocity_query: Query<'w, 's, &'static FlowVelocity>,
+    /// The fertility of each tile of terrain.
+    fertility_query: Query<'w, 's, &'static Fertility>,
+    /// The signals present on the map.
+    signals: Res<'w, Signals>,
+    /// The locations of terrain entities.
+    map_geometry: Res<'w, MapGeometry>,
+    /// The color ramps used to visualize each overlay.
+    tile_overlay: Res<'w, TileOverlay>,
+    /// Used to measure rates of change.
+    time: Res<'w, Time>,
+}
+
+impl<'w, 's> OverlayMaterials<'w, 's> {
+    /// Gets the material that should be used to visualize `overlay_type` on the tile at `voxel_pos`, if any.
+    ///
+    /// If this is `None`, then there is nothing to show and the tile's overlay should be invisible.
+    pub(crate) fn get(
+        &self,
+        overlay_type: OverlayType,
+        voxel_pos: VoxelPos,
+    ) -> Option<Handle<StandardMaterial>> {
+        let tile_overlay = &self.tile_overlay;
+
+        match overlay_type {
             OverlayType::None => None,
             OverlayType::Single(signal_type) => {
                 // We must look at the voxel above the terrain to get the signal strength, as those are the voxels that units can walk in
-                let signal_strength = signals.get(signal_type, voxel_pos.above());
+                let signal_strength = self.signals.get(signal_type, voxel_pos.above());
                 let signal_kind = signal_type.into();
                 tile_overlay.get_signal_material(signal_kind, signal_strength)
             }
-            OverlayType::StrongestSignal => signals
+            OverlayType::StrongestSignal => self
+                .signals
                 // We must look at the voxel above the terrain to get the signal strength, as those are the voxels that units can walk in
                 .strongest_goal_signal_at_position(voxel_pos.above())
                 .and_then(|(signal_type, signal_strength)| {
@@ -516,15 +569,15 @@ fn set_overlay_material(
                     tile_overlay.get_signal_material(signal_kind, signal_strength)
                 }),
             OverlayType::DepthToWaterTable => {
-                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
-                let water_depth = *water_depth_query.get(terrain_entity).unwrap();
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let water_depth = *self.water_depth_query.get(terrain_entity).unwrap();
 
                 tile_overlay.get_water_table_material(water_depth)
             }
             OverlayType::HeightOfWaterTable => {
-                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
-                let water_depth = *water_depth_query.get(terrain_entity).unwrap();
-                let terrain_height = terrain_pos_query.get(terrain_entity).unwrap().height();
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let water_depth = *self.water_depth_query.get(terrain_entity).unwrap();
+                let terrain_height = self.terrain_pos_query.get(terrain_entity).unwrap().height();
                 let water_table_height = water_depth.water_table_height(terrain_height);
 
                 // FIXME: use a dedicated color ramp for this, rather than hacking it
@@ -536,30 +589,52 @@ fn set_overlay_material(
                 tile_overlay.get_water_table_material(inverted_height)
             }
             OverlayType::VelocityOfWaterTable => {
-                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
-                let flow_velocity = flow_velocity_query.get(terrain_entity).unwrap();
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let flow_velocity = self.flow_velocity_query.get(terrain_entity).unwrap();
 
                 tile_overlay.get_flow_velocity_material(flow_velocity)
             }
             OverlayType::NetWater => {
-                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
                 let (current_water_volume, previous_water_volume) =
-                    water_volume_query.get(terrain_entity).unwrap();
+                    self.water_volume_query.get(terrain_entity).unwrap();
 
                 let net_water = *current_water_volume - previous_water_volume.0;
-                let volume_per_second = net_water.volume() / time.delta().as_secs_f32();
+                let volume_per_second = net_water.volume() / self.time.delta().as_secs_f32();
 
                 Some(tile_overlay.get_water_flux_material(volume_per_second))
             }
             OverlayType::LightLevel => {
-                let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
-                let received_light = terrain_query.get(terrain_entity).unwrap();
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let received_light = self.terrain_query.get(terrain_entity).unwrap();
 
                 tile_overlay.get_light_level_material(received_light)
             }
-        };
+            OverlayType::Fertility => {
+                let terrain_entity = self.map_geometry.get_terrain(voxel_pos.hex).unwrap();
+                let &fertility = self.fertility_query.get(terrain_entity).unwrap();
+
+                Some(tile_overlay.get_fertility_material(fertility))
+            }
+        }
+    }
+}
 
-        match maybe_material {
+/// Sets the material for the currently visualized map overlay.
+fn set_overlay_material(
+    mut overlay_query: Query<
+        (&VoxelPos, &mut Handle<StandardMaterial>, &mut Visibility),
+        With<Overlay>,
+    >,
+    overlay_materials: OverlayMaterials,
+    tile_overlay: Res<TileOverlay>,
+) {
+    if tile_overlay.overlay_type == OverlayType::None {
+        return;
+    }
+
+    for (&voxel_pos, mut overlay_material, mut overlay_visibility) in overlay_query.iter_mut() {
+        match overlay_materials.get(tile_overlay.overlay_type, voxel_pos) {
             Some(material) => {
                 *overlay_visibility = Visibility::Visible;
                 *overlay_material = material;
@@ -766,7 +841,7 @@ impl DiscretizedMagnitude {
 
 /// A marker component for overlay entities.
 #[derive(Debug, Clone, Copy, Component)]
-struct Overlay;
+pub(crate) struct Overlay;
 
 /// The components used by an entity that is used to visualize spatial information about the world.
 #[derive(Bundle)]
@@ -110,6 +110,10 @@ pub(crate) mod infovis {
                 SignalKind::Work => 300.,
                 // Red
                 SignalKind::Demolish => 0.,
+                // Pink
+                SignalKind::Repair => 330.,
+                // Indigo
+                SignalKind::Visit => 260.,
                 // Blue
                 SignalKind::Unit => 220.,
             }
@@ -149,6 +153,11 @@ pub(crate) mod infovis {
     /// The color used to indicate that water is near the surface.
     pub(crate) const WATER_TABLE_COLOR_LOW: Color = Color::hsla(195., 0.7, 0.2, OVERLAY_ALPHA);
 
+    /// The color used to indicate that soil is very fertile.
+    pub(crate) const FERTILITY_COLOR_HIGH: Color = Color::hsla(100., 0.6, 0.35, OVERLAY_ALPHA);
+    /// The color used to indicate that soil is barren.
+    pub(crate) const FERTILITY_COLOR_LOW: Color = Color::hsla(35., 0.3, 0.75, OVERLAY_ALPHA);
+
     impl Illuminance {
         /// The color used to describe the illuminance of a tile.
         pub(crate) fn info_vis_color(&self) -> Color {
@@ -215,6 +224,26 @@ pub(crate) mod lighting {
     pub(crate) const LIGHT_STARS: Color = Color::WHITE;
 }
 
+/// Colors used by the minimap
+pub(crate) mod minimap {
+    use bevy::prelude::Color;
+
+    /// The color of the least fertile terrain
+    pub(crate) const BARREN_TERRAIN: Color = Color::hsl(35., 0.3, 0.6);
+
+    /// The color of the most fertile terrain
+    pub(crate) const FERTILE_TERRAIN: Color = Color::hsl(100., 0.5, 0.35);
+
+    /// The color of deep water, including the ocean
+    pub(crate) const DEEP_WATER: Color = Color::hsl(202., 0.7, 0.35);
+
+    /// The color of tiles with a structure on them
+    pub(crate) const STRUCTURE: Color = Color::hsl(0., 0., 0.85);
+
+    /// The color of the outline of the area that the camera can see
+    pub(crate) const CAMERA_VIEW: Color = Color::hsl(55., 0.9, 0.6);
+}
+
 /// Colors used in the UI
 pub(crate) mod ui {
     use bevy::prelude::Color;
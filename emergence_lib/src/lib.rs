@@ -23,6 +23,7 @@ pub mod player_interaction;
 pub mod signals;
 pub mod simulation;
 pub mod structures;
+pub mod technology;
 pub mod terrain;
 pub mod ui;
 pub mod units;
@@ -2,6 +2,7 @@
 
 use bevy::prelude::*;
 use core::fmt::Display;
+use std::f32::consts::PI;
 
 use emergence_macros::IterableEnum;
 use serde::{Deserialize, Serialize};
@@ -23,12 +24,19 @@ pub(super) struct LightPlugin;
 
 impl Plugin for LightPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TotalLight>().add_systems(
-            FixedUpdate,
-            (compute_light, compute_shade, compute_received_light)
-                .chain()
-                .in_set(SimulationSet),
-        );
+        app.init_resource::<TotalLight>()
+            .init_resource::<SunPosition>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    compute_sun_position,
+                    compute_light,
+                    compute_shade,
+                    compute_received_light,
+                )
+                    .chain()
+                    .in_set(SimulationSet),
+            );
     }
 }
 
@@ -42,6 +50,75 @@ impl Display for TotalLight {
     }
 }
 
+/// The position of the sun in the sky, as seen from the map.
+///
+/// This is used to determine which tiles are shaded by taller terrain and structures.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SunPosition {
+    /// The compass direction of the sun, in radians.
+    ///
+    /// The sun rises at 0 (east, or `+x`), passes through PI / 2 (south, or `-z`) at noon and sets at PI (west, or `-x`).
+    pub(crate) azimuth: f32,
+    /// The angle of the sun above the horizon, in radians.
+    ///
+    /// This is 0 or less when the sun is not up.
+    pub(crate) elevation: f32,
+}
+
+impl Default for SunPosition {
+    fn default() -> Self {
+        SunPosition::from_fraction_of_day(0.35)
+    }
+}
+
+impl SunPosition {
+    /// The elevation of the sun at noon, in radians.
+    ///
+    /// In real life this changes with latitude and season.
+    const NOON_ELEVATION: f32 = PI * 65. / 180.;
+
+    /// The elevation below which the sun only provides dim light, in radians.
+    const TWILIGHT_ELEVATION: f32 = PI * 12. / 180.;
+
+    /// Computes the position of the sun at the provided `fraction_of_day`.
+    ///
+    /// See [`InGameTime::fraction_of_day`] for how this value is defined.
+    pub(crate) fn from_fraction_of_day(fraction_of_day: f32) -> Self {
+        match TimeOfDay::from_fraction_of_day(fraction_of_day) {
+            TimeOfDay::Day => {
+                let fraction_of_daylight = fraction_of_day / TimeOfDay::DAYLIGHT_FRACTION;
+
+                SunPosition {
+                    azimuth: PI * fraction_of_daylight,
+                    elevation: SunPosition::NOON_ELEVATION * (PI * fraction_of_daylight).sin(),
+                }
+            }
+            TimeOfDay::Night => SunPosition {
+                azimuth: PI,
+                elevation: 0.,
+            },
+        }
+    }
+
+    /// Is the sun above the horizon?
+    pub(crate) fn is_up(&self) -> bool {
+        self.elevation > 0.
+    }
+
+    /// The direction that shadows are cast in, as a unit vector in the horizontal plane.
+    ///
+    /// The `x` and `y` coordinates of this vector correspond to the `x` and `z` coordinates of world space respectively.
+    pub(crate) fn shadow_direction(&self) -> Vec2 {
+        // Shadows point directly away from the sun
+        -Vec2::new(self.azimuth.cos(), -self.azimuth.sin())
+    }
+
+    /// How much a shadow falls for each unit of horizontal distance travelled away from its caster.
+    pub(crate) fn shadow_slope(&self) -> f32 {
+        self.elevation.tan()
+    }
+}
+
 /// A qualitative measurement of light intensity.
 #[derive(
     Debug,
@@ -76,16 +153,22 @@ impl Display for Illuminance {
     }
 }
 
-/// Computes the amount of light available based on the weather and time of day.
+/// Tracks the position of the sun over the course of the day.
+fn compute_sun_position(in_game_time: Res<InGameTime>, mut sun_position: ResMut<SunPosition>) {
+    *sun_position = SunPosition::from_fraction_of_day(in_game_time.fraction_of_day());
+}
+
+/// Computes the amount of light available based on the weather and the position of the sun.
 fn compute_light(
-    in_game_time: Res<InGameTime>,
+    sun_position: Res<SunPosition>,
     current_weather: Res<CurrentWeather>,
     mut total_light: ResMut<TotalLight>,
 ) {
-    let time_of_day = in_game_time.time_of_day();
-
-    total_light.0 = if time_of_day == TimeOfDay::Night {
+    total_light.0 = if !sun_position.is_up() {
         Illuminance::Dark
+    } else if sun_position.elevation < SunPosition::TWILIGHT_ELEVATION {
+        // The sun is low in the sky around dawn and dusk
+        Illuminance::DimlyLit
     } else {
         match current_weather.get() {
             Weather::Clear => Illuminance::BrightlyLit,
@@ -94,3 +177,35 @@ fn compute_light(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_is_highest_at_noon() {
+        let noon = SunPosition::from_fraction_of_day(TimeOfDay::DAYLIGHT_FRACTION / 2.);
+        let morning = SunPosition::from_fraction_of_day(TimeOfDay::DAYLIGHT_FRACTION / 4.);
+
+        assert!((noon.elevation - SunPosition::NOON_ELEVATION).abs() < 1e-4);
+        assert!(morning.elevation < noon.elevation);
+        assert!(morning.is_up());
+    }
+
+    #[test]
+    fn sun_is_down_at_night() {
+        let midnight = SunPosition::from_fraction_of_day(0.9);
+        assert!(!midnight.is_up());
+    }
+
+    #[test]
+    fn shadows_point_away_from_the_sun() {
+        // In the morning, the sun is in the east, so shadows point west
+        let morning = SunPosition::from_fraction_of_day(0.01);
+        assert!(morning.shadow_direction().x < 0.);
+
+        // In the evening, the sun is in the west, so shadows point east
+        let evening = SunPosition::from_fraction_of_day(TimeOfDay::DAYLIGHT_FRACTION - 0.01);
+        assert!(evening.shadow_direction().x > 0.);
+    }
+}
@@ -1,13 +1,10 @@
 //! Shade is cast by structures and terrain based on their height and the position of the sun.
 
-use crate::{
-    geometry::{DiscreteHeight, MapGeometry, VoxelPos},
-    simulation::time::{InGameTime, TimeOfDay},
-};
-use bevy::prelude::*;
+use crate::geometry::{DiscreteHeight, MapGeometry, MAP_LAYOUT};
+use bevy::{prelude::*, utils::HashMap};
 use hexx::Hex;
 
-use super::{Illuminance, TotalLight};
+use super::{Illuminance, SunPosition, TotalLight};
 
 use std::fmt::Display;
 
@@ -69,49 +66,83 @@ impl Display for ReceivedLight {
 }
 
 /// Computes the amount of shade on each tile.
+///
+/// Each column of light-blocking voxels casts a shadow directly away from the sun.
+/// The shadow falls as it travels, at a rate set by the elevation of the sun,
+/// and shades any terrain whose surface lies underneath it.
+/// Each distinct column that shades a tile adds another level of [`Shade`].
 pub(super) fn compute_shade(
     mut shade_query: Query<&mut Shade>,
     map_geometry: Res<MapGeometry>,
-    in_game_time: Res<InGameTime>,
+    sun_position: Res<SunPosition>,
 ) {
     // PERF: we can be much less aggressive about computing these values
-    // They only need to be recomputed when the map geometry changes, or when the time of day changes
+    // They only need to be recomputed when the map geometry changes, or when the sun moves significantly
 
-    /// The unit vector pointing away from the sun.
-    const SHADOW_DIRECTION: Hex = Hex { x: 0, y: 1 };
+    /// The maximum distance that a shadow can be cast, in world units.
+    ///
+    /// This prevents shadows from stretching across the entire map at dawn and dusk.
+    const MAX_SHADOW_LENGTH: f32 = 8.;
+
+    /// The distance travelled along the shadow between each sample, in world units.
+    ///
+    /// This must be small enough relative to the size of a hex that no tiles are skipped.
+    const SAMPLE_DISTANCE: f32 = 0.5;
 
     // Reset the shade for all tiles
     for mut shade in shade_query.iter_mut() {
         *shade = Shade::FullSun;
     }
 
-    if in_game_time.time_of_day() == TimeOfDay::Night {
+    if !sun_position.is_up() {
         return;
     }
 
+    // Find the top of the tallest light-blocking voxel in each column
+    let mut casters: HashMap<Hex, DiscreteHeight> = HashMap::new();
     for (voxel_pos, voxel_data) in map_geometry.all_voxels() {
         if !voxel_data.object_kind.blocks_light() {
             continue;
         }
 
-        let mut i = 0;
+        let caster_height = casters.entry(voxel_pos.hex).or_insert(voxel_pos.height);
+        *caster_height = caster_height.max(voxel_pos.height);
+    }
+
+    let shadow_direction = sun_position.shadow_direction();
+    let shadow_slope = sun_position.shadow_slope();
+
+    for (&caster_hex, &caster_height) in casters.iter() {
+        let origin = MAP_LAYOUT.hex_to_world_pos(caster_hex);
+        let mut previous_hex = caster_hex;
+        let mut distance = SAMPLE_DISTANCE;
 
-        while DiscreteHeight(i) < voxel_pos.height {
-            let current_height = voxel_pos.height - DiscreteHeight(i);
-            let current_hex = SHADOW_DIRECTION * i as i32;
+        while distance <= MAX_SHADOW_LENGTH {
+            let shadow_height = caster_height.into_world_pos() - distance * shadow_slope;
+            if shadow_height <= 0. {
+                break;
+            }
+
+            let shaded_hex = MAP_LAYOUT.world_pos_to_hex(origin + shadow_direction * distance);
+            distance += SAMPLE_DISTANCE;
+
+            // Don't shade the same tile twice from the same caster
+            if shaded_hex == previous_hex {
+                continue;
+            }
+            previous_hex = shaded_hex;
 
-            let shaded_voxel = VoxelPos {
-                hex: current_hex,
-                height: current_height,
+            let Ok(terrain_height) = map_geometry.get_height(shaded_hex) else {
+                // The shadow has left the map
+                break;
             };
 
-            if let Some(voxel_data) = map_geometry.get_voxel(shaded_voxel) {
-                let entity = voxel_data.entity;
-                if let Ok(mut shade) = shade_query.get_mut(entity) {
+            if terrain_height.into_world_pos() < shadow_height {
+                let terrain_entity = map_geometry.get_terrain(shaded_hex).unwrap();
+                if let Ok(mut shade) = shade_query.get_mut(terrain_entity) {
                     shade.add_shade();
                 }
             }
-            i += 1;
         }
     }
 }
@@ -13,6 +13,7 @@ use crate::geometry::MAP_LAYOUT;
 use crate::items::inventory::InventoryState;
 use crate::items::item_manifest::Item;
 use crate::items::ItemCount;
+use crate::organisms::genetics::Genome;
 use crate::terrain::terrain_assets::TerrainHandles;
 use crate::{
     crafting::{inventories::StorageInventory, item_tags::ItemKind},
@@ -44,22 +45,30 @@ struct LitterBundle {
 #[derive(Component, Clone, Debug, Deref, DerefMut)]
 pub(crate) struct Litter {
     /// The items that are littered on the ground.
+    #[deref]
     pub(crate) contents: StorageInventory,
+    /// The genome of the organism that produced these items, if known.
+    ///
+    /// Seeds that sprout from this litter inherit this genome.
+    pub(crate) parent_genome: Option<Genome>,
 }
 
 impl Litter {
-    /// Creates a new litter inventory with a single item.
+    /// Creates a new litter inventory with a single item, produced by an organism with the provided `parent_genome`.
     ///
     /// # Panics
     ///
     /// Panics if the stack size is 0 or the item is not found in the manifest.
-    fn new(item_id: Id<Item>, item_manifest: &ItemManifest) -> Self {
+    fn new(item_id: Id<Item>, parent_genome: Option<Genome>, item_manifest: &ItemManifest) -> Self {
         let mut contents = StorageInventory::new(1, None);
         contents
             .add_item_all_or_nothing(&ItemCount { item_id, count: 1 }, item_manifest)
             .unwrap();
 
-        Litter { contents }
+        Litter {
+            contents,
+            parent_genome,
+        }
     }
 }
 
@@ -85,6 +94,7 @@ impl Default for Litter {
     fn default() -> Self {
         Litter {
             contents: StorageInventory::new(1, None),
+            parent_genome: None,
         }
     }
 }
@@ -294,32 +304,40 @@ pub(crate) trait LitterCommandsExt {
     /// Spawns a piece of litter at the given position out of the provided `item`.
     /// This should be called once for each item to be turned into litter.
     ///
+    /// If the item was produced by an organism, its `parent_genome` should be provided so seeds can inherit from it.
+    ///
     /// This will never fail; if the position is invalid, the litter will be spawned at the nearest valid position.
-    fn spawn_litter(&mut self, position: VoxelPos, item: Id<Item>);
+    fn spawn_litter(&mut self, position: VoxelPos, item: Id<Item>, parent_genome: Option<Genome>);
 }
 
 impl LitterCommandsExt for Commands<'_, '_> {
-    fn spawn_litter(&mut self, position: VoxelPos, item: Id<Item>) {
+    fn spawn_litter(&mut self, position: VoxelPos, item: Id<Item>, parent_genome: Option<Genome>) {
         self.add(SpawnLitterCommand {
             voxel_pos: position,
             item,
+            parent_genome,
         })
     }
 }
 
 /// A custom [`Command`] that spawns a litter entity.
-struct SpawnLitterCommand {
+///
+/// This is usually added via [`LitterCommandsExt::spawn_litter`],
+/// but can be applied directly from inside of other commands.
+pub(crate) struct SpawnLitterCommand {
     /// The position to try spawn the litter at
-    voxel_pos: VoxelPos,
+    pub(crate) voxel_pos: VoxelPos,
     /// The type of item that is being turned into litter.
-    item: Id<Item>,
+    pub(crate) item: Id<Item>,
+    /// The genome of the organism that produced the item, if known.
+    pub(crate) parent_genome: Option<Genome>,
 }
 
 impl Command for SpawnLitterCommand {
     fn apply(self, world: &mut World) {
         let item_manifest = world.resource::<ItemManifest>();
 
-        let litter = Litter::new(self.item, item_manifest);
+        let litter = Litter::new(self.item, self.parent_genome, item_manifest);
 
         let terrain_handles = world.resource::<TerrainHandles>();
         let litter_models = &terrain_handles.litter_models;
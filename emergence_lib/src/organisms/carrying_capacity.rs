@@ -0,0 +1,211 @@
+//! Organisms compete with their neighbors, limiting how densely they can pack themselves into an area.
+//!
+//! Without this, vegetative spread and seeds would keep filling every empty tile,
+//! and wild flora would saturate the map.
+//! Instead, the chance that an organism reproduces falls as its surroundings grow more crowded:
+//! - organisms of the same species compete for the same niche, up to a local density limit
+//! - organisms whose [`RootZone`]s overlap split the water beneath them
+//! - shaded tiles receive less light, starving any seedlings that sprout there
+//!
+//! Together, these form a density-dependent feedback that allows wild populations to settle into a stable size.
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
+use hexx::shapes::hexagon;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    light::shade::Shade,
+    structures::structure_manifest::{Structure, StructureManifest},
+    water::roots::RootZone,
+};
+
+use super::Organism;
+
+/// Limits the local density of a species of structure.
+///
+/// All life stages of a species count towards its density.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CarryingCapacity {
+    /// The distance, in tiles, within which organisms of the same species compete with each other.
+    pub radius: u32,
+    /// The number of organisms of the same species within `radius` at which reproduction stops entirely.
+    pub max_density: u32,
+}
+
+impl CarryingCapacity {
+    /// The chance that an organism with this carrying capacity successfully reproduces.
+    ///
+    /// `density` is the number of organisms of the same species nearby,
+    /// `root_competitors` is the number of other organisms whose roots overlap with this one,
+    /// and `shade` is the shade on the tile where the offspring would grow.
+    pub(crate) fn reproduction_chance(
+        &self,
+        density: u32,
+        root_competitors: u32,
+        shade: &Shade,
+    ) -> f32 {
+        let density_factor = 1. - density as f32 / self.max_density.max(1) as f32;
+
+        // Water is split evenly between all of the roots that can reach it
+        let water_factor = 1. / (1 + root_competitors) as f32;
+
+        let light_factor = match shade {
+            Shade::FullSun => 1.0,
+            Shade::PartialSun => 0.5,
+            Shade::FullShade => 0.25,
+        };
+
+        (density_factor * water_factor * light_factor).clamp(0., 1.)
+    }
+}
+
+/// Measures how crowded the area around a structure is.
+#[derive(SystemParam)]
+pub(crate) struct CrowdingQuery<'w, 's> {
+    /// The living structures that might compete with each other.
+    organism_query: Query<'w, 's, &'static Id<Structure>, With<Organism>>,
+    /// The shade on each tile of terrain.
+    shade_query: Query<'w, 's, &'static Shade>,
+}
+
+impl<'w, 's> CrowdingQuery<'w, 's> {
+    /// The chance that a structure of type `structure_id` successfully reproduces into `voxel_pos`.
+    ///
+    /// Structures without a [`CarryingCapacity`] always succeed.
+    pub(crate) fn reproduction_chance(
+        &self,
+        structure_id: Id<Structure>,
+        voxel_pos: VoxelPos,
+        structure_manifest: &StructureManifest,
+        map_geometry: &MapGeometry,
+    ) -> f32 {
+        let structure_data = structure_manifest.get(structure_id);
+        let Some(carrying_capacity) = &structure_data.carrying_capacity else {
+            return 1.0;
+        };
+
+        // Different life stages of the same species compete for the same niche
+        let species_of = |structure_id: Id<Structure>| {
+            structure_manifest
+                .get(structure_id)
+                .organism_variety
+                .as_ref()
+                .map(|variety| variety.prototypical_form)
+        };
+        let species = species_of(structure_id);
+
+        let density = self
+            .organisms_within(voxel_pos, carrying_capacity.radius, map_geometry)
+            .filter(|&(_, &neighbor_id)| {
+                neighbor_id == structure_id || species_of(neighbor_id) == species
+            })
+            .count() as u32;
+
+        let root_competitors = match &structure_data.root_zone {
+            Some(root_zone) => {
+                self.root_competitors(voxel_pos, root_zone, structure_manifest, map_geometry)
+            }
+            None => 0,
+        };
+
+        let shade = map_geometry
+            .get_terrain(voxel_pos.hex)
+            .ok()
+            .and_then(|terrain_entity| self.shade_query.get(terrain_entity).ok())
+            .cloned()
+            .unwrap_or_default();
+
+        carrying_capacity.reproduction_chance(density, root_competitors, &shade)
+    }
+
+    /// The number of living structures whose root zones overlap with a `root_zone` centered on `voxel_pos`.
+    fn root_competitors(
+        &self,
+        voxel_pos: VoxelPos,
+        root_zone: &RootZone,
+        structure_manifest: &StructureManifest,
+        map_geometry: &MapGeometry,
+    ) -> u32 {
+        // Root zones that are larger than ours can still overlap from further away,
+        // but we only look as far as our own roots could possibly reach to keep this cheap
+        self.organisms_within(voxel_pos, root_zone.radius * 2, map_geometry)
+            .filter(|&(neighbor_pos, &neighbor_id)| {
+                structure_manifest
+                    .get(neighbor_id)
+                    .root_zone
+                    .as_ref()
+                    .is_some_and(|neighbor_root_zone| {
+                        voxel_pos.hex.unsigned_distance_to(neighbor_pos.hex)
+                            <= root_zone.radius + neighbor_root_zone.radius
+                    })
+            })
+            .count() as u32
+    }
+
+    /// Returns each living structure within `radius` of `voxel_pos`, along with the position of its tile.
+    ///
+    /// Structures that take up multiple tiles are only counted once.
+    fn organisms_within<'a>(
+        &'a self,
+        voxel_pos: VoxelPos,
+        radius: u32,
+        map_geometry: &'a MapGeometry,
+    ) -> impl Iterator<Item = (VoxelPos, &'a Id<Structure>)> + 'a {
+        let mut seen = HashSet::new();
+
+        hexagon(voxel_pos.hex, radius).filter_map(move |hex| {
+            let height = map_geometry.get_height(hex).ok()?;
+            let neighbor_pos = VoxelPos {
+                hex,
+                height: height.above(),
+            };
+            let entity = map_geometry.get_structure(neighbor_pos)?;
+            if !seen.insert(entity) {
+                return None;
+            }
+
+            let structure_id = self.organism_query.get(entity).ok()?;
+            Some((neighbor_pos, structure_id))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crowding_reduces_reproduction() {
+        let carrying_capacity = CarryingCapacity {
+            radius: 2,
+            max_density: 4,
+        };
+
+        let empty = carrying_capacity.reproduction_chance(0, 0, &Shade::FullSun);
+        let crowded = carrying_capacity.reproduction_chance(2, 0, &Shade::FullSun);
+        let full = carrying_capacity.reproduction_chance(4, 0, &Shade::FullSun);
+        let overfull = carrying_capacity.reproduction_chance(6, 0, &Shade::FullSun);
+
+        assert_eq!(empty, 1.0);
+        assert_eq!(crowded, 0.5);
+        assert_eq!(full, 0.0);
+        assert_eq!(overfull, 0.0);
+    }
+
+    #[test]
+    fn competition_for_water_and_light_reduces_reproduction() {
+        let carrying_capacity = CarryingCapacity {
+            radius: 2,
+            max_density: 4,
+        };
+
+        let sunny = carrying_capacity.reproduction_chance(0, 0, &Shade::FullSun);
+        let shady = carrying_capacity.reproduction_chance(0, 0, &Shade::FullShade);
+        let thirsty = carrying_capacity.reproduction_chance(0, 1, &Shade::FullSun);
+
+        assert!(shady < sunny);
+        assert_eq!(thirsty, 0.5);
+    }
+}
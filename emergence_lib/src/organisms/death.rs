@@ -0,0 +1,434 @@
+//! Organisms die for a variety of reasons, leaving behind their remains and anything they were carrying.
+
+use bevy::{ecs::system::Command, prelude::*};
+use core::fmt::Display;
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::inventories::{InputInventory, OutputInventory, StorageInventory},
+    geometry::VoxelPos,
+    items::item_manifest::Item,
+    litter::SpawnLitterCommand,
+    structures::{
+        commands::DespawnStructureCommand,
+        structure_manifest::{Structure, StructureManifest},
+    },
+    units::{
+        item_interaction::UnitInventory,
+        unit_manifest::{Unit, UnitManifest},
+    },
+};
+
+use super::{genetics::Genome, OrganismId};
+
+/// Sent whenever an organism dies.
+///
+/// This is sent after the organism has been despawned, and its remains have been dropped.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct OrganismDied {
+    /// The entity that died.
+    ///
+    /// This entity no longer exists.
+    pub entity: Entity,
+    /// The variety of organism that died.
+    pub organism_id: OrganismId,
+    /// Where the organism died.
+    pub voxel_pos: VoxelPos,
+    /// Why the organism died.
+    pub cause: CauseOfDeath,
+}
+
+/// The reason that an organism died.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CauseOfDeath {
+    /// The organism ran out of energy.
+    Starvation,
+    /// The organism ran out of oxygen.
+    Drowning,
+    /// The organism lived past its maximum age.
+    OldAge,
+    /// The organism was removed by a worker.
+    Demolition,
+    /// The organism was eaten by another organism.
+    Predation,
+    /// The organism was killed by a disease.
+    Disease,
+}
+
+impl Display for CauseOfDeath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            CauseOfDeath::Starvation => "Starvation",
+            CauseOfDeath::Drowning => "Drowning",
+            CauseOfDeath::OldAge => "Old age",
+            CauseOfDeath::Demolition => "Demolition",
+            CauseOfDeath::Predation => "Predation",
+            CauseOfDeath::Disease => "Disease",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// An extension trait for [`Commands`] for killing and removing organisms.
+pub(crate) trait DeathCommandsExt {
+    /// Kills the organism `entity` due to `cause`.
+    ///
+    /// Its remains and anything it was holding are dropped as litter,
+    /// and an [`OrganismDied`] event is sent.
+    /// Has no effect if the entity has already been despawned.
+    fn kill_organism(&mut self, entity: Entity, cause: CauseOfDeath);
+
+    /// Removes the organism `entity` without killing it, such as when it transforms into a new form.
+    ///
+    /// Anything it was holding is dropped as litter, but it leaves no remains.
+    /// Has no effect if the entity has already been despawned.
+    fn despawn_organism(&mut self, entity: Entity);
+}
+
+impl DeathCommandsExt for Commands<'_, '_> {
+    fn kill_organism(&mut self, entity: Entity, cause: CauseOfDeath) {
+        self.add(KillOrganismCommand { entity, cause });
+    }
+
+    fn despawn_organism(&mut self, entity: Entity) {
+        self.add(DespawnOrganismCommand { entity });
+    }
+}
+
+/// A [`Command`] used to kill organisms.
+///
+/// This is usually added via [`DeathCommandsExt::kill_organism`],
+/// but can be applied directly from inside of other commands.
+pub(crate) struct KillOrganismCommand {
+    /// The organism to kill.
+    pub(crate) entity: Entity,
+    /// Why the organism died.
+    pub(crate) cause: CauseOfDeath,
+}
+
+impl Command for KillOrganismCommand {
+    fn apply(self, world: &mut World) {
+        // Organisms can die of several causes in the same tick
+        let Some(entity_ref) = world.get_entity(self.entity) else {
+            return;
+        };
+
+        let Some(&voxel_pos) = entity_ref.get::<VoxelPos>() else {
+            return;
+        };
+
+        let organism_id = if let Some(&structure_id) = entity_ref.get::<Id<Structure>>() {
+            OrganismId::Structure(structure_id)
+        } else if let Some(&unit_id) = entity_ref.get::<Id<Unit>>() {
+            OrganismId::Unit(unit_id)
+        } else {
+            return;
+        };
+
+        // The organism's remains and produce are its offspring, but anything else it held came from elsewhere
+        let genome = entity_ref.get::<Genome>().cloned();
+        let mut dropped_items: Vec<(Id<Item>, Option<Genome>)> = Vec::new();
+
+        let remains = match organism_id {
+            OrganismId::Structure(structure_id) => world
+                .resource::<StructureManifest>()
+                .get(structure_id)
+                .organism_variety
+                .as_ref()
+                .map(|variety| variety.remains.clone())
+                .unwrap_or_default(),
+            OrganismId::Unit(unit_id) => world
+                .resource::<UnitManifest>()
+                .get(unit_id)
+                .organism_variety
+                .remains
+                .clone(),
+        };
+
+        for (item_id, count) in remains {
+            dropped_items.extend(std::iter::repeat((item_id, genome.clone())).take(count as usize));
+        }
+        dropped_items.extend(carried_items(entity_ref));
+
+        despawn_and_drop(world, self.entity, organism_id, voxel_pos, dropped_items);
+
+        world.send_event(OrganismDied {
+            entity: self.entity,
+            organism_id,
+            voxel_pos,
+            cause: self.cause,
+        });
+    }
+}
+
+/// A [`Command`] used to remove organisms from the world without killing them.
+///
+/// This is usually added via [`DeathCommandsExt::despawn_organism`].
+pub(crate) struct DespawnOrganismCommand {
+    /// The organism to remove.
+    pub(crate) entity: Entity,
+}
+
+impl Command for DespawnOrganismCommand {
+    fn apply(self, world: &mut World) {
+        let Some(entity_ref) = world.get_entity(self.entity) else {
+            return;
+        };
+
+        let Some(&voxel_pos) = entity_ref.get::<VoxelPos>() else {
+            return;
+        };
+
+        let Some(organism_id) = OrganismId::from_components(
+            entity_ref.get::<Id<Structure>>(),
+            entity_ref.get::<Id<Unit>>(),
+        ) else {
+            return;
+        };
+
+        let dropped_items = carried_items(entity_ref);
+        despawn_and_drop(world, self.entity, organism_id, voxel_pos, dropped_items);
+    }
+}
+
+/// The items that the organism `entity_ref` is holding or storing, paired with the genome of their parent organism.
+///
+/// Produce in its output inventory is the organism's offspring, but anything else it held came from elsewhere.
+fn carried_items(entity_ref: EntityRef) -> Vec<(Id<Item>, Option<Genome>)> {
+    let genome = entity_ref.get::<Genome>();
+    let mut carried_items: Vec<(Id<Item>, Option<Genome>)> = Vec::new();
+
+    if let Some(unit_inventory) = entity_ref.get::<UnitInventory>() {
+        if let Some(held_item) = unit_inventory.held_item {
+            carried_items.push((held_item, unit_inventory.held_item_parent.clone()));
+        }
+    }
+
+    let output_slots = entity_ref
+        .get::<OutputInventory>()
+        .into_iter()
+        .flat_map(|output| output.iter());
+
+    for item_slot in output_slots {
+        carried_items.extend(
+            std::iter::repeat((item_slot.item_id(), genome.cloned()))
+                .take(item_slot.count() as usize),
+        );
+    }
+
+    let input_slots = entity_ref
+        .get::<InputInventory>()
+        .into_iter()
+        .flat_map(|input| input.iter());
+    let storage_slots = entity_ref
+        .get::<StorageInventory>()
+        .into_iter()
+        .flat_map(|storage| storage.iter());
+
+    for item_slot in input_slots.chain(storage_slots) {
+        carried_items.extend(
+            std::iter::repeat((item_slot.item_id(), None)).take(item_slot.count() as usize),
+        );
+    }
+
+    carried_items
+}
+
+/// Despawns the organism `entity`, then drops `dropped_items` as litter where it stood.
+fn despawn_and_drop(
+    world: &mut World,
+    entity: Entity,
+    organism_id: OrganismId,
+    voxel_pos: VoxelPos,
+    dropped_items: Vec<(Id<Item>, Option<Genome>)>,
+) {
+    match organism_id {
+        OrganismId::Structure(_) => DespawnStructureCommand { center: voxel_pos }.apply(world),
+        OrganismId::Unit(_) => world.entity_mut(entity).despawn_recursive(),
+    }
+
+    for (item, parent_genome) in dropped_items {
+        SpawnLitterCommand {
+            voxel_pos,
+            item,
+            parent_genome,
+        }
+        .apply(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+    use crate::{
+        geometry::MapGeometry,
+        items::{
+            inventory::InventoryState,
+            item_manifest::{ItemData, ItemManifest},
+        },
+        litter::Litter,
+        organisms::genetics::Gene,
+        terrain::terrain_assets::TerrainHandles,
+        units::{basic_needs::Diet, unit_manifest::UnitData},
+    };
+
+    /// Item data that is valid for both remains and held items.
+    fn item_data() -> ItemData {
+        ItemData {
+            stack_size: 1,
+            compostable: false,
+            fluid: false,
+            buoyant: false,
+            seed: None,
+        }
+    }
+
+    /// Creates a world with everything needed to kill a unit named "ant",
+    /// which leaves behind a single "chitin" when it dies.
+    fn world_with_ant() -> World {
+        let mut world = World::new();
+
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        world.insert_resource(map_geometry);
+
+        let mut item_manifest = ItemManifest::new();
+        item_manifest.insert("chitin".to_string(), item_data());
+        item_manifest.insert("acacia_leaf".to_string(), item_data());
+        world.insert_resource(item_manifest);
+
+        let mut unit_data = UnitData::simple("ant", Diet::simple("acacia_leaf"));
+        unit_data
+            .organism_variety
+            .remains
+            .insert(Id::from_name("chitin".to_string()), 1);
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert("ant".to_string(), unit_data);
+        world.insert_resource(unit_manifest);
+
+        let mut litter_models = HashMap::new();
+        litter_models.insert(InventoryState::Partial, Handle::default());
+        world.insert_resource(TerrainHandles {
+            scenes: HashMap::new(),
+            topper_mesh: Handle::default(),
+            column_mesh: Handle::default(),
+            column_material: Handle::default(),
+            interaction_materials: HashMap::new(),
+            litter_models,
+        });
+
+        world.init_resource::<Events<OrganismDied>>();
+
+        world
+    }
+
+    #[test]
+    fn killed_units_drop_their_remains_and_held_item() {
+        let mut world = world_with_ant();
+        let ant = Id::<Unit>::from_name("ant".to_string());
+        let chitin = Id::<Item>::from_name("chitin".to_string());
+        let acacia_leaf = Id::<Item>::from_name("acacia_leaf".to_string());
+        let voxel_pos = VoxelPos::ZERO.above();
+        let mut genome = Genome::default();
+        genome.set(Gene::MaxEnergy, 2.);
+
+        let entity = world
+            .spawn((
+                ant,
+                voxel_pos,
+                genome.clone(),
+                UnitInventory {
+                    held_item: Some(acacia_leaf),
+                    held_item_parent: None,
+                },
+            ))
+            .id();
+
+        KillOrganismCommand {
+            entity,
+            cause: CauseOfDeath::Starvation,
+        }
+        .apply(&mut world);
+
+        assert!(world.get_entity(entity).is_none());
+
+        let mut dropped_items: Vec<(Id<Item>, Option<Genome>)> = world
+            .query::<&Litter>()
+            .iter(&world)
+            .flat_map(|litter| {
+                litter
+                    .contents
+                    .iter()
+                    .map(|item_slot| (item_slot.item_id(), litter.parent_genome.clone()))
+            })
+            .collect();
+        dropped_items.sort_by_key(|(item_id, _)| item_id == &chitin);
+        // Only the remains are descended from the organism that died
+        assert_eq!(
+            dropped_items,
+            vec![(acacia_leaf, None), (chitin, Some(genome))]
+        );
+
+        let events = world.resource::<Events<OrganismDied>>();
+        let deaths: Vec<&OrganismDied> = events.get_reader().read(events).collect();
+        assert_eq!(
+            deaths,
+            vec![&OrganismDied {
+                entity,
+                organism_id: OrganismId::Unit(ant),
+                voxel_pos,
+                cause: CauseOfDeath::Starvation,
+            }]
+        );
+    }
+
+    #[test]
+    fn killing_a_missing_organism_does_nothing() {
+        let mut world = world_with_ant();
+        let entity = world.spawn_empty().id();
+        world.despawn(entity);
+
+        KillOrganismCommand {
+            entity,
+            cause: CauseOfDeath::OldAge,
+        }
+        .apply(&mut world);
+
+        assert_eq!(world.query::<&Litter>().iter(&world).count(), 0);
+        assert!(world.resource::<Events<OrganismDied>>().is_empty());
+    }
+
+    #[test]
+    fn despawned_organisms_drop_what_they_carry_but_leave_no_remains() {
+        let mut world = world_with_ant();
+        let ant = Id::<Unit>::from_name("ant".to_string());
+        let acacia_leaf = Id::<Item>::from_name("acacia_leaf".to_string());
+
+        let entity = world
+            .spawn((
+                ant,
+                VoxelPos::ZERO.above(),
+                Genome::default(),
+                UnitInventory {
+                    held_item: Some(acacia_leaf),
+                    held_item_parent: None,
+                },
+            ))
+            .id();
+
+        DespawnOrganismCommand { entity }.apply(&mut world);
+
+        assert!(world.get_entity(entity).is_none());
+
+        let dropped_items: Vec<Id<Item>> = world
+            .query::<&Litter>()
+            .iter(&world)
+            .flat_map(|litter| litter.contents.iter().map(|item_slot| item_slot.item_id()))
+            .collect();
+        assert_eq!(dropped_items, vec![acacia_leaf]);
+        // The organism lives on in its new form
+        assert!(world.resource::<Events<OrganismDied>>().is_empty());
+    }
+}
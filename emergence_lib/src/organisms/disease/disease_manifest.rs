@@ -0,0 +1,159 @@
+//! Defines write-only data for each disease.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
+    organisms::{energy::Energy, OrganismId, RawOrganismId},
+    simulation::time::Days,
+};
+
+/// The marker type for [`Id<Disease>`](super::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct Disease;
+/// Stores the read-only definitions for all diseases.
+pub type DiseaseManifest = Manifest<Disease, DiseaseData>;
+
+impl DiseaseManifest {
+    /// Returns the diseases that can infect the provided `species`.
+    ///
+    /// Species are identified by their prototypical form.
+    pub(crate) fn diseases_of(
+        &self,
+        species: OrganismId,
+    ) -> impl Iterator<Item = Id<Disease>> + '_ {
+        self.data_map()
+            .iter()
+            .filter(move |(_, data)| data.hosts.contains(&species))
+            .map(|(&disease_id, _)| disease_id)
+    }
+}
+
+/// Data stored in a [`DiseaseManifest`] for each [`Id<Disease>`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiseaseData {
+    /// The species that this disease can infect, identified by their prototypical form.
+    pub hosts: Vec<OrganismId>,
+    /// The chance per day that an infected organism passes this disease on to each susceptible organism it is in contact with.
+    pub transmission_rate: f32,
+    /// The chance per day that a healthy host contracts this disease without being exposed to it.
+    pub outbreak_rate: f32,
+    /// The chance per day that an infected organism recovers.
+    pub recovery_rate: f32,
+    /// How long an organism is immune to this disease after recovering from it.
+    pub immunity_duration: Days,
+    /// The effects of this disease on infected organisms.
+    pub effects: DiseaseEffects,
+}
+
+/// The unprocessed equivalent of [`DiseaseData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawDiseaseData {
+    /// The species that this disease can infect, identified by their prototypical form.
+    pub hosts: Vec<RawOrganismId>,
+    /// The chance per day that an infected organism passes this disease on to each susceptible organism it is in contact with.
+    pub transmission_rate: f32,
+    /// The chance per day that a healthy host contracts this disease without being exposed to it.
+    pub outbreak_rate: f32,
+    /// The chance per day that an infected organism recovers.
+    pub recovery_rate: f32,
+    /// How long, in days, an organism is immune to this disease after recovering from it.
+    pub immunity_duration: f32,
+    /// The effects of this disease on infected organisms.
+    pub effects: RawDiseaseEffects,
+}
+
+impl From<RawDiseaseData> for DiseaseData {
+    fn from(raw: RawDiseaseData) -> Self {
+        DiseaseData {
+            hosts: raw.hosts.into_iter().map(Into::into).collect(),
+            transmission_rate: raw.transmission_rate,
+            outbreak_rate: raw.outbreak_rate,
+            recovery_rate: raw.recovery_rate,
+            immunity_duration: Days(raw.immunity_duration),
+            effects: raw.effects.into(),
+        }
+    }
+}
+
+/// How a disease harms the organisms that it infects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiseaseEffects {
+    /// The additional energy lost by infected organisms each second.
+    pub energy_drain: Energy,
+    /// Multiplies the speed at which infected structures craft.
+    ///
+    /// Values below 1.0 slow crafting down.
+    pub work_speed: f32,
+    /// Multiplies the maximum age of infected units.
+    ///
+    /// Values below 1.0 cause infected units to age faster.
+    pub lifespan: f32,
+}
+
+impl Default for DiseaseEffects {
+    fn default() -> Self {
+        DiseaseEffects {
+            energy_drain: Energy(0.),
+            work_speed: 1.0,
+            lifespan: 1.0,
+        }
+    }
+}
+
+/// The unprocessed equivalent of [`DiseaseEffects`].
+///
+/// Any effects that are `None` leave infected organisms unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RawDiseaseEffects {
+    /// The additional energy lost by infected organisms each second.
+    pub energy_drain: Option<f32>,
+    /// Multiplies the speed at which infected structures craft.
+    pub work_speed: Option<f32>,
+    /// Multiplies the maximum age of infected units.
+    pub lifespan: Option<f32>,
+}
+
+impl From<RawDiseaseEffects> for DiseaseEffects {
+    fn from(raw: RawDiseaseEffects) -> Self {
+        let default = DiseaseEffects::default();
+
+        DiseaseEffects {
+            energy_drain: raw.energy_drain.map_or(default.energy_drain, Energy),
+            work_speed: raw.work_speed.unwrap_or(default.work_speed),
+            lifespan: raw.lifespan.unwrap_or(default.lifespan),
+        }
+    }
+}
+
+/// The [`DiseaseManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "b3e1c9a4-7d2f-4a6b-8e5c-1f0d9a2b7c63"]
+pub struct RawDiseaseManifest {
+    /// The data for each disease.
+    pub diseases: HashMap<String, RawDiseaseData>,
+}
+
+impl IsRawManifest for RawDiseaseManifest {
+    const EXTENSION: &'static str = "disease_manifest.json";
+
+    type Marker = Disease;
+    type Data = DiseaseData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.diseases.clone() {
+            let data = raw_data.into();
+
+            manifest.insert(raw_id, data)
+        }
+
+        manifest
+    }
+}
@@ -0,0 +1,411 @@
+//! Diseases and parasites spread between organisms of the species that they infect.
+//!
+//! Sessile organisms infect their neighbors, while units infect the units they share a tile with.
+//! Infected organisms eventually recover, and are then immune for a while.
+//! Dense monocultures are particularly vulnerable to outbreaks.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+use leafwing_abilities::prelude::Pool;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    simulation::time::{Days, InGameTime},
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use self::disease_manifest::{Disease, DiseaseManifest};
+
+use super::{
+    death::{CauseOfDeath, DeathCommandsExt},
+    energy::{Energy, EnergyPool},
+    OrganismId,
+};
+
+pub mod disease_manifest;
+
+/// Sent whenever an organism is infected by a disease.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct OrganismInfected {
+    /// The organism that was infected.
+    pub entity: Entity,
+    /// The disease that it was infected with.
+    pub disease_id: Id<Disease>,
+    /// Where the organism was infected.
+    pub voxel_pos: VoxelPos,
+}
+
+/// The diseases that an organism is infected with or immune to.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Infections {
+    /// The diseases that this organism is currently infected with.
+    active: HashSet<Id<Disease>>,
+    /// The diseases that this organism has recovered from, and how much longer it will remain immune to each.
+    immunities: HashMap<Id<Disease>, Days>,
+}
+
+impl Infections {
+    /// Is this organism infected with any disease?
+    pub fn is_infected(&self) -> bool {
+        !self.active.is_empty()
+    }
+
+    /// Is this organism infected with the provided disease?
+    pub fn has(&self, disease_id: Id<Disease>) -> bool {
+        self.active.contains(&disease_id)
+    }
+
+    /// Is this organism currently immune to the provided disease?
+    pub fn is_immune(&self, disease_id: Id<Disease>) -> bool {
+        self.immunities.contains_key(&disease_id)
+    }
+
+    /// Infects this organism with the provided disease.
+    ///
+    /// Returns `true` if the organism was newly infected.
+    /// Organisms that are already infected or are immune are unaffected.
+    pub(crate) fn infect(&mut self, disease_id: Id<Disease>) -> bool {
+        if self.is_immune(disease_id) {
+            return false;
+        }
+
+        self.active.insert(disease_id)
+    }
+
+    /// Cures this organism of the provided disease, granting immunity to it for `immunity_duration`.
+    pub(crate) fn recover(&mut self, disease_id: Id<Disease>, immunity_duration: Days) {
+        if self.active.remove(&disease_id) && immunity_duration > Days::ZERO {
+            self.immunities.insert(disease_id, immunity_duration);
+        }
+    }
+
+    /// Advances the time remaining on each immunity by `delta_days`, removing any that have expired.
+    fn tick_immunities(&mut self, delta_days: Days) {
+        for remaining in self.immunities.values_mut() {
+            *remaining -= delta_days;
+        }
+
+        self.immunities
+            .retain(|_, remaining| *remaining > Days::ZERO);
+    }
+
+    /// The total additional energy lost by this organism each second.
+    pub(crate) fn energy_drain(&self, disease_manifest: &DiseaseManifest) -> Energy {
+        self.active
+            .iter()
+            .map(|&disease_id| disease_manifest.get(disease_id).effects.energy_drain)
+            .fold(Energy(0.), |total, drain| total + drain)
+    }
+
+    /// The multiplier applied to this organism's crafting speed.
+    pub(crate) fn work_speed(&self, disease_manifest: &DiseaseManifest) -> f32 {
+        self.active
+            .iter()
+            .map(|&disease_id| disease_manifest.get(disease_id).effects.work_speed)
+            .product()
+    }
+
+    /// The multiplier applied to this organism's maximum age.
+    pub(crate) fn lifespan(&self, disease_manifest: &DiseaseManifest) -> f32 {
+        self.active
+            .iter()
+            .map(|&disease_id| disease_manifest.get(disease_id).effects.lifespan)
+            .product()
+    }
+
+    /// The pretty formatting for this type.
+    pub(crate) fn display(&self, disease_manifest: &DiseaseManifest) -> String {
+        let mut lines: Vec<String> = self
+            .active
+            .iter()
+            .map(|&disease_id| format!("{} (infected)", disease_manifest.name(disease_id)))
+            .collect();
+
+        lines.extend(self.immunities.iter().map(|(&disease_id, remaining)| {
+            format!(
+                "{} (immune for {:.1} days)",
+                disease_manifest.name(disease_id),
+                remaining.0
+            )
+        }));
+
+        match lines.is_empty() {
+            true => "Healthy".to_string(),
+            false => lines.join(", "),
+        }
+    }
+}
+
+/// The organisms that an organism at `voxel_pos` is in contact with.
+///
+/// Units only touch organisms that share their voxel,
+/// while structures also touch the organisms on each neighboring tile.
+fn contacts<'a>(
+    voxel_pos: VoxelPos,
+    is_unit: bool,
+    organisms_by_hex: &'a HashMap<Hex, Vec<(Entity, VoxelPos, OrganismId)>>,
+) -> impl Iterator<Item = &'a (Entity, VoxelPos, OrganismId)> + 'a {
+    let hexes: Vec<Hex> = match is_unit {
+        true => vec![voxel_pos.hex],
+        false => std::iter::once(voxel_pos.hex)
+            .chain(voxel_pos.hex.all_neighbors())
+            .collect(),
+    };
+
+    hexes
+        .into_iter()
+        .filter_map(|hex| organisms_by_hex.get(&hex))
+        .flatten()
+        .filter(move |(_, other_pos, _)| !is_unit || *other_pos == voxel_pos)
+}
+
+/// Starts new outbreaks, spreads diseases between organisms in contact with each other, and lets infected organisms recover.
+pub(super) fn spread_disease(
+    mut organism_query: Query<(
+        Entity,
+        &VoxelPos,
+        &mut Infections,
+        Option<&Id<Structure>>,
+        Option<&Id<Unit>>,
+    )>,
+    disease_manifest: Res<DiseaseManifest>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+    mut infection_events: EventWriter<OrganismInfected>,
+) {
+    if disease_manifest.data_map().is_empty() {
+        return;
+    }
+
+    let rng = &mut thread_rng();
+    let delta_days = Days(time.delta().as_secs_f32() / in_game_time.seconds_per_day());
+    // Converts a rate per day into the chance that the event occurs during this tick
+    let chance = |rate_per_day: f32| (rate_per_day * delta_days.0).clamp(0., 1.) as f64;
+
+    let mut organisms_by_hex: HashMap<Hex, Vec<(Entity, VoxelPos, OrganismId)>> =
+        HashMap::default();
+    let mut species: HashMap<Entity, OrganismId> = HashMap::default();
+
+    for (entity, &voxel_pos, _, maybe_structure_id, maybe_unit_id) in organism_query.iter() {
+        let Some(organism_species) = OrganismId::from_components(maybe_structure_id, maybe_unit_id)
+            .and_then(|organism_id| organism_id.variety(&structure_manifest, &unit_manifest))
+            .map(|variety| variety.prototypical_form)
+        else {
+            continue;
+        };
+
+        organisms_by_hex.entry(voxel_pos.hex).or_default().push((
+            entity,
+            voxel_pos,
+            organism_species,
+        ));
+        species.insert(entity, organism_species);
+    }
+
+    let mut new_infections: Vec<(Entity, Id<Disease>)> = Vec::new();
+
+    for (entity, &voxel_pos, mut infections, _, maybe_unit_id) in organism_query.iter_mut() {
+        let Some(&organism_species) = species.get(&entity) else {
+            continue;
+        };
+
+        // Healthy hosts occasionally contract diseases on their own
+        for disease_id in disease_manifest.diseases_of(organism_species) {
+            if !infections.has(disease_id)
+                && !infections.is_immune(disease_id)
+                && rng.gen_bool(chance(disease_manifest.get(disease_id).outbreak_rate))
+            {
+                new_infections.push((entity, disease_id));
+            }
+        }
+
+        if !infections.is_infected() && infections.immunities.is_empty() {
+            continue;
+        }
+
+        // Infected organisms spread their diseases to susceptible hosts that they are touching
+        for &disease_id in infections.active.iter() {
+            let disease_data = disease_manifest.get(disease_id);
+
+            for &(other_entity, _, other_species) in
+                contacts(voxel_pos, maybe_unit_id.is_some(), &organisms_by_hex)
+            {
+                if other_entity != entity
+                    && disease_data.hosts.contains(&other_species)
+                    && rng.gen_bool(chance(disease_data.transmission_rate))
+                {
+                    new_infections.push((other_entity, disease_id));
+                }
+            }
+        }
+
+        let recovered: Vec<Id<Disease>> = infections
+            .active
+            .iter()
+            .copied()
+            .filter(|&disease_id| {
+                rng.gen_bool(chance(disease_manifest.get(disease_id).recovery_rate))
+            })
+            .collect();
+
+        infections.tick_immunities(delta_days);
+        for disease_id in recovered {
+            let immunity_duration = disease_manifest.get(disease_id).immunity_duration;
+            infections.recover(disease_id, immunity_duration);
+        }
+    }
+
+    for (entity, disease_id) in new_infections {
+        let Ok((_, &voxel_pos, mut infections, ..)) = organism_query.get_mut(entity) else {
+            continue;
+        };
+
+        if infections.infect(disease_id) {
+            infection_events.send(OrganismInfected {
+                entity,
+                disease_id,
+                voxel_pos,
+            });
+        }
+    }
+}
+
+/// Drains energy from organisms that are infected with debilitating diseases.
+pub(super) fn drain_energy_from_infected(
+    mut organism_query: Query<(Entity, &Infections, &mut EnergyPool)>,
+    disease_manifest: Res<DiseaseManifest>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    for (entity, infections, mut energy_pool) in organism_query.iter_mut() {
+        if !infections.is_infected() {
+            continue;
+        }
+
+        let drain = infections.energy_drain(&disease_manifest);
+        if drain <= Energy(0.) {
+            continue;
+        }
+
+        let proposed = energy_pool.current() - drain * delta_time;
+        energy_pool.set_current(proposed);
+
+        if energy_pool.is_empty() {
+            commands.kill_organism(entity, CauseOfDeath::Disease);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disease_manifest::{DiseaseData, DiseaseEffects};
+    use super::*;
+
+    /// A manifest with a single disease that infects ants.
+    fn test_manifest() -> DiseaseManifest {
+        let mut manifest = DiseaseManifest::default();
+        manifest.insert(
+            "ant_flu".to_string(),
+            DiseaseData {
+                hosts: vec![OrganismId::Unit(Id::from_name("ant".to_string()))],
+                transmission_rate: 1.0,
+                outbreak_rate: 0.0,
+                recovery_rate: 1.0,
+                immunity_duration: Days(2.0),
+                effects: DiseaseEffects {
+                    energy_drain: Energy(1.0),
+                    work_speed: 0.5,
+                    lifespan: 0.8,
+                },
+            },
+        );
+        manifest
+    }
+
+    #[test]
+    fn recovery_grants_temporary_immunity() {
+        let ant_flu = Id::from_name("ant_flu".to_string());
+        let mut infections = Infections::default();
+
+        assert!(infections.infect(ant_flu));
+        assert!(infections.has(ant_flu));
+        // Reinfection has no effect
+        assert!(!infections.infect(ant_flu));
+
+        infections.recover(ant_flu, Days(2.0));
+        assert!(!infections.is_infected());
+        assert!(infections.is_immune(ant_flu));
+        assert!(!infections.infect(ant_flu));
+
+        infections.tick_immunities(Days(1.5));
+        assert!(infections.is_immune(ant_flu));
+
+        infections.tick_immunities(Days(1.0));
+        assert!(!infections.is_immune(ant_flu));
+        assert!(infections.infect(ant_flu));
+    }
+
+    #[test]
+    fn effects_only_apply_while_infected() {
+        let manifest = test_manifest();
+        let ant_flu = Id::from_name("ant_flu".to_string());
+        let mut infections = Infections::default();
+
+        assert_eq!(infections.energy_drain(&manifest), Energy(0.));
+        assert_eq!(infections.work_speed(&manifest), 1.0);
+        assert_eq!(infections.lifespan(&manifest), 1.0);
+
+        infections.infect(ant_flu);
+        assert_eq!(infections.energy_drain(&manifest), Energy(1.0));
+        assert_eq!(infections.work_speed(&manifest), 0.5);
+        assert_eq!(infections.lifespan(&manifest), 0.8);
+    }
+
+    #[test]
+    fn diseases_only_infect_their_hosts() {
+        let manifest = test_manifest();
+
+        let ant = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let leuco = OrganismId::Structure(Id::from_name("leuco".to_string()));
+
+        assert_eq!(manifest.diseases_of(ant).count(), 1);
+        assert_eq!(manifest.diseases_of(leuco).count(), 0);
+    }
+
+    #[test]
+    fn units_only_touch_organisms_in_the_same_voxel() {
+        let ant = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let origin = VoxelPos::ZERO;
+        let above = origin.above();
+        let neighbor = VoxelPos::from_xy(1, 0);
+
+        let mut organisms_by_hex: HashMap<Hex, Vec<(Entity, VoxelPos, OrganismId)>> =
+            HashMap::default();
+        organisms_by_hex.insert(
+            origin.hex,
+            vec![
+                (Entity::from_raw(0), origin, ant),
+                (Entity::from_raw(1), above, ant),
+            ],
+        );
+        organisms_by_hex.insert(neighbor.hex, vec![(Entity::from_raw(2), neighbor, ant)]);
+
+        let unit_contacts: Vec<Entity> = contacts(origin, true, &organisms_by_hex)
+            .map(|(entity, ..)| *entity)
+            .collect();
+        assert_eq!(unit_contacts, vec![Entity::from_raw(0)]);
+
+        let structure_contacts = contacts(origin, false, &organisms_by_hex).count();
+        assert_eq!(structure_contacts, 3);
+    }
+}
@@ -0,0 +1,343 @@
+//! Organisms are either wild or domesticated.
+//!
+//! Wild organisms do not cooperate with the colony: wild units ignore the signals that direct colony work,
+//! and the structures of undomesticated species cannot be built.
+//! Species are domesticated by keeping them alive (and breeding them) inside of research reserves,
+//! until enough biotic mastery has been gained.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use core::fmt::Display;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    player_interaction::{selection::CurrentSelection, PlayerAction},
+    signals::SignalType,
+    simulation::time::InGameTime,
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use super::{lifecycle::OrganismBorn, OrganismId, OrganismVariety};
+
+/// Whether or not an organism cooperates with the colony.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Strain {
+    /// This organism ignores the colony.
+    Wild,
+    /// This organism is part of the colony.
+    #[default]
+    Domesticated,
+}
+
+impl Strain {
+    /// The strain of organisms that are generated with the world.
+    ///
+    /// Species that require mastery start wild.
+    pub(crate) fn initial(variety: &OrganismVariety) -> Strain {
+        match variety.mastery_required {
+            Some(_) => Strain::Wild,
+            None => Strain::Domesticated,
+        }
+    }
+
+    /// Should units of this strain respond to signals of the provided type?
+    ///
+    /// Wild units still avoid other units and visit their symbiotic partners,
+    /// but ignore signals that coordinate colony work.
+    pub(crate) fn responds_to(&self, signal_type: SignalType) -> bool {
+        match self {
+            Strain::Wild => matches!(signal_type, SignalType::Unit(_) | SignalType::Visit(_)),
+            Strain::Domesticated => true,
+        }
+    }
+}
+
+impl Display for Strain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Strain::Wild => "Wild",
+            Strain::Domesticated => "Domesticated",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// A terrain tile that is part of a research reserve.
+///
+/// Wild organisms that live and are born inside of research reserves grant biotic mastery of their species.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct ResearchReserve;
+
+/// Sent when the player gains enough biotic mastery to domesticate a species.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SpeciesDomesticated {
+    /// The prototypical form of the species that was domesticated.
+    pub species: OrganismId,
+}
+
+/// The player's progress towards domesticating each species.
+///
+/// Species are identified by the prototypical form of their [`OrganismVariety`].
+#[derive(Resource, Debug, Default)]
+pub struct Domestication {
+    /// The biotic mastery gained for each species that is not yet domesticated.
+    mastery: HashMap<OrganismId, f32>,
+    /// The species that have been domesticated over the course of the game.
+    ///
+    /// Species that do not require mastery are always domesticated, and are not stored here.
+    domesticated: HashSet<OrganismId>,
+}
+
+impl Domestication {
+    /// The biotic mastery gained each time a wild organism is born inside of a research reserve.
+    pub const MASTERY_PER_BIRTH: f32 = 1.0;
+
+    /// The biotic mastery gained for each wild organism kept alive inside of a research reserve, per in-game day.
+    pub const MASTERY_PER_DAY: f32 = 0.5;
+
+    /// Has the species of the provided `variety` been domesticated?
+    pub fn is_domesticated(&self, variety: &OrganismVariety) -> bool {
+        variety.mastery_required.is_none() || self.domesticated.contains(&variety.prototypical_form)
+    }
+
+    /// Returns the biotic mastery gained for the provided `species`.
+    pub fn mastery(&self, species: OrganismId) -> f32 {
+        self.mastery.get(&species).copied().unwrap_or_default()
+    }
+
+    /// Returns the current and required biotic mastery for the species of the provided `variety`.
+    ///
+    /// Returns `None` if the species is already domesticated.
+    pub fn mastery_progress(&self, variety: &OrganismVariety) -> Option<(f32, f32)> {
+        if self.is_domesticated(variety) {
+            return None;
+        }
+
+        let required = variety.mastery_required?;
+        Some((self.mastery(variety.prototypical_form), required))
+    }
+
+    /// Adds `amount` of biotic mastery to the species of the provided `variety`.
+    ///
+    /// Returns `true` if the species became domesticated as a result.
+    pub(crate) fn add_mastery(&mut self, variety: &OrganismVariety, amount: f32) -> bool {
+        let Some((current, required)) = self.mastery_progress(variety) else {
+            return false;
+        };
+
+        let species = variety.prototypical_form;
+        let proposed = current + amount;
+        if proposed >= required {
+            self.mastery.remove(&species);
+            self.domesticated.insert(species);
+            true
+        } else {
+            self.mastery.insert(species, proposed);
+            false
+        }
+    }
+
+    /// The strain of a new organism of the provided `variety` whose parents are unknown.
+    pub(crate) fn newborn_strain(&self, variety: &OrganismVariety) -> Strain {
+        match self.is_domesticated(variety) {
+            true => Strain::Domesticated,
+            false => Strain::Wild,
+        }
+    }
+
+    /// Can the player build the structure with the provided `structure_id`?
+    ///
+    /// Only structures that belong to a domesticated species (or are not organisms at all) can be built.
+    pub(crate) fn can_build(
+        &self,
+        structure_id: Id<Structure>,
+        structure_manifest: &StructureManifest,
+    ) -> bool {
+        match &structure_manifest.get(structure_id).organism_variety {
+            Some(variety) => self.is_domesticated(variety),
+            None => true,
+        }
+    }
+}
+
+/// Grants biotic mastery for wild organisms that live and are born inside of research reserves.
+///
+/// Once a species is domesticated, its organisms inside of research reserves join the colony.
+pub(super) fn advance_mastery(
+    mut organism_query: Query<(
+        &VoxelPos,
+        &mut Strain,
+        Option<&Id<Structure>>,
+        Option<&Id<Unit>>,
+    )>,
+    reserve_query: Query<(), With<ResearchReserve>>,
+    mut birth_events: EventReader<OrganismBorn>,
+    map_geometry: Res<MapGeometry>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+    mut domestication: ResMut<Domestication>,
+    mut domesticated_events: EventWriter<SpeciesDomesticated>,
+) {
+    let in_reserve = |voxel_pos: VoxelPos| {
+        map_geometry
+            .get_terrain(voxel_pos.hex)
+            .is_ok_and(|terrain_entity| reserve_query.contains(terrain_entity))
+    };
+
+    let delta_days = time.delta().as_secs_f32() / in_game_time.seconds_per_day();
+    let mut newly_domesticated: Vec<OrganismId> = Vec::new();
+
+    let mut add_mastery = |organism_id: OrganismId, amount: f32| {
+        let Some(variety) = organism_id.variety(&structure_manifest, &unit_manifest) else {
+            return;
+        };
+
+        if domestication.add_mastery(variety, amount) {
+            newly_domesticated.push(variety.prototypical_form);
+        }
+    };
+
+    for birth in birth_events.read() {
+        if birth.strain == Strain::Wild && in_reserve(birth.voxel_pos) {
+            add_mastery(birth.organism_id, Domestication::MASTERY_PER_BIRTH);
+        }
+    }
+
+    for (&voxel_pos, &strain, maybe_structure_id, maybe_unit_id) in organism_query.iter() {
+        if strain != Strain::Wild || !in_reserve(voxel_pos) {
+            continue;
+        }
+
+        if let Some(organism_id) = OrganismId::from_components(maybe_structure_id, maybe_unit_id) {
+            add_mastery(organism_id, Domestication::MASTERY_PER_DAY * delta_days);
+        }
+    }
+
+    if newly_domesticated.is_empty() {
+        return;
+    }
+
+    // The population that was studied joins the colony
+    for (&voxel_pos, mut strain, maybe_structure_id, maybe_unit_id) in organism_query.iter_mut() {
+        if !in_reserve(voxel_pos) {
+            continue;
+        }
+
+        let Some(species) = OrganismId::from_components(maybe_structure_id, maybe_unit_id)
+            .and_then(|organism_id| organism_id.variety(&structure_manifest, &unit_manifest))
+            .map(|variety| variety.prototypical_form)
+        else {
+            continue;
+        };
+
+        if newly_domesticated.contains(&species) {
+            *strain = Strain::Domesticated;
+        }
+    }
+
+    for species in newly_domesticated {
+        domesticated_events.send(SpeciesDomesticated { species });
+    }
+}
+
+/// Adds or removes the selected tiles from research reserves.
+///
+/// If every selected tile is already part of a reserve, they are all removed.
+/// Otherwise, they are all added.
+pub(crate) fn toggle_research_reserve(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    reserve_query: Query<(), With<ResearchReserve>>,
+    map_geometry: Res<MapGeometry>,
+    mut commands: Commands,
+) {
+    if !actions.just_pressed(PlayerAction::ToggleResearchReserve) {
+        return;
+    }
+
+    let CurrentSelection::Voxels(selected_voxels) = &*current_selection else {
+        return;
+    };
+
+    let terrain_entities: Vec<Entity> = selected_voxels
+        .iter()
+        .filter_map(|voxel_pos| map_geometry.get_terrain(voxel_pos.hex).ok())
+        .collect();
+
+    let all_reserved = terrain_entities
+        .iter()
+        .all(|&terrain_entity| reserve_query.contains(terrain_entity));
+
+    for terrain_entity in terrain_entities {
+        match all_reserved {
+            true => commands.entity(terrain_entity).remove::<ResearchReserve>(),
+            false => commands.entity(terrain_entity).insert(ResearchReserve),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::construction::ghosts::WorkplaceId;
+
+    /// A species that must be domesticated before it cooperates with the colony.
+    fn wild_variety(mastery_required: f32) -> OrganismVariety {
+        let mut variety = OrganismVariety::simple("wild_thing");
+        variety.mastery_required = Some(mastery_required);
+        variety
+    }
+
+    #[test]
+    fn species_without_mastery_requirements_start_domesticated() {
+        let variety = OrganismVariety::simple("ant");
+        let domestication = Domestication::default();
+
+        assert!(domestication.is_domesticated(&variety));
+        assert_eq!(domestication.mastery_progress(&variety), None);
+        assert_eq!(domestication.newborn_strain(&variety), Strain::Domesticated);
+        assert_eq!(Strain::initial(&variety), Strain::Domesticated);
+    }
+
+    #[test]
+    fn mastery_domesticates_species() {
+        let variety = wild_variety(2.0);
+        let mut domestication = Domestication::default();
+
+        assert!(!domestication.is_domesticated(&variety));
+        assert_eq!(Strain::initial(&variety), Strain::Wild);
+        assert_eq!(domestication.newborn_strain(&variety), Strain::Wild);
+
+        assert!(!domestication.add_mastery(&variety, 1.5));
+        assert_eq!(domestication.mastery_progress(&variety), Some((1.5, 2.0)));
+
+        assert!(domestication.add_mastery(&variety, 1.0));
+        assert!(domestication.is_domesticated(&variety));
+        assert_eq!(domestication.newborn_strain(&variety), Strain::Domesticated);
+
+        // Further mastery has no effect
+        assert!(!domestication.add_mastery(&variety, 1.0));
+    }
+
+    #[test]
+    fn wild_units_ignore_colony_signals() {
+        let unit_signal = SignalType::Unit(Id::from_name("ant".to_string()));
+        let visit_signal = SignalType::Visit(Id::from_name("leuco".to_string()));
+        let work_signal =
+            SignalType::Work(WorkplaceId::Structure(Id::from_name("leuco".to_string())));
+
+        assert!(Strain::Wild.responds_to(unit_signal));
+        assert!(Strain::Wild.responds_to(visit_signal));
+        assert!(!Strain::Wild.responds_to(work_signal));
+        assert!(Strain::Domesticated.responds_to(work_signal));
+    }
+}
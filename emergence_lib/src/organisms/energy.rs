@@ -8,9 +8,14 @@ use leafwing_abilities::{pool::MaxPoolLessThanMin, prelude::Pool};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::asset_management::manifest::Id;
-use crate::structures::structure_manifest::Structure;
-use crate::{geometry::VoxelPos, structures::commands::StructureCommandsExt};
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    simulation::alerts::{Alert, AlertKind, EdgeTriggeredAlerts},
+    units::unit_manifest::Unit,
+};
+
+use super::death::{CauseOfDeath, DeathCommandsExt};
 
 /// The amount of energy available to an organism.
 /// If they run out, they die.
@@ -47,6 +52,19 @@ impl EnergyPool {
         self.current = Energy(current);
     }
 
+    /// Returns a copy of this pool with its current energy, capacity and thresholds multiplied by `factor`.
+    ///
+    /// The rate of regeneration is unchanged.
+    pub(crate) fn scaled(&self, factor: f32) -> Self {
+        EnergyPool {
+            current: self.current * factor,
+            max: self.max * factor,
+            warning_threshold: self.warning_threshold * factor,
+            satiation_threshold: self.satiation_threshold * factor,
+            regen_per_second: self.regen_per_second,
+        }
+    }
+
     /// Is this organism out of energy?
     pub(crate) fn is_empty(&self) -> bool {
         self.current <= Energy(0.)
@@ -184,17 +202,28 @@ pub(super) fn consume_energy(time: Res<Time>, mut energy_query: Query<&mut Energ
     }
 }
 
-/// Despawns organisms when they run out of energy
+/// Kills organisms when they run out of energy
 pub(super) fn kill_organisms_when_out_of_energy(
-    organism_query: Query<(Entity, &EnergyPool, &VoxelPos, Option<&Id<Structure>>)>,
+    organism_query: Query<(Entity, &EnergyPool)>,
     mut commands: Commands,
 ) {
-    for (entity, energy_pool, voxel_pos, maybe_structure) in organism_query.iter() {
+    for (entity, energy_pool) in organism_query.iter() {
         if energy_pool.is_empty() {
-            match maybe_structure {
-                Some(_) => commands.despawn_structure(*voxel_pos),
-                None => commands.entity(entity).despawn_recursive(),
-            }
+            commands.kill_organism(entity, CauseOfDeath::Starvation);
         }
     }
 }
+
+/// Raises an alert when a unit becomes hungry enough that it is in danger of starving.
+pub(super) fn alert_starving_units(
+    unit_query: Query<(Entity, &VoxelPos, &EnergyPool), With<Id<Unit>>>,
+    mut alerts: EventWriter<Alert>,
+    mut starving: Local<EdgeTriggeredAlerts>,
+) {
+    let hungry_units = unit_query
+        .iter()
+        .filter(|(.., energy_pool)| energy_pool.is_hungry())
+        .map(|(entity, &voxel_pos, _)| (entity, voxel_pos));
+
+    alerts.send_batch(starving.update(AlertKind::Starving, hungry_units));
+}
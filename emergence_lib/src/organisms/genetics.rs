@@ -0,0 +1,618 @@
+//! Heritable traits allow individual organisms to differ from the baseline of their species.
+//!
+//! Each organism carries a [`Genome`], which scales the stats defined in its manifest.
+//! When organisms reproduce, their offspring inherit their parent's genome, with some random mutation.
+//! By choosing which traits to favor, players can guide the evolution of the species in their colony.
+
+use bevy::{prelude::*, utils::HashMap};
+use core::fmt::Display;
+use leafwing_input_manager::prelude::ActionState;
+use rand::{seq::SliceRandom, Rng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::MapGeometry,
+    player_interaction::{selection::CurrentSelection, PlayerAction},
+    simulation::time::Days,
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use super::{energy::EnergyPool, OrganismId};
+
+/// A heritable trait, which scales one of the stats of an organism.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Gene {
+    /// Scales the maximum energy that the organism can store.
+    MaxEnergy,
+    /// Scales the maximum age of the organism.
+    ///
+    /// Only units age, so this has no effect on structures.
+    Lifespan,
+    /// Scales how quickly the organism walks.
+    ///
+    /// Only units walk, so this has no effect on structures.
+    WalkingSpeed,
+    /// Scales how much impatience the organism can accumulate before giving up on a goal.
+    ///
+    /// Only units have goals, so this has no effect on structures.
+    Patience,
+    /// Scales how quickly the organism crafts recipes.
+    WorkSpeed,
+}
+
+impl Gene {
+    /// All of the possible genes, in display order.
+    pub const ALL: [Gene; 5] = [
+        Gene::MaxEnergy,
+        Gene::Lifespan,
+        Gene::WalkingSpeed,
+        Gene::Patience,
+        Gene::WorkSpeed,
+    ];
+
+    /// Returns the gene after this one in [`Gene::ALL`], or `None` if this is the last gene.
+    pub(crate) fn next(&self) -> Option<Gene> {
+        let index = Gene::ALL.iter().position(|gene| gene == self).unwrap();
+        Gene::ALL.get(index + 1).copied()
+    }
+}
+
+impl Display for Gene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            Gene::MaxEnergy => "Max energy",
+            Gene::Lifespan => "Lifespan",
+            Gene::WalkingSpeed => "Walking speed",
+            Gene::Patience => "Patience",
+            Gene::WorkSpeed => "Work speed",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// The heritable traits of a single organism.
+///
+/// Each [`Gene`] is expressed as a multiplier on the baseline stats of the organism's species,
+/// as defined in its manifest.
+/// Missing genes are expressed at exactly the baseline.
+#[derive(Component, Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Genome {
+    /// The multiplier applied to the stat controlled by each gene.
+    expression: HashMap<Gene, f32>,
+}
+
+impl Genome {
+    /// The smallest multiplier that any gene can be expressed at.
+    pub const MIN_EXPRESSION: f32 = 0.25;
+
+    /// The largest multiplier that any gene can be expressed at.
+    pub const MAX_EXPRESSION: f32 = 4.0;
+
+    /// The default standard deviation of gene expression within a species.
+    pub const DEFAULT_VARIATION: f32 = 0.1;
+
+    /// The size of mutations relative to the variation of the species.
+    ///
+    /// Offspring are more similar to their parents than to a random member of their species.
+    pub const MUTATION_SCALE: f32 = 0.5;
+
+    /// Returns the multiplier for the provided `gene`.
+    pub fn get(&self, gene: Gene) -> f32 {
+        self.expression.get(&gene).copied().unwrap_or(1.0)
+    }
+
+    /// Sets the multiplier for the provided `gene`, clamping it to the allowed range.
+    pub fn set(&mut self, gene: Gene, multiplier: f32) {
+        let multiplier = multiplier.clamp(Self::MIN_EXPRESSION, Self::MAX_EXPRESSION);
+        self.expression.insert(gene, multiplier);
+    }
+
+    /// Generates a new genome for an organism with no known parents.
+    ///
+    /// Each gene is normally distributed around the species baseline, with a standard deviation of `variation`.
+    pub(crate) fn sample(variation: f32, rng: &mut impl Rng) -> Self {
+        Genome::default().mutate(variation, rng)
+    }
+
+    /// Generates the genome of a child of this organism.
+    ///
+    /// Each gene is inherited from the parent, with random mutations whose size is controlled by `variation`.
+    pub(crate) fn inherit(&self, variation: f32, rng: &mut impl Rng) -> Self {
+        self.mutate(variation * Self::MUTATION_SCALE, rng)
+    }
+
+    /// Returns a copy of this genome, with normally distributed noise with a standard deviation of `std_dev` added to each gene.
+    fn mutate(&self, std_dev: f32, rng: &mut impl Rng) -> Self {
+        let Ok(distribution) = Normal::new(0., std_dev.max(0.)) else {
+            return self.clone();
+        };
+
+        let mut child = self.clone();
+        for gene in Gene::ALL {
+            child.set(gene, self.get(gene) + distribution.sample(rng));
+        }
+        child
+    }
+
+    /// Scales the `baseline` energy pool of the species by this organism's [`Gene::MaxEnergy`].
+    pub(crate) fn energy_pool(&self, baseline: &EnergyPool) -> EnergyPool {
+        baseline.scaled(self.get(Gene::MaxEnergy))
+    }
+
+    /// Scales the `baseline` max age of the species by this organism's [`Gene::Lifespan`].
+    pub(crate) fn max_age(&self, baseline: Days) -> Days {
+        baseline * self.get(Gene::Lifespan)
+    }
+
+    /// Scales the `baseline` max impatience of the species by this organism's [`Gene::Patience`].
+    ///
+    /// Units always have at least one point of patience.
+    pub(crate) fn max_impatience(&self, baseline: u8) -> u8 {
+        (baseline as f32 * self.get(Gene::Patience))
+            .round()
+            .clamp(1., u8::MAX as f32) as u8
+    }
+
+    /// Pretty formatting for this type.
+    ///
+    /// Each gene is compared to the `distributions` of its species, and the `favored_gene` is marked.
+    pub(crate) fn display(
+        &self,
+        distributions: &HashMap<Gene, GeneDistribution>,
+        favored_gene: Option<Gene>,
+    ) -> String {
+        let mut string = String::new();
+        for gene in Gene::ALL {
+            string += &format!("\n{gene}: x{:.2}", self.get(gene));
+
+            if let Some(distribution) = distributions.get(&gene) {
+                string += &format!(" (population: {distribution})");
+            }
+
+            if favored_gene == Some(gene) {
+                string += " [favored]";
+            }
+        }
+        string
+    }
+}
+
+/// Returns the species of the provided organism, and how much the genomes of that species vary.
+///
+/// Returns `None` if `organism_id` is a structure that is not an organism.
+pub(crate) fn genetic_variation(
+    organism_id: OrganismId,
+    structure_manifest: &StructureManifest,
+    unit_manifest: &UnitManifest,
+) -> Option<(OrganismId, f32)> {
+    organism_id
+        .variety(structure_manifest, unit_manifest)
+        .map(|variety| (variety.prototypical_form, variety.genetic_variation))
+}
+
+/// Generates the genome of a newborn organism of type `organism_id`.
+///
+/// If the `parent` is known, the newborn inherits from it.
+/// Otherwise, a parent is chosen from the living members of its species in the [`GenePool`],
+/// favoring the gene selected in [`SelectiveBreeding`].
+/// If the species has no living members, a new genome is sampled instead.
+pub(crate) fn offspring_genome(
+    organism_id: OrganismId,
+    parent: Option<&Genome>,
+    structure_manifest: &StructureManifest,
+    unit_manifest: &UnitManifest,
+    gene_pool: &GenePool,
+    selective_breeding: &SelectiveBreeding,
+    rng: &mut impl Rng,
+) -> Genome {
+    let Some((species, variation)) =
+        genetic_variation(organism_id, structure_manifest, unit_manifest)
+    else {
+        return Genome::default();
+    };
+
+    let parent = parent.or_else(|| {
+        gene_pool.choose_parent(species, selective_breeding.favored_gene(species), rng)
+    });
+
+    match parent {
+        Some(parent) => parent.inherit(variation, rng),
+        None => Genome::sample(variation, rng),
+    }
+}
+
+/// Summary statistics for a single [`Gene`] across a population.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneDistribution {
+    /// The number of living organisms sampled.
+    pub count: usize,
+    /// The smallest multiplier found in the population.
+    pub min: f32,
+    /// The average multiplier of the population.
+    pub mean: f32,
+    /// The largest multiplier found in the population.
+    pub max: f32,
+}
+
+impl Display for GeneDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.2}-{:.2}, mean {:.2}, n={}",
+            self.min, self.max, self.mean, self.count
+        )
+    }
+}
+
+/// The genomes of all living organisms, grouped by species.
+///
+/// Species are identified by the prototypical form of their [`OrganismVariety`],
+/// so organisms at every stage of their lifecycle are counted together.
+#[derive(Resource, Debug, Default)]
+pub struct GenePool {
+    /// The genomes of every living member of each species.
+    genomes: HashMap<OrganismId, Vec<Genome>>,
+}
+
+impl GenePool {
+    /// Returns the number of living organisms of the provided `species`.
+    pub fn population(&self, species: OrganismId) -> usize {
+        self.genomes.get(&species).map_or(0, Vec::len)
+    }
+
+    /// Records that a living organism of the provided `species` has the given `genome`.
+    pub(crate) fn add(&mut self, species: OrganismId, genome: Genome) {
+        self.genomes.entry(species).or_default().push(genome);
+    }
+
+    /// Summarizes how the provided `gene` is expressed across the living members of `species`.
+    ///
+    /// Returns `None` if the species has no living members.
+    pub fn distribution(&self, species: OrganismId, gene: Gene) -> Option<GeneDistribution> {
+        let genomes = self.genomes.get(&species)?;
+        if genomes.is_empty() {
+            return None;
+        }
+
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        let mut sum = 0.;
+
+        for genome in genomes {
+            let value = genome.get(gene);
+            min = min.min(value);
+            max = max.max(value);
+            sum += value;
+        }
+
+        Some(GeneDistribution {
+            count: genomes.len(),
+            min,
+            mean: sum / genomes.len() as f32,
+            max,
+        })
+    }
+
+    /// Summarizes how each gene is expressed across the living members of `species`.
+    pub fn distributions(&self, species: OrganismId) -> HashMap<Gene, GeneDistribution> {
+        Gene::ALL
+            .into_iter()
+            .filter_map(|gene| Some((gene, self.distribution(species, gene)?)))
+            .collect()
+    }
+
+    /// Chooses a living member of `species` to be the parent of a new organism.
+    ///
+    /// If a `favored_gene` is provided, several candidates are drawn and the one that expresses that gene most strongly is chosen.
+    /// Returns `None` if the species has no living members.
+    pub(crate) fn choose_parent(
+        &self,
+        species: OrganismId,
+        favored_gene: Option<Gene>,
+        rng: &mut impl Rng,
+    ) -> Option<&Genome> {
+        /// The number of candidates considered when a gene is favored.
+        ///
+        /// Higher values make selective breeding more effective.
+        const TOURNAMENT_SIZE: usize = 3;
+
+        let genomes = self.genomes.get(&species)?;
+
+        match favored_gene {
+            Some(gene) => (0..TOURNAMENT_SIZE)
+                .filter_map(|_| genomes.choose(rng))
+                .max_by(|a, b| a.get(gene).total_cmp(&b.get(gene))),
+            None => genomes.choose(rng),
+        }
+    }
+}
+
+/// The traits that the player wants to encourage in each species.
+///
+/// Seeds and eggs are more likely to be descended from organisms that strongly express the favored gene of their species.
+#[derive(Resource, Debug, Default)]
+pub struct SelectiveBreeding {
+    /// The gene favored for each species.
+    favored_genes: HashMap<OrganismId, Gene>,
+}
+
+impl SelectiveBreeding {
+    /// Returns the gene favored for the provided `species`, if any.
+    pub fn favored_gene(&self, species: OrganismId) -> Option<Gene> {
+        self.favored_genes.get(&species).copied()
+    }
+
+    /// Sets the gene favored for the provided `species`.
+    ///
+    /// If `gene` is `None`, no gene will be favored and parents will be chosen at random.
+    pub fn set_favored_gene(&mut self, species: OrganismId, gene: Option<Gene>) {
+        match gene {
+            Some(gene) => self.favored_genes.insert(species, gene),
+            None => self.favored_genes.remove(&species),
+        };
+    }
+
+    /// Favors the next gene in [`Gene::ALL`] for the provided `species`.
+    ///
+    /// After the last gene, no gene is favored.
+    pub(crate) fn cycle_favored_gene(&mut self, species: OrganismId) {
+        let next = match self.favored_gene(species) {
+            Some(gene) => gene.next(),
+            None => Gene::ALL.first().copied(),
+        };
+
+        self.set_favored_gene(species, next);
+    }
+}
+
+/// Collects the genomes of all living organisms into the [`GenePool`].
+pub(super) fn update_gene_pool(
+    organism_query: Query<(&Genome, Option<&Id<Structure>>, Option<&Id<Unit>>)>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut gene_pool: ResMut<GenePool>,
+) {
+    // PERF: this could be updated incrementally as organisms are born and die
+    let mut new_gene_pool = GenePool::default();
+
+    for (genome, maybe_structure_id, maybe_unit_id) in organism_query.iter() {
+        let Some(organism_id) = OrganismId::from_components(maybe_structure_id, maybe_unit_id)
+        else {
+            continue;
+        };
+
+        if let Some((species, _)) =
+            genetic_variation(organism_id, &structure_manifest, &unit_manifest)
+        {
+            new_gene_pool.add(species, genome.clone());
+        }
+    }
+
+    *gene_pool = new_gene_pool;
+}
+
+/// Changes which gene is favored for the species of the selected organism.
+pub(crate) fn cycle_favored_gene(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    structure_query: Query<&Id<Structure>, With<Genome>>,
+    unit_query: Query<&Id<Unit>>,
+    map_geometry: Res<MapGeometry>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut selective_breeding: ResMut<SelectiveBreeding>,
+) {
+    if !actions.just_pressed(PlayerAction::CycleFavoredGene) {
+        return;
+    }
+
+    let organism_id = match &*current_selection {
+        CurrentSelection::Unit(unit_entity) => unit_query
+            .get(*unit_entity)
+            .ok()
+            .map(|&unit_id| OrganismId::Unit(unit_id)),
+        CurrentSelection::Voxels(selected_voxels) => selected_voxels
+            .iter()
+            .filter_map(|voxel_pos| map_geometry.get_structure(*voxel_pos))
+            .find_map(|entity| structure_query.get(entity).ok())
+            .map(|&structure_id| OrganismId::Structure(structure_id)),
+        CurrentSelection::None => None,
+    };
+
+    let Some((species, _)) = organism_id.and_then(|organism_id| {
+        genetic_variation(organism_id, &structure_manifest, &unit_manifest)
+    }) else {
+        return;
+    };
+
+    selective_breeding.cycle_favored_gene(species);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::{basic_needs::Diet, unit_manifest::UnitData};
+    use leafwing_abilities::prelude::Pool;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn baseline_genome_does_not_change_stats() {
+        let genome = Genome::default();
+        let baseline = EnergyPool::simple(100.);
+
+        for gene in Gene::ALL {
+            assert_eq!(genome.get(gene), 1.0);
+        }
+
+        assert_eq!(genome.energy_pool(&baseline), baseline);
+        assert_eq!(genome.max_age(Days(10.)), Days(10.));
+        assert_eq!(genome.max_impatience(10), 10);
+    }
+
+    #[test]
+    fn gene_expression_is_clamped() {
+        let mut genome = Genome::default();
+
+        genome.set(Gene::MaxEnergy, 100.);
+        assert_eq!(genome.get(Gene::MaxEnergy), Genome::MAX_EXPRESSION);
+
+        genome.set(Gene::MaxEnergy, -1.);
+        assert_eq!(genome.get(Gene::MaxEnergy), Genome::MIN_EXPRESSION);
+        assert!(genome.max_impatience(1) >= 1);
+    }
+
+    #[test]
+    fn genes_scale_stats() {
+        let mut genome = Genome::default();
+        genome.set(Gene::MaxEnergy, 2.);
+        genome.set(Gene::Lifespan, 0.5);
+
+        let energy_pool = genome.energy_pool(&EnergyPool::simple(100.));
+        assert_eq!(energy_pool.max().0, 200.);
+        assert_eq!(genome.max_age(Days(10.)), Days(5.));
+    }
+
+    #[test]
+    fn inheritance_without_variation_is_exact() {
+        let rng = &mut rand::thread_rng();
+        let mut parent = Genome::default();
+        parent.set(Gene::WorkSpeed, 1.5);
+
+        let child = parent.inherit(0., rng);
+        assert_eq!(child.get(Gene::WorkSpeed), 1.5);
+    }
+
+    #[test]
+    fn sampled_genomes_stay_in_bounds() {
+        let rng = &mut rand::thread_rng();
+
+        for _ in 0..100 {
+            let genome = Genome::sample(10., rng);
+            for gene in Gene::ALL {
+                let value = genome.get(gene);
+                assert!(value >= Genome::MIN_EXPRESSION);
+                assert!(value <= Genome::MAX_EXPRESSION);
+            }
+        }
+    }
+
+    #[test]
+    fn favored_gene_cycles_through_all_genes() {
+        let species = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let mut selective_breeding = SelectiveBreeding::default();
+
+        for gene in Gene::ALL {
+            selective_breeding.cycle_favored_gene(species);
+            assert_eq!(selective_breeding.favored_gene(species), Some(gene));
+        }
+
+        selective_breeding.cycle_favored_gene(species);
+        assert_eq!(selective_breeding.favored_gene(species), None);
+    }
+
+    #[test]
+    fn gene_pool_summarizes_population() {
+        let species = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let mut gene_pool = GenePool::default();
+        assert_eq!(gene_pool.distribution(species, Gene::MaxEnergy), None);
+
+        for multiplier in [0.5, 1.0, 1.5] {
+            let mut genome = Genome::default();
+            genome.set(Gene::MaxEnergy, multiplier);
+            gene_pool.add(species, genome);
+        }
+
+        let distribution = gene_pool.distribution(species, Gene::MaxEnergy).unwrap();
+        assert_eq!(gene_pool.population(species), 3);
+        assert_eq!(distribution.count, 3);
+        assert_eq!(distribution.min, 0.5);
+        assert_eq!(distribution.mean, 1.0);
+        assert_eq!(distribution.max, 1.5);
+    }
+
+    #[test]
+    fn favored_parents_are_chosen_more_often() {
+        let rng = &mut SmallRng::seed_from_u64(42);
+        let species = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let mut gene_pool = GenePool::default();
+
+        for multiplier in [0.5, 2.0] {
+            let mut genome = Genome::default();
+            genome.set(Gene::WalkingSpeed, multiplier);
+            gene_pool.add(species, genome);
+        }
+
+        let n_fast = (0..1000)
+            .filter(|_| {
+                let parent = gene_pool
+                    .choose_parent(species, Some(Gene::WalkingSpeed), rng)
+                    .unwrap();
+                parent.get(Gene::WalkingSpeed) > 1.0
+            })
+            .count();
+
+        // With a tournament of size 3, the fast parent should be chosen 7/8 of the time
+        assert!(n_fast > 750);
+    }
+
+    /// A manifest containing only ants, whose genomes do not mutate when inherited.
+    fn ant_manifest() -> UnitManifest {
+        let mut unit_data = UnitData::simple("ant", Diet::simple("acacia_leaf"));
+        unit_data.organism_variety.genetic_variation = 0.;
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert("ant".to_string(), unit_data);
+        unit_manifest
+    }
+
+    /// A genome with the provided expression of [`Gene::WalkingSpeed`].
+    fn walking_speed(multiplier: f32) -> Genome {
+        let mut genome = Genome::default();
+        genome.set(Gene::WalkingSpeed, multiplier);
+        genome
+    }
+
+    #[test]
+    fn offspring_inherit_from_their_known_parent() {
+        let rng = &mut SmallRng::seed_from_u64(42);
+        let ant = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let mut gene_pool = GenePool::default();
+        gene_pool.add(ant, walking_speed(0.5));
+
+        let offspring = offspring_genome(
+            ant,
+            Some(&walking_speed(2.0)),
+            &StructureManifest::new(),
+            &ant_manifest(),
+            &gene_pool,
+            &SelectiveBreeding::default(),
+            rng,
+        );
+
+        assert_eq!(offspring.get(Gene::WalkingSpeed), 2.0);
+    }
+
+    #[test]
+    fn offspring_of_unknown_parents_inherit_from_the_gene_pool() {
+        let rng = &mut SmallRng::seed_from_u64(42);
+        let ant = OrganismId::Unit(Id::from_name("ant".to_string()));
+        let mut gene_pool = GenePool::default();
+        gene_pool.add(ant, walking_speed(0.5));
+
+        let offspring = offspring_genome(
+            ant,
+            None,
+            &StructureManifest::new(),
+            &ant_manifest(),
+            &gene_pool,
+            &SelectiveBreeding::default(),
+            rng,
+        );
+
+        assert_eq!(offspring.get(Gene::WalkingSpeed), 0.5);
+    }
+}
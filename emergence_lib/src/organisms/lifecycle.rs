@@ -1,6 +1,6 @@
 //! The patterns and mechanisms of how organisms change over time.
 
-use bevy::prelude::*;
+use bevy::{ecs::system::Command, prelude::*};
 use leafwing_abilities::prelude::Pool;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -21,7 +21,11 @@ use crate::{
 };
 
 use super::{
+    carrying_capacity::CrowdingQuery,
+    death::DeathCommandsExt,
+    domestication::{Domestication, Strain},
     energy::{Energy, EnergyPool, StartingEnergy},
+    genetics::{offspring_genome, GenePool, Genome, SelectiveBreeding},
     OrganismId, RawOrganismId,
 };
 
@@ -202,6 +206,19 @@ impl LifePath {
     }
 }
 
+/// Sent whenever a new organism is born.
+///
+/// This is not sent when an existing organism transforms into a new form.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct OrganismBorn {
+    /// The variety of organism that was born.
+    pub organism_id: OrganismId,
+    /// Where the organism was born.
+    pub voxel_pos: VoxelPos,
+    /// Whether the newborn organism cooperates with the colony.
+    pub strain: Strain,
+}
+
 /// Checks if lifecycles are complete, and transitions the organism to that form.
 pub(super) fn transform_when_lifecycle_complete(
     query: Query<(
@@ -210,7 +227,8 @@ pub(super) fn transform_when_lifecycle_complete(
         &VoxelPos,
         &Facing,
         &EnergyPool,
-        Option<&Id<Unit>>,
+        &Genome,
+        &Strain,
     )>,
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
@@ -218,7 +236,7 @@ pub(super) fn transform_when_lifecycle_complete(
     map_geometry: Res<MapGeometry>,
     mut commands: Commands,
 ) {
-    for (entity, lifecycle, &voxel_pos, &facing, energy_pool, maybe_unit) in query.iter() {
+    for (entity, lifecycle, &voxel_pos, &facing, energy_pool, genome, &strain) in query.iter() {
         for new_form in lifecycle.new_forms() {
             // Make sure that there's a valid place to spawn the new form.
             if let OrganismId::Structure(structure_id) = new_form {
@@ -230,12 +248,8 @@ pub(super) fn transform_when_lifecycle_complete(
                 }
             }
 
-            // Cleanup is handled on the basis of what this organism *currently* is.
-            if maybe_unit.is_some() {
-                commands.entity(entity).despawn_recursive();
-            } else {
-                commands.despawn_structure(voxel_pos);
-            }
+            // Anything the organism was carrying is dropped, rather than lost
+            commands.despawn_organism(entity);
 
             match new_form {
                 OrganismId::Structure(structure_id) => {
@@ -250,7 +264,14 @@ pub(super) fn transform_when_lifecycle_complete(
                     // Preserve the energy of the parent organism.
                     let starting_energy = StartingEnergy::Specific(energy_pool.current());
 
-                    commands.spawn_structure(voxel_pos, data, starting_energy);
+                    // This is the same individual in a new form, so its genome and strain are unchanged
+                    commands.spawn_organism_structure(
+                        voxel_pos,
+                        data,
+                        starting_energy,
+                        genome.clone(),
+                        strain,
+                    );
                 }
                 OrganismId::Unit(unit_id) => {
                     let unit_data = unit_manifest.get(unit_id).clone();
@@ -259,6 +280,8 @@ pub(super) fn transform_when_lifecycle_complete(
                         unit_id,
                         voxel_pos,
                         unit_data,
+                        genome.clone(),
+                        strain,
                         &unit_handles,
                     ));
                 }
@@ -271,13 +294,21 @@ pub(super) fn transform_when_lifecycle_complete(
 }
 
 /// Items with [`ItemTag::Seed`](crate::crafting::item_tags::ItemTag) that are dropped on the ground will be consumed and transformed into a new organism.
+///
+/// Each new organism inherits its genome from the organism that produced the seed, as recorded on the [`Litter`].
+/// If that isn't known, a living member of its species is chosen from the [`GenePool`] to be its parent instead.
+/// New organisms are only domesticated if their species has been domesticated.
+/// Seeds that land in crowded areas may fail to sprout, as described by their [`CarryingCapacity`](super::carrying_capacity::CarryingCapacity).
 pub(super) fn sprout_seeds(
     mut litter_query: Query<(&VoxelPos, &mut Litter)>,
     item_manifest: Res<ItemManifest>,
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
-    unit_handles: Res<UnitHandles>,
     map_geometry: Res<MapGeometry>,
+    gene_pool: Res<GenePool>,
+    selective_breeding: Res<SelectiveBreeding>,
+    domestication: Res<Domestication>,
+    crowding_query: CrowdingQuery,
     mut commands: Commands,
 ) {
     // TODO: add germination conditions, and vary this based on the seed type.
@@ -292,6 +323,8 @@ pub(super) fn sprout_seeds(
             continue;
         }
 
+        let parent_genome = litter.parent_genome.clone();
+
         for item_slot in litter.contents.iter_mut() {
             let item_id = item_slot.item_id();
             let Some(organism_id) = item_manifest.get(item_id).seed else {
@@ -312,6 +345,19 @@ pub(super) fn sprout_seeds(
                     // We can't germinate here
                     continue;
                 }
+
+                let reproduction_chance = crowding_query.reproduction_chance(
+                    structure_id,
+                    voxel_pos,
+                    &structure_manifest,
+                    &map_geometry,
+                );
+
+                // Seedlings in crowded areas are outcompeted by their neighbors, and the seed is lost
+                if rng.gen::<f32>() >= reproduction_chance {
+                    let _ = item_slot.remove_all_or_nothing(1);
+                    continue;
+                }
             } else {
                 // For units, just make sure the tile is empty.
                 if map_geometry.is_voxel_clear(voxel_pos).is_err() {
@@ -324,6 +370,22 @@ pub(super) fn sprout_seeds(
                 continue;
             };
 
+            let genome = offspring_genome(
+                organism_id,
+                parent_genome.as_ref(),
+                &structure_manifest,
+                &unit_manifest,
+                &gene_pool,
+                &selective_breeding,
+                rng,
+            );
+
+            let strain = organism_id
+                .variety(&structure_manifest, &unit_manifest)
+                .map_or(Strain::Domesticated, |variety| {
+                    domestication.newborn_strain(variety)
+                });
+
             match organism_id {
                 OrganismId::Structure(structure_id) => {
                     let data = ClipboardData {
@@ -334,19 +396,155 @@ pub(super) fn sprout_seeds(
                             .starting_recipe()
                             .clone(),
                     };
-                    commands.spawn_structure(voxel_pos, data, StartingEnergy::Full);
+                    commands.spawn_newborn_structure(
+                        voxel_pos,
+                        data,
+                        StartingEnergy::Full,
+                        genome,
+                        strain,
+                    );
                 }
                 OrganismId::Unit(unit_id) => {
-                    let unit_data = unit_manifest.get(unit_id).clone();
-
-                    commands.spawn(UnitBundle::newborn(
+                    commands.add(SpawnNewbornUnitCommand {
                         unit_id,
                         voxel_pos,
-                        unit_data,
-                        &unit_handles,
-                    ));
+                        genome,
+                        strain,
+                    });
                 }
             }
         }
     }
 }
+
+/// A [`Command`] that spawns a newborn unit, and sends an [`OrganismBorn`] event once it has been spawned.
+struct SpawnNewbornUnitCommand {
+    /// The variety of unit to spawn.
+    unit_id: Id<Unit>,
+    /// Where the unit should be born.
+    voxel_pos: VoxelPos,
+    /// The heritable traits of the newborn.
+    genome: Genome,
+    /// Whether the newborn cooperates with the colony.
+    strain: Strain,
+}
+
+impl Command for SpawnNewbornUnitCommand {
+    fn apply(self, world: &mut World) {
+        // Something else may have moved in since the birth was planned
+        if world
+            .resource::<MapGeometry>()
+            .is_voxel_clear(self.voxel_pos)
+            .is_err()
+        {
+            return;
+        }
+
+        let unit_data = world.resource::<UnitManifest>().get(self.unit_id).clone();
+        let unit_bundle = UnitBundle::newborn(
+            self.unit_id,
+            self.voxel_pos,
+            unit_data,
+            self.genome,
+            self.strain,
+            world.resource::<UnitHandles>(),
+        );
+        world.spawn(unit_bundle);
+
+        world.send_event(OrganismBorn {
+            organism_id: OrganismId::Unit(self.unit_id),
+            voxel_pos: self.voxel_pos,
+            strain: self.strain,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+    use crate::units::{basic_needs::Diet, unit_manifest::UnitData};
+
+    /// Creates a world in which ants can be born.
+    fn world_with_ants() -> World {
+        let mut world = World::new();
+
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource(map_geometry);
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData::simple("ant", Diet::simple("acacia_leaf")),
+        );
+        world.insert_resource(unit_manifest);
+
+        let mut scenes = HashMap::new();
+        scenes.insert(Id::from_name("ant".to_string()), Handle::default());
+        world.insert_resource(UnitHandles {
+            scenes,
+            picking_mesh: Handle::default(),
+        });
+
+        world.init_resource::<Events<OrganismBorn>>();
+
+        world
+    }
+
+    #[test]
+    fn births_are_announced_once_the_unit_exists() {
+        let mut world = world_with_ants();
+        let ant = Id::from_name("ant".to_string());
+        let voxel_pos = VoxelPos::ZERO.above();
+
+        SpawnNewbornUnitCommand {
+            unit_id: ant,
+            voxel_pos,
+            genome: Genome::default(),
+            strain: Strain::Wild,
+        }
+        .apply(&mut world);
+
+        let newborns: Vec<VoxelPos> = world
+            .query_filtered::<&VoxelPos, With<Id<Unit>>>()
+            .iter(&world)
+            .copied()
+            .collect();
+        assert_eq!(newborns, vec![voxel_pos]);
+
+        let events = world.resource::<Events<OrganismBorn>>();
+        let births: Vec<&OrganismBorn> = events.get_reader().read(events).collect();
+        assert_eq!(
+            births,
+            vec![&OrganismBorn {
+                organism_id: OrganismId::Unit(ant),
+                voxel_pos,
+                strain: Strain::Wild,
+            }]
+        );
+    }
+
+    #[test]
+    fn failed_births_are_not_announced() {
+        let mut world = world_with_ants();
+
+        // The terrain already fills this voxel
+        SpawnNewbornUnitCommand {
+            unit_id: Id::from_name("ant".to_string()),
+            voxel_pos: VoxelPos::ZERO,
+            genome: Genome::default(),
+            strain: Strain::Wild,
+        }
+        .apply(&mut world);
+
+        assert_eq!(
+            world
+                .query_filtered::<(), With<Id<Unit>>>()
+                .iter(&world)
+                .count(),
+            0
+        );
+        assert!(world.resource::<Events<OrganismBorn>>().is_empty());
+    }
+}
@@ -1,25 +1,49 @@
 //! Models organisms, which have two primary types: units (organisms that can move around freely)
 //! and structures (organisms that are fixed in place).
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    asset_management::manifest::Id,
+    asset_management::manifest::{plugin::ManifestPlugin, Id},
+    construction::demolition::set_emitter_for_structures_to_be_demolished,
+    crafting::{set_crafting_emitter, set_storage_emitter},
+    items::item_manifest::Item,
     simulation::SimulationSet,
-    structures::structure_manifest::{Structure, StructureManifest},
+    structures::{
+        logistic_buildings::logistic_buildings_signals,
+        structure_manifest::{Structure, StructureManifest},
+    },
     units::unit_manifest::{Unit, UnitManifest},
 };
 
 use self::{
-    energy::{consume_energy, kill_organisms_when_out_of_energy, EnergyPool},
-    lifecycle::{sprout_seeds, transform_when_lifecycle_complete, Lifecycle, RawLifecycle},
-    oxygen::{manage_oxygen, Oxygen, OxygenPool},
+    death::OrganismDied,
+    disease::{
+        disease_manifest::RawDiseaseManifest, drain_energy_from_infected, spread_disease,
+        Infections, OrganismInfected,
+    },
+    domestication::{advance_mastery, Domestication, SpeciesDomesticated, Strain},
+    energy::{alert_starving_units, consume_energy, kill_organisms_when_out_of_energy, EnergyPool},
+    genetics::{update_gene_pool, GenePool, Genome, SelectiveBreeding},
+    lifecycle::{
+        sprout_seeds, transform_when_lifecycle_complete, Lifecycle, OrganismBorn, RawLifecycle,
+    },
+    oxygen::{alert_suffocating_organisms, manage_oxygen, Oxygen, OxygenPool},
+    symbiosis::{
+        expire_visit_bonuses, regain_energy_from_partners, set_emitter_for_visitable_structures,
+    },
     vegetative_reproduction::vegetative_spread,
 };
 
+pub mod carrying_capacity;
+pub mod death;
+pub mod disease;
+pub mod domestication;
 pub mod energy;
+pub mod genetics;
 pub mod lifecycle;
 pub mod oxygen;
+pub mod symbiosis;
 pub mod vegetative_reproduction;
 
 /// The [`Id`] of an organism.
@@ -64,6 +88,37 @@ impl From<RawOrganismId> for OrganismId {
 }
 
 impl OrganismId {
+    /// Identifies an organism from its [`Id<Structure>`] or [`Id<Unit>`] component.
+    ///
+    /// Returns `None` if neither is present.
+    pub(crate) fn from_components(
+        maybe_structure_id: Option<&Id<Structure>>,
+        maybe_unit_id: Option<&Id<Unit>>,
+    ) -> Option<OrganismId> {
+        match (maybe_structure_id, maybe_unit_id) {
+            (Some(&structure_id), _) => Some(OrganismId::Structure(structure_id)),
+            (None, Some(&unit_id)) => Some(OrganismId::Unit(unit_id)),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the [`OrganismVariety`] that this organism belongs to.
+    ///
+    /// Returns `None` if this is a structure that is not an organism.
+    pub(crate) fn variety<'a>(
+        &self,
+        structure_manifest: &'a StructureManifest,
+        unit_manifest: &'a UnitManifest,
+    ) -> Option<&'a OrganismVariety> {
+        match self {
+            OrganismId::Structure(structure_id) => structure_manifest
+                .get(*structure_id)
+                .organism_variety
+                .as_ref(),
+            OrganismId::Unit(unit_id) => Some(&unit_manifest.get(*unit_id).organism_variety),
+        }
+    }
+
     /// Pretty formatting for this type.
     pub(crate) fn display(
         &self,
@@ -90,17 +145,33 @@ pub(crate) struct OrganismBundle {
     oxygen_pool: OxygenPool,
     /// The ways this organism can transform, and the progress toward doing so.
     lifecycle: Lifecycle,
+    /// The heritable traits of this organism.
+    genome: Genome,
+    /// Whether this organism cooperates with the colony.
+    strain: Strain,
+    /// The diseases this organism is infected with or immune to.
+    infections: Infections,
 }
 
 impl OrganismBundle {
     /// Create a new [`OrganismBundle`]
-    pub(crate) fn new(energy_pool: EnergyPool, lifecycle: Lifecycle) -> OrganismBundle {
+    ///
+    /// The `energy_pool` should already be scaled by the `genome`.
+    pub(crate) fn new(
+        energy_pool: EnergyPool,
+        lifecycle: Lifecycle,
+        genome: Genome,
+        strain: Strain,
+    ) -> OrganismBundle {
         OrganismBundle {
             organism: Organism,
             energy_pool,
             // TODO: consider making this configurable on a per-organism basis
             oxygen_pool: OxygenPool::new(Oxygen::STANDARD_MAX, 0.5),
             lifecycle,
+            genome,
+            strain,
+            infections: Infections::default(),
         }
     }
 }
@@ -114,6 +185,16 @@ pub struct OrganismVariety {
     pub lifecycle: Lifecycle,
     /// Controls the maximum energy, and the rate at which it drains.
     pub energy_pool: EnergyPool,
+    /// The items dropped as litter when this organism dies.
+    pub remains: HashMap<Id<Item>, u32>,
+    /// The standard deviation of each [`Gene`](genetics::Gene) within this species.
+    ///
+    /// Higher values allow for more diverse populations, and faster evolution.
+    pub genetic_variation: f32,
+    /// The biotic mastery that must be gained to domesticate this species.
+    ///
+    /// If `None`, this species is domesticated from the start of the game.
+    pub mastery_required: Option<f32>,
 }
 
 impl OrganismVariety {
@@ -126,6 +207,9 @@ impl OrganismVariety {
             prototypical_form: OrganismId::Unit(Id::from_name(name.to_string())),
             lifecycle: Lifecycle::default(),
             energy_pool: EnergyPool::default(),
+            remains: HashMap::new(),
+            genetic_variation: Genome::DEFAULT_VARIATION,
+            mastery_required: None,
         }
     }
 }
@@ -139,6 +223,16 @@ pub struct RawOrganismVariety {
     pub lifecycle: RawLifecycle,
     /// Controls the maximum energy, and the rate at which it drains.
     pub energy_pool: EnergyPool,
+    /// The names and counts of the items dropped as litter when this organism dies.
+    pub remains: Option<HashMap<String, u32>>,
+    /// The standard deviation of each [`Gene`](genetics::Gene) within this species.
+    ///
+    /// If `None`, [`Genome::DEFAULT_VARIATION`] is used.
+    pub genetic_variation: Option<f32>,
+    /// The biotic mastery that must be gained to domesticate this species.
+    ///
+    /// If `None`, this species is domesticated from the start of the game.
+    pub mastery_required: Option<f32>,
 }
 
 impl From<RawOrganismVariety> for OrganismVariety {
@@ -147,6 +241,14 @@ impl From<RawOrganismVariety> for OrganismVariety {
             prototypical_form: raw.prototypical_form.into(),
             lifecycle: raw.lifecycle.into(),
             energy_pool: raw.energy_pool,
+            remains: raw
+                .remains
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(item_name, count)| (Id::from_name(item_name), count))
+                .collect(),
+            genetic_variation: raw.genetic_variation.unwrap_or(Genome::DEFAULT_VARIATION),
+            mastery_required: raw.mastery_required,
         }
     }
 }
@@ -160,17 +262,39 @@ pub struct OrganismPlugin;
 
 impl Plugin for OrganismPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            FixedUpdate,
-            (
-                consume_energy,
-                kill_organisms_when_out_of_energy,
-                transform_when_lifecycle_complete,
-                vegetative_spread,
-                sprout_seeds,
-                manage_oxygen,
-            )
-                .in_set(SimulationSet),
-        );
+        app.add_plugins(ManifestPlugin::<RawDiseaseManifest>::new())
+            .add_event::<OrganismDied>()
+            .add_event::<OrganismBorn>()
+            .add_event::<SpeciesDomesticated>()
+            .add_event::<OrganismInfected>()
+            .init_resource::<GenePool>()
+            .init_resource::<SelectiveBreeding>()
+            .init_resource::<Domestication>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    consume_energy,
+                    kill_organisms_when_out_of_energy,
+                    transform_when_lifecycle_complete,
+                    vegetative_spread,
+                    sprout_seeds.after(update_gene_pool),
+                    manage_oxygen,
+                    alert_starving_units.after(consume_energy),
+                    alert_suffocating_organisms.after(manage_oxygen),
+                    update_gene_pool,
+                    advance_mastery.after(sprout_seeds).after(vegetative_spread),
+                    spread_disease,
+                    drain_energy_from_infected.after(spread_disease),
+                    expire_visit_bonuses,
+                    regain_energy_from_partners,
+                    // Must run after the other emitters, as they wipe out existing signals
+                    set_emitter_for_visitable_structures
+                        .after(set_crafting_emitter)
+                        .after(set_storage_emitter)
+                        .after(logistic_buildings_signals)
+                        .before(set_emitter_for_structures_to_be_demolished),
+                )
+                    .in_set(SimulationSet),
+            );
     }
 }
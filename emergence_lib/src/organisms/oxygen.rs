@@ -11,12 +11,16 @@ use std::{
 use crate::{
     asset_management::manifest::Id,
     geometry::{Height, MapGeometry, VoxelPos},
-    structures::{commands::StructureCommandsExt, Footprint},
+    simulation::alerts::{Alert, AlertKind, EdgeTriggeredAlerts},
+    structures::Footprint,
     units::unit_manifest::Unit,
     water::WaterDepth,
 };
 
-use super::Organism;
+use super::{
+    death::{CauseOfDeath, DeathCommandsExt},
+    Organism,
+};
 
 /// The amount of oxygen available to an organism.
 /// If they run out, they die.
@@ -157,7 +161,7 @@ impl Pool for OxygenPool {
 pub(super) fn manage_oxygen(
     mut unit_query: Query<(Entity, &VoxelPos, &mut OxygenPool), With<Id<Unit>>>,
     mut structure_query: Query<
-        (&VoxelPos, &Footprint, &mut OxygenPool),
+        (Entity, &VoxelPos, &Footprint, &mut OxygenPool),
         (Without<Id<Unit>>, With<Organism>),
     >,
     water_depth_query: Query<&WaterDepth>,
@@ -179,7 +183,7 @@ pub(super) fn manage_oxygen(
             oxygen_pool.set_current(proposed);
 
             if oxygen_pool.is_empty() {
-                commands.entity(entity).despawn_recursive();
+                commands.kill_organism(entity, CauseOfDeath::Drowning);
             }
         } else {
             let proposed = oxygen_pool.current + Oxygen::REGEN_RATE * delta_time;
@@ -187,7 +191,7 @@ pub(super) fn manage_oxygen(
         }
     }
 
-    for (&voxel_pos, footprint, mut oxygen_pool) in structure_query.iter_mut() {
+    for (entity, &voxel_pos, footprint, mut oxygen_pool) in structure_query.iter_mut() {
         let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
         let surface_water_depth = water_depth_query
             .get(terrain_entity)
@@ -199,7 +203,7 @@ pub(super) fn manage_oxygen(
             oxygen_pool.set_current(proposed);
 
             if oxygen_pool.is_empty() {
-                commands.despawn_structure(voxel_pos);
+                commands.kill_organism(entity, CauseOfDeath::Drowning);
             }
         } else {
             let proposed = oxygen_pool.current + Oxygen::REGEN_RATE * delta_time;
@@ -207,3 +211,17 @@ pub(super) fn manage_oxygen(
         }
     }
 }
+
+/// Raises an alert when an organism starts to panic because it is running out of oxygen.
+pub(super) fn alert_suffocating_organisms(
+    organism_query: Query<(Entity, &VoxelPos, &OxygenPool)>,
+    mut alerts: EventWriter<Alert>,
+    mut suffocating: Local<EdgeTriggeredAlerts>,
+) {
+    let panicking_organisms = organism_query
+        .iter()
+        .filter(|(.., oxygen_pool)| oxygen_pool.should_panic())
+        .map(|(entity, &voxel_pos, _)| (entity, voxel_pos));
+
+    alerts.send_batch(suffocating.update(AlertKind::Suffocating, panicking_organisms));
+}
@@ -0,0 +1,278 @@
+//! Symbiosis lets units and structures of different species help each other out.
+//!
+//! Structures with a symbiosis emit a [`SignalType::Visit`] signal, which attracts their partner units.
+//! When a partner visits, the structure's recipes produce more for a while,
+//! and partners that linger next to the structure regain energy.
+//! This allows mutualisms like pollination or ant-fungus farming to be expressed entirely in the manifest.
+
+use bevy::{
+    ecs::{query::Has, system::SystemParam},
+    prelude::*,
+};
+use leafwing_abilities::prelude::Pool;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::Ghost,
+    geometry::{Height, MapGeometry, VoxelPos},
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::time::{Days, InGameTime},
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::{needs::need_manifest::NeedManifest, unit_manifest::Unit},
+};
+
+use super::energy::{Energy, EnergyPool};
+
+/// How a structure cooperates with the units that visit it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbiosisData {
+    /// The units that are attracted to this structure.
+    pub partners: Vec<Id<Unit>>,
+    /// The benefit this structure gains when a partner visits it, if any.
+    pub visit_bonus: Option<VisitBonus>,
+    /// The energy gained each second by partners standing on or next to this structure.
+    pub partner_energy_regen: Energy,
+}
+
+impl SymbiosisData {
+    /// Is the provided unit one of this structure's partners?
+    pub(crate) fn is_partner(&self, unit_id: Id<Unit>) -> bool {
+        self.partners.contains(&unit_id)
+    }
+}
+
+/// The unprocessed equivalent of [`SymbiosisData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawSymbiosisData {
+    /// The units that are attracted to this structure.
+    pub partners: Vec<String>,
+    /// The benefit this structure gains when a partner visits it, if any.
+    pub visit_bonus: Option<VisitBonus>,
+    /// The energy gained each second by partners standing on or next to this structure.
+    ///
+    /// If this is [`None`], partners gain no energy.
+    pub partner_energy_regen: Option<f32>,
+}
+
+impl From<RawSymbiosisData> for SymbiosisData {
+    fn from(raw: RawSymbiosisData) -> Self {
+        SymbiosisData {
+            partners: raw.partners.into_iter().map(Id::from_name).collect(),
+            visit_bonus: raw.visit_bonus,
+            partner_energy_regen: Energy(raw.partner_energy_regen.unwrap_or_default()),
+        }
+    }
+}
+
+/// The temporary boost that a structure gets when one of its partners visits.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VisitBonus {
+    /// Multiplies the number of items produced by each recipe that completes while the bonus is active.
+    pub yield_multiplier: f32,
+    /// How long the bonus lasts, in in-game days.
+    pub duration: f32,
+}
+
+/// A structure that has recently been visited by one of its symbiotic partners.
+///
+/// While this component is present, the structure stops calling for visitors.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub(crate) struct Visited {
+    /// Multiplies the number of items produced by each recipe.
+    yield_multiplier: f32,
+    /// How much longer the bonus lasts.
+    remaining: Days,
+}
+
+impl Visited {
+    /// Starts a new visit, granting the provided `visit_bonus`.
+    pub(crate) fn new(visit_bonus: &VisitBonus) -> Self {
+        Visited {
+            yield_multiplier: visit_bonus.yield_multiplier,
+            remaining: Days(visit_bonus.duration),
+        }
+    }
+
+    /// The multiplier applied to the number of items produced by each recipe.
+    pub(crate) fn yield_multiplier(&self) -> f32 {
+        self.yield_multiplier
+    }
+
+    /// Advances the time remaining by `delta_days`.
+    ///
+    /// Returns `true` if the bonus has expired.
+    fn tick(&mut self, delta_days: Days) -> bool {
+        self.remaining -= delta_days;
+        self.remaining <= Days::ZERO
+    }
+}
+
+/// A query for the structures that are waiting for their partners to visit.
+#[derive(SystemParam)]
+pub(crate) struct PartnerQuery<'w, 's> {
+    /// The contained query type.
+    query: Query<'w, 's, (&'static Id<Structure>, Has<Visited>), Without<Ghost>>,
+}
+
+impl<'w, 's> PartnerQuery<'w, 's> {
+    /// Is there a structure of type `structure_id` at `target` that is waiting for a visit?
+    ///
+    /// If so, returns `Some(matching_structure_entity)`.
+    pub(crate) fn awaiting_visit(
+        &self,
+        current: VoxelPos,
+        target: VoxelPos,
+        structure_id: Id<Structure>,
+        map_geometry: &MapGeometry,
+    ) -> Option<Entity> {
+        // This is only a viable target if the unit can reach it!
+        if current.abs_height_diff(target) > Height::MAX_STEP {
+            return None;
+        }
+
+        let entity = map_geometry.get_structure(target)?;
+
+        let (&found_structure_id, visited) = self.query.get(entity).ok()?;
+
+        match found_structure_id == structure_id && !visited {
+            true => Some(entity),
+            false => None,
+        }
+    }
+}
+
+/// Asks units to visit symbiotic structures that are not currently benefiting from a visit.
+///
+/// Structures that satisfy a unit's [`Need`](crate::units::needs::need_manifest::Need) call for visitors in the same way.
+pub(crate) fn set_emitter_for_visitable_structures(
+    mut structure_query: Query<(&mut Emitter, &Id<Structure>, Has<Visited>), Without<Ghost>>,
+    structure_manifest: Res<StructureManifest>,
+    need_manifest: Res<NeedManifest>,
+) {
+    /// The strength of the signal emitted by structures looking for visitors.
+    const VISIT_SIGNAL_STRENGTH: f32 = 20.;
+
+    for (mut emitter, &structure_id, visited) in structure_query.iter_mut() {
+        let symbiosis = &structure_manifest.get(structure_id).symbiosis;
+        let satisfies_needs = need_manifest.satisfied_by_structure(structure_id);
+
+        if symbiosis.is_none() && !satisfies_needs {
+            continue;
+        }
+
+        // Not every emitter is reset each tick, so clear out any stale visit signals first
+        emitter
+            .signals
+            .retain(|(signal_type, _)| !matches!(signal_type, SignalType::Visit(_)));
+
+        // Structures that only feed or otherwise serve their visitors always welcome more of them
+        let wants_visit = satisfies_needs
+            || !visited
+            || symbiosis
+                .as_ref()
+                .is_some_and(|symbiosis| symbiosis.visit_bonus.is_none());
+
+        if wants_visit {
+            emitter.signals.push((
+                SignalType::Visit(structure_id),
+                SignalStrength::new(VISIT_SIGNAL_STRENGTH),
+            ));
+        }
+    }
+}
+
+/// Counts down the remaining time on each visit bonus, removing any that have expired.
+pub(super) fn expire_visit_bonuses(
+    mut structure_query: Query<(Entity, &mut Visited)>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+    mut commands: Commands,
+) {
+    let delta_days = Days(time.delta().as_secs_f32() / in_game_time.seconds_per_day());
+
+    for (entity, mut visited) in structure_query.iter_mut() {
+        if visited.tick(delta_days) {
+            commands.entity(entity).remove::<Visited>();
+        }
+    }
+}
+
+/// Units regain energy when standing on or next to structures that they are partnered with.
+pub(super) fn regain_energy_from_partners(
+    mut unit_query: Query<(&Id<Unit>, &VoxelPos, &mut EnergyPool)>,
+    structure_query: Query<&Id<Structure>>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    for (&unit_id, &unit_pos, mut energy_pool) in unit_query.iter_mut() {
+        let regen = partner_energy_regen(
+            unit_id,
+            unit_pos,
+            &structure_query,
+            &structure_manifest,
+            &map_geometry,
+        );
+
+        if regen > Energy(0.) {
+            let proposed = energy_pool.current() + regen * delta_time;
+            energy_pool.set_current(proposed);
+        }
+    }
+}
+
+/// The total energy per second that a unit of type `unit_id` at `unit_pos` gains from nearby partner structures.
+fn partner_energy_regen(
+    unit_id: Id<Unit>,
+    unit_pos: VoxelPos,
+    structure_query: &Query<&Id<Structure>>,
+    structure_manifest: &StructureManifest,
+    map_geometry: &MapGeometry,
+) -> Energy {
+    // This includes the voxel that the unit is standing in
+    unit_pos
+        .reachable_neighbors()
+        .into_iter()
+        .filter_map(|voxel_pos| map_geometry.get_structure(voxel_pos))
+        .filter_map(|structure_entity| structure_query.get(structure_entity).ok())
+        .filter_map(|&structure_id| structure_manifest.get(structure_id).symbiosis.as_ref())
+        .filter(|symbiosis| symbiosis.is_partner(unit_id))
+        .fold(Energy(0.), |total, symbiosis| {
+            total + symbiosis.partner_energy_regen
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visit_bonus_expires() {
+        let bonus = VisitBonus {
+            yield_multiplier: 2.0,
+            duration: 1.0,
+        };
+
+        let mut visited = Visited::new(&bonus);
+        assert_eq!(visited.yield_multiplier(), 2.0);
+        assert!(!visited.tick(Days(0.5)));
+        assert!(visited.tick(Days(0.5)));
+    }
+
+    #[test]
+    fn only_partners_benefit() {
+        let raw = RawSymbiosisData {
+            partners: vec!["ant".to_string()],
+            visit_bonus: None,
+            partner_energy_regen: Some(1.0),
+        };
+
+        let symbiosis = SymbiosisData::from(raw);
+        assert!(symbiosis.is_partner(Id::from_name("ant".to_string())));
+        assert!(!symbiosis.is_partner(Id::from_name("basket_crab".to_string())));
+        assert_eq!(symbiosis.partner_energy_regen, Energy(1.0));
+    }
+}
@@ -3,7 +3,7 @@
 //! In Emergence, this allows organisms to spread to nearby tiles without seeds.
 use bevy::prelude::*;
 use leafwing_abilities::prelude::Pool;
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 
@@ -17,7 +17,12 @@ use crate::{
     },
 };
 
-use super::energy::{Energy, EnergyPool, StartingEnergy};
+use super::{
+    carrying_capacity::CrowdingQuery,
+    domestication::Strain,
+    energy::{Energy, EnergyPool, StartingEnergy},
+    genetics::Genome,
+};
 
 /// A component that allows an organism to spread to nearby tiles.
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
@@ -63,13 +68,18 @@ impl From<RawVegetativeReproduction> for VegetativeReproduction {
 }
 
 /// Spreads organisms to nearby tiles.
+///
+/// Organisms in crowded areas are less likely to spread, as described by their [`CarryingCapacity`](super::carrying_capacity::CarryingCapacity).
 pub(super) fn vegetative_spread(
     mut query: Query<(
         &VoxelPos,
         &Id<Structure>,
         &mut VegetativeReproduction,
         &mut EnergyPool,
+        &Genome,
+        &Strain,
     )>,
+    crowding_query: CrowdingQuery,
     map_geometry: Res<MapGeometry>,
     structure_manifest: Res<StructureManifest>,
     time: Res<Time>,
@@ -78,8 +88,14 @@ pub(super) fn vegetative_spread(
     let mut rng = rand::thread_rng();
     let delta_time = time.delta();
 
-    for (&voxel_pos, &structure_id, mut vegetative_reproduction, mut energy_pool) in
-        query.iter_mut()
+    for (
+        &voxel_pos,
+        &structure_id,
+        mut vegetative_reproduction,
+        mut energy_pool,
+        genome,
+        &strain,
+    ) in query.iter_mut()
     {
         vegetative_reproduction.timer.tick(delta_time);
         if !vegetative_reproduction.timer.finished() {
@@ -100,6 +116,19 @@ pub(super) fn vegetative_spread(
             continue;
         };
 
+        let reproduction_chance = crowding_query.reproduction_chance(
+            structure_id,
+            tile_to_spawn_in,
+            &structure_manifest,
+            &map_geometry,
+        );
+
+        // Offspring are outcompeted by their neighbors, so wait and try again later
+        if rng.gen::<f32>() >= reproduction_chance {
+            vegetative_reproduction.timer.reset();
+            continue;
+        }
+
         let clipboard_data = ClipboardData {
             structure_id,
             facing: Facing::random(&mut rng),
@@ -113,10 +142,21 @@ pub(super) fn vegetative_spread(
         let half_current = current_energy / 2.;
         energy_pool.set_current(half_current);
 
-        commands.spawn_structure(
+        let genetic_variation = structure_manifest
+            .get(structure_id)
+            .organism_variety
+            .as_ref()
+            .map_or(Genome::DEFAULT_VARIATION, |variety| {
+                variety.genetic_variation
+            });
+
+        // Offspring share the strain of their parent
+        commands.spawn_newborn_structure(
             tile_to_spawn_in,
             clipboard_data,
             StartingEnergy::Specific(half_current),
+            genome.inherit(genetic_variation, &mut rng),
+            strain,
         );
 
         // Reset the timer once we've successfully spawned a new organism
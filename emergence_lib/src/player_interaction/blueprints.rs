@@ -0,0 +1,584 @@
+//! Blueprints are named, reusable layouts of structures and terraforming that are saved to disk.
+//!
+//! Blueprints can be saved from the current selection, browsed in the blueprint library,
+//! and pasted (with rotation) just like the contents of the clipboard.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::{Hex, HexIterExt};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use thiserror::Error;
+
+use crate::{
+    construction::{ghosts::Preview, terraform::TerraformingAction},
+    geometry::{DiscreteHeight, MapGeometry, VoxelPos},
+    utils::persistence::{read_json, write_json, PersistenceError},
+};
+
+use super::{
+    clipboard::{ClipboardData, ClipboardQuery, Tool},
+    selection::CurrentSelection,
+    InteractionSystem, PlayerAction,
+};
+
+/// Code and data for saving, loading and selecting blueprints.
+pub(super) struct BlueprintPlugin;
+
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlueprintLibrary>()
+            .add_systems(Startup, load_blueprints)
+            .add_systems(
+                Update,
+                (save_blueprint, cycle_blueprints)
+                    .chain()
+                    .in_set(InteractionSystem::SetClipboard)
+                    .after(InteractionSystem::SelectTiles),
+            );
+    }
+}
+
+/// A reusable layout of structures and terraforming actions.
+///
+/// All positions are stored relative to the center of the blueprint.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Blueprint {
+    /// The structures to place, and their relative positions.
+    ///
+    /// Stored as a list of pairs, as JSON maps can only have string keys.
+    pub(crate) structures: Vec<(VoxelPos, ClipboardData)>,
+    /// The terraforming actions to perform, and their relative positions.
+    pub(crate) terraforming: Vec<(Hex, TerraformingAction)>,
+}
+
+impl Blueprint {
+    /// Creates a new [`Blueprint`] from absolute positions, centering it on the mean position.
+    pub(crate) fn new(
+        structures: HashMap<VoxelPos, ClipboardData>,
+        terraforming: HashMap<Hex, TerraformingAction>,
+    ) -> Self {
+        let mut blueprint = Blueprint {
+            structures: structures.into_iter().collect(),
+            terraforming: terraforming.into_iter().collect(),
+        };
+        blueprint.normalize_positions();
+        blueprint
+    }
+
+    /// Does this blueprint contain anything to place?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.structures.is_empty() && self.terraforming.is_empty()
+    }
+
+    /// Centers the contents of the blueprint on the mean tile position, rounded to the nearest tile.
+    ///
+    /// Heights are made relative to the lowest structure.
+    fn normalize_positions(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+
+        let structure_hexes = self.structures.iter().map(|(voxel_pos, _)| voxel_pos.hex);
+        let terraforming_hexes = self.terraforming.iter().map(|(hex, _)| *hex);
+        let center_hex = structure_hexes.chain(terraforming_hexes).center();
+        let min_height = self
+            .structures
+            .iter()
+            .map(|(voxel_pos, _)| voxel_pos.height)
+            .min()
+            .unwrap_or(DiscreteHeight::ZERO);
+
+        let center = VoxelPos {
+            hex: center_hex,
+            height: min_height,
+        };
+
+        for (voxel_pos, _) in self.structures.iter_mut() {
+            *voxel_pos = *voxel_pos - center;
+        }
+
+        for (hex, _) in self.terraforming.iter_mut() {
+            *hex -= center_hex;
+        }
+    }
+
+    /// Rotates the contents of the blueprint around its center by one 60 degree step.
+    pub(crate) fn rotate(&mut self, clockwise: bool) {
+        for (voxel_pos, clipboard_data) in self.structures.iter_mut() {
+            if clockwise {
+                voxel_pos.hex = voxel_pos.hex.clockwise();
+                clipboard_data.facing.rotate_clockwise();
+            } else {
+                voxel_pos.hex = voxel_pos.hex.counter_clockwise();
+                clipboard_data.facing.rotate_counterclockwise();
+            }
+        }
+
+        for (hex, _) in self.terraforming.iter_mut() {
+            *hex = match clockwise {
+                true => hex.clockwise(),
+                false => hex.counter_clockwise(),
+            };
+        }
+    }
+
+    /// The structures in this blueprint, shifted so that the blueprint is centered on `origin`.
+    pub(crate) fn offset_structures(
+        &self,
+        origin: VoxelPos,
+    ) -> impl Iterator<Item = (VoxelPos, &ClipboardData)> + '_ {
+        self.structures
+            .iter()
+            .map(move |(voxel_pos, clipboard_data)| (*voxel_pos + origin, clipboard_data))
+    }
+
+    /// The terraforming actions in this blueprint, shifted so that the blueprint is centered on `origin`.
+    pub(crate) fn offset_terraforming(
+        &self,
+        origin: Hex,
+    ) -> impl Iterator<Item = (Hex, TerraformingAction)> + '_ {
+        self.terraforming
+            .iter()
+            .map(move |(hex, action)| (*hex + origin, *action))
+    }
+}
+
+/// An error produced when reading or writing blueprints.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BlueprintError {
+    /// The blueprint file could not be read or written.
+    #[error(transparent)]
+    Persistence(#[from] PersistenceError),
+    /// The name cannot be used as a file name.
+    #[error("\"{0}\" is not a valid blueprint name")]
+    InvalidName(String),
+    /// Another blueprint already has this name.
+    #[error("A blueprint named \"{0}\" already exists")]
+    NameTaken(String),
+    /// There is no blueprint with this name.
+    #[error("There is no blueprint named \"{0}\"")]
+    NotFound(String),
+}
+
+/// All of the blueprints available to the player, indexed by name.
+///
+/// Each blueprint is stored in its own file in the blueprint directory,
+/// so they can easily be renamed and shared between players.
+#[derive(Resource, Debug, Clone)]
+pub(crate) struct BlueprintLibrary {
+    /// The directory where blueprints are stored.
+    directory: PathBuf,
+    /// The blueprints, sorted by name.
+    blueprints: BTreeMap<String, Blueprint>,
+    /// The name of the blueprint that is currently selected, if any.
+    selected: Option<String>,
+}
+
+impl Default for BlueprintLibrary {
+    fn default() -> Self {
+        BlueprintLibrary::new(BlueprintLibrary::DEFAULT_DIRECTORY)
+    }
+}
+
+impl BlueprintLibrary {
+    /// The directory where blueprints are stored by default.
+    const DEFAULT_DIRECTORY: &'static str = "blueprints";
+
+    /// The file extension used for blueprint files.
+    const EXTENSION: &'static str = "blueprint.json";
+
+    /// Creates a new empty library, backed by the provided `directory`.
+    pub(crate) fn new(directory: impl Into<PathBuf>) -> Self {
+        BlueprintLibrary {
+            directory: directory.into(),
+            blueprints: BTreeMap::new(),
+            selected: None,
+        }
+    }
+
+    /// The path to the file that stores the blueprint with the given `name`.
+    fn path(&self, name: &str) -> PathBuf {
+        self.directory
+            .join(format!("{name}.{}", BlueprintLibrary::EXTENSION))
+    }
+
+    /// Loads all blueprints found in the blueprint directory.
+    ///
+    /// The name of each blueprint is taken from its file name.
+    /// Files that cannot be read are skipped, and reported in the returned list of errors.
+    pub(crate) fn load_all(&mut self) -> Vec<(PathBuf, BlueprintError)> {
+        let mut errors = Vec::new();
+
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            // No blueprints have been saved yet
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return errors,
+            Err(error) => {
+                errors.push((self.directory.clone(), PersistenceError::from(error).into()));
+                return errors;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(name) = file_name.strip_suffix(&format!(".{}", BlueprintLibrary::EXTENSION))
+            else {
+                continue;
+            };
+            let name = name.to_string();
+
+            match read_json(&path) {
+                Ok(blueprint) => {
+                    self.blueprints.insert(name, blueprint);
+                }
+                Err(error) => errors.push((path, error.into())),
+            }
+        }
+
+        errors
+    }
+
+    /// Saves the `blueprint` to disk under the provided `name`, replacing any existing blueprint with that name.
+    ///
+    /// The saved blueprint is also added to the library and selected.
+    pub(crate) fn save(
+        &mut self,
+        name: String,
+        blueprint: Blueprint,
+    ) -> Result<(), BlueprintError> {
+        write_json(&self.path(&name), &blueprint)?;
+
+        self.blueprints.insert(name.clone(), blueprint);
+        self.selected = Some(name);
+        Ok(())
+    }
+
+    /// Can `name` be used as the name of a blueprint?
+    ///
+    /// Names are used as file names, so only letters, digits, spaces, dashes and underscores are allowed.
+    pub(crate) fn is_valid_name(name: &str) -> bool {
+        !name.trim().is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+    }
+
+    /// Renames the blueprint called `old_name` to `new_name`, both in the library and on disk.
+    ///
+    /// If the renamed blueprint was selected, it stays selected.
+    pub(crate) fn rename(&mut self, old_name: &str, new_name: &str) -> Result<(), BlueprintError> {
+        let new_name = new_name.trim();
+        if new_name == old_name {
+            return Ok(());
+        }
+
+        if !BlueprintLibrary::is_valid_name(new_name) {
+            return Err(BlueprintError::InvalidName(new_name.to_string()));
+        }
+
+        if self.blueprints.contains_key(new_name) {
+            return Err(BlueprintError::NameTaken(new_name.to_string()));
+        }
+
+        if !self.blueprints.contains_key(old_name) {
+            return Err(BlueprintError::NotFound(old_name.to_string()));
+        }
+
+        std::fs::rename(self.path(old_name), self.path(new_name))
+            .map_err(PersistenceError::from)?;
+
+        let blueprint = self.blueprints.remove(old_name).unwrap();
+        self.blueprints.insert(new_name.to_string(), blueprint);
+
+        if self.selected.as_deref() == Some(old_name) {
+            self.selected = Some(new_name.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Generates a name that is not yet used by any blueprint in the library.
+    pub(crate) fn unused_name(&self) -> String {
+        (1..)
+            .map(|n| format!("blueprint_{n}"))
+            .find(|name| !self.blueprints.contains_key(name))
+            .unwrap()
+    }
+
+    /// Gets the blueprint with the provided `name`, if it exists.
+    pub(crate) fn get(&self, name: &str) -> Option<&Blueprint> {
+        self.blueprints.get(name)
+    }
+
+    /// The names of all blueprints in the library, in alphabetical order.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.blueprints.keys().map(String::as_str)
+    }
+
+    /// The name of the currently selected blueprint, if any.
+    pub(crate) fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    /// Selects the next blueprint in the library, wrapping around to the first.
+    ///
+    /// Returns the newly selected blueprint, if the library is not empty.
+    pub(crate) fn select_next(&mut self) -> Option<&Blueprint> {
+        let next_name = match &self.selected {
+            Some(current) => self
+                .blueprints
+                .range::<String, _>((
+                    std::ops::Bound::Excluded(current),
+                    std::ops::Bound::Unbounded,
+                ))
+                .next()
+                .or_else(|| self.blueprints.iter().next()),
+            None => self.blueprints.iter().next(),
+        }
+        .map(|(name, _)| name.clone());
+
+        self.selected = next_name;
+        self.get(self.selected.as_deref()?)
+    }
+}
+
+/// Reads the saved blueprints from disk when the game starts.
+fn load_blueprints(mut blueprint_library: ResMut<BlueprintLibrary>) {
+    for (path, error) in blueprint_library.load_all() {
+        warn!("Could not load blueprint at {}: {error}", path.display());
+    }
+}
+
+/// Saves the selected structures and terraforming as a new blueprint.
+///
+/// If nothing is selected, the contents of the clipboard are saved instead.
+fn save_blueprint(
+    actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    mut tool: ResMut<Tool>,
+    mut blueprint_library: ResMut<BlueprintLibrary>,
+    structure_query: Query<ClipboardQuery, Without<Preview>>,
+    terraforming_query: Query<&TerraformingAction, Without<Preview>>,
+    map_geometry: Res<MapGeometry>,
+) {
+    if !actions.just_pressed(PlayerAction::SaveBlueprint) {
+        return;
+    }
+
+    let blueprint = match &*current_selection {
+        CurrentSelection::Voxels(selected_voxels) if !selected_voxels.is_empty() => {
+            let mut structures = HashMap::new();
+            let mut terraforming = HashMap::new();
+
+            for &voxel_pos in selected_voxels.iter() {
+                let maybe_structure_entity = map_geometry
+                    .get_ghost_structure(voxel_pos)
+                    .or_else(|| map_geometry.get_structure(voxel_pos));
+
+                if let Some(entity) = maybe_structure_entity {
+                    if let Ok(query_item) = structure_query.get(entity) {
+                        structures.insert(voxel_pos, query_item.into());
+                    }
+                }
+
+                if let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) {
+                    if let Ok(&action) = terraforming_query.get(terrain_entity) {
+                        if action != TerraformingAction::None {
+                            terraforming.insert(voxel_pos.hex, action);
+                        }
+                    }
+                }
+            }
+
+            Blueprint::new(structures, terraforming)
+        }
+        _ => match &*tool {
+            Tool::Structures(map) => Blueprint::new(map.clone(), HashMap::new()),
+            Tool::Blueprint(blueprint) => blueprint.clone(),
+            Tool::Terraform(_) | Tool::None => Blueprint::default(),
+        },
+    };
+
+    if blueprint.is_empty() {
+        return;
+    }
+
+    let name = blueprint_library.unused_name();
+    match blueprint_library.save(name.clone(), blueprint.clone()) {
+        Ok(()) => info!("Saved blueprint {name}."),
+        Err(error) => warn!("Could not save blueprint {name}: {error}"),
+    }
+
+    *tool = Tool::Blueprint(blueprint);
+}
+
+/// Selects the next blueprint from the library, placing it on the clipboard.
+fn cycle_blueprints(
+    actions: Res<ActionState<PlayerAction>>,
+    mut tool: ResMut<Tool>,
+    mut blueprint_library: ResMut<BlueprintLibrary>,
+) {
+    if actions.just_pressed(PlayerAction::CycleBlueprints) {
+        if let Some(blueprint) = blueprint_library.select_next() {
+            *tool = Tool::Blueprint(blueprint.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id, crafting::recipe::ActiveRecipe, geometry::Facing,
+        utils::persistence::TemporaryDirectory,
+    };
+
+    fn test_blueprint() -> Blueprint {
+        let mut structures = HashMap::new();
+        structures.insert(
+            VoxelPos {
+                hex: Hex::new(3, 4),
+                height: DiscreteHeight(2),
+            },
+            ClipboardData {
+                structure_id: Id::from_name("test".to_string()),
+                facing: Facing::default(),
+                active_recipe: ActiveRecipe::NONE,
+            },
+        );
+        structures.insert(
+            VoxelPos {
+                hex: Hex::new(5, 4),
+                height: DiscreteHeight(3),
+            },
+            ClipboardData {
+                structure_id: Id::from_name("other".to_string()),
+                facing: Facing::default(),
+                active_recipe: ActiveRecipe::NONE,
+            },
+        );
+
+        let mut terraforming = HashMap::new();
+        terraforming.insert(Hex::new(4, 4), TerraformingAction::Raise);
+
+        Blueprint::new(structures, terraforming)
+    }
+
+    #[test]
+    fn blueprints_are_centered() {
+        let blueprint = test_blueprint();
+
+        assert!(blueprint
+            .terraforming
+            .contains(&(Hex::ZERO, TerraformingAction::Raise)));
+
+        let min_height = blueprint
+            .structures
+            .iter()
+            .map(|(voxel_pos, _)| voxel_pos.height)
+            .min()
+            .unwrap();
+        assert_eq!(min_height, DiscreteHeight::ZERO);
+    }
+
+    #[test]
+    fn six_rotations_are_identity() {
+        let original = test_blueprint();
+        let mut clockwise = original.clone();
+        let mut counterclockwise = original.clone();
+
+        for _ in 0..6 {
+            clockwise.rotate(true);
+            counterclockwise.rotate(false);
+        }
+
+        assert_eq!(clockwise, original);
+        assert_eq!(counterclockwise, original);
+    }
+
+    #[test]
+    fn rotation_preserves_height_and_turns_structures() {
+        let original = test_blueprint();
+        let mut rotated = original.clone();
+        rotated.rotate(true);
+
+        for ((original_pos, original_data), (rotated_pos, rotated_data)) in
+            original.structures.iter().zip(rotated.structures.iter())
+        {
+            assert_eq!(original_pos.height, rotated_pos.height);
+            assert_eq!(rotated_pos.hex, original_pos.hex.clockwise());
+            assert_ne!(original_data.facing, rotated_data.facing);
+        }
+    }
+
+    #[test]
+    fn blueprints_round_trip_through_disk() {
+        let directory = TemporaryDirectory::new("blueprint_test");
+        let blueprint = test_blueprint();
+
+        let mut library = BlueprintLibrary::new(directory.path());
+        let name = library.unused_name();
+        library.save(name.clone(), blueprint.clone()).unwrap();
+        assert_eq!(library.selected(), Some(name.as_str()));
+
+        let mut reloaded_library = BlueprintLibrary::new(directory.path());
+        assert!(reloaded_library.load_all().is_empty());
+        assert_eq!(reloaded_library.get(&name), Some(&blueprint));
+    }
+
+    #[test]
+    fn blueprints_can_be_renamed() {
+        let directory = TemporaryDirectory::new("blueprint_rename_test");
+
+        let mut library = BlueprintLibrary::new(directory.path());
+        library.save("first".to_string(), test_blueprint()).unwrap();
+        library
+            .save("second".to_string(), Blueprint::default())
+            .unwrap();
+
+        let invalid = library.rename("second", "../escape");
+        let taken = library.rename("second", "first");
+        let renamed = library.rename("second", "Crab farm");
+
+        let mut reloaded_library = BlueprintLibrary::new(directory.path());
+        let errors = reloaded_library.load_all();
+
+        assert!(matches!(invalid, Err(BlueprintError::InvalidName(_))));
+        assert!(matches!(taken, Err(BlueprintError::NameTaken(_))));
+        assert!(renamed.is_ok());
+        assert_eq!(library.selected(), Some("Crab farm"));
+        assert_eq!(library.get("second"), None);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            reloaded_library.names().collect::<Vec<_>>(),
+            vec!["Crab farm", "first"]
+        );
+    }
+
+    #[test]
+    fn selection_cycles_through_library() {
+        let directory = std::env::temp_dir().join("emergence_blueprint_cycle_test");
+        let mut library = BlueprintLibrary::new(directory);
+        assert_eq!(library.select_next(), None);
+
+        library
+            .blueprints
+            .insert("a".to_string(), Blueprint::default());
+        library
+            .blueprints
+            .insert("b".to_string(), Blueprint::default());
+
+        library.select_next();
+        assert_eq!(library.selected(), Some("a"));
+        library.select_next();
+        assert_eq!(library.selected(), Some("b"));
+        library.select_next();
+        assert_eq!(library.selected(), Some("a"));
+    }
+}
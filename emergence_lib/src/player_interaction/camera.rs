@@ -1,6 +1,6 @@
 //! Camera controls and movement.
 //!
-//! This RTS-style camera can zoom, pan and rotate.
+//! This RTS-style camera can zoom, pan and rotate, and can follow a unit as it moves.
 
 use std::f32::consts::PI;
 
@@ -9,9 +9,11 @@ use bevy::input::mouse::MouseMotion;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy_mod_raycast::deferred::RaycastSource;
+use hexx::Hex;
 use leafwing_input_manager::orientation::Rotation;
 use leafwing_input_manager::prelude::ActionState;
 
+use crate::crafting::diagnostics::Bottlenecks;
 use crate::geometry::DiscreteHeight;
 use crate::geometry::MapGeometry;
 use crate::geometry::VoxelPos;
@@ -30,7 +32,9 @@ pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(WorldGenState::Complete), setup_camera)
+        app.add_event::<FocusOnSelection>()
+            .add_event::<FocusOnTile>()
+            .add_systems(OnEnter(WorldGenState::Complete), setup_camera)
             .add_systems(Update, mousewheel_zoom.before(zoom))
             .add_systems(Update, zoom)
             .add_systems(
@@ -39,11 +43,11 @@ impl Plugin for CameraPlugin {
                     .before(set_camera_inclination)
                     .before(rotate_camera),
             )
+            .add_systems(Update, toggle_follow_mode.before(set_camera_focus))
             .add_systems(
                 Update,
                 set_camera_focus
                     // Allow users to break out of CameraMode::Follow by moving the camera manually
-                    .before(rotate_camera)
                     .before(pan_camera)
                     // Avoid jittering when the camera is following a unit
                     .after(drag_camera),
@@ -52,6 +56,12 @@ impl Plugin for CameraPlugin {
                 Update,
                 set_camera_inclination.before(InteractionSystem::MoveCamera),
             )
+            .add_systems(
+                Update,
+                (jump_to_bottleneck, focus_on_tile)
+                    .after(set_camera_focus)
+                    .before(InteractionSystem::MoveCamera),
+            )
             .add_systems(Update, rotate_camera.before(InteractionSystem::MoveCamera))
             .add_systems(Update, pan_camera.before(InteractionSystem::MoveCamera))
             .add_systems(
@@ -99,13 +109,20 @@ fn setup_camera(mut commands: Commands) {
 ///
 /// When panning and zooming, this struct is updated, rather than modifying the camera's [`Transform`] directly.
 #[derive(Component, Debug)]
-struct CameraFocus {
+pub(crate) struct CameraFocus {
     /// The coordinate that the camera is looking at.
     ///
     /// This should be the top of the column at the center of the screen.
-    translation: Vec3,
+    pub(super) translation: Vec3,
     /// The distance from the camera to the target
-    distance: f32,
+    pub(super) distance: f32,
+}
+
+impl CameraFocus {
+    /// The coordinate that the camera is looking at.
+    pub(crate) fn translation(&self) -> Vec3 {
+        self.translation
+    }
 }
 
 impl Default for CameraFocus {
@@ -127,7 +144,7 @@ pub(crate) struct CameraSettings {
     /// Controls the rate that the camera can moves from side to side.
     pan_speed: Speed,
     /// The angle in radians that the camera forms around the y axis.
-    facing: Rotation,
+    pub(super) facing: Rotation,
     /// Controls how fast the camera rotates around the vertical axis.
     ///
     /// Units are in radians per second.
@@ -145,7 +162,7 @@ pub(crate) struct CameraSettings {
     /// Increasing this value will result in a "smoother ride" over the hills and valleys of the map.
     float_radius: u32,
     /// The angle in radians that the camera forms with the ground.
-    inclination: Rotation,
+    pub(super) inclination: Rotation,
     /// The rate in radians per second that the inclination changes.
     inclination_speed: Speed,
     /// How much should dragging the mouse rotate the camera?
@@ -175,8 +192,10 @@ impl Default for CameraSettings {
 pub(crate) enum CameraMode {
     /// The camera is free to move around the map.
     Free,
-    /// The camera is following the selected unit.
-    FollowUnit,
+    /// The camera is keeping the provided entity centered on the screen as it moves.
+    ///
+    /// If the entity is despawned, the camera returns to [`CameraMode::Free`].
+    Follow(Entity),
 }
 
 /// Contains the [`Speed`] struct.
@@ -262,7 +281,7 @@ fn drag_camera(
     actions: Res<ActionState<PlayerAction>>,
     mut mouse_motion_events: EventReader<MouseMotion>,
     mut camera_query: Query<&mut CameraSettings>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     if actions.pressed(PlayerAction::DragCamera) {
         let Ok(mut settings) = camera_query.get_single_mut() else {
@@ -285,7 +304,7 @@ fn drag_camera(
 fn set_camera_inclination(
     mut camera_query: Query<&mut CameraSettings, With<Camera3d>>,
     actions: Res<ActionState<PlayerAction>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     let Ok(mut settings) = camera_query.get_single_mut() else {
         return;
@@ -308,7 +327,7 @@ fn set_camera_inclination(
 fn zoom(
     mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
     actions: Res<ActionState<PlayerAction>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
         return;
@@ -330,9 +349,41 @@ fn zoom(
     focus.distance = (focus.distance + delta_zoom).clamp(settings.min_zoom, settings.max_zoom);
 }
 
+/// Snaps the camera to the [`CurrentSelection`], as if [`PlayerAction::CenterCameraOnSelection`] was pressed.
+///
+/// Send this event after changing the selection to show it to the player.
+#[derive(Event, Debug, Clone, Copy, Default)]
+pub(crate) struct FocusOnSelection;
+
+/// Moves the camera to look at the provided tile.
+#[derive(Event, Debug, Clone, Copy)]
+pub(crate) struct FocusOnTile(pub(crate) Hex);
+
+/// Starts following the selected unit, or stops following whatever the camera is following.
+fn toggle_follow_mode(
+    actions: Res<ActionState<PlayerAction>>,
+    selection: Res<CurrentSelection>,
+    mut camera_query: Query<&mut CameraSettings, With<Camera3d>>,
+) {
+    if !actions.just_pressed(PlayerAction::FollowSelection) {
+        return;
+    }
+
+    let Ok(mut settings) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    settings.camera_mode = match (settings.camera_mode, &*selection) {
+        (CameraMode::Follow(_), _) => CameraMode::Free,
+        (CameraMode::Free, CurrentSelection::Unit(entity)) => CameraMode::Follow(*entity),
+        (CameraMode::Free, _) => CameraMode::Free,
+    };
+}
+
 /// Sets the tile that the camera is  camera's focus.
 fn set_camera_focus(
     actions: Res<ActionState<PlayerAction>>,
+    mut focus_events: EventReader<FocusOnSelection>,
     selection: Res<CurrentSelection>,
     unit_query: Query<&Transform>,
     mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
@@ -342,9 +393,10 @@ fn set_camera_focus(
     };
 
     // Snap to selected object
-    if actions.pressed(PlayerAction::CenterCameraOnSelection)
-        || settings.camera_mode == CameraMode::FollowUnit
-    {
+    let focus_requested = focus_events.read().count() > 0;
+    if actions.pressed(PlayerAction::CenterCameraOnSelection) || focus_requested {
+        settings.camera_mode = CameraMode::Free;
+
         let tile_to_snap_to = match &*selection {
             CurrentSelection::Voxels(selected_voxels) => Some(VoxelPos {
                 hex: selected_voxels.center(),
@@ -362,25 +414,65 @@ fn set_camera_focus(
         }
     }
 
-    // Also rotate the camera to match the orientation of the unit we're following
-    if settings.camera_mode == CameraMode::FollowUnit {
-        if let CurrentSelection::Unit(entity) = &*selection {
-            let unit_transform = unit_query.get(*entity).unwrap();
-            let quat = unit_transform.rotation;
-            let euler = quat.to_euler(EulerRot::YXZ);
-            let angle_around_y = euler.0;
-            settings.facing = Rotation::from_radians(angle_around_y);
-        } else {
-            // If we don't have a unit selected, go back to free camera mode
-            settings.camera_mode = CameraMode::Free;
+    // Keep the followed entity centered, even if the selection has changed
+    if let CameraMode::Follow(entity) = settings.camera_mode {
+        match unit_query.get(entity) {
+            Ok(transform) => focus.translation = transform.translation,
+            // The entity we were following no longer exists
+            Err(_) => settings.camera_mode = CameraMode::Free,
         }
     }
 }
 
+/// Snaps the camera to each of the worst [`Bottlenecks`] in turn.
+fn jump_to_bottleneck(
+    actions: Res<ActionState<PlayerAction>>,
+    bottlenecks: Res<Bottlenecks>,
+    mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
+    mut next_rank: Local<usize>,
+) {
+    if !actions.just_pressed(PlayerAction::JumpToBottleneck) || bottlenecks.is_empty() {
+        return;
+    }
+
+    let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    // The ranking changes as the simulation runs, so wrap around rather than running off the end
+    let rank = *next_rank % bottlenecks.len();
+    if let Some(bottleneck) = bottlenecks.get(rank) {
+        focus.translation = bottleneck.voxel_pos.top_of_tile();
+        settings.camera_mode = CameraMode::Free;
+    }
+    *next_rank = rank + 1;
+}
+
+/// Moves the camera to the tiles requested by [`FocusOnTile`] events.
+fn focus_on_tile(
+    mut focus_events: EventReader<FocusOnTile>,
+    mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
+) {
+    let Some(&FocusOnTile(hex)) = focus_events.read().last() else {
+        return;
+    };
+
+    let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    focus.translation = VoxelPos {
+        hex,
+        height: DiscreteHeight::ZERO,
+    }
+    .top_of_tile();
+    settings.camera_mode = CameraMode::Free;
+}
+
 /// Pan the camera
 fn pan_camera(
     mut camera_query: Query<(&Transform, &mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     actions: Res<ActionState<PlayerAction>>,
     maybe_map_geometry: Option<Res<MapGeometry>>,
 ) {
@@ -426,7 +518,7 @@ fn pan_camera(
 fn rotate_camera(
     mut camera_query: Query<&mut CameraSettings, With<Camera3d>>,
     actions: Res<ActionState<PlayerAction>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     let Ok(mut settings) = camera_query.get_single_mut() else {
         return;
@@ -436,12 +528,10 @@ fn rotate_camera(
 
     // Set facing
     if actions.pressed(PlayerAction::RotateCameraLeft) {
-        settings.camera_mode = CameraMode::Free;
         settings.facing -= Rotation::from_radians(delta);
     }
 
     if actions.pressed(PlayerAction::RotateCameraRight) {
-        settings.camera_mode = CameraMode::Free;
         settings.facing += Rotation::from_radians(delta);
     }
 }
@@ -0,0 +1,299 @@
+//! Camera bookmarks let the player store the current view in a numbered slot, and jump back to it later.
+//!
+//! Each bookmark records where the camera is looking, how far away it is, and how it is rotated and tilted.
+//! The game does not yet have full save files, so bookmarks are written to their own file in the save directory
+//! whenever they change, and read back when the game starts.
+//! Each generated world gets its own file, keyed by its seed, so that views of one map are never applied to another.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::{orientation::Rotation, prelude::ActionState};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::persistence::{read_json, warn_on_load_error, write_json, PersistenceError},
+    world_gen::GenerationConfig,
+};
+
+use super::{
+    camera::{CameraFocus, CameraMode, CameraSettings},
+    InteractionSystem, PlayerAction,
+};
+
+/// Stores, recalls and persists camera bookmarks.
+pub(super) struct CameraBookmarkPlugin;
+
+impl Plugin for CameraBookmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_camera_bookmarks).add_systems(
+            Update,
+            (save_camera_bookmarks, recall_camera_bookmarks)
+                .chain()
+                .before(InteractionSystem::MoveCamera)
+                .run_if(resource_exists::<CameraBookmarks>()),
+        );
+    }
+}
+
+/// The number of camera bookmarks available to the player.
+const N_BOOKMARKS: usize = 4;
+
+/// The actions that store the current view in each bookmark, in order.
+const SAVE_ACTIONS: [PlayerAction; N_BOOKMARKS] = [
+    PlayerAction::SaveCameraBookmark1,
+    PlayerAction::SaveCameraBookmark2,
+    PlayerAction::SaveCameraBookmark3,
+    PlayerAction::SaveCameraBookmark4,
+];
+
+/// The actions that move the camera to each bookmark, in order.
+const RECALL_ACTIONS: [PlayerAction; N_BOOKMARKS] = [
+    PlayerAction::RecallCameraBookmark1,
+    PlayerAction::RecallCameraBookmark2,
+    PlayerAction::RecallCameraBookmark3,
+    PlayerAction::RecallCameraBookmark4,
+];
+
+/// A stored view of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CameraBookmark {
+    /// The coordinate that the camera was looking at.
+    translation: [f32; 3],
+    /// The distance from the camera to the coordinate it was looking at.
+    distance: f32,
+    /// The angle in radians that the camera formed around the y axis.
+    facing: f32,
+    /// The angle in radians that the camera formed with the ground.
+    inclination: f32,
+}
+
+impl CameraBookmark {
+    /// Records the current view of the camera.
+    fn capture(focus: &CameraFocus, settings: &CameraSettings) -> Self {
+        CameraBookmark {
+            translation: focus.translation.to_array(),
+            distance: focus.distance,
+            facing: settings.facing.into_radians(),
+            inclination: settings.inclination.into_radians(),
+        }
+    }
+
+    /// Moves the camera to this view.
+    ///
+    /// The camera stops following any unit, and the zoom is kept within the limits set in the [`CameraSettings`].
+    fn apply(&self, focus: &mut CameraFocus, settings: &mut CameraSettings) {
+        focus.translation = Vec3::from_array(self.translation);
+        focus.distance = self.distance.clamp(settings.min_zoom, settings.max_zoom);
+        settings.facing = Rotation::from_radians(self.facing);
+        settings.inclination = Rotation::from_radians(self.inclination);
+        settings.camera_mode = CameraMode::Free;
+    }
+}
+
+/// The numbered views of the map that the player has stored.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(crate) struct CameraBookmarks {
+    /// The file that these bookmarks are loaded from and saved to.
+    path: PathBuf,
+    /// The stored bookmarks, indexed by slot.
+    ///
+    /// Slots that have not been used yet are `None`.
+    slots: [Option<CameraBookmark>; N_BOOKMARKS],
+}
+
+impl CameraBookmarks {
+    /// The directory that bookmarks for each world are stored in.
+    const SAVE_DIRECTORY: &'static str = "saves";
+
+    /// Creates an empty set of bookmarks for the world generated from `generation_config`.
+    ///
+    /// These are stored in a file keyed by the world's seed.
+    pub(crate) fn for_world(generation_config: &GenerationConfig) -> Self {
+        CameraBookmarks::new(
+            Path::new(Self::SAVE_DIRECTORY)
+                .join(format!("world_{}", generation_config.seed))
+                .join("camera_bookmarks.json"),
+        )
+    }
+
+    /// Creates an empty set of bookmarks, backed by the file at `path`.
+    pub(crate) fn new(path: impl Into<PathBuf>) -> Self {
+        CameraBookmarks {
+            path: path.into(),
+            slots: [None; N_BOOKMARKS],
+        }
+    }
+
+    /// The bookmark stored in `slot`, if any.
+    ///
+    /// Slots are numbered from 0.
+    pub(crate) fn get(&self, slot: usize) -> Option<&CameraBookmark> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    /// Stores `bookmark` in `slot`, replacing any existing bookmark.
+    ///
+    /// Slots that do not exist are ignored.
+    pub(crate) fn set(&mut self, slot: usize, bookmark: CameraBookmark) {
+        if let Some(existing) = self.slots.get_mut(slot) {
+            *existing = Some(bookmark);
+        }
+    }
+
+    /// Replaces these bookmarks with those stored on disk.
+    pub(crate) fn load(&mut self) -> Result<(), PersistenceError> {
+        self.slots = read_json(&self.path)?;
+        Ok(())
+    }
+
+    /// Writes these bookmarks to disk.
+    pub(crate) fn save(&self) -> Result<(), PersistenceError> {
+        write_json(&self.path, &self.slots)
+    }
+
+    /// The file that these bookmarks are loaded from and saved to.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Reads the saved camera bookmarks for the current world from disk when the game starts.
+fn load_camera_bookmarks(mut commands: Commands, generation_config: Res<GenerationConfig>) {
+    let mut camera_bookmarks = CameraBookmarks::for_world(&generation_config);
+    let result = camera_bookmarks.load();
+    warn_on_load_error(result, "camera bookmarks", camera_bookmarks.path());
+    commands.insert_resource(camera_bookmarks);
+}
+
+/// Stores the current view in a bookmark, and writes the bookmarks to disk.
+fn save_camera_bookmarks(
+    actions: Res<ActionState<PlayerAction>>,
+    camera_query: Query<(&CameraFocus, &CameraSettings), With<Camera3d>>,
+    mut camera_bookmarks: ResMut<CameraBookmarks>,
+) {
+    let Some(slot) = SAVE_ACTIONS
+        .iter()
+        .position(|action| actions.just_pressed(action.clone()))
+    else {
+        return;
+    };
+
+    let Ok((focus, settings)) = camera_query.get_single() else {
+        return;
+    };
+
+    camera_bookmarks.set(slot, CameraBookmark::capture(focus, settings));
+
+    match camera_bookmarks.save() {
+        Ok(()) => info!("Saved camera bookmark {}", slot + 1),
+        Err(error) => warn!(
+            "Could not save camera bookmarks to {}: {error}",
+            camera_bookmarks.path().display()
+        ),
+    }
+}
+
+/// Moves the camera to a bookmarked view.
+fn recall_camera_bookmarks(
+    actions: Res<ActionState<PlayerAction>>,
+    mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
+    camera_bookmarks: Res<CameraBookmarks>,
+) {
+    let Some(slot) = RECALL_ACTIONS
+        .iter()
+        .position(|action| actions.just_pressed(action.clone()))
+    else {
+        return;
+    };
+
+    let Some(bookmark) = camera_bookmarks.get(slot) else {
+        return;
+    };
+
+    let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    bookmark.apply(&mut focus, &mut settings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::persistence::TemporaryDirectory;
+
+    /// A bookmark with easily recognizable values.
+    fn bookmark(x: f32) -> CameraBookmark {
+        CameraBookmark {
+            translation: [x, 1., -x],
+            distance: 50.,
+            facing: 0.5,
+            inclination: 0.25,
+        }
+    }
+
+    #[test]
+    fn bookmarks_are_stored_in_slots() {
+        let mut bookmarks = CameraBookmarks::new("unused.json");
+        assert_eq!(bookmarks.get(0), None);
+
+        bookmarks.set(1, bookmark(3.));
+        assert_eq!(bookmarks.get(0), None);
+        assert_eq!(bookmarks.get(1), Some(&bookmark(3.)));
+
+        bookmarks.set(1, bookmark(4.));
+        assert_eq!(bookmarks.get(1), Some(&bookmark(4.)));
+
+        // Slots past the end are ignored
+        bookmarks.set(N_BOOKMARKS, bookmark(5.));
+        assert_eq!(bookmarks.get(N_BOOKMARKS), None);
+    }
+
+    #[test]
+    fn applying_a_bookmark_restores_the_view() {
+        let mut focus = CameraFocus::default();
+        let mut settings = CameraSettings::default();
+        settings.camera_mode = CameraMode::Follow(Entity::from_raw(0));
+
+        bookmark(7.).apply(&mut focus, &mut settings);
+        assert_eq!(settings.camera_mode, CameraMode::Free);
+        assert_eq!(focus.translation(), Vec3::new(7., 1., -7.));
+        assert_eq!(focus.distance, 50.);
+        // Rotations are stored with limited precision
+        assert!((settings.facing.into_radians() - 0.5).abs() < 0.01);
+        assert!((settings.inclination.into_radians() - 0.25).abs() < 0.01);
+
+        // Zoom limits are respected, even if they have changed since the bookmark was made
+        let mut too_far = bookmark(7.);
+        too_far.distance = settings.max_zoom * 2.;
+        too_far.apply(&mut focus, &mut settings);
+        assert_eq!(focus.distance, settings.max_zoom);
+    }
+
+    #[test]
+    fn each_world_has_its_own_bookmarks() {
+        let mut generation_config = GenerationConfig::testing();
+        generation_config.seed = 1;
+        let first_world = CameraBookmarks::for_world(&generation_config);
+        generation_config.seed = 2;
+        let second_world = CameraBookmarks::for_world(&generation_config);
+
+        assert_ne!(first_world.path(), second_world.path());
+    }
+
+    #[test]
+    fn bookmarks_round_trip_through_disk() {
+        let directory = TemporaryDirectory::new("camera_bookmark_test");
+        let path = directory.join("camera_bookmarks.json");
+
+        let mut bookmarks = CameraBookmarks::new(&path);
+        bookmarks.set(0, bookmark(1.));
+        bookmarks.set(3, bookmark(2.));
+        bookmarks.save().unwrap();
+
+        let mut reloaded = CameraBookmarks::new(&path);
+        assert!(reloaded.load().is_ok());
+        assert_eq!(reloaded, bookmarks);
+    }
+}
@@ -3,6 +3,7 @@
 use bevy::{ecs::query::WorldQuery, prelude::*, utils::HashMap};
 use hexx::HexIterExt;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
@@ -12,7 +13,10 @@ use crate::{
     structures::structure_manifest::{Structure, StructureManifest},
 };
 
-use super::{picking::CursorPos, selection::CurrentSelection, InteractionSystem, PlayerAction};
+use super::{
+    blueprints::Blueprint, picking::CursorPos, selection::CurrentSelection, InteractionSystem,
+    PlayerAction,
+};
 
 /// Code and data for working with the clipboard
 pub(super) struct ClipboardPlugin;
@@ -49,6 +53,8 @@ pub(crate) enum Tool {
     Terraform(TerraformingTool),
     /// A structure / structure to place
     Structures(HashMap<VoxelPos, ClipboardData>),
+    /// A saved layout of structures and terraforming to place.
+    Blueprint(Blueprint),
     /// No tool is selected.
     #[default]
     None,
@@ -60,6 +66,7 @@ impl Tool {
         match self {
             Tool::None => true,
             Tool::Structures(map) => map.is_empty(),
+            Tool::Blueprint(blueprint) => blueprint.is_empty(),
             Tool::Terraform(_) => false,
         }
     }
@@ -78,7 +85,7 @@ impl Tool {
 }
 
 /// The data copied via the clipboard for a single structure.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub(crate) struct ClipboardData {
     /// The identity of the structure.
     pub(crate) structure_id: Id<Structure>,
@@ -153,6 +160,10 @@ impl Tool {
     ///
     /// You must ensure that the contents are normalized first.
     fn rotate(&mut self, clockwise: bool) {
+        if let Tool::Blueprint(blueprint) = self {
+            blueprint.rotate(clockwise);
+        }
+
         if let Tool::Structures(map) = self {
             let mut new_map = HashMap::with_capacity(map.capacity());
 
@@ -188,7 +199,7 @@ fn clear_clipboard(mut tool: ResMut<Tool>, actions: Res<ActionState<PlayerAction
 
 /// Data needed for [`copy_selection`] to populate [`ClipboardData`].
 #[derive(WorldQuery)]
-struct ClipboardQuery {
+pub(crate) struct ClipboardQuery {
     /// The position of the structure
     voxel_pos: &'static VoxelPos,
     /// The type of the structure
@@ -0,0 +1,566 @@
+//! Keybindings can be changed by the player, and are saved to a config file so they persist between sessions.
+//!
+//! Each [`PlayerAction`] has at most one keyboard and mouse binding and one gamepad binding.
+//! When an input is bound to a new action, any other action whose binding [overlaps](overlaps) it on the same device is unbound.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    prelude::InputMap,
+    user_input::{InputKind, UserInput},
+    Actionlike,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::persistence::{read_json, warn_on_load_error, write_json, PersistenceError};
+
+use super::PlayerAction;
+
+/// Loads, applies and saves the player's keybindings.
+pub(super) struct KeybindingPlugin;
+
+impl Plugin for KeybindingPlugin {
+    fn build(&self, app: &mut App) {
+        let keybindings = Keybindings::default();
+
+        app.insert_resource(keybindings.input_map())
+            .insert_resource(keybindings)
+            .add_systems(Startup, load_keybindings)
+            .add_systems(PreUpdate, apply_keybindings);
+    }
+}
+
+/// The kind of input device that a binding is used with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum InputDevice {
+    /// A keyboard and mouse.
+    #[default]
+    KeyboardAndMouse,
+    /// A gamepad.
+    Gamepad,
+}
+
+impl InputDevice {
+    /// The other kind of input device.
+    pub(crate) fn other(self) -> Self {
+        match self {
+            InputDevice::KeyboardAndMouse => InputDevice::Gamepad,
+            InputDevice::Gamepad => InputDevice::KeyboardAndMouse,
+        }
+    }
+}
+
+/// The inputs bound to a single [`PlayerAction`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ActionBindings {
+    /// The keyboard and mouse binding, if any.
+    keyboard_and_mouse: Option<UserInput>,
+    /// The gamepad binding, if any.
+    gamepad: Option<UserInput>,
+}
+
+impl ActionBindings {
+    /// The default bindings for the provided `action`.
+    fn default_for(action: &PlayerAction) -> Self {
+        ActionBindings {
+            keyboard_and_mouse: Some(action.kbm_binding()),
+            gamepad: Some(action.gamepad_binding()),
+        }
+    }
+
+    /// The binding for the provided `device`, if any.
+    pub(crate) fn get(&self, device: InputDevice) -> Option<&UserInput> {
+        match device {
+            InputDevice::KeyboardAndMouse => self.keyboard_and_mouse.as_ref(),
+            InputDevice::Gamepad => self.gamepad.as_ref(),
+        }
+    }
+
+    /// Sets the binding for the provided `device`.
+    fn set(&mut self, device: InputDevice, input: Option<UserInput>) {
+        match device {
+            InputDevice::KeyboardAndMouse => self.keyboard_and_mouse = input,
+            InputDevice::Gamepad => self.gamepad = input,
+        }
+    }
+}
+
+/// The inputs bound to every [`PlayerAction`], as configured by the player.
+///
+/// The [`InputMap`] used to read player input is rebuilt from this whenever it changes.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Keybindings {
+    /// The file that these keybindings are loaded from and saved to.
+    path: PathBuf,
+    /// The bindings for each action, in the order that actions are declared.
+    ///
+    /// Stored as a list of pairs, as JSON maps can only have string keys.
+    bindings: Vec<(PlayerAction, ActionBindings)>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings::new(Keybindings::DEFAULT_PATH)
+    }
+}
+
+impl Keybindings {
+    /// The file where keybindings are stored by default.
+    const DEFAULT_PATH: &'static str = "settings/keybindings.json";
+
+    /// Creates the default keybindings, backed by the file at `path`.
+    pub(crate) fn new(path: impl Into<PathBuf>) -> Self {
+        Keybindings {
+            path: path.into(),
+            bindings: PlayerAction::variants()
+                .map(|action| {
+                    let bindings = ActionBindings::default_for(&action);
+                    (action, bindings)
+                })
+                .collect(),
+        }
+    }
+
+    /// The actions and their bindings, in the order that actions are declared.
+    pub(crate) fn iter(&self) -> impl ExactSizeIterator<Item = &(PlayerAction, ActionBindings)> {
+        self.bindings.iter()
+    }
+
+    /// The bindings for the provided `action`.
+    pub(crate) fn get(&self, action: &PlayerAction) -> Option<&ActionBindings> {
+        self.bindings
+            .iter()
+            .find(|(candidate, _)| candidate == action)
+            .map(|(_, bindings)| bindings)
+    }
+
+    /// Binds `input` to `action` for the provided `device`, replacing its previous binding.
+    ///
+    /// Any other actions whose bindings on the same device [overlap](overlaps) `input` are unbound, and returned.
+    pub(crate) fn rebind(
+        &mut self,
+        action: &PlayerAction,
+        device: InputDevice,
+        input: UserInput,
+    ) -> Vec<PlayerAction> {
+        let mut displaced = Vec::new();
+
+        for (candidate, bindings) in self.bindings.iter_mut() {
+            if candidate == action {
+                bindings.set(device, Some(input.clone()));
+            } else if bindings
+                .get(device)
+                .is_some_and(|existing| overlaps(existing, &input))
+            {
+                bindings.set(device, None);
+                displaced.push(candidate.clone());
+            }
+        }
+
+        displaced
+    }
+
+    /// Restores the default bindings of `action` for both devices.
+    ///
+    /// Any other actions that were bound to those inputs are unbound, and returned.
+    pub(crate) fn reset(&mut self, action: &PlayerAction) -> Vec<PlayerAction> {
+        let mut displaced =
+            self.rebind(action, InputDevice::KeyboardAndMouse, action.kbm_binding());
+        displaced.extend(self.rebind(action, InputDevice::Gamepad, action.gamepad_binding()));
+        displaced
+    }
+
+    /// Does the binding of `action` for `device` [overlap](overlaps) that of any other action?
+    pub(crate) fn is_conflicted(&self, action: &PlayerAction, device: InputDevice) -> bool {
+        let Some(input) = self.get(action).and_then(|bindings| bindings.get(device)) else {
+            return false;
+        };
+
+        self.bindings.iter().any(|(candidate, bindings)| {
+            candidate != action
+                && bindings
+                    .get(device)
+                    .is_some_and(|existing| overlaps(existing, input))
+        })
+    }
+
+    /// Builds the [`InputMap`] used to read player input.
+    pub(crate) fn input_map(&self) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::default();
+
+        for (action, bindings) in self.bindings.iter() {
+            for device in [InputDevice::KeyboardAndMouse, InputDevice::Gamepad] {
+                if let Some(input) = bindings.get(device) {
+                    input_map.insert(input.clone(), action.clone());
+                }
+            }
+        }
+
+        input_map
+    }
+
+    /// Replaces these keybindings with those stored on disk.
+    ///
+    /// Actions that are missing from the file keep their default bindings,
+    /// unless that input has since been bound to another action.
+    pub(crate) fn load(&mut self) -> Result<(), PersistenceError> {
+        let saved: Vec<(PlayerAction, ActionBindings)> = read_json(&self.path)?;
+
+        let mut loaded = Keybindings {
+            path: self.path.clone(),
+            bindings: Vec::with_capacity(self.bindings.len()),
+        };
+        let mut missing = Vec::new();
+
+        for action in PlayerAction::variants() {
+            match saved.iter().find(|(candidate, _)| *candidate == action) {
+                Some((_, bindings)) => loaded.bindings.push((action, bindings.clone())),
+                None => {
+                    loaded
+                        .bindings
+                        .push((action.clone(), ActionBindings::default()));
+                    missing.push(action);
+                }
+            }
+        }
+
+        for action in missing {
+            let defaults = ActionBindings::default_for(&action);
+            for device in [InputDevice::KeyboardAndMouse, InputDevice::Gamepad] {
+                let Some(input) = defaults.get(device) else {
+                    continue;
+                };
+
+                let already_used = loaded.bindings.iter().any(|(_, bindings)| {
+                    bindings
+                        .get(device)
+                        .is_some_and(|existing| overlaps(existing, input))
+                });
+
+                if !already_used {
+                    loaded.rebind(&action, device, input.clone());
+                }
+            }
+        }
+
+        *self = loaded;
+        Ok(())
+    }
+
+    /// Writes these keybindings to disk.
+    pub(crate) fn save(&self) -> Result<(), PersistenceError> {
+        write_json(&self.path, &self.bindings)
+    }
+
+    /// The file that these keybindings are loaded from and saved to.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Would pressing one of these inputs also trigger the other?
+///
+/// Inputs overlap when every input kind of one is also part of the other, regardless of the order of a chord.
+/// Chords built by holding a modifier are the exception: the longest chord takes priority when inputs clash,
+/// so adding modifiers to a binding, or adding to a binding made only of modifiers, does not count as an overlap.
+pub(crate) fn overlaps(a: &UserInput, b: &UserInput) -> bool {
+    let a = input_kinds(a);
+    let b = input_kinds(b);
+    let (shorter, longer) = match a.len() <= b.len() {
+        true => (a, b),
+        false => (b, a),
+    };
+
+    if !shorter.iter().all(|input_kind| longer.contains(input_kind)) {
+        return false;
+    }
+
+    let mut added = longer
+        .iter()
+        .filter(|input_kind| !shorter.contains(input_kind))
+        .peekable();
+
+    // The two inputs are the same
+    if added.peek().is_none() {
+        return true;
+    }
+
+    let only_modifiers_added = added.all(is_modifier);
+    !only_modifiers_added && !shorter.iter().all(is_modifier)
+}
+
+/// Every individual input that must be pressed for `user_input` to be triggered.
+fn input_kinds(user_input: &UserInput) -> Vec<InputKind> {
+    match user_input {
+        UserInput::Single(input_kind) => vec![input_kind.clone()],
+        UserInput::Chord(input_kinds) => input_kinds.clone(),
+        UserInput::VirtualDPad(dpad) => vec![
+            dpad.up.clone(),
+            dpad.down.clone(),
+            dpad.left.clone(),
+            dpad.right.clone(),
+        ],
+        UserInput::VirtualAxis(axis) => vec![axis.negative.clone(), axis.positive.clone()],
+    }
+}
+
+/// Is this input held to change what other inputs do?
+///
+/// On gamepads, these are the buttons used as modifiers by the default bindings.
+fn is_modifier(input_kind: &InputKind) -> bool {
+    match input_kind {
+        InputKind::Modifier(_) => true,
+        InputKind::Keyboard(key_code) => matches!(
+            key_code,
+            KeyCode::AltLeft
+                | KeyCode::AltRight
+                | KeyCode::ControlLeft
+                | KeyCode::ControlRight
+                | KeyCode::ShiftLeft
+                | KeyCode::ShiftRight
+                | KeyCode::SuperLeft
+                | KeyCode::SuperRight
+        ),
+        InputKind::GamepadButton(button) => matches!(
+            button,
+            GamepadButtonType::LeftTrigger
+                | GamepadButtonType::LeftTrigger2
+                | GamepadButtonType::RightTrigger
+                | GamepadButtonType::RightTrigger2
+                | GamepadButtonType::Select
+        ),
+        _ => false,
+    }
+}
+
+/// Does `action` need a directional input on `device`, such as a joystick, rather than a button?
+pub(crate) fn is_directional(action: &PlayerAction, device: InputDevice) -> bool {
+    let default_input = match device {
+        InputDevice::KeyboardAndMouse => action.kbm_binding(),
+        InputDevice::Gamepad => action.gamepad_binding(),
+    };
+
+    matches!(
+        default_input,
+        UserInput::VirtualDPad(_) | UserInput::Single(InputKind::DualAxis(_))
+    )
+}
+
+/// A short, human-readable description of a `user_input`, for display in menus.
+pub(crate) fn describe_input(user_input: &UserInput) -> String {
+    match user_input {
+        UserInput::Single(input_kind) => describe_input_kind(input_kind),
+        UserInput::Chord(input_kinds) => input_kinds
+            .iter()
+            .map(describe_input_kind)
+            .collect::<Vec<_>>()
+            .join(" + "),
+        UserInput::VirtualDPad(dpad) => [&dpad.up, &dpad.left, &dpad.down, &dpad.right]
+            .into_iter()
+            .map(describe_input_kind)
+            .collect::<Vec<_>>()
+            .join("/"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// A short, human-readable description of a single `input_kind`.
+fn describe_input_kind(input_kind: &InputKind) -> String {
+    match input_kind {
+        InputKind::Keyboard(key_code) => format!("{key_code:?}"),
+        InputKind::Modifier(modifier) => format!("{modifier:?}"),
+        InputKind::Mouse(mouse_button) => format!("Mouse {mouse_button:?}"),
+        InputKind::GamepadButton(button) => format!("{button:?}"),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Reads the saved keybindings from disk when the game starts.
+fn load_keybindings(mut keybindings: ResMut<Keybindings>) {
+    let result = keybindings.load();
+    warn_on_load_error(result, "keybindings", keybindings.path());
+}
+
+/// Rebuilds the [`InputMap`] whenever the keybindings change.
+fn apply_keybindings(keybindings: Res<Keybindings>, mut input_map: ResMut<InputMap<PlayerAction>>) {
+    if keybindings.is_changed() {
+        *input_map = keybindings.input_map();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::persistence::TemporaryDirectory;
+
+    #[test]
+    fn every_action_has_default_bindings() {
+        let keybindings = Keybindings::new("unused.json");
+        assert_eq!(keybindings.iter().len(), PlayerAction::variants().count());
+
+        for (_, bindings) in keybindings.iter() {
+            assert!(bindings.get(InputDevice::KeyboardAndMouse).is_some());
+            assert!(bindings.get(InputDevice::Gamepad).is_some());
+        }
+    }
+
+    #[test]
+    fn default_bindings_do_not_overlap() {
+        let actions: Vec<PlayerAction> = PlayerAction::variants().collect();
+
+        for (i, a) in actions.iter().enumerate() {
+            for b in &actions[i + 1..] {
+                assert!(
+                    !overlaps(&a.kbm_binding(), &b.kbm_binding()),
+                    "The default keyboard and mouse bindings for {a:?} and {b:?} overlap"
+                );
+                assert!(
+                    !overlaps(&a.gamepad_binding(), &b.gamepad_binding()),
+                    "The default gamepad bindings for {a:?} and {b:?} overlap"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rebinding_displaces_conflicting_actions() {
+        let mut keybindings = Keybindings::new("unused.json");
+        let pause_key = PlayerAction::TogglePause.kbm_binding();
+
+        let displaced = keybindings.rebind(
+            &PlayerAction::CycleBlueprints,
+            InputDevice::KeyboardAndMouse,
+            pause_key.clone(),
+        );
+
+        assert_eq!(displaced, vec![PlayerAction::TogglePause]);
+        assert_eq!(
+            keybindings
+                .get(&PlayerAction::CycleBlueprints)
+                .unwrap()
+                .get(InputDevice::KeyboardAndMouse),
+            Some(&pause_key)
+        );
+        assert_eq!(
+            keybindings
+                .get(&PlayerAction::TogglePause)
+                .unwrap()
+                .get(InputDevice::KeyboardAndMouse),
+            None
+        );
+        // Gamepad bindings are unaffected
+        assert!(keybindings
+            .get(&PlayerAction::TogglePause)
+            .unwrap()
+            .get(InputDevice::Gamepad)
+            .is_some());
+
+        // Resetting restores the original binding, displacing the new one
+        let displaced = keybindings.reset(&PlayerAction::TogglePause);
+        assert_eq!(displaced, vec![PlayerAction::CycleBlueprints]);
+        assert!(
+            !keybindings.is_conflicted(&PlayerAction::TogglePause, InputDevice::KeyboardAndMouse)
+        );
+    }
+
+    #[test]
+    fn overlapping_inputs_are_detected() {
+        use leafwing_input_manager::{prelude::VirtualDPad, user_input::Modifier};
+
+        // Order does not matter
+        assert!(overlaps(
+            &UserInput::chord([KeyCode::Q, KeyCode::E]),
+            &UserInput::chord([KeyCode::E, KeyCode::Q])
+        ));
+        // Nor does which input is the subset
+        assert!(overlaps(&KeyCode::W.into(), &VirtualDPad::wasd().into()));
+        assert!(overlaps(
+            &UserInput::chord([GamepadButtonType::West, GamepadButtonType::North]),
+            &GamepadButtonType::West.into()
+        ));
+
+        // Chords built from modifiers take priority
+        assert!(!overlaps(
+            &KeyCode::R.into(),
+            &UserInput::modified(Modifier::Shift, KeyCode::R)
+        ));
+        assert!(!overlaps(
+            &Modifier::Shift.into(),
+            &UserInput::modified(Modifier::Shift, KeyCode::R)
+        ));
+        assert!(!overlaps(
+            &GamepadButtonType::West.into(),
+            &UserInput::chord([GamepadButtonType::RightTrigger, GamepadButtonType::West])
+        ));
+
+        assert!(!overlaps(&KeyCode::Q.into(), &KeyCode::E.into()));
+    }
+
+    #[test]
+    fn rebinding_displaces_overlapping_chords() {
+        let mut keybindings = Keybindings::new("unused.json");
+        keybindings.rebind(
+            &PlayerAction::CycleBlueprints,
+            InputDevice::KeyboardAndMouse,
+            UserInput::chord([KeyCode::Q, KeyCode::E]),
+        );
+
+        let displaced = keybindings.rebind(
+            &PlayerAction::CycleFavoredGene,
+            InputDevice::KeyboardAndMouse,
+            UserInput::chord([KeyCode::E, KeyCode::Q]),
+        );
+
+        assert_eq!(displaced, vec![PlayerAction::CycleBlueprints]);
+        assert!(!keybindings.is_conflicted(
+            &PlayerAction::CycleFavoredGene,
+            InputDevice::KeyboardAndMouse
+        ));
+    }
+
+    #[test]
+    fn keybindings_round_trip_through_disk() {
+        let directory = TemporaryDirectory::new("keybinding_test");
+        let path = directory.join("keybindings.json");
+
+        let mut keybindings = Keybindings::new(&path);
+        keybindings.rebind(
+            &PlayerAction::TogglePause,
+            InputDevice::KeyboardAndMouse,
+            KeyCode::P.into(),
+        );
+        keybindings.save().unwrap();
+
+        let mut reloaded = Keybindings::new(&path);
+        assert!(reloaded.load().is_ok());
+        assert_eq!(reloaded, keybindings);
+    }
+
+    #[test]
+    fn missing_actions_get_defaults_without_conflicts() {
+        let directory = TemporaryDirectory::new("keybinding_missing_test");
+        let path = directory.join("keybindings.json");
+
+        // Simulate a file saved before TogglePause existed, where its key was used for something else
+        let mut saved = Keybindings::new(&path);
+        saved.rebind(
+            &PlayerAction::CycleBlueprints,
+            InputDevice::KeyboardAndMouse,
+            PlayerAction::TogglePause.kbm_binding(),
+        );
+        saved
+            .bindings
+            .retain(|(action, _)| *action != PlayerAction::TogglePause);
+        saved.save().unwrap();
+
+        let mut reloaded = Keybindings::new(&path);
+        assert!(reloaded.load().is_ok());
+        let pause_bindings = reloaded.get(&PlayerAction::TogglePause).unwrap();
+        assert_eq!(pause_bindings.get(InputDevice::KeyboardAndMouse), None);
+        assert_eq!(
+            pause_bindings.get(InputDevice::Gamepad),
+            Some(&PlayerAction::TogglePause.gamepad_binding())
+        );
+    }
+}
@@ -4,19 +4,27 @@ use crate::enum_iter::IterableEnum;
 use crate::{self as emergence_lib};
 use bevy::prelude::*;
 use emergence_macros::IterableEnum;
+use serde::{Deserialize, Serialize};
 
 use leafwing_input_manager::{
-    prelude::{ActionState, DualAxis, InputManagerPlugin, InputMap, VirtualDPad},
+    prelude::{ActionState, DualAxis, InputManagerPlugin, VirtualDPad},
     user_input::{Modifier, UserInput},
     Actionlike,
 };
 
-use crate::world_gen::WorldGenState;
+use crate::{
+    organisms::{domestication::toggle_research_reserve, genetics::cycle_favored_gene},
+    world_gen::WorldGenState,
+};
 
+pub(crate) mod blueprints;
 pub(crate) mod camera;
+mod camera_bookmarks;
 pub(crate) mod clipboard;
+pub(crate) mod keybindings;
 pub(crate) mod picking;
 pub(crate) mod selection;
+mod smart_selection;
 
 /// All of the code needed for users to interact with the simulation.
 pub struct InteractionPlugin;
@@ -25,11 +33,21 @@ impl Plugin for InteractionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<PlayerAction>::default())
             .init_resource::<ActionState<PlayerAction>>()
-            .insert_resource(PlayerAction::default_input_map())
+            .add_plugins(keybindings::KeybindingPlugin)
             .add_plugins(camera::CameraPlugin)
+            .add_plugins(camera_bookmarks::CameraBookmarkPlugin)
             .add_plugins(picking::PickingPlugin)
             .add_plugins(selection::SelectionPlugin)
+            .add_plugins(smart_selection::SmartSelectionPlugin)
             .add_plugins(clipboard::ClipboardPlugin)
+            .add_plugins(blueprints::BlueprintPlugin)
+            .add_systems(
+                Update,
+                (
+                    cycle_favored_gene.after(InteractionSystem::SelectTiles),
+                    toggle_research_reserve.after(InteractionSystem::SelectTiles),
+                ),
+            )
             .configure_sets(
                 Update,
                 PlayerModifiesWorld.run_if(in_state(WorldGenState::Complete)),
@@ -65,10 +83,16 @@ pub struct PlayerModifiesWorld;
 /// Actions that the player can take to modify the game world or their view of it.
 ///
 /// This should only store actions that need a dedicated keybinding.
-#[derive(Actionlike, Reflect, Clone, Debug)]
+#[derive(Actionlike, Reflect, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) enum PlayerAction {
     /// Pause or unpause the game.
     TogglePause,
+    /// While paused, advances the simulation by a single tick.
+    StepSimulation,
+    /// Makes the simulation run faster.
+    SpeedUpSimulation,
+    /// Makes the simulation run slower.
+    SlowDownSimulation,
     /// When the clipboard is full, places the clipboard contents on the map.
     ///
     /// When the clipboard is empty, selects a tile or group of tiles.
@@ -87,6 +111,12 @@ pub(crate) enum PlayerAction {
     Area,
     /// Modifies the selection to cover a line between the start and end of the selection.
     Line,
+    /// Selects all contiguous tiles that share the terrain type, height or water state of the hovered tile.
+    FloodFillSelection,
+    /// Selects every structure of the same type as the hovered structure, on screen or map-wide.
+    SelectAllSimilar,
+    /// Selects every crafting structure that matches a filter, such as all idle structures.
+    SelectByFilter,
     /// Selects a structure from a wheel menu.
     SelectStructure,
     /// Select a terraforming tool from a wheel menu.
@@ -101,12 +131,46 @@ pub(crate) enum PlayerAction {
     Paste,
     /// Cancels any planned actions (ghosts) selected.
     ClearZoning,
+    /// Reverts the most recent zoning, terraforming or demolition order.
+    Undo,
+    /// Reapplies the most recently undone zoning, terraforming or demolition order.
+    Redo,
     /// Rotates the contents of the clipboard counterclockwise.
     RotateClipboardLeft,
     /// Rotates the contents of the clipboard clockwise.
     RotateClipboardRight,
+    /// Saves the selected structures and terraforming (or the clipboard) as a new blueprint.
+    SaveBlueprint,
+    /// Places the next blueprint from the blueprint library on the clipboard.
+    CycleBlueprints,
+    /// Starts typing a new name for the selected blueprint.
+    RenameBlueprint,
+    /// Changes which gene is favored when breeding the species of the selected organism.
+    CycleFavoredGene,
+    /// Adds or removes the selected tiles from research reserves, where wild organisms can be studied.
+    ToggleResearchReserve,
     /// Snaps the camera to the selected object
     CenterCameraOnSelection,
+    /// Snaps the camera to the next worst bottleneck in the production chain
+    JumpToBottleneck,
+    /// Keeps the selected unit centered as it moves, or stops following it
+    FollowSelection,
+    /// Stores the current camera position in the first bookmark
+    SaveCameraBookmark1,
+    /// Stores the current camera position in the second bookmark
+    SaveCameraBookmark2,
+    /// Stores the current camera position in the third bookmark
+    SaveCameraBookmark3,
+    /// Stores the current camera position in the fourth bookmark
+    SaveCameraBookmark4,
+    /// Moves the camera to the position stored in the first bookmark
+    RecallCameraBookmark1,
+    /// Moves the camera to the position stored in the second bookmark
+    RecallCameraBookmark2,
+    /// Moves the camera to the position stored in the third bookmark
+    RecallCameraBookmark3,
+    /// Moves the camera to the position stored in the fourth bookmark
+    RecallCameraBookmark4,
     /// Drag the camera with the cursor
     DragCamera,
     /// Move the camera from side to side
@@ -135,6 +199,14 @@ pub(crate) enum PlayerAction {
     ToggleWaterTableOverlay,
     /// Show / hide the light overlay
     ToggleLightOverlay,
+    /// Show / hide the soil fertility overlay
+    ToggleFertilityOverlay,
+    /// Writes the recorded statistics to disk
+    ExportStatistics,
+    /// Writes the recent decisions of the selected unit to disk
+    ExportDecisionTrace,
+    /// Opens the menu used to change keybindings
+    ToggleKeybindingMenu,
 }
 
 impl PlayerAction {
@@ -143,6 +215,9 @@ impl PlayerAction {
         use PlayerAction::*;
         match self {
             TogglePause => KeyCode::Space.into(),
+            StepSimulation => KeyCode::Period.into(),
+            SpeedUpSimulation => KeyCode::BracketRight.into(),
+            SlowDownSimulation => KeyCode::BracketLeft.into(),
             UseTool => MouseButton::Left.into(),
             Deselect => MouseButton::Right.into(),
             // Plus and Equals are swapped. See: https://github.com/rust-windowing/winit/issues/2682
@@ -151,15 +226,35 @@ impl PlayerAction {
             Multiple => Modifier::Shift.into(),
             Area => Modifier::Control.into(),
             Line => Modifier::Alt.into(),
+            FloodFillSelection => KeyCode::F.into(),
+            SelectAllSimilar => KeyCode::X.into(),
+            SelectByFilter => KeyCode::I.into(),
             SelectStructure => KeyCode::Key1.into(),
             SelectTerraform => KeyCode::Key2.into(),
             SelectAbility => KeyCode::Key3.into(),
             Copy => UserInput::modified(Modifier::Control, KeyCode::C),
             Paste => UserInput::modified(Modifier::Control, KeyCode::V),
             ClearZoning => KeyCode::Back.into(),
+            Undo => UserInput::modified(Modifier::Control, KeyCode::Z),
+            Redo => UserInput::modified(Modifier::Control, KeyCode::Y),
             RotateClipboardLeft => UserInput::modified(Modifier::Shift, KeyCode::R),
             RotateClipboardRight => KeyCode::R.into(),
+            SaveBlueprint => UserInput::modified(Modifier::Control, KeyCode::B),
+            CycleBlueprints => KeyCode::B.into(),
+            RenameBlueprint => UserInput::modified(Modifier::Shift, KeyCode::B),
+            CycleFavoredGene => KeyCode::G.into(),
+            ToggleResearchReserve => KeyCode::Z.into(),
             CenterCameraOnSelection => KeyCode::L.into(),
+            JumpToBottleneck => KeyCode::J.into(),
+            FollowSelection => KeyCode::C.into(),
+            SaveCameraBookmark1 => UserInput::modified(Modifier::Control, KeyCode::Key1),
+            SaveCameraBookmark2 => UserInput::modified(Modifier::Control, KeyCode::Key2),
+            SaveCameraBookmark3 => UserInput::modified(Modifier::Control, KeyCode::Key3),
+            SaveCameraBookmark4 => UserInput::modified(Modifier::Control, KeyCode::Key4),
+            RecallCameraBookmark1 => UserInput::modified(Modifier::Alt, KeyCode::Key1),
+            RecallCameraBookmark2 => UserInput::modified(Modifier::Alt, KeyCode::Key2),
+            RecallCameraBookmark3 => UserInput::modified(Modifier::Alt, KeyCode::Key3),
+            RecallCameraBookmark4 => UserInput::modified(Modifier::Alt, KeyCode::Key4),
             DragCamera => MouseButton::Middle.into(),
             Pan => VirtualDPad::wasd().into(),
             MoveCursor => VirtualDPad::arrow_keys().into(),
@@ -176,6 +271,10 @@ impl PlayerAction {
             ToggleStrongestSignalOverlay => KeyCode::F3.into(),
             ToggleWaterTableOverlay => KeyCode::F4.into(),
             ToggleLightOverlay => KeyCode::F5.into(),
+            ToggleFertilityOverlay => KeyCode::F6.into(),
+            ExportStatistics => KeyCode::F10.into(),
+            ExportDecisionTrace => KeyCode::F9.into(),
+            ToggleKeybindingMenu => KeyCode::F12.into(),
         }
     }
 
@@ -188,9 +287,14 @@ impl PlayerAction {
         let radius_modifier = LeftTrigger;
         let infovis_modifier = LeftTrigger2;
         let selection_modifier = RightTrigger;
+        // This has no action of its own, so holding it never triggers anything by itself
+        let system_modifier = GamepadButtonType::Select;
 
         match self {
-            TogglePause => GamepadButtonType::Select.into(),
+            TogglePause => UserInput::chord([system_modifier, DPadDown]),
+            StepSimulation => UserInput::chord([system_modifier, DPadUp]),
+            SpeedUpSimulation => UserInput::chord([system_modifier, DPadRight]),
+            SlowDownSimulation => UserInput::chord([system_modifier, DPadLeft]),
             PlayerAction::UseTool => South.into(),
             Deselect => East.into(),
             Multiple => RightTrigger.into(),
@@ -198,22 +302,42 @@ impl PlayerAction {
             DecreaseSelectionRadius => UserInput::chord([radius_modifier, DPadDown]),
             Area => LeftTrigger.into(),
             Line => LeftTrigger2.into(),
+            FloodFillSelection => UserInput::chord([radius_modifier, DPadLeft]),
+            SelectAllSimilar => UserInput::chord([radius_modifier, DPadRight]),
+            SelectByFilter => UserInput::chord([radius_modifier, West]),
             Copy => West.into(),
             Paste => North.into(),
             ClearZoning => DPadUp.into(),
+            Undo => UserInput::chord([system_modifier, West]),
+            Redo => UserInput::chord([system_modifier, East]),
             SelectStructure => UserInput::chord([selection_modifier, West]),
             SelectTerraform => UserInput::chord([selection_modifier, North]),
             SelectAbility => UserInput::chord([selection_modifier, East]),
             RotateClipboardLeft => DPadLeft.into(),
             RotateClipboardRight => DPadRight.into(),
+            SaveBlueprint => UserInput::chord([selection_modifier, LeftThumb]),
+            CycleBlueprints => UserInput::chord([selection_modifier, DPadRight]),
+            RenameBlueprint => UserInput::chord([selection_modifier, South]),
+            CycleFavoredGene => UserInput::chord([selection_modifier, DPadLeft]),
+            ToggleResearchReserve => UserInput::chord([selection_modifier, DPadUp]),
             CenterCameraOnSelection => GamepadButtonType::LeftThumb.into(),
+            JumpToBottleneck => UserInput::chord([camera_modifier, South]),
+            FollowSelection => UserInput::chord([camera_modifier, RightThumb]),
+            SaveCameraBookmark1 => UserInput::chord([system_modifier, camera_modifier, North]),
+            SaveCameraBookmark2 => UserInput::chord([system_modifier, camera_modifier, West]),
+            SaveCameraBookmark3 => UserInput::chord([system_modifier, camera_modifier, East]),
+            SaveCameraBookmark4 => UserInput::chord([system_modifier, camera_modifier, LeftThumb]),
+            RecallCameraBookmark1 => UserInput::chord([camera_modifier, North]),
+            RecallCameraBookmark2 => UserInput::chord([camera_modifier, West]),
+            RecallCameraBookmark3 => UserInput::chord([camera_modifier, East]),
+            RecallCameraBookmark4 => UserInput::chord([camera_modifier, LeftThumb]),
             DragCamera => GamepadButtonType::RightThumb.into(),
             Pan => DualAxis::left_stick().into(),
             MoveCursor => DualAxis::right_stick().into(),
             ZoomIn => UserInput::chord([camera_modifier, DPadUp]),
             ZoomOut => UserInput::chord([camera_modifier, DPadDown]),
-            TiltCameraUp => UserInput::chord([RightTrigger, DPadDown]),
-            TiltCameraDown => UserInput::chord([RightTrigger, DPadDown]),
+            TiltCameraUp => UserInput::chord([camera_modifier, selection_modifier, DPadUp]),
+            TiltCameraDown => UserInput::chord([camera_modifier, selection_modifier, DPadDown]),
             RotateCameraLeft => UserInput::chord([camera_modifier, DPadLeft]),
             RotateCameraRight => UserInput::chord([camera_modifier, DPadRight]),
             ToggleStatusInfo => UserInput::chord([infovis_modifier, DPadLeft]),
@@ -221,18 +345,11 @@ impl PlayerAction {
             ToggleSignalOverlay => UserInput::chord([infovis_modifier, DPadUp]),
             ToggleStrongestSignalOverlay => UserInput::chord([infovis_modifier, DPadRight]),
             ToggleWaterTableOverlay => UserInput::chord([infovis_modifier, DPadDown]),
-            ToggleLightOverlay => UserInput::chord([infovis_modifier, DPadUp]),
-        }
-    }
-
-    /// The default key bindings
-    fn default_input_map() -> InputMap<PlayerAction> {
-        let mut input_map = InputMap::default();
-
-        for variant in PlayerAction::variants() {
-            input_map.insert(variant.kbm_binding(), variant.clone());
-            input_map.insert(variant.gamepad_binding(), variant);
+            ToggleLightOverlay => UserInput::chord([infovis_modifier, South]),
+            ToggleFertilityOverlay => UserInput::chord([infovis_modifier, West]),
+            ExportStatistics => UserInput::chord([infovis_modifier, East]),
+            ExportDecisionTrace => UserInput::chord([infovis_modifier, North]),
+            ToggleKeybindingMenu => GamepadButtonType::Start.into(),
         }
-        input_map
     }
 }
@@ -294,6 +294,23 @@ impl CurrentSelection {
         }
     }
 
+    /// Selects the provided `voxels`.
+    ///
+    /// If `multiple` is true, they are added to any existing tile selection rather than replacing it.
+    pub(crate) fn select_voxels(
+        &mut self,
+        voxels: impl IntoIterator<Item = VoxelPos>,
+        multiple: bool,
+    ) {
+        let mut selected_voxels = match (&*self, multiple) {
+            (CurrentSelection::Voxels(existing_selection), true) => existing_selection.clone(),
+            _ => SelectedVoxels::default(),
+        };
+
+        selected_voxels.extend(voxels);
+        *self = CurrentSelection::Voxels(selected_voxels);
+    }
+
     /// Just select the terrain.
     #[must_use]
     fn select_terrain(
@@ -0,0 +1,393 @@
+//! Selection modes that pick many related tiles or structures at once, rather than a fixed shape.
+//!
+//! Each of these modes can be repeated without changing the selection in between to cycle through its variants.
+//! For example, flood filling the same tile twice switches from matching terrain type to matching height.
+
+use std::fmt::Display;
+use std::mem::discriminant;
+
+use bevy::{prelude::*, utils::HashSet};
+use hexx::Hex;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::ghosts::{Ghost, Preview},
+    crafting::{diagnostics::CraftingDiagnostics, inventories::CraftingState},
+    geometry::{MapGeometry, VoxelPos},
+    structures::structure_manifest::Structure,
+    terrain::terrain_manifest::Terrain,
+    water::WaterDepth,
+};
+
+use super::{
+    picking::CursorPos,
+    selection::{set_tile_interactions, CurrentSelection, SelectedVoxels},
+    InteractionSystem, PlayerAction,
+};
+
+/// Adds flood fill, select-all-similar and select-by-filter selection modes.
+pub(super) struct SmartSelectionPlugin;
+
+impl Plugin for SmartSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (flood_fill_selection, select_all_similar, select_by_filter)
+                .in_set(InteractionSystem::SelectTiles)
+                .after(InteractionSystem::ComputeCursorPos)
+                .before(set_tile_interactions),
+        );
+    }
+}
+
+/// Which property tiles must share with the starting tile to be included in a flood fill.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum FloodFillCriterion {
+    /// The same [`Id<Terrain>`].
+    #[default]
+    TerrainType,
+    /// The same height.
+    Height,
+    /// The same kind of [`WaterDepth`]: dry, underground or flooded.
+    WaterState,
+}
+
+impl FloodFillCriterion {
+    /// The criterion to use when the flood fill is repeated.
+    fn next(self) -> Self {
+        match self {
+            FloodFillCriterion::TerrainType => FloodFillCriterion::Height,
+            FloodFillCriterion::Height => FloodFillCriterion::WaterState,
+            FloodFillCriterion::WaterState => FloodFillCriterion::TerrainType,
+        }
+    }
+}
+
+impl Display for FloodFillCriterion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            FloodFillCriterion::TerrainType => "terrain type",
+            FloodFillCriterion::Height => "height",
+            FloodFillCriterion::WaterState => "water state",
+        };
+
+        write!(f, "{string}")
+    }
+}
+
+/// Which structures of the same type are picked by [`select_all_similar`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SimilarScope {
+    /// Only structures that are currently visible on screen.
+    #[default]
+    OnScreen,
+    /// Every structure on the map.
+    MapWide,
+}
+
+impl SimilarScope {
+    /// The scope to use when the selection is repeated.
+    fn next(self) -> Self {
+        match self {
+            SimilarScope::OnScreen => SimilarScope::MapWide,
+            SimilarScope::MapWide => SimilarScope::OnScreen,
+        }
+    }
+}
+
+/// A property of crafting structures that can be used to select them all at once.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SelectionFilter {
+    /// Crafting structures that are not working, because they have no recipe or are waiting on inputs.
+    #[default]
+    Idle,
+    /// Crafting structures whose output is full and needs to be emptied.
+    OutputFull,
+    /// Crafting structures that have recently spent most of their time blocked.
+    Bottlenecked,
+}
+
+impl SelectionFilter {
+    /// The fraction of recent time that a structure must be blocked for to count as [`SelectionFilter::Bottlenecked`].
+    const BOTTLENECK_THRESHOLD: f32 = 0.5;
+
+    /// The filter to use when the selection is repeated.
+    fn next(self) -> Self {
+        match self {
+            SelectionFilter::Idle => SelectionFilter::OutputFull,
+            SelectionFilter::OutputFull => SelectionFilter::Bottlenecked,
+            SelectionFilter::Bottlenecked => SelectionFilter::Idle,
+        }
+    }
+
+    /// Does a crafting structure with the provided state pass this filter?
+    fn matches(self, crafting_state: &CraftingState, diagnostics: &CraftingDiagnostics) -> bool {
+        match self {
+            SelectionFilter::Idle => matches!(
+                crafting_state,
+                CraftingState::NoRecipe | CraftingState::NeedsInput
+            ),
+            SelectionFilter::OutputFull => {
+                matches!(crafting_state, CraftingState::FullAndBlocked)
+            }
+            SelectionFilter::Bottlenecked => {
+                diagnostics.blocked_fraction() >= Self::BOTTLENECK_THRESHOLD
+            }
+        }
+    }
+}
+
+/// Remembers the mode and result of the last smart selection of a given kind.
+///
+/// If the player repeats the selection without changing anything, the next mode is used instead.
+#[derive(Debug, Default)]
+struct LastSmartSelection<T> {
+    /// The mode that was used, and the tiles that it selected.
+    previous: Option<(T, SelectedVoxels)>,
+}
+
+impl<T: Copy + Default> LastSmartSelection<T> {
+    /// Returns the mode to use for this selection.
+    ///
+    /// The mode advances if the current selection is exactly what this selection mode produced last time.
+    fn mode(&self, current_selection: &CurrentSelection, next: impl Fn(T) -> T) -> T {
+        match (&self.previous, current_selection) {
+            (Some((mode, previous_voxels)), CurrentSelection::Voxels(selected_voxels))
+                if previous_voxels == selected_voxels =>
+            {
+                next(*mode)
+            }
+            _ => T::default(),
+        }
+    }
+
+    /// Stores the result of this selection.
+    fn record(&mut self, mode: T, current_selection: &CurrentSelection) {
+        self.previous = match current_selection {
+            CurrentSelection::Voxels(selected_voxels) => Some((mode, selected_voxels.clone())),
+            _ => None,
+        };
+    }
+}
+
+/// Collects every tile connected to `start` through tiles for which `is_similar` returns true.
+///
+/// The starting tile is always included.
+fn flood_fill(
+    start: Hex,
+    map_geometry: &MapGeometry,
+    is_similar: impl Fn(Hex) -> bool,
+) -> HashSet<VoxelPos> {
+    let mut visited = HashSet::from_iter([start]);
+    let mut frontier = vec![start];
+
+    while let Some(hex) = frontier.pop() {
+        for neighbor in map_geometry.adjacent_hexes(hex).into_iter().flatten() {
+            if !visited.contains(&neighbor) && is_similar(neighbor) {
+                visited.insert(neighbor);
+                frontier.push(neighbor);
+            }
+        }
+    }
+
+    visited
+        .into_iter()
+        .filter_map(|hex| {
+            let height = map_geometry.get_height(hex).ok()?;
+            Some(VoxelPos { hex, height })
+        })
+        .collect()
+}
+
+/// Selects all contiguous tiles that share the terrain type, height or water state of the hovered tile.
+fn flood_fill_selection(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    mut current_selection: ResMut<CurrentSelection>,
+    terrain_query: Query<(&Id<Terrain>, &WaterDepth)>,
+    map_geometry: Res<MapGeometry>,
+    mut last_selection: Local<LastSmartSelection<FloodFillCriterion>>,
+) {
+    if !actions.just_pressed(PlayerAction::FloodFillSelection) {
+        return;
+    }
+
+    let Some(hovered_tile) = cursor_pos.maybe_voxel_pos() else {
+        return;
+    };
+
+    let start = hovered_tile.hex;
+    let Ok(start_entity) = map_geometry.get_terrain(start) else {
+        return;
+    };
+    let Ok((&start_terrain, start_water_depth)) = terrain_query.get(start_entity) else {
+        return;
+    };
+    let start_height = map_geometry.get_height(start).ok();
+
+    let criterion = last_selection.mode(&current_selection, FloodFillCriterion::next);
+
+    let is_similar = |hex: Hex| -> bool {
+        let Ok(entity) = map_geometry.get_terrain(hex) else {
+            return false;
+        };
+        let Ok((&terrain_id, water_depth)) = terrain_query.get(entity) else {
+            return false;
+        };
+
+        match criterion {
+            FloodFillCriterion::TerrainType => terrain_id == start_terrain,
+            FloodFillCriterion::Height => map_geometry.get_height(hex).ok() == start_height,
+            FloodFillCriterion::WaterState => {
+                discriminant(water_depth) == discriminant(start_water_depth)
+            }
+        }
+    };
+
+    let filled = flood_fill(start, &map_geometry, is_similar);
+    info!(
+        "Flood filled {} tiles with the same {criterion}.",
+        filled.len()
+    );
+
+    current_selection.select_voxels(filled, actions.pressed(PlayerAction::Multiple));
+    last_selection.record(criterion, &current_selection);
+}
+
+/// Selects every structure of the same type as the hovered structure.
+///
+/// The first use selects only the structures on screen; repeating it selects them across the whole map.
+fn select_all_similar(
+    actions: Res<ActionState<PlayerAction>>,
+    cursor_pos: Res<CursorPos>,
+    mut current_selection: ResMut<CurrentSelection>,
+    structure_query: Query<(&Id<Structure>, &VoxelPos), (Without<Ghost>, Without<Preview>)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    map_geometry: Res<MapGeometry>,
+    mut last_selection: Local<LastSmartSelection<SimilarScope>>,
+) {
+    if !actions.just_pressed(PlayerAction::SelectAllSimilar) {
+        return;
+    }
+
+    let Some(hovered_tile) = cursor_pos.maybe_voxel_pos() else {
+        return;
+    };
+
+    // The cursor may be pointing at either the structure itself, or the terrain beneath it
+    let Some(hovered_structure) = map_geometry
+        .get_structure(hovered_tile)
+        .or_else(|| map_geometry.get_structure(hovered_tile.above()))
+    else {
+        return;
+    };
+
+    let Ok((&structure_id, _)) = structure_query.get(hovered_structure) else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    let scope = last_selection.mode(&current_selection, SimilarScope::next);
+
+    let is_on_screen = |voxel_pos: &VoxelPos| -> bool {
+        let Some(viewport_size) = camera.logical_viewport_size() else {
+            return false;
+        };
+
+        camera
+            .world_to_viewport(camera_transform, voxel_pos.into_world_pos())
+            .is_some_and(|screen_pos| {
+                screen_pos.cmpge(Vec2::ZERO).all() && screen_pos.cmple(viewport_size).all()
+            })
+    };
+
+    let similar = structure_query
+        .iter()
+        .filter(|(&id, _)| id == structure_id)
+        .map(|(_, voxel_pos)| voxel_pos)
+        .filter(|voxel_pos| scope == SimilarScope::MapWide || is_on_screen(voxel_pos))
+        .copied();
+
+    current_selection.select_voxels(similar, actions.pressed(PlayerAction::Multiple));
+    last_selection.record(scope, &current_selection);
+}
+
+/// Selects every crafting structure that passes a [`SelectionFilter`], such as all idle structures.
+///
+/// Repeating the selection cycles through the available filters.
+fn select_by_filter(
+    actions: Res<ActionState<PlayerAction>>,
+    mut current_selection: ResMut<CurrentSelection>,
+    crafting_query: Query<(&VoxelPos, &CraftingState, &CraftingDiagnostics), Without<Ghost>>,
+    mut last_selection: Local<LastSmartSelection<SelectionFilter>>,
+) {
+    if !actions.just_pressed(PlayerAction::SelectByFilter) {
+        return;
+    }
+
+    let filter = last_selection.mode(&current_selection, SelectionFilter::next);
+
+    let filtered = crafting_query
+        .iter()
+        .filter(|(_, crafting_state, diagnostics)| filter.matches(crafting_state, diagnostics))
+        .map(|(&voxel_pos, ..)| voxel_pos);
+
+    current_selection.select_voxels(filtered, actions.pressed(PlayerAction::Multiple));
+    info!("Selected crafting structures matching the {filter:?} filter.");
+    last_selection.record(filter, &current_selection);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flood_fill_stops_at_dissimilar_tiles() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+
+        // Only tiles on the positive side of the map are similar
+        let filled = flood_fill(Hex::ZERO, &map_geometry, |hex| hex.x >= 0);
+        let expected = map_geometry.all_hexes().filter(|hex| hex.x >= 0).count();
+
+        assert_eq!(filled.len(), expected);
+        assert!(filled.iter().all(|voxel_pos| voxel_pos.hex.x >= 0));
+    }
+
+    #[test]
+    fn flood_fill_includes_isolated_start() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+
+        let filled = flood_fill(Hex::ZERO, &map_geometry, |_| false);
+        assert_eq!(filled.len(), 1);
+    }
+
+    #[test]
+    fn repeating_a_selection_advances_the_mode() {
+        let mut last_selection = LastSmartSelection::<FloodFillCriterion>::default();
+        let mut current_selection = CurrentSelection::None;
+        assert_eq!(
+            last_selection.mode(&current_selection, FloodFillCriterion::next),
+            FloodFillCriterion::TerrainType
+        );
+
+        current_selection.select_voxels([VoxelPos::ZERO], false);
+        last_selection.record(FloodFillCriterion::TerrainType, &current_selection);
+        assert_eq!(
+            last_selection.mode(&current_selection, FloodFillCriterion::next),
+            FloodFillCriterion::Height
+        );
+
+        // Changing the selection resets the mode
+        current_selection.select_voxels([VoxelPos::from_xy(1, 0)], true);
+        assert_eq!(
+            last_selection.mode(&current_selection, FloodFillCriterion::next),
+            FloodFillCriterion::TerrainType
+        );
+    }
+}
@@ -210,6 +210,8 @@ impl Signals {
             Goal::Wander { .. } => HashMap::new(),
             // Follows gradient of water depth instead of signal
             Goal::Breathe => HashMap::new(),
+            // Follows whatever satisfies the need instead, which varies by need
+            Goal::Satisfy(_) => HashMap::new(),
             Goal::Fetch(item_kind)
             | Goal::Eat(item_kind)
             | Goal::Store(item_kind)
@@ -238,7 +240,7 @@ impl Signals {
             Goal::Work(structure_id) => {
                 self.neighboring_signals(SignalType::Work(*structure_id), voxel_pos, map_geometry)
             }
-            Goal::Avoid(unit_id) => {
+            Goal::Avoid(unit_id) | Goal::Hunt(unit_id) => {
                 self.neighboring_signals(SignalType::Unit(*unit_id), voxel_pos, map_geometry)
             }
             Goal::Demolish(structure_id) => self.neighboring_signals(
@@ -246,6 +248,12 @@ impl Signals {
                 voxel_pos,
                 map_geometry,
             ),
+            Goal::Repair(structure_id) => {
+                self.neighboring_signals(SignalType::Repair(*structure_id), voxel_pos, map_geometry)
+            }
+            Goal::Visit(structure_id) => {
+                self.neighboring_signals(SignalType::Visit(*structure_id), voxel_pos, map_geometry)
+            }
         }
     }
 
@@ -310,7 +318,7 @@ impl Signals {
 }
 
 /// All of the signals on a single tile.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct LocalSignals {
     /// Internal data storage
     map: HashMap<SignalType, SignalStrength>,
@@ -432,6 +440,10 @@ pub enum SignalType {
     Work(WorkplaceId),
     /// Destroy a structure of this type
     Demolish(Id<Structure>),
+    /// Repair a damaged structure of this type
+    Repair(Id<Structure>),
+    /// Visit a structure of this type, to the benefit of you both
+    Visit(Id<Structure>),
     /// Has an item of this type, in case you were looking.
     ///
     /// The passive form of `Push`.
@@ -507,6 +519,12 @@ impl SignalType {
             SignalType::Demolish(structure_id) => {
                 format!("Demolish({})", structure_manifest.name(*structure_id))
             }
+            SignalType::Repair(structure_id) => {
+                format!("Repair({})", structure_manifest.name(*structure_id))
+            }
+            SignalType::Visit(structure_id) => {
+                format!("Visit({})", structure_manifest.name(*structure_id))
+            }
             SignalType::Contains(item_kind) => {
                 format!("Contains({})", item_manifest.name_of_kind(*item_kind))
             }
@@ -531,6 +549,10 @@ pub(crate) enum SignalKind {
     Work,
     /// Destroy a structure of this type
     Demolish,
+    /// Repair a damaged structure of this type
+    Repair,
+    /// Visit a structure of this type, to the benefit of you both
+    Visit,
     /// Has an item of this type, in case you were looking.
     ///
     /// The passive form of `Push`.
@@ -550,6 +572,8 @@ impl From<SignalType> for SignalKind {
             SignalType::Pull(_) => SignalKind::Pull,
             SignalType::Work(_) => SignalKind::Work,
             SignalType::Demolish(_) => SignalKind::Demolish,
+            SignalType::Repair(_) => SignalKind::Repair,
+            SignalType::Visit(_) => SignalKind::Visit,
             SignalType::Contains(_) => SignalKind::Contains,
             SignalType::Stores(_) => SignalKind::Stores,
             SignalType::Unit(_) => SignalKind::Unit,
@@ -1038,6 +1062,82 @@ mod tests {
             .is_some());
     }
 
+    #[test]
+    fn units_follow_signals_up_ramps() {
+        use crate::{geometry::DiscreteHeight, structures::Footprint};
+        use hexx::Hex;
+
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let item_manifest = test_manifest();
+        let footprint = Footprint::single();
+        let facing = Facing::default();
+
+        // A two-story tower with a walkable roof, whose roof wants the item
+        let tower_hex = Hex::ZERO.neighbor(facing.direction);
+        for height in [DiscreteHeight(1), DiscreteHeight(2)] {
+            map_geometry
+                .add_structure(
+                    VoxelPos {
+                        hex: tower_hex,
+                        height,
+                    },
+                    facing,
+                    &footprint,
+                    true,
+                    false,
+                    false,
+                    Entity::from_bits(42),
+                )
+                .unwrap();
+        }
+
+        let ground = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight::ONE,
+        };
+        let roof = VoxelPos {
+            hex: tower_hex,
+            height: DiscreteHeight(3),
+        };
+        signals.add_signal(SignalType::Pull(test_item()), roof, SignalStrength(1.));
+
+        // The roof is two steps up, so it can't be reached from the ground
+        assert_eq!(
+            signals.upstream(
+                ground,
+                &Goal::Store(test_item()),
+                &item_manifest,
+                &map_geometry
+            ),
+            None
+        );
+
+        // Until a ramp facing the tower is built
+        map_geometry
+            .add_structure(
+                ground,
+                facing,
+                &footprint,
+                false,
+                true,
+                true,
+                Entity::from_bits(43),
+            )
+            .unwrap();
+
+        assert_eq!(
+            signals.upstream(
+                ground,
+                &Goal::Store(test_item()),
+                &item_manifest,
+                &map_geometry
+            ),
+            Some(roof)
+        );
+    }
+
     #[test]
     fn item_signal_types_are_correct() {
         let item_kind = test_item();
@@ -0,0 +1,327 @@
+//! Alerts tell the player about problems in their colony as they happen, so they don't need to watch for them.
+//!
+//! Simulation systems send [`Alert`] events when something goes wrong, such as a unit starting to starve.
+//! These are collected into the [`AlertFeed`], which groups similar alerts and rate-limits repeated ones.
+//! Each kind of alert can be muted using the [`AlertSettings`].
+
+use core::fmt::Display;
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use emergence_macros::IterableEnum;
+
+use crate as emergence_lib;
+use crate::{
+    geometry::VoxelPos,
+    organisms::{death::OrganismDied, OrganismId},
+};
+
+use super::time::{Days, InGameTime};
+
+/// Collects, groups and rate-limits alerts about the colony.
+pub(super) struct AlertPlugin;
+
+impl Plugin for AlertPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Alert>()
+            .init_resource::<AlertFeed>()
+            .init_resource::<AlertSettings>()
+            .add_systems(Update, (alert_unit_deaths, collect_alerts).chain());
+    }
+}
+
+/// The kinds of problems that the player can be alerted about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IterableEnum)]
+pub enum AlertKind {
+    /// A unit is running out of energy.
+    Starving,
+    /// An organism is running out of oxygen, and is panicking.
+    Suffocating,
+    /// A structure is underwater, and is wearing down quickly.
+    Flooded,
+    /// The rising tide is about to submerge a structure.
+    HighTide,
+    /// A unit has died.
+    UnitDied,
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            AlertKind::Starving => "Unit starving",
+            AlertKind::Suffocating => "Organism suffocating",
+            AlertKind::Flooded => "Structure flooded",
+            AlertKind::HighTide => "High tide approaching",
+            AlertKind::UnitDied => "Unit died",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// Sent whenever something happens that the player should know about.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// What kind of problem occurred.
+    pub kind: AlertKind,
+    /// The entity that the alert is about.
+    ///
+    /// This entity may no longer exist.
+    pub entity: Entity,
+    /// Where the problem occurred.
+    pub voxel_pos: VoxelPos,
+}
+
+/// Raises alerts only when a problem starts, rather than on every tick that it continues.
+///
+/// Store one of these in a [`Local`] for each kind of ongoing problem that a system watches for.
+#[derive(Debug, Default)]
+pub(crate) struct EdgeTriggeredAlerts {
+    /// The entities that had the problem as of the last update.
+    affected: HashSet<Entity>,
+}
+
+impl EdgeTriggeredAlerts {
+    /// Records every entity that currently has the problem, returning an alert of `kind` for each one that did not have it at the last update.
+    ///
+    /// Entities that are no longer affected (or have been despawned) are forgotten,
+    /// so a new alert is raised if the problem comes back.
+    pub(crate) fn update(
+        &mut self,
+        kind: AlertKind,
+        affected: impl IntoIterator<Item = (Entity, VoxelPos)>,
+    ) -> Vec<Alert> {
+        let previously_affected = std::mem::take(&mut self.affected);
+        let mut alerts = Vec::new();
+
+        for (entity, voxel_pos) in affected {
+            if !previously_affected.contains(&entity) {
+                alerts.push(Alert {
+                    kind,
+                    entity,
+                    voxel_pos,
+                });
+            }
+
+            self.affected.insert(entity);
+        }
+
+        alerts
+    }
+}
+
+/// Alerts of the same kind that occurred close together in time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AlertGroup {
+    /// The kind of alert.
+    pub(crate) kind: AlertKind,
+    /// The entity that the most recent alert was about.
+    pub(crate) entity: Entity,
+    /// The location of the most recent alert.
+    pub(crate) voxel_pos: VoxelPos,
+    /// The number of alerts in this group.
+    pub(crate) count: u32,
+    /// The in-game day on which the most recent alert in this group occurred.
+    pub(crate) last_seen: f32,
+}
+
+/// The recent alerts, grouped by kind and ordered from newest to oldest.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct AlertFeed {
+    /// The groups of alerts, newest first.
+    groups: VecDeque<AlertGroup>,
+    /// The last time that each entity raised each kind of alert, in in-game days.
+    last_alerted: HashMap<(AlertKind, Entity), f32>,
+}
+
+impl AlertFeed {
+    /// The maximum number of groups that are kept.
+    const MAX_GROUPS: usize = 20;
+
+    /// Alerts of the same kind that occur within this time of each other are grouped together.
+    const GROUPING_WINDOW: Days = Days(0.1);
+
+    /// The minimum time between alerts of the same kind about the same entity.
+    const COOLDOWN: Days = Days(0.5);
+
+    /// Adds `alert` to the feed, which occurred on the in-game day `now`.
+    ///
+    /// Returns `false` if the alert was ignored, as the same entity raised the same alert too recently.
+    fn push(&mut self, alert: &Alert, now: f32) -> bool {
+        self.last_alerted
+            .retain(|_, &mut last_time| now - last_time < AlertFeed::COOLDOWN.0);
+
+        let key = (alert.kind, alert.entity);
+        if self.last_alerted.contains_key(&key) {
+            return false;
+        }
+        self.last_alerted.insert(key, now);
+
+        let existing_index = self.groups.iter().position(|group| {
+            group.kind == alert.kind && now - group.last_seen < AlertFeed::GROUPING_WINDOW.0
+        });
+
+        let group = match existing_index.and_then(|index| self.groups.remove(index)) {
+            Some(mut group) => {
+                group.entity = alert.entity;
+                group.voxel_pos = alert.voxel_pos;
+                group.count += 1;
+                group.last_seen = now;
+                group
+            }
+            None => AlertGroup {
+                kind: alert.kind,
+                entity: alert.entity,
+                voxel_pos: alert.voxel_pos,
+                count: 1,
+                last_seen: now,
+            },
+        };
+
+        self.groups.push_front(group);
+        self.groups.truncate(AlertFeed::MAX_GROUPS);
+        true
+    }
+
+    /// The groups of alerts, from newest to oldest.
+    pub(crate) fn iter(&self) -> impl ExactSizeIterator<Item = &AlertGroup> + '_ {
+        self.groups.iter()
+    }
+
+    /// The group of alerts at `index`, where 0 is the newest.
+    pub(crate) fn get(&self, index: usize) -> Option<&AlertGroup> {
+        self.groups.get(index)
+    }
+}
+
+/// Controls which kinds of alerts the player is shown.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct AlertSettings {
+    /// The kinds of alerts that are ignored.
+    muted: HashSet<AlertKind>,
+}
+
+impl AlertSettings {
+    /// Is the provided `kind` of alert muted?
+    pub(crate) fn is_muted(&self, kind: AlertKind) -> bool {
+        self.muted.contains(&kind)
+    }
+
+    /// Mutes the provided `kind` of alert if it was unmuted, and unmutes it otherwise.
+    pub(crate) fn toggle_mute(&mut self, kind: AlertKind) {
+        if !self.muted.remove(&kind) {
+            self.muted.insert(kind);
+        }
+    }
+}
+
+/// Raises an alert whenever a unit dies.
+fn alert_unit_deaths(mut death_events: EventReader<OrganismDied>, mut alerts: EventWriter<Alert>) {
+    for death in death_events.read() {
+        if let OrganismId::Unit(_) = death.organism_id {
+            alerts.send(Alert {
+                kind: AlertKind::UnitDied,
+                entity: death.entity,
+                voxel_pos: death.voxel_pos,
+            });
+        }
+    }
+}
+
+/// Adds new alerts to the [`AlertFeed`], unless they have been muted.
+fn collect_alerts(
+    mut alert_events: EventReader<Alert>,
+    alert_settings: Res<AlertSettings>,
+    in_game_time: Res<InGameTime>,
+    mut alert_feed: ResMut<AlertFeed>,
+) {
+    let now = in_game_time.elapsed_days();
+
+    for alert in alert_events.read() {
+        if !alert_settings.is_muted(alert.kind) {
+            alert_feed.push(alert, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an alert of the provided `kind` about `entity`.
+    fn alert(kind: AlertKind, entity: Entity) -> Alert {
+        Alert {
+            kind,
+            entity,
+            voxel_pos: VoxelPos::ZERO,
+        }
+    }
+
+    #[test]
+    fn similar_alerts_are_grouped() {
+        let mut feed = AlertFeed::default();
+        assert!(feed.push(&alert(AlertKind::Starving, Entity::from_raw(0)), 0.));
+        assert!(feed.push(&alert(AlertKind::Starving, Entity::from_raw(1)), 0.01));
+        assert!(feed.push(&alert(AlertKind::Flooded, Entity::from_raw(2)), 0.02));
+
+        assert_eq!(feed.iter().len(), 2);
+        assert_eq!(feed.get(0).unwrap().kind, AlertKind::Flooded);
+        assert_eq!(feed.get(1).unwrap().count, 2);
+        assert_eq!(feed.get(1).unwrap().entity, Entity::from_raw(1));
+
+        // Too much time has passed to join the existing group
+        assert!(feed.push(&alert(AlertKind::Starving, Entity::from_raw(3)), 1.));
+        assert_eq!(feed.iter().len(), 3);
+    }
+
+    #[test]
+    fn repeated_alerts_are_rate_limited() {
+        let mut feed = AlertFeed::default();
+        let entity = Entity::from_raw(0);
+
+        assert!(feed.push(&alert(AlertKind::Starving, entity), 0.));
+        assert!(!feed.push(&alert(AlertKind::Starving, entity), 0.1));
+        // Other kinds of alerts about the same entity are not affected
+        assert!(feed.push(&alert(AlertKind::Suffocating, entity), 0.1));
+        assert!(feed.push(&alert(AlertKind::Starving, entity), 1.));
+    }
+
+    #[test]
+    fn ongoing_problems_are_only_alerted_when_they_start() {
+        let mut starving = EdgeTriggeredAlerts::default();
+        let first = Entity::from_raw(0);
+        let second = Entity::from_raw(1);
+
+        let alerts = starving.update(AlertKind::Starving, [(first, VoxelPos::ZERO)]);
+        assert_eq!(alerts, vec![alert(AlertKind::Starving, first)]);
+
+        let alerts = starving.update(
+            AlertKind::Starving,
+            [(first, VoxelPos::ZERO), (second, VoxelPos::ZERO)],
+        );
+        assert_eq!(alerts, vec![alert(AlertKind::Starving, second)]);
+
+        // Once the problem is resolved, it is alerted again if it comes back
+        assert!(starving
+            .update(AlertKind::Starving, [(second, VoxelPos::ZERO)])
+            .is_empty());
+        let alerts = starving.update(AlertKind::Starving, [(first, VoxelPos::ZERO)]);
+        assert_eq!(alerts, vec![alert(AlertKind::Starving, first)]);
+    }
+
+    #[test]
+    fn alerts_can_be_muted() {
+        let mut settings = AlertSettings::default();
+        assert!(!settings.is_muted(AlertKind::HighTide));
+
+        settings.toggle_mute(AlertKind::HighTide);
+        assert!(settings.is_muted(AlertKind::HighTide));
+        assert!(!settings.is_muted(AlertKind::Flooded));
+
+        settings.toggle_mute(AlertKind::HighTide);
+        assert!(!settings.is_muted(AlertKind::HighTide));
+    }
+}
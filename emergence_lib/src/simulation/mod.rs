@@ -9,10 +9,13 @@ use crate::geometry::sync_rotation_to_facing;
 use crate::light::LightPlugin;
 use crate::organisms::OrganismPlugin;
 use crate::signals::SignalsPlugin;
+use crate::simulation::alerts::AlertPlugin;
 use crate::simulation::rng::GlobalRng;
+use crate::simulation::statistics::StatisticsPlugin;
 use crate::simulation::time::TemporalPlugin;
 use crate::simulation::weather::WeatherPlugin;
 use crate::structures::StructuresPlugin;
+use crate::technology::TechnologyPlugin;
 use crate::terrain::TerrainPlugin;
 use crate::units::UnitsPlugin;
 use crate::water::WaterPlugin;
@@ -20,7 +23,9 @@ use crate::world_gen::{GenerationConfig, GenerationPlugin, WorldGenState};
 use bevy::core::FrameCount;
 use bevy::prelude::*;
 
+pub mod alerts;
 pub mod rng;
+pub mod statistics;
 pub mod time;
 pub mod weather;
 
@@ -38,7 +43,16 @@ impl Plugin for SimulationPlugin {
             .configure_sets(
                 FixedUpdate,
                 SimulationSet
-                    .run_if(in_state(PauseState::Playing))
+                    .run_if(playing_or_stepping)
+                    .run_if(in_state(AssetState::FullyLoaded))
+                    .run_if(world_gen_ready)
+                    .run_if(max_ticks_not_reached),
+            )
+            .configure_sets(
+                FixedUpdate,
+                SimulationEventSet
+                    .after(SimulationSet)
+                    .run_if(playing_or_stepping)
                     .run_if(in_state(AssetState::FullyLoaded))
                     .run_if(world_gen_ready)
                     .run_if(max_ticks_not_reached),
@@ -47,13 +61,23 @@ impl Plugin for SimulationPlugin {
                 Update,
                 update_ticks_this_frame.run_if(max_ticks_not_reached),
             )
+            .add_systems(
+                FixedUpdate,
+                finish_step
+                    .after(SimulationSet)
+                    .after(SimulationEventSet)
+                    .run_if(in_state(AssetState::FullyLoaded))
+                    .run_if(world_gen_ready),
+            )
             .insert_resource(TicksThisFrame { current: 0, max: 3 })
+            .init_resource::<StepRequested>()
             .add_plugins(GenerationPlugin {
                 config: self.gen_config.clone(),
             })
             .add_plugins(CraftingPlugin)
             .add_plugins(ConstructionPlugin)
             .add_plugins(StructuresPlugin)
+            .add_plugins(TechnologyPlugin)
             .add_plugins(TerrainPlugin)
             .add_plugins(OrganismPlugin)
             .add_plugins(UnitsPlugin)
@@ -61,7 +85,9 @@ impl Plugin for SimulationPlugin {
             .add_plugins(TemporalPlugin)
             .add_plugins(LightPlugin)
             .add_plugins(WaterPlugin)
-            .add_plugins(WeatherPlugin);
+            .add_plugins(WeatherPlugin)
+            .add_plugins(StatisticsPlugin)
+            .add_plugins(AlertPlugin);
     }
 }
 
@@ -79,11 +105,20 @@ enum PauseState {
 ///
 /// These:
 /// - are run in [`FixedUpdate`]
-/// - only run in [`PauseState::Playing`]
+/// - only run in [`PauseState::Playing`], or for a single tick when stepping while paused
 /// - only run in [`AssetState::FullyLoaded`]
 #[derive(SystemSet, PartialEq, Eq, Hash, Debug, Clone)]
 pub(crate) struct SimulationSet;
 
+/// Simulation systems that read events sent by commands, such as [`OrganismDied`](crate::organisms::death::OrganismDied).
+///
+/// Events are cleared once per frame, rather than once per tick,
+/// so readers in a later tick can miss them.
+/// These run after the [`SimulationSet`], once its commands have been applied,
+/// and otherwise follow the same rules.
+#[derive(SystemSet, PartialEq, Eq, Hash, Debug, Clone)]
+pub(crate) struct SimulationEventSet;
+
 /// Tracks how many ticks have passed this frame.
 // BLOCKED: this is a workaround for https://github.com/bevyengine/bevy/issues/8543.
 // Once that's fixed and released all this code should be removed.
@@ -95,6 +130,10 @@ struct TicksThisFrame {
     max: u8,
 }
 
+/// Has the player asked to advance the paused simulation by a single tick?
+#[derive(Resource, Debug, Default)]
+struct StepRequested(bool);
+
 /// Updates [`TicksThisFrame`].
 fn update_ticks_this_frame(mut ticks: ResMut<TicksThisFrame>, frame_count: Res<FrameCount>) {
     if frame_count.is_changed() {
@@ -113,6 +152,19 @@ fn max_ticks_not_reached(frame_count: Res<FrameCount>, ticks: Res<TicksThisFrame
     ticks.current < ticks.max
 }
 
+/// Simulation systems run while the game is playing, or for one tick after a step is requested.
+fn playing_or_stepping(
+    pause_state: Res<State<PauseState>>,
+    step_requested: Res<StepRequested>,
+) -> bool {
+    *pause_state.get() == PauseState::Playing || step_requested.0
+}
+
+/// Clears any requested step once the simulation has run for a tick.
+fn finish_step(mut step_requested: ResMut<StepRequested>) {
+    step_requested.0 = false;
+}
+
 /// Ensures that simulation systems do not run until world gen is ready for them.
 fn world_gen_ready(world_gen_state: Res<State<WorldGenState>>) -> bool {
     *world_gen_state.get() == WorldGenState::Complete
@@ -0,0 +1,457 @@
+//! Records how the colony changes over time, so that trends can be graphed and analyzed.
+//!
+//! Every [`StatisticsHistory::SAMPLE_INTERVAL`], a [`StatisticsSample`] is taken,
+//! summarizing the population, crafting, births and deaths, water and unit goals since the last sample.
+//! Only the most recent samples are kept (60 in-game days worth by default), and these can be exported to disk as CSV or JSON.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    path::PathBuf,
+};
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{
+        recipe::{Recipe, RecipeInput, RecipeManifest},
+        RecipeCompleted,
+    },
+    geometry::Volume,
+    organisms::{death::OrganismDied, lifecycle::OrganismBorn, Organism, OrganismId},
+    player_interaction::PlayerAction,
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::{
+        goals::{Goal, GoalKind},
+        unit_manifest::{Unit, UnitManifest},
+    },
+    utils::persistence::{write_file, PersistenceError},
+    water::WaterVolume,
+};
+
+use super::{
+    time::{Days, InGameTime},
+    SimulationEventSet,
+};
+
+/// Records and exports statistics about the simulation.
+pub(super) struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatisticsHistory>()
+            .init_resource::<PendingStatistics>()
+            .add_systems(
+                FixedUpdate,
+                (count_statistics_events, record_statistics)
+                    .chain()
+                    .in_set(SimulationEventSet),
+            )
+            .add_systems(Update, export_statistics);
+    }
+}
+
+/// A snapshot of the state of the colony, taken at a single point in time.
+///
+/// Event-based values, like births and crafting, are totals since the previous sample.
+/// Everything is stored by name, so that exported data can be understood without the manifests.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatisticsSample {
+    /// The number of in-game days elapsed when this sample was taken.
+    pub day: f32,
+    /// The number of living organisms of each species.
+    ///
+    /// All life stages of a species are counted together.
+    pub population: BTreeMap<String, u32>,
+    /// The number of items produced and consumed by each recipe.
+    pub recipes: BTreeMap<String, RecipeThroughput>,
+    /// The number of organisms born.
+    pub births: u32,
+    /// The number of organisms that died.
+    pub deaths: u32,
+    /// The total volume of water on the map.
+    pub total_water: f32,
+    /// The number of units pursuing each kind of goal.
+    pub goals: BTreeMap<String, u32>,
+}
+
+impl StatisticsSample {
+    /// The total number of living organisms, across all species.
+    pub fn total_population(&self) -> u32 {
+        self.population.values().sum()
+    }
+}
+
+/// The items flowing through a single recipe.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeThroughput {
+    /// The number of items produced.
+    pub produced: u32,
+    /// The number of items consumed.
+    pub consumed: u32,
+}
+
+/// The most recent [`StatisticsSample`]s, oldest first.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct StatisticsHistory {
+    /// The recorded samples.
+    samples: VecDeque<StatisticsSample>,
+    /// The maximum number of samples to keep.
+    max_samples: usize,
+    /// The in-game time at which the next sample should be taken.
+    next_sample: Days,
+    /// The directory where exported statistics are written.
+    export_directory: PathBuf,
+}
+
+impl Default for StatisticsHistory {
+    fn default() -> Self {
+        StatisticsHistory::new(StatisticsHistory::DEFAULT_MAX_SAMPLES)
+    }
+}
+
+impl StatisticsHistory {
+    /// The in-game time between samples.
+    pub const SAMPLE_INTERVAL: Days = Days(0.25);
+
+    /// The number of samples kept by default: 60 in-game days worth.
+    const DEFAULT_MAX_SAMPLES: usize = 240;
+
+    /// The directory where statistics are exported by default.
+    const DEFAULT_EXPORT_DIRECTORY: &'static str = "statistics";
+
+    /// Creates a new empty history, which keeps at most `max_samples` samples.
+    pub fn new(max_samples: usize) -> Self {
+        StatisticsHistory {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+            next_sample: Days::ZERO,
+            export_directory: PathBuf::from(StatisticsHistory::DEFAULT_EXPORT_DIRECTORY),
+        }
+    }
+
+    /// Adds a new sample, discarding the oldest sample if the history is full.
+    pub fn push(&mut self, sample: StatisticsSample) {
+        while self.samples.len() >= self.max_samples.max(1) {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    /// The recorded samples, oldest first.
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = &StatisticsSample> + '_ {
+        self.samples.iter()
+    }
+
+    /// The most recently recorded sample, if any.
+    pub fn latest(&self) -> Option<&StatisticsSample> {
+        self.samples.back()
+    }
+
+    /// Formats the history as CSV, with one row per recorded value.
+    ///
+    /// Each row is `day,category,key,value`, which keeps the columns fixed
+    /// even as species and recipes come and go.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("day,category,key,value\n");
+
+        for sample in &self.samples {
+            let day = sample.day;
+            let mut row = |category: &str, key: &str, value: &dyn std::fmt::Display| {
+                // Writing to a String cannot fail
+                writeln!(csv, "{day},{category},{key},{value}").unwrap();
+            };
+
+            for (species, count) in &sample.population {
+                row("population", species, count);
+            }
+            for (recipe, throughput) in &sample.recipes {
+                row("produced", recipe, &throughput.produced);
+                row("consumed", recipe, &throughput.consumed);
+            }
+            row("births", "total", &sample.births);
+            row("deaths", "total", &sample.deaths);
+            row("water", "total", &sample.total_water);
+            for (goal, count) in &sample.goals {
+                row("goal", goal, count);
+            }
+        }
+
+        csv
+    }
+
+    /// Formats the history as a JSON list of samples.
+    pub fn to_json(&self) -> Result<String, PersistenceError> {
+        Ok(serde_json::to_string_pretty(&self.samples)?)
+    }
+
+    /// Writes the retained samples to disk as both CSV and JSON.
+    ///
+    /// Samples that have already been discarded are not included.
+    ///
+    /// Returns the paths of the files written.
+    pub fn export(&self) -> Result<Vec<PathBuf>, PersistenceError> {
+        let csv_path = self.export_directory.join("statistics.csv");
+        write_file(&csv_path, self.to_csv())?;
+
+        let json_path = self.export_directory.join("statistics.json");
+        write_file(&json_path, self.to_json()?)?;
+
+        Ok(vec![csv_path, json_path])
+    }
+}
+
+/// Event-based statistics that have built up since the last sample was taken.
+#[derive(Resource, Debug, Default)]
+struct PendingStatistics {
+    /// The items produced and consumed by each recipe.
+    recipes: BTreeMap<Id<Recipe>, RecipeThroughput>,
+    /// The number of organisms born.
+    births: u32,
+    /// The number of organisms that died.
+    deaths: u32,
+}
+
+impl PendingStatistics {
+    /// Records the items that flowed through a single completed recipe.
+    fn record_recipe(&mut self, event: &RecipeCompleted, recipe_manifest: &RecipeManifest) {
+        let consumed = match &recipe_manifest.get(event.recipe_id).inputs {
+            RecipeInput::Exact(inputs) => inputs.iter().map(|input| input.count).sum(),
+            RecipeInput::Flexible { count, .. } => *count,
+        };
+
+        let throughput = self.recipes.entry(event.recipe_id).or_default();
+        throughput.produced += event
+            .produced
+            .iter()
+            .map(|output| output.count)
+            .sum::<u32>();
+        throughput.consumed += consumed;
+    }
+}
+
+/// Tallies up crafting, births and deaths as they happen.
+///
+/// Births and deaths are sent by commands, so this must run in the [`SimulationEventSet`].
+fn count_statistics_events(
+    mut recipe_completed_events: EventReader<RecipeCompleted>,
+    mut birth_events: EventReader<OrganismBorn>,
+    mut death_events: EventReader<OrganismDied>,
+    mut pending: ResMut<PendingStatistics>,
+    recipe_manifest: Res<RecipeManifest>,
+) {
+    for event in recipe_completed_events.read() {
+        pending.record_recipe(event, &recipe_manifest);
+    }
+
+    pending.births += birth_events.read().count() as u32;
+    pending.deaths += death_events.read().count() as u32;
+}
+
+/// Takes a new [`StatisticsSample`] once enough in-game time has passed.
+fn record_statistics(
+    mut history: ResMut<StatisticsHistory>,
+    mut pending: ResMut<PendingStatistics>,
+    in_game_time: Res<InGameTime>,
+    organism_query: Query<(Option<&Id<Structure>>, Option<&Id<Unit>>), With<Organism>>,
+    goal_query: Query<&Goal>,
+    water_query: Query<&WaterVolume>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    recipe_manifest: Res<RecipeManifest>,
+) {
+    let today = Days(in_game_time.elapsed_days());
+    if today < history.next_sample {
+        return;
+    }
+    history.next_sample = today + StatisticsHistory::SAMPLE_INTERVAL;
+
+    let mut population = BTreeMap::new();
+    for (maybe_structure_id, maybe_unit_id) in organism_query.iter() {
+        let Some(organism_id) = OrganismId::from_components(maybe_structure_id, maybe_unit_id)
+        else {
+            continue;
+        };
+
+        let species = organism_id
+            .variety(&structure_manifest, &unit_manifest)
+            .map_or(organism_id, |variety| variety.prototypical_form);
+        *population
+            .entry(species.display(&structure_manifest, &unit_manifest))
+            .or_default() += 1;
+    }
+
+    let mut goals = BTreeMap::new();
+    for goal in goal_query.iter() {
+        *goals
+            .entry(format!("{:?}", GoalKind::from(goal)))
+            .or_default() += 1;
+    }
+
+    let total_water = water_query
+        .iter()
+        .fold(Volume::ZERO, |total, water_volume| {
+            total + water_volume.volume()
+        });
+
+    let pending = std::mem::take(&mut *pending);
+    let recipes = pending
+        .recipes
+        .into_iter()
+        .map(|(recipe_id, throughput)| (recipe_manifest.name(recipe_id).to_string(), throughput))
+        .collect();
+
+    history.push(StatisticsSample {
+        day: today.0,
+        population,
+        recipes,
+        births: pending.births,
+        deaths: pending.deaths,
+        total_water: total_water.0,
+        goals,
+    });
+}
+
+/// Writes the recorded statistics to disk when prompted by player input.
+fn export_statistics(
+    history: Res<StatisticsHistory>,
+    player_actions: Res<ActionState<PlayerAction>>,
+) {
+    if player_actions.just_pressed(PlayerAction::ExportStatistics) {
+        match history.export() {
+            Ok(paths) => info!("Exported statistics to {paths:?}"),
+            Err(error) => error!("Failed to export statistics: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        crafting::recipe::{RecipeConditions, RecipeData, RecipeOutput},
+        geometry::VoxelPos,
+        items::ItemCount,
+        organisms::{death::CauseOfDeath, domestication::Strain},
+        simulation::SimulationSet,
+    };
+
+    /// Creates a sample taken on the provided `day`, with a single species.
+    fn sample(day: f32) -> StatisticsSample {
+        StatisticsSample {
+            day,
+            population: BTreeMap::from([("acacia (S)".to_string(), 3)]),
+            births: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = StatisticsHistory::new(3);
+
+        for day in 0..5 {
+            history.push(sample(day as f32));
+        }
+
+        let days: Vec<f32> = history.samples().map(|sample| sample.day).collect();
+        assert_eq!(days, vec![2., 3., 4.]);
+        assert_eq!(history.latest().unwrap().total_population(), 3);
+    }
+
+    #[test]
+    fn history_exports_to_csv_and_json() {
+        let mut history = StatisticsHistory::new(3);
+        history.push(sample(0.5));
+
+        let csv = history.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("day,category,key,value"));
+        assert_eq!(lines.next(), Some("0.5,population,acacia (S),3"));
+        assert!(csv.contains("0.5,births,total,1"));
+
+        let json = history.to_json().unwrap();
+        let samples: Vec<StatisticsSample> = serde_json::from_str(&json).unwrap();
+        assert_eq!(samples, vec![sample(0.5)]);
+    }
+
+    /// Sends the same events as a tick of the simulation: crafting directly, births and deaths by commands.
+    fn simulate_tick(
+        mut commands: Commands,
+        mut recipe_completed_events: EventWriter<RecipeCompleted>,
+    ) {
+        let organism_id = OrganismId::Unit(Id::from_name("ant".to_string()));
+
+        recipe_completed_events.send(RecipeCompleted {
+            recipe_id: Id::from_name("weaving".to_string()),
+            voxel_pos: VoxelPos::ZERO,
+            produced: vec![ItemCount::new(Id::from_name("basket".to_string()), 2)],
+        });
+
+        commands.add(move |world: &mut World| {
+            world.send_event(OrganismBorn {
+                organism_id,
+                voxel_pos: VoxelPos::ZERO,
+                strain: Strain::Domesticated,
+            });
+            world.send_event(OrganismDied {
+                entity: Entity::PLACEHOLDER,
+                organism_id,
+                voxel_pos: VoxelPos::ZERO,
+                cause: CauseOfDeath::Starvation,
+            });
+        });
+    }
+
+    #[test]
+    fn events_are_counted_in_the_tick_they_are_sent() {
+        let mut recipe_manifest = RecipeManifest::new();
+        recipe_manifest.insert(
+            "weaving".to_string(),
+            RecipeData {
+                inputs: RecipeInput::Exact(vec![ItemCount::new(
+                    Id::from_name("reed".to_string()),
+                    3,
+                )]),
+                outputs: RecipeOutput::EMPTY,
+                craft_time: Duration::from_secs(1),
+                conditions: RecipeConditions::NONE,
+                energy: None,
+            },
+        );
+
+        let mut app = App::new();
+        app.add_event::<RecipeCompleted>()
+            .add_event::<OrganismBorn>()
+            .add_event::<OrganismDied>()
+            .init_resource::<PendingStatistics>()
+            .insert_resource(recipe_manifest)
+            .configure_sets(FixedUpdate, SimulationEventSet.after(SimulationSet))
+            .add_systems(
+                FixedUpdate,
+                (
+                    simulate_tick.in_set(SimulationSet),
+                    count_statistics_events.in_set(SimulationEventSet),
+                ),
+            );
+
+        app.world.run_schedule(FixedUpdate);
+
+        let pending = app.world.resource::<PendingStatistics>();
+        assert_eq!(pending.births, 1);
+        assert_eq!(pending.deaths, 1);
+        assert_eq!(
+            pending.recipes[&Id::from_name("weaving".to_string())],
+            RecipeThroughput {
+                produced: 2,
+                consumed: 3,
+            }
+        );
+    }
+}
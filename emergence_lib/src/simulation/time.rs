@@ -14,7 +14,7 @@ use crate::graphics::lighting::{Moon, Sun};
 use crate::organisms::lifecycle::Lifecycle;
 use crate::player_interaction::PlayerAction;
 
-use super::{PauseState, SimulationSet};
+use super::{PauseState, SimulationSet, StepRequested};
 
 /// Introduces temporal variation into the environment.
 pub(crate) struct TemporalPlugin;
@@ -33,8 +33,12 @@ impl Plugin for TemporalPlugin {
                     .chain()
                     .in_set(SimulationSet),
             )
-            .add_systems(Update, pause_game)
-            .init_resource::<InGameTime>();
+            .add_systems(
+                Update,
+                (pause_game, step_simulation, change_simulation_speed),
+            )
+            .init_resource::<InGameTime>()
+            .init_resource::<SimulationSpeed>();
     }
 }
 
@@ -96,11 +100,14 @@ pub enum TimeOfDay {
 }
 
 impl TimeOfDay {
+    /// The fraction of each day, starting from dawn, during which the sun is out.
+    pub(crate) const DAYLIGHT_FRACTION: f32 = 0.7;
+
     /// Returns the time of day that is closest to the given fraction of a day.
     ///
     /// Values outside of [0.0, 1.0] are modulo'd to fit the range.
     pub fn from_fraction_of_day(fraction: f32) -> Self {
-        if fraction < 0.7 {
+        if fraction < TimeOfDay::DAYLIGHT_FRACTION {
             TimeOfDay::Day
         } else {
             TimeOfDay::Night
@@ -205,6 +212,89 @@ fn pause_game(
     }
 }
 
+/// Advances the simulation by a single tick when prompted by player input while paused.
+///
+/// This is useful for inspecting exactly how units and structures make their decisions.
+fn step_simulation(
+    current_pause_state: Res<State<PauseState>>,
+    mut step_requested: ResMut<StepRequested>,
+    player_actions: Res<ActionState<PlayerAction>>,
+) {
+    if *current_pause_state.get() == PauseState::Paused
+        && player_actions.just_pressed(PlayerAction::StepSimulation)
+    {
+        step_requested.0 = true;
+    }
+}
+
+/// How quickly the simulation runs, relative to wall-clock time.
+///
+/// Every tick covers the same amount of simulated time, regardless of speed:
+/// faster speeds simply run more ticks each second.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSpeed {
+    /// The index of the current speed in [`SimulationSpeed::PRESETS`].
+    index: usize,
+}
+
+impl SimulationSpeed {
+    /// The speeds that the player can choose between.
+    pub const PRESETS: [f32; 6] = [0.5, 1., 2., 3., 5., 10.];
+
+    /// The index of normal speed in [`SimulationSpeed::PRESETS`].
+    const NORMAL_INDEX: usize = 1;
+
+    /// The multiplier applied to the rate at which simulated time passes.
+    pub fn multiplier(&self) -> f32 {
+        Self::PRESETS[self.index]
+    }
+
+    /// Switches to the next faster speed, if any.
+    pub fn speed_up(&mut self) {
+        self.index = (self.index + 1).min(Self::PRESETS.len() - 1);
+    }
+
+    /// Switches to the next slower speed, if any.
+    pub fn slow_down(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+}
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        SimulationSpeed {
+            index: Self::NORMAL_INDEX,
+        }
+    }
+}
+
+impl Display for SimulationSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x speed", self.multiplier())
+    }
+}
+
+/// Changes the [`SimulationSpeed`] when prompted by player input.
+///
+/// The fixed timestep is driven by virtual time, so speeding it up increases the number of ticks run per second.
+fn change_simulation_speed(
+    mut simulation_speed: ResMut<SimulationSpeed>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    player_actions: Res<ActionState<PlayerAction>>,
+) {
+    if player_actions.just_pressed(PlayerAction::SpeedUpSimulation) {
+        simulation_speed.speed_up();
+    }
+
+    if player_actions.just_pressed(PlayerAction::SlowDownSimulation) {
+        simulation_speed.slow_down();
+    }
+
+    if simulation_speed.is_changed() {
+        virtual_time.set_relative_speed(simulation_speed.multiplier());
+    }
+}
+
 /// A [`Pool`] of [`Days`], which builds up and will eventually be filled (at which point some event will occur).
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct TimePool {
@@ -273,3 +363,24 @@ fn record_elapsed_time_for_lifecycles(
         lifecycle.record_elapsed_time(delta_days);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_speed_stays_within_presets() {
+        let mut simulation_speed = SimulationSpeed::default();
+        assert_eq!(simulation_speed.multiplier(), 1.);
+
+        for _ in 0..SimulationSpeed::PRESETS.len() + 1 {
+            simulation_speed.speed_up();
+        }
+        assert_eq!(simulation_speed.multiplier(), 10.);
+
+        for _ in 0..SimulationSpeed::PRESETS.len() + 1 {
+            simulation_speed.slow_down();
+        }
+        assert_eq!(simulation_speed.multiplier(), 0.5);
+    }
+}
@@ -8,15 +8,24 @@ use crate::{
     construction::ghosts::{GhostHandles, GhostKind, GhostStructureBundle, StructurePreviewBundle},
     crafting::{
         inventories::{InputInventory, OutputInventory, StorageInventory},
-        recipe::RecipeManifest,
+        recipe::{ActiveRecipe, RecipeManifest},
         CraftingBundle,
     },
     geometry::{Facing, MapGeometry, VoxelPos},
     graphics::InheritedMaterial,
-    items::{inventory::Inventory, item_manifest::ItemManifest},
-    organisms::{energy::StartingEnergy, OrganismBundle},
+    items::{inventory::Inventory, item_manifest::ItemManifest, ItemCount},
+    litter::SpawnLitterCommand,
+    organisms::{
+        domestication::{Domestication, Strain},
+        energy::StartingEnergy,
+        genetics::Genome,
+        lifecycle::OrganismBorn,
+        OrganismBundle, OrganismId,
+    },
     player_interaction::clipboard::ClipboardData,
     signals::Emitter,
+    technology::{technology_manifest::TechnologyManifest, ResearchProgress},
+    units::needs::need_manifest::NeedManifest,
 };
 
 use super::{
@@ -38,11 +47,43 @@ pub(crate) trait StructureCommandsExt {
         starting_energy: StartingEnergy,
     );
 
+    /// Spawns an organism defined by `data` at `voxel_pos`, with the heritable traits stored in `genome` and the provided `strain`.
+    ///
+    /// This is used when organisms reproduce or transform, so that their traits are passed on.
+    /// Has no effect if the tile position is already occupied by an existing structure.
+    fn spawn_organism_structure(
+        &mut self,
+        voxel_pos: VoxelPos,
+        data: ClipboardData,
+        starting_energy: StartingEnergy,
+        genome: Genome,
+        strain: Strain,
+    );
+
+    /// Spawns a newborn organism defined by `data` at `voxel_pos`, with the heritable traits stored in `genome` and the provided `strain`.
+    ///
+    /// An [`OrganismBorn`] event is sent once the organism has been spawned.
+    /// Has no effect if the tile position is already occupied by an existing structure.
+    fn spawn_newborn_structure(
+        &mut self,
+        voxel_pos: VoxelPos,
+        data: ClipboardData,
+        starting_energy: StartingEnergy,
+        genome: Genome,
+        strain: Strain,
+    );
+
     /// Despawns any structure at the provided `voxel_pos`.
     ///
     /// Has no effect if the tile position is already empty.
     fn despawn_structure(&mut self, voxel_pos: VoxelPos);
 
+    /// Replaces the structure at `voxel_pos` with the upgraded structure defined by `data`.
+    ///
+    /// Any items stored in the existing structure are moved into the new one, or dropped as litter if they don't fit.
+    /// Has no effect if the existing structure cannot be upgraded into the new one.
+    fn upgrade_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData);
+
     /// Spawns a ghost with data defined by `data` at `voxel_pos`.
     ///
     /// Replaces any existing ghost.
@@ -70,6 +111,45 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
             center: voxel_pos,
             data,
             starting_energy,
+            genome: None,
+            strain: None,
+            newborn: false,
+        });
+    }
+
+    fn spawn_organism_structure(
+        &mut self,
+        voxel_pos: VoxelPos,
+        data: ClipboardData,
+        starting_energy: StartingEnergy,
+        genome: Genome,
+        strain: Strain,
+    ) {
+        self.add(SpawnStructureCommand {
+            center: voxel_pos,
+            data,
+            starting_energy,
+            genome: Some(genome),
+            strain: Some(strain),
+            newborn: false,
+        });
+    }
+
+    fn spawn_newborn_structure(
+        &mut self,
+        voxel_pos: VoxelPos,
+        data: ClipboardData,
+        starting_energy: StartingEnergy,
+        genome: Genome,
+        strain: Strain,
+    ) {
+        self.add(SpawnStructureCommand {
+            center: voxel_pos,
+            data,
+            starting_energy,
+            genome: Some(genome),
+            strain: Some(strain),
+            newborn: true,
         });
     }
 
@@ -77,6 +157,13 @@ impl<'w, 's> StructureCommandsExt for Commands<'w, 's> {
         self.add(DespawnStructureCommand { center: voxel_pos });
     }
 
+    fn upgrade_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData) {
+        self.add(UpgradeStructureCommand {
+            center: voxel_pos,
+            data,
+        });
+    }
+
     fn spawn_ghost_structure(&mut self, voxel_pos: VoxelPos, data: ClipboardData) {
         self.add(SpawnStructureGhostCommand {
             center: voxel_pos,
@@ -104,6 +191,18 @@ struct SpawnStructureCommand {
     data: ClipboardData,
     /// The amount of energy to give the organism.
     starting_energy: StartingEnergy,
+    /// The heritable traits of the organism.
+    ///
+    /// If `None`, a new genome is sampled from the natural variation of its species.
+    genome: Option<Genome>,
+    /// Whether the organism cooperates with the colony.
+    ///
+    /// If `None`, the organism is domesticated only if its species has been domesticated.
+    strain: Option<Strain>,
+    /// Should an [`OrganismBorn`] event be sent once the organism has been spawned?
+    ///
+    /// This is `false` for organisms that are transforming into a new form.
+    newborn: bool,
 }
 
 impl Command for SpawnStructureCommand {
@@ -124,6 +223,11 @@ impl Command for SpawnStructureCommand {
         if geometry
             .is_space_available(self.center, &structure_data.footprint, self.data.facing)
             .is_err()
+            || !geometry.is_footprint_supported(
+                self.center,
+                &structure_data.footprint,
+                self.data.facing,
+            )
         {
             // Just give up if the terrain is wrong.
             return;
@@ -166,7 +270,17 @@ impl Command for SpawnStructureCommand {
 
         // PERF: these operations could be done in a single archetype move with more branching
         if let Some(organism_details) = &structure_data.organism_variety {
-            let mut energy_pool = organism_details.energy_pool.clone();
+            let genome = self.genome.unwrap_or_else(|| {
+                Genome::sample(organism_details.genetic_variation, &mut rand::thread_rng())
+            });
+            let strain = self.strain.unwrap_or_else(|| {
+                world
+                    .get_resource::<Domestication>()
+                    .map_or(Strain::initial(organism_details), |domestication| {
+                        domestication.newborn_strain(organism_details)
+                    })
+            });
+            let mut energy_pool = genome.energy_pool(&organism_details.energy_pool);
             match self.starting_energy {
                 StartingEnergy::Specific(energy) => {
                     energy_pool.set_current(energy);
@@ -184,9 +298,21 @@ impl Command for SpawnStructureCommand {
                 .insert(OrganismBundle::new(
                     energy_pool,
                     organism_details.lifecycle.clone(),
+                    genome,
+                    strain,
                 ));
+
+            if self.newborn {
+                world.send_event(OrganismBorn {
+                    organism_id: OrganismId::Structure(structure_id),
+                    voxel_pos: self.center,
+                    strain,
+                });
+            }
         };
 
+        let is_ramp = structure_data.kind == StructureKind::Ramp;
+
         match structure_data.kind {
             StructureKind::Storage {
                 max_slot_count,
@@ -214,7 +340,7 @@ impl Command for SpawnStructureCommand {
                     })
                 })
             }
-            StructureKind::Path => {}
+            StructureKind::Path | StructureKind::Ramp => {}
             StructureKind::Landmark => {
                 world.entity_mut(structure_entity).insert(Landmark);
             }
@@ -247,6 +373,29 @@ impl Command for SpawnStructureCommand {
                 .insert(vegetative_reproduction);
         }
 
+        if let Some(durability_data) = &structure_data.durability {
+            let mut entity_mut = world.entity_mut(structure_entity);
+            entity_mut.insert(durability_data.pool());
+
+            // Damaged structures need to be able to call for repairs
+            if !entity_mut.contains::<Emitter>() {
+                entity_mut.insert(Emitter::default());
+            }
+        }
+
+        // Symbiotic structures need to be able to call for their partners,
+        // and structures that satisfy needs must be able to call for units in need
+        let satisfies_needs = world
+            .resource::<NeedManifest>()
+            .satisfied_by_structure(structure_id);
+
+        if structure_data.symbiosis.is_some() || satisfies_needs {
+            let mut entity_mut = world.entity_mut(structure_entity);
+            if !entity_mut.contains::<Emitter>() {
+                entity_mut.insert(Emitter::default());
+            }
+        }
+
         let mut geometry = world.resource_mut::<MapGeometry>();
         // We've already verified that we can build here, so we can safely unwrap at this point
         geometry
@@ -256,6 +405,7 @@ impl Command for SpawnStructureCommand {
                 &structure_data.footprint,
                 structure_data.can_walk_on_roof,
                 structure_data.can_walk_through,
+                is_ramp,
                 structure_entity,
             )
             .unwrap();
@@ -263,9 +413,11 @@ impl Command for SpawnStructureCommand {
 }
 
 /// A [`Command`] used to despawn a structure via [`StructureCommandsExt`].
-struct DespawnStructureCommand {
+///
+/// This can also be applied directly from inside of other commands.
+pub(crate) struct DespawnStructureCommand {
     /// The tile position at which the structure to be despawned is found.
-    center: VoxelPos,
+    pub(crate) center: VoxelPos,
 }
 
 impl Command for DespawnStructureCommand {
@@ -284,6 +436,7 @@ impl Command for DespawnStructureCommand {
         let footprint = structure_data.footprint.clone();
 
         let mut geometry = world.resource_mut::<MapGeometry>();
+        let maybe_upgrade_ghost = geometry.get_upgrade_ghost(self.center);
         let maybe_entity = geometry.remove_structure(self.center, &footprint, facing);
 
         // Check that there's something there to despawn
@@ -294,6 +447,147 @@ impl Command for DespawnStructureCommand {
         let structure_entity = maybe_entity.unwrap();
         // Make sure to despawn all children, which represent the meshes stored in the loaded gltf scene.
         world.entity_mut(structure_entity).despawn_recursive();
+
+        // Any planned upgrade is abandoned along with the structure it was upgrading
+        if let Some(upgrade_ghost) =
+            maybe_upgrade_ghost.and_then(|entity| world.get_entity_mut(entity))
+        {
+            upgrade_ghost.despawn_recursive();
+        }
+    }
+}
+
+/// A [`Command`] used to upgrade a structure via [`StructureCommandsExt`].
+struct UpgradeStructureCommand {
+    /// The tile position at which the structure to be upgraded is found.
+    center: VoxelPos,
+    /// Data about the upgraded structure to spawn.
+    data: ClipboardData,
+}
+
+impl Command for UpgradeStructureCommand {
+    fn apply(self, world: &mut World) {
+        let structure_manifest = world.resource::<StructureManifest>();
+        let Some(upgrades_from) = structure_manifest.upgrades_from(self.data.structure_id) else {
+            warn!("Tried to upgrade into a structure that is not an upgrade.");
+            return;
+        };
+        let footprint = structure_manifest.footprint(self.data.structure_id).clone();
+        let starting_energy = match structure_manifest
+            .get(self.data.structure_id)
+            .organism_variety
+        {
+            Some(_) => StartingEnergy::Full,
+            None => StartingEnergy::NotAnOrganism,
+        };
+
+        let Some(existing_entity) = find_upgrade_target(world, self.center, upgrades_from) else {
+            warn!("Tried to upgrade a structure that could not be found.");
+            return;
+        };
+
+        let map_geometry = world.resource::<MapGeometry>();
+        if !map_geometry.is_space_available_to_transform(
+            existing_entity,
+            self.center,
+            &footprint,
+            self.data.facing,
+        ) {
+            warn!("Tried to upgrade a structure without enough space.");
+            return;
+        }
+
+        // Collect the contents of the existing structure, so they can be carried over
+        let existing_entity_ref = world.entity(existing_entity);
+        let input_slots = existing_entity_ref
+            .get::<InputInventory>()
+            .into_iter()
+            .flat_map(|input| input.iter());
+        let output_slots = existing_entity_ref
+            .get::<OutputInventory>()
+            .into_iter()
+            .flat_map(|output| output.iter());
+        let storage_slots = existing_entity_ref
+            .get::<StorageInventory>()
+            .into_iter()
+            .flat_map(|storage| storage.iter());
+
+        let item_counts: Vec<ItemCount> = input_slots
+            .chain(output_slots)
+            .chain(storage_slots)
+            .filter(|item_slot| item_slot.count() > 0)
+            .map(|item_slot| item_slot.item_count())
+            .collect();
+
+        // Upgraded organisms keep their heritable traits
+        let genome = existing_entity_ref.get::<Genome>().cloned();
+        let strain = existing_entity_ref.get::<Strain>().copied();
+
+        DespawnStructureCommand {
+            center: self.center,
+        }
+        .apply(world);
+
+        SpawnStructureCommand {
+            center: self.center,
+            data: self.data,
+            starting_energy,
+            genome,
+            strain,
+            newborn: false,
+        }
+        .apply(world);
+
+        let map_geometry = world.resource::<MapGeometry>();
+        let maybe_upgraded_entity = map_geometry.get_structure(self.center);
+
+        let leftover_items = world.resource_scope(|world, item_manifest: Mut<ItemManifest>| {
+            let mut leftover_items: Vec<ItemCount> = Vec::new();
+
+            for item_count in item_counts {
+                let mut stored = false;
+
+                if let Some(upgraded_entity) = maybe_upgraded_entity {
+                    let mut upgraded_entity_mut = world.entity_mut(upgraded_entity);
+
+                    if let Some(mut storage_inventory) =
+                        upgraded_entity_mut.get_mut::<StorageInventory>()
+                    {
+                        stored = storage_inventory
+                            .add_item_all_or_nothing(&item_count, &item_manifest)
+                            .is_ok();
+                    }
+
+                    if !stored {
+                        if let Some(mut input_inventory) =
+                            upgraded_entity_mut.get_mut::<InputInventory>()
+                        {
+                            stored = input_inventory
+                                .fill_with_items(&item_count, &item_manifest)
+                                .is_ok();
+                        }
+                    }
+                }
+
+                if !stored {
+                    leftover_items.push(item_count);
+                }
+            }
+
+            leftover_items
+        });
+
+        // Anything that doesn't fit gets dropped on the ground
+        for item_count in leftover_items {
+            for _ in 0..item_count.count {
+                SpawnLitterCommand {
+                    voxel_pos: self.center,
+                    item: item_count.item_id,
+                    parent_genome: None,
+                }
+                .apply(world);
+            }
+        }
     }
 }
 
@@ -306,7 +600,7 @@ struct SpawnStructureGhostCommand {
 }
 
 impl Command for SpawnStructureGhostCommand {
-    fn apply(self, world: &mut World) {
+    fn apply(mut self, world: &mut World) {
         let structure_id = self.data.structure_id;
         let map_geometry = world.resource::<MapGeometry>();
 
@@ -317,20 +611,69 @@ impl Command for SpawnStructureGhostCommand {
         }
 
         let manifest = world.resource::<StructureManifest>();
+
+        // Wild species must be domesticated, and new technologies researched, before they can be built
+        let research_progress = world.resource::<ResearchProgress>();
+        let technology_manifest = world.resource::<TechnologyManifest>();
+        if !world
+            .resource::<Domestication>()
+            .can_build(structure_id, manifest)
+            || !research_progress.structure_unlocked(structure_id, technology_manifest)
+        {
+            return;
+        }
+
+        // Recipes that have not been unlocked cannot be selected
+        if let Some(recipe_id) = *self.data.active_recipe.recipe_id() {
+            if !research_progress.recipe_unlocked(recipe_id, technology_manifest) {
+                self.data.active_recipe = ActiveRecipe::NONE;
+            }
+        }
+
         let footprint = manifest.footprint(structure_id).clone();
+        let maybe_upgrades_from = manifest.upgrades_from(structure_id);
         let facing = self.data.facing;
 
         let world_pos = self.center.below().top_of_tile();
 
+        // Upgrades are built on top of an existing structure of the correct type
+        let maybe_existing_entity = match maybe_upgrades_from {
+            Some(upgrades_from) => {
+                let Some(existing_entity) = find_upgrade_target(world, self.center, upgrades_from)
+                else {
+                    warn!("Tried to upgrade a structure that could not be found.");
+                    return;
+                };
+
+                Some(existing_entity)
+            }
+            None => None,
+        };
+
         // Check that the tiles needed are appropriate.
-        if map_geometry
-            .is_space_available(self.center, &footprint, facing)
-            .is_err()
-        {
+        let space_available = match maybe_existing_entity {
+            Some(existing_entity) => map_geometry.is_space_available_to_transform(
+                existing_entity,
+                self.center,
+                &footprint,
+                facing,
+            ),
+            None => map_geometry
+                .is_space_available(self.center, &footprint, facing)
+                .is_ok(),
+        };
+
+        if !space_available {
             warn!("Tried to spawn a structure in an occupied location.");
             return;
         }
 
+        // Structures must be built on terrain or on a walkable roof
+        if !map_geometry.is_footprint_supported(self.center, &footprint, facing) {
+            warn!("Tried to spawn a structure without anything to support it.");
+            return;
+        }
+
         // Remove any existing ghosts
         let map_geometry = world.resource::<MapGeometry>();
 
@@ -390,9 +733,19 @@ impl Command for SpawnStructureGhostCommand {
             let structure_variety = structure_manifest.get(structure_id);
             let footprint = &structure_variety.footprint;
 
-            map_geometry
-                .add_ghost_structure(facing, self.center, footprint, ghost_entity)
-                .unwrap();
+            match maybe_existing_entity {
+                Some(existing_entity) => map_geometry.add_upgrade_ghost(
+                    existing_entity,
+                    facing,
+                    self.center,
+                    footprint,
+                    ghost_entity,
+                ),
+                None => {
+                    map_geometry.add_ghost_structure(facing, self.center, footprint, ghost_entity)
+                }
+            }
+            .unwrap();
         });
     }
 }
@@ -444,6 +797,17 @@ impl Command for SpawnStructurePreviewCommand {
         }
 
         let manifest = world.resource::<StructureManifest>();
+
+        if !world
+            .resource::<Domestication>()
+            .can_build(structure_id, manifest)
+            || !world
+                .resource::<ResearchProgress>()
+                .structure_unlocked(structure_id, world.resource::<TechnologyManifest>())
+        {
+            return;
+        }
+
         let structure_data = manifest.get(structure_id).clone();
 
         let geometry = world.resource::<MapGeometry>();
@@ -451,10 +815,29 @@ impl Command for SpawnStructurePreviewCommand {
         // Compute the world position
         let world_pos = self.center.below().top_of_tile();
 
+        // Upgrades must be placed on top of the structure that they are upgrading
+        let space_available = match manifest.upgrades_from(structure_id) {
+            Some(upgrades_from) => find_upgrade_target(world, self.center, upgrades_from)
+                .is_some_and(|existing_entity| {
+                    geometry.is_space_available_to_transform(
+                        existing_entity,
+                        self.center,
+                        &structure_data.footprint,
+                        self.data.facing,
+                    )
+                }),
+            None => geometry
+                .is_space_available(self.center, &structure_data.footprint, self.data.facing)
+                .is_ok(),
+        };
+
         // Check that the tiles needed are appropriate.
-        let forbidden = geometry
-            .is_space_available(self.center, &structure_data.footprint, self.data.facing)
-            .is_err();
+        let forbidden = !space_available
+            || !geometry.is_footprint_supported(
+                self.center,
+                &structure_data.footprint,
+                self.data.facing,
+            );
 
         // Fetch the scene and material to use
         let structure_handles = world.resource::<StructureHandles>();
@@ -483,3 +866,16 @@ impl Command for SpawnStructurePreviewCommand {
         ));
     }
 }
+
+/// Finds the existing structure at `center` that can be upgraded, if it is of the `upgrades_from` type.
+fn find_upgrade_target(
+    world: &World,
+    center: VoxelPos,
+    upgrades_from: Id<Structure>,
+) -> Option<Entity> {
+    let map_geometry = world.resource::<MapGeometry>();
+
+    map_geometry
+        .get_structure(center)
+        .filter(|&entity| world.get::<Id<Structure>>(entity) == Some(&upgrades_from))
+}
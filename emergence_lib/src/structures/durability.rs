@@ -0,0 +1,457 @@
+//! Structures wear down over time, and must be repaired by units or they will collapse.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use derive_more::{Add, AddAssign, Sub, SubAssign};
+use leafwing_abilities::{pool::MaxPoolLessThanMin, prelude::Pool};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    ops::{Div, Mul},
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    construction::demolition::set_emitter_for_structures_to_be_demolished,
+    crafting::{
+        inventories::{InputInventory, OutputInventory, StorageInventory},
+        set_crafting_emitter, set_storage_emitter,
+    },
+    geometry::{DiscreteHeight, Height, MapGeometry, VoxelPos},
+    items::item_manifest::Item,
+    litter::LitterCommandsExt,
+    signals::{Emitter, SignalStrength, SignalType},
+    simulation::{
+        alerts::{Alert, AlertKind, EdgeTriggeredAlerts},
+        SimulationSet,
+    },
+    water::WaterDepth,
+};
+
+use super::{
+    commands::StructureCommandsExt,
+    logistic_buildings::logistic_buildings_signals,
+    structure_manifest::{Structure, StructureManifest},
+    Footprint,
+};
+
+/// Systems that wear down, repair and collapse structures.
+pub(super) struct DurabilityPlugin;
+
+impl Plugin for DurabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (decay_durability, collapse_structures).chain(),
+                // Must run after the other emitters, as they wipe out existing signals
+                // Structures that are going to be demolished anyways shouldn't be repaired
+                set_emitter_for_structures_needing_repair
+                    .after(set_crafting_emitter)
+                    .after(set_storage_emitter)
+                    .after(logistic_buildings_signals)
+                    .before(set_emitter_for_structures_to_be_demolished),
+            )
+                .in_set(SimulationSet),
+        );
+    }
+}
+
+/// How durable each variety of structure is, and how it can be repaired.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurabilityData {
+    /// The durability of a freshly built structure.
+    pub max: Durability,
+    /// The amount of durability lost per second.
+    pub decay_rate: Durability,
+    /// The item that units must bring in order to repair this structure, if any.
+    ///
+    /// If this is [`None`], units can repair the structure empty-handed.
+    pub repair_material: Option<Id<Item>>,
+    /// The amount of durability restored by each repair action.
+    pub repair_amount: Durability,
+}
+
+impl DurabilityData {
+    /// Creates a new [`DurabilityPool`] for a freshly built structure.
+    pub(crate) fn pool(&self) -> DurabilityPool {
+        DurabilityPool::new(self.max)
+    }
+}
+
+/// The unprocessed equivalent of [`DurabilityData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawDurabilityData {
+    /// The durability of a freshly built structure.
+    pub max: f32,
+    /// The amount of durability lost per second.
+    pub decay_rate: f32,
+    /// The name of the item that units must bring in order to repair this structure, if any.
+    pub repair_material: Option<String>,
+    /// The amount of durability restored by each repair action.
+    pub repair_amount: f32,
+}
+
+impl From<RawDurabilityData> for DurabilityData {
+    fn from(raw: RawDurabilityData) -> Self {
+        DurabilityData {
+            max: Durability(raw.max),
+            decay_rate: Durability(raw.decay_rate),
+            repair_material: raw.repair_material.map(Id::from_name),
+            repair_amount: Durability(raw.repair_amount),
+        }
+    }
+}
+
+/// The remaining durability of a structure.
+///
+/// If this reaches zero, the structure collapses.
+#[derive(Debug, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct DurabilityPool {
+    /// The current durability.
+    current: Durability,
+    /// The maximum durability.
+    max: Durability,
+}
+
+impl DurabilityPool {
+    /// The fraction of the maximum durability below which units will be asked to repair the structure.
+    const REPAIR_THRESHOLD: f32 = 0.75;
+
+    /// Constructs a new full durability pool with a max durability of `max`.
+    pub fn new(max: Durability) -> Self {
+        DurabilityPool { current: max, max }
+    }
+
+    /// Has this structure worn down completely?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.current <= Durability(0.)
+    }
+
+    /// Is this structure damaged enough that units should repair it?
+    pub(crate) fn needs_repair(&self) -> bool {
+        self.current < self.max * DurabilityPool::REPAIR_THRESHOLD
+    }
+
+    /// Is this structure in need of any repairs at all?
+    pub(crate) fn is_damaged(&self) -> bool {
+        self.current < self.max
+    }
+}
+
+impl Display for DurabilityPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.current, self.max)
+    }
+}
+
+/// A quantity of durability, used to modify a [`DurabilityPool`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Default,
+    Add,
+    Sub,
+    AddAssign,
+    SubAssign,
+    Serialize,
+    Deserialize,
+)]
+pub struct Durability(pub f32);
+
+impl Display for Durability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}", self.0)
+    }
+}
+
+impl Mul<f32> for Durability {
+    type Output = Durability;
+
+    fn mul(self, rhs: f32) -> Durability {
+        Durability(self.0 * rhs)
+    }
+}
+
+impl Div<f32> for Durability {
+    type Output = Durability;
+
+    fn div(self, rhs: f32) -> Durability {
+        Durability(self.0 / rhs)
+    }
+}
+
+impl Pool for DurabilityPool {
+    type Quantity = Durability;
+    const MIN: Durability = Durability(0.);
+
+    fn current(&self) -> Self::Quantity {
+        self.current
+    }
+
+    fn set_current(&mut self, new_quantity: Self::Quantity) -> Self::Quantity {
+        let actual_value = Durability(new_quantity.0.clamp(0., self.max.0));
+        self.current = actual_value;
+        self.current
+    }
+
+    fn max(&self) -> Self::Quantity {
+        self.max
+    }
+
+    fn set_max(&mut self, new_max: Self::Quantity) -> Result<(), MaxPoolLessThanMin> {
+        if new_max < Self::MIN {
+            Err(MaxPoolLessThanMin)
+        } else {
+            self.max = new_max;
+            self.set_current(self.current);
+            Ok(())
+        }
+    }
+}
+
+/// Wears down structures over time.
+///
+/// Submerged structures decay much more quickly, and raise an alert when they are first flooded.
+fn decay_durability(
+    mut structure_query: Query<(Entity, &VoxelPos, &Id<Structure>, &mut DurabilityPool)>,
+    structure_manifest: Res<StructureManifest>,
+    water_depth_query: Query<&WaterDepth>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+    mut alerts: EventWriter<Alert>,
+    mut flooded: Local<EdgeTriggeredAlerts>,
+) {
+    /// The multiplier applied to the decay rate of structures that are underwater.
+    const UNDERWATER_DECAY_MULTIPLIER: f32 = 4.;
+
+    let delta_time = time.delta().as_secs_f32();
+    let mut submerged_structures = Vec::new();
+
+    for (entity, &voxel_pos, &structure_id, mut durability_pool) in structure_query.iter_mut() {
+        let structure_data = structure_manifest.get(structure_id);
+        let Some(durability_data) = &structure_data.durability else {
+            continue;
+        };
+
+        let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) else {
+            continue;
+        };
+
+        let Ok(water_depth) = water_depth_query.get(terrain_entity) else {
+            continue;
+        };
+
+        let Ok(terrain_height) = map_geometry.get_height(voxel_pos.hex) else {
+            continue;
+        };
+
+        let submerged = is_submerged(
+            voxel_pos,
+            &structure_data.footprint,
+            terrain_height,
+            water_depth,
+        );
+        if submerged {
+            submerged_structures.push((entity, voxel_pos));
+        }
+
+        let decay = match submerged {
+            true => durability_data.decay_rate * UNDERWATER_DECAY_MULTIPLIER,
+            false => durability_data.decay_rate,
+        };
+
+        let proposed = durability_pool.current() - decay * delta_time;
+        durability_pool.set_current(proposed);
+    }
+
+    alerts.send_batch(flooded.update(AlertKind::Flooded, submerged_structures));
+}
+
+/// Is the top of a structure at `voxel_pos` with the provided `footprint` below the surface water?
+///
+/// Structures may be stacked on top of others, so this is measured from the terrain at `terrain_height`.
+fn is_submerged(
+    voxel_pos: VoxelPos,
+    footprint: &Footprint,
+    terrain_height: DiscreteHeight,
+    water_depth: &WaterDepth,
+) -> bool {
+    // Structures on the ground occupy the voxel just above the terrain
+    let height_above_ground = voxel_pos.height - terrain_height.above();
+    let top = height_above_ground + footprint.max_height();
+
+    Height::from(top) < water_depth.surface_water_depth()
+}
+
+/// Asks units to repair structures that are badly worn down.
+fn set_emitter_for_structures_needing_repair(
+    mut structure_query: Query<(&mut Emitter, &Id<Structure>, &DurabilityPool)>,
+) {
+    /// The strength of the repair signal emitted by damaged structures.
+    const REPAIR_SIGNAL_STRENGTH: f32 = 20.;
+
+    for (mut emitter, &structure_id, durability_pool) in structure_query.iter_mut() {
+        // Not every emitter is reset each tick, so clear out any stale repair signals first
+        emitter
+            .signals
+            .retain(|(signal_type, _)| !matches!(signal_type, SignalType::Repair(_)));
+
+        if durability_pool.needs_repair() {
+            emitter.signals.push((
+                SignalType::Repair(structure_id),
+                SignalStrength::new(REPAIR_SIGNAL_STRENGTH),
+            ));
+        }
+    }
+}
+
+/// Destroys structures that have run out of durability, dropping their contents as litter.
+fn collapse_structures(
+    structure_query: Query<(
+        &VoxelPos,
+        &DurabilityPool,
+        Option<&InputInventory>,
+        Option<&OutputInventory>,
+        Option<&StorageInventory>,
+    )>,
+    mut commands: Commands,
+) {
+    for (&voxel_pos, durability_pool, maybe_input, maybe_output, maybe_storage) in
+        structure_query.iter()
+    {
+        if !durability_pool.is_empty() {
+            continue;
+        }
+
+        let input_slots = maybe_input.into_iter().flat_map(|input| input.iter());
+        let output_slots = maybe_output.into_iter().flat_map(|output| output.iter());
+        let storage_slots = maybe_storage.into_iter().flat_map(|storage| storage.iter());
+
+        for item_slot in input_slots.chain(output_slots).chain(storage_slots) {
+            for _ in 0..item_slot.count() {
+                commands.spawn_litter(voxel_pos, item_slot.item_id(), None);
+            }
+        }
+
+        commands.despawn_structure(voxel_pos);
+    }
+}
+
+/// A query for the structures that need to be repaired.
+#[derive(SystemParam)]
+pub(crate) struct RepairQuery<'w, 's> {
+    /// The contained query type.
+    query: Query<'w, 's, (&'static Id<Structure>, &'static DurabilityPool)>,
+}
+
+impl<'w, 's> RepairQuery<'w, 's> {
+    /// Is there a structure of type `structure_id` at `target` that needs to be repaired?
+    ///
+    /// If so, returns `Some(matching_structure_entity_that_needs_to_be_repaired)`.
+    pub(crate) fn needs_repair(
+        &self,
+        current: VoxelPos,
+        target: VoxelPos,
+        structure_id: Id<Structure>,
+        map_geometry: &MapGeometry,
+    ) -> Option<Entity> {
+        // This is only a viable target if the unit can reach it!
+        if current.abs_height_diff(target) > Height::MAX_STEP {
+            return None;
+        }
+
+        let entity = map_geometry.get_structure(target)?;
+
+        let (&found_structure_id, durability_pool) = self.query.get(entity).ok()?;
+
+        match found_structure_id == structure_id && durability_pool.is_damaged() {
+            true => Some(entity),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::Hex;
+
+    use super::*;
+
+    #[test]
+    fn only_structures_below_the_water_surface_are_submerged() {
+        let footprint = Footprint::single();
+        let terrain_height = DiscreteHeight(2);
+        let on_the_ground = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(3),
+        };
+        let on_a_roof = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(5),
+        };
+
+        let shallow = WaterDepth::Flooded(Height(1.));
+        assert!(is_submerged(
+            on_the_ground,
+            &footprint,
+            terrain_height,
+            &shallow
+        ));
+        assert!(!is_submerged(
+            on_a_roof,
+            &footprint,
+            terrain_height,
+            &shallow
+        ));
+
+        let deep = WaterDepth::Flooded(Height(3.));
+        assert!(is_submerged(on_a_roof, &footprint, terrain_height, &deep));
+
+        let dry = WaterDepth::Underground(Height(1.));
+        assert!(!is_submerged(
+            on_the_ground,
+            &footprint,
+            terrain_height,
+            &dry
+        ));
+    }
+
+    #[test]
+    fn durability_pool_starts_full() {
+        let durability_pool = DurabilityPool::new(Durability(100.));
+
+        assert!(!durability_pool.is_damaged());
+        assert!(!durability_pool.needs_repair());
+        assert!(!durability_pool.is_empty());
+    }
+
+    #[test]
+    fn slightly_worn_structures_do_not_request_repairs() {
+        let mut durability_pool = DurabilityPool::new(Durability(100.));
+        durability_pool.set_current(Durability(90.));
+
+        assert!(durability_pool.is_damaged());
+        assert!(!durability_pool.needs_repair());
+    }
+
+    #[test]
+    fn badly_worn_structures_request_repairs() {
+        let mut durability_pool = DurabilityPool::new(Durability(100.));
+        durability_pool.set_current(Durability(10.));
+
+        assert!(durability_pool.needs_repair());
+        assert!(!durability_pool.is_empty());
+    }
+
+    #[test]
+    fn durability_cannot_go_below_zero() {
+        let mut durability_pool = DurabilityPool::new(Durability(100.));
+        durability_pool.set_current(Durability(-50.));
+
+        assert_eq!(durability_pool.current(), Durability(0.));
+        assert!(durability_pool.is_empty());
+    }
+}
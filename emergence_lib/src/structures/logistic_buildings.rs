@@ -121,7 +121,7 @@ fn absorb_items(
 }
 
 /// Sets the emitters for logistic buildings.
-fn logistic_buildings_signals(
+pub(crate) fn logistic_buildings_signals(
     mut release_query: Query<
         (&mut Emitter, &mut InputInventory),
         (With<ReleasesItems>, Without<AbsorbsItems>),
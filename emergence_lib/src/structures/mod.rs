@@ -20,14 +20,16 @@ use crate::{
 };
 
 use self::{
+    durability::DurabilityPlugin,
     logistic_buildings::LogisticsPlugin,
     structure_assets::StructureHandles,
     structure_manifest::{RawStructureManifest, Structure},
 };
 
 pub(crate) mod commands;
+pub mod durability;
 pub(crate) mod logistic_buildings;
-mod structure_assets;
+pub(crate) mod structure_assets;
 pub mod structure_manifest;
 
 /// The systems that make structures tick.
@@ -37,6 +39,7 @@ impl Plugin for StructuresPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawStructureManifest>::new())
             .add_plugins(LogisticsPlugin)
+            .add_plugins(DurabilityPlugin)
             .add_asset_collection::<StructureHandles>();
     }
 }
@@ -6,6 +6,8 @@ use crate::{
     crafting::recipe::{ActiveRecipe, RawActiveRecipe},
     items::item_manifest::Item,
     organisms::{
+        carrying_capacity::CarryingCapacity,
+        symbiosis::{RawSymbiosisData, SymbiosisData},
         vegetative_reproduction::{RawVegetativeReproduction, VegetativeReproduction},
         OrganismId, OrganismVariety, RawOrganismVariety,
     },
@@ -18,7 +20,10 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::Footprint;
+use super::{
+    durability::{DurabilityData, RawDurabilityData},
+    Footprint,
+};
 
 /// The marker type for [`Id<Structure>`](super::Id).
 #[derive(Reflect, Clone, Copy, PartialEq, Eq)]
@@ -36,18 +41,29 @@ impl StructureManifest {
         match initial_strategy {
             ConstructionStrategy::Seedling(seedling_id) => self.construction_data(*seedling_id),
             ConstructionStrategy::Direct(data) => Some(data),
+            ConstructionStrategy::Upgrade { data, .. } => Some(data),
             ConstructionStrategy::Landmark { .. } => None,
         }
     }
 
+    /// Fetches the variety of structure that can be upgraded into the given structure type, if any.
+    ///
+    /// Returns [`None`] if the structure is not built by upgrading another structure.
+    pub fn upgrades_from(&self, structure_id: Id<Structure>) -> Option<Id<Structure>> {
+        match self.get(structure_id).construction_strategy {
+            ConstructionStrategy::Upgrade { from, .. } => Some(from),
+            _ => None,
+        }
+    }
+
     /// Fetches the [`Footprint`] for the initial form of a given structure type.
     pub fn footprint(&self, structure_id: Id<Structure>) -> &Footprint {
         let strategy = &self.get(structure_id).construction_strategy;
         match strategy {
             ConstructionStrategy::Seedling(seedling_id) => self.footprint(*seedling_id),
-            ConstructionStrategy::Direct(..) | ConstructionStrategy::Landmark => {
-                &self.get(structure_id).footprint
-            }
+            ConstructionStrategy::Direct(..)
+            | ConstructionStrategy::Upgrade { .. }
+            | ConstructionStrategy::Landmark => &self.get(structure_id).footprint,
         }
     }
 }
@@ -65,6 +81,10 @@ pub struct StructureData {
     pub construction_strategy: ConstructionStrategy,
     /// Can this structure spread vegetatively? If so, how?
     pub vegetative_reproduction: Option<VegetativeReproduction>,
+    /// How densely can this structure pack itself into an area before it stops reproducing?
+    ///
+    /// If this is [`None`], reproduction is only limited by the space available.
+    pub carrying_capacity: Option<CarryingCapacity>,
     /// The maximum number of workers that can work at this structure at once.
     pub max_workers: u8,
     /// The tiles taken up by this building.
@@ -75,6 +95,12 @@ pub struct StructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// How quickly does this structure wear down, and how can it be repaired?
+    ///
+    /// If this is [`None`], the structure never decays.
+    pub durability: Option<DurabilityData>,
+    /// Which units cooperate with this structure, and how they benefit each other.
+    pub symbiosis: Option<SymbiosisData>,
 }
 
 #[cfg(test)]
@@ -86,11 +112,14 @@ impl StructureData {
             kind: StructureKind::Path,
             construction_strategy: ConstructionStrategy::Direct(ConstructionData::default()),
             vegetative_reproduction: None,
+            carrying_capacity: None,
             max_workers: 6,
             footprint: Footprint::single(),
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            durability: None,
+            symbiosis: None,
         }
     }
 
@@ -101,11 +130,14 @@ impl StructureData {
             kind: StructureKind::Path,
             construction_strategy: ConstructionStrategy::Direct(ConstructionData::default()),
             vegetative_reproduction: None,
+            carrying_capacity: None,
             max_workers: 6,
             footprint: Footprint::single(),
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            durability: None,
+            symbiosis: None,
         }
     }
 
@@ -116,11 +148,14 @@ impl StructureData {
             kind: StructureKind::Path,
             construction_strategy: ConstructionStrategy::Direct(ConstructionData::default()),
             vegetative_reproduction: None,
+            carrying_capacity: None,
             max_workers: 6,
             footprint: Footprint::single(),
             root_zone: None,
             can_walk_through: false,
             can_walk_on_roof: false,
+            durability: None,
+            symbiosis: None,
         }
     }
 }
@@ -138,6 +173,8 @@ pub struct RawStructureData {
     pub construction_strategy: RawConstructionStrategy,
     /// Can this structure spread vegetatively? If so, how?
     pub vegetative_reproduction: Option<RawVegetativeReproduction>,
+    /// How densely can this structure pack itself into an area before it stops reproducing?
+    pub carrying_capacity: Option<CarryingCapacity>,
     /// The maximum number of workers that can work at this structure at once.
     pub max_workers: u8,
     /// The tiles taken up by this building.
@@ -148,6 +185,10 @@ pub struct RawStructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// How quickly does this structure wear down, and how can it be repaired?
+    pub durability: Option<RawDurabilityData>,
+    /// Which units cooperate with this structure, and how they benefit each other.
+    pub symbiosis: Option<RawSymbiosisData>,
 }
 
 impl From<RawStructureData> for StructureData {
@@ -157,11 +198,14 @@ impl From<RawStructureData> for StructureData {
             kind: raw.kind.into(),
             construction_strategy: raw.construction_strategy.into(),
             vegetative_reproduction: raw.vegetative_reproduction.map(Into::into),
+            carrying_capacity: raw.carrying_capacity,
             max_workers: raw.max_workers,
             footprint: raw.footprint.unwrap_or_default(),
             root_zone: raw.root_zone,
             can_walk_through: raw.can_walk_through,
             can_walk_on_roof: raw.can_walk_on_roof,
+            durability: raw.durability.map(Into::into),
+            symbiosis: raw.symbiosis.map(Into::into),
         }
     }
 }
@@ -183,6 +227,10 @@ pub enum StructureKind {
     },
     /// A structure that can be walked over.
     Path,
+    /// A structure that can be walked through, which allows units to climb an extra step in the direction it is facing.
+    ///
+    /// Ramps are used to connect the ground to walkable roofs that are more than one step high.
+    Ramp,
     /// A structure that is used to define a special element of the world.
     Landmark,
     /// A structure that spits out items.
@@ -208,6 +256,10 @@ pub enum RawStructureKind {
     },
     /// A structure that can be walked over.
     Path,
+    /// A structure that can be walked through, which allows units to climb an extra step in the direction it is facing.
+    ///
+    /// Ramps are used to connect the ground to walkable roofs that are more than one step high.
+    Ramp,
     /// A structure that is used to define a special element of the world.
     Landmark,
     /// A structure that spits out items.
@@ -230,6 +282,7 @@ impl From<RawStructureKind> for StructureKind {
                 starting_recipe: starting_recipe.into(),
             },
             RawStructureKind::Path => Self::Path,
+            RawStructureKind::Ramp => Self::Ramp,
             RawStructureKind::Landmark => Self::Landmark,
             RawStructureKind::Releaser => Self::Releaser,
             RawStructureKind::Absorber => Self::Absorber,
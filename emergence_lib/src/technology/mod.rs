@@ -0,0 +1,361 @@
+//! Technologies unlock new structures, recipes and terraforming tools as the colony develops.
+//!
+//! The tech tree is defined in the [`TechnologyManifest`].
+//! Each technology is unlocked once all of its [`Prerequisite`]s are met,
+//! and any content that is not unlocked by a technology is available from the start.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    asset_management::manifest::{plugin::ManifestPlugin, Id},
+    construction::terraform::TerraformingTool,
+    crafting::{progress_crafting, recipe::Recipe, RecipeCompleted},
+    items::{item_manifest::Item, ItemCount},
+    organisms::{domestication::Domestication, OrganismId},
+    simulation::SimulationSet,
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::unit_manifest::UnitManifest,
+};
+
+use self::technology_manifest::{
+    Prerequisite, RawTechnologyManifest, Technology, TechnologyManifest, Unlocks,
+};
+
+pub mod technology_manifest;
+
+/// Tracks and unlocks technologies.
+pub(crate) struct TechnologyPlugin;
+
+impl Plugin for TechnologyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ManifestPlugin::<RawTechnologyManifest>::new())
+            .init_resource::<ResearchProgress>()
+            .add_event::<TechnologyUnlocked>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    record_crafting_progress.after(progress_crafting),
+                    unlock_technologies,
+                )
+                    .chain()
+                    .in_set(SimulationSet),
+            );
+    }
+}
+
+/// Sent whenever a new technology is unlocked.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct TechnologyUnlocked {
+    /// The technology that was unlocked.
+    pub technology_id: Id<Technology>,
+}
+
+/// The colony's progress through the tech tree.
+#[derive(Resource, Debug, Default)]
+pub struct ResearchProgress {
+    /// The total number of each item produced by crafting.
+    items_produced: HashMap<Id<Item>, u32>,
+    /// The number of times that each recipe has been crafted.
+    recipes_completed: HashMap<Id<Recipe>, u32>,
+    /// The technologies that have been unlocked.
+    unlocked: HashSet<Id<Technology>>,
+}
+
+impl ResearchProgress {
+    /// Records that `recipe_id` was crafted, producing the `produced` items.
+    pub(crate) fn record_recipe(&mut self, recipe_id: Id<Recipe>, produced: &[ItemCount]) {
+        *self.recipes_completed.entry(recipe_id).or_default() += 1;
+
+        for item_count in produced {
+            *self.items_produced.entry(item_count.item_id).or_default() += item_count.count;
+        }
+    }
+
+    /// The total number of `item_id` produced by crafting.
+    pub fn items_produced(&self, item_id: Id<Item>) -> u32 {
+        self.items_produced
+            .get(&item_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of times that `recipe_id` has been crafted.
+    pub fn recipes_completed(&self, recipe_id: Id<Recipe>) -> u32 {
+        self.recipes_completed
+            .get(&recipe_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Has the technology with the provided `technology_id` been unlocked?
+    pub fn is_unlocked(&self, technology_id: Id<Technology>) -> bool {
+        self.unlocked.contains(&technology_id)
+    }
+
+    /// Is the provided `prerequisite` met?
+    ///
+    /// The `is_domesticated` closure reports whether the species with the provided prototypical form has been domesticated.
+    fn is_met(
+        &self,
+        prerequisite: &Prerequisite,
+        is_domesticated: impl Fn(OrganismId) -> bool,
+    ) -> bool {
+        match *prerequisite {
+            Prerequisite::ProduceItem { item_id, count } => self.items_produced(item_id) >= count,
+            Prerequisite::CompleteRecipe { recipe_id, count } => {
+                self.recipes_completed(recipe_id) >= count
+            }
+            Prerequisite::DomesticateSpecies(species) => is_domesticated(species),
+            Prerequisite::Technology(technology_id) => self.is_unlocked(technology_id),
+        }
+    }
+
+    /// Unlocks every technology whose prerequisites are met.
+    ///
+    /// Returns the technologies that were newly unlocked.
+    pub(crate) fn unlock_available(
+        &mut self,
+        technology_manifest: &TechnologyManifest,
+        is_domesticated: impl Fn(OrganismId) -> bool,
+    ) -> Vec<Id<Technology>> {
+        let mut newly_unlocked = Vec::new();
+
+        // Unlocking one technology may satisfy the prerequisites of another
+        loop {
+            let available: Vec<Id<Technology>> = technology_manifest
+                .data_map()
+                .iter()
+                .filter(|(technology_id, _)| !self.is_unlocked(**technology_id))
+                .filter(|(_, data)| {
+                    data.prerequisites
+                        .iter()
+                        .all(|prerequisite| self.is_met(prerequisite, &is_domesticated))
+                })
+                .map(|(&technology_id, _)| technology_id)
+                .collect();
+
+            if available.is_empty() {
+                return newly_unlocked;
+            }
+
+            self.unlocked.extend(available.iter().copied());
+            newly_unlocked.extend(available);
+        }
+    }
+
+    /// Is the content matching `predicate` available?
+    ///
+    /// Content is available if it is not unlocked by any technology,
+    /// or if at least one of the technologies that unlock it has been unlocked.
+    fn is_available(
+        &self,
+        technology_manifest: &TechnologyManifest,
+        predicate: impl Fn(&Unlocks) -> bool,
+    ) -> bool {
+        let mut gated = false;
+
+        for technology_id in technology_manifest.unlocked_by(predicate) {
+            if self.is_unlocked(technology_id) {
+                return true;
+            }
+
+            gated = true;
+        }
+
+        !gated
+    }
+
+    /// Can the structure with the provided `structure_id` be built?
+    pub fn structure_unlocked(
+        &self,
+        structure_id: Id<Structure>,
+        technology_manifest: &TechnologyManifest,
+    ) -> bool {
+        self.is_available(technology_manifest, |unlocks| {
+            unlocks.structures.contains(&structure_id)
+        })
+    }
+
+    /// Can the recipe with the provided `recipe_id` be selected?
+    pub fn recipe_unlocked(
+        &self,
+        recipe_id: Id<Recipe>,
+        technology_manifest: &TechnologyManifest,
+    ) -> bool {
+        self.is_available(technology_manifest, |unlocks| {
+            unlocks.recipes.contains(&recipe_id)
+        })
+    }
+
+    /// Can the provided terraforming tool be used?
+    pub(crate) fn terraforming_unlocked(
+        &self,
+        terraforming_tool: TerraformingTool,
+        technology_manifest: &TechnologyManifest,
+    ) -> bool {
+        self.is_available(technology_manifest, |unlocks| {
+            unlocks.terraforming.contains(&terraforming_tool)
+        })
+    }
+}
+
+/// Counts the recipes completed and items produced by crafting.
+fn record_crafting_progress(
+    mut recipe_completed_events: EventReader<RecipeCompleted>,
+    mut research_progress: ResMut<ResearchProgress>,
+) {
+    // Content only becomes available when technologies are unlocked,
+    // so there's no need to trigger change detection here
+    let research_progress = research_progress.bypass_change_detection();
+
+    for event in recipe_completed_events.read() {
+        research_progress.record_recipe(event.recipe_id, &event.produced);
+    }
+}
+
+/// Unlocks technologies once their prerequisites are met.
+fn unlock_technologies(
+    mut research_progress: ResMut<ResearchProgress>,
+    technology_manifest: Res<TechnologyManifest>,
+    domestication: Res<Domestication>,
+    structure_manifest: Res<StructureManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut technology_unlocked_events: EventWriter<TechnologyUnlocked>,
+) {
+    let is_domesticated = |species: OrganismId| {
+        species
+            .variety(&structure_manifest, &unit_manifest)
+            .is_some_and(|variety| domestication.is_domesticated(variety))
+    };
+
+    // Avoid triggering change detection every tick
+    let newly_unlocked = research_progress
+        .bypass_change_detection()
+        .unlock_available(&technology_manifest, is_domesticated);
+
+    if newly_unlocked.is_empty() {
+        return;
+    }
+
+    research_progress.set_changed();
+    for technology_id in newly_unlocked {
+        info!(
+            "Unlocked technology: {}",
+            technology_manifest.name(technology_id)
+        );
+        technology_unlocked_events.send(TechnologyUnlocked { technology_id });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::technology_manifest::TechnologyData;
+    use super::*;
+
+    /// A tech tree where `storage` requires leuco chunks and `bridges` requires `storage`.
+    fn test_manifest() -> TechnologyManifest {
+        let mut manifest = TechnologyManifest::default();
+        manifest.insert(
+            "storage".to_string(),
+            TechnologyData {
+                prerequisites: vec![Prerequisite::ProduceItem {
+                    item_id: Id::from_name("leuco_chunk".to_string()),
+                    count: 2,
+                }],
+                unlocks: Unlocks {
+                    structures: vec![Id::from_name("large_storage".to_string())],
+                    ..Default::default()
+                },
+            },
+        );
+        manifest.insert(
+            "bridges".to_string(),
+            TechnologyData {
+                prerequisites: vec![Prerequisite::Technology(Id::from_name(
+                    "storage".to_string(),
+                ))],
+                unlocks: Unlocks {
+                    recipes: vec![Id::from_name("bridge_building".to_string())],
+                    terraforming: vec![TerraformingTool::Change(Id::from_name(
+                        "swampy".to_string(),
+                    ))],
+                    ..Default::default()
+                },
+            },
+        );
+        manifest.insert(
+            "husbandry".to_string(),
+            TechnologyData {
+                prerequisites: vec![Prerequisite::DomesticateSpecies(OrganismId::Unit(
+                    Id::from_name("salt_shell".to_string()),
+                ))],
+                unlocks: Unlocks::default(),
+            },
+        );
+
+        manifest
+    }
+
+    #[test]
+    fn content_without_technologies_is_always_available() {
+        let manifest = test_manifest();
+        let research_progress = ResearchProgress::default();
+
+        assert!(
+            research_progress.structure_unlocked(Id::from_name("storage".to_string()), &manifest)
+        );
+        assert!(research_progress.recipe_unlocked(
+            Id::from_name("leuco_chunk_production".to_string()),
+            &manifest
+        ));
+        assert!(research_progress.terraforming_unlocked(TerraformingTool::Raise, &manifest));
+    }
+
+    #[test]
+    fn prerequisites_unlock_chains_of_technologies() {
+        let manifest = test_manifest();
+        let mut research_progress = ResearchProgress::default();
+        let large_storage = Id::from_name("large_storage".to_string());
+        let swampy = TerraformingTool::Change(Id::from_name("swampy".to_string()));
+
+        assert!(!research_progress.structure_unlocked(large_storage, &manifest));
+        assert!(!research_progress.terraforming_unlocked(swampy, &manifest));
+        assert!(research_progress
+            .unlock_available(&manifest, |_| false)
+            .is_empty());
+
+        let leuco_chunk = ItemCount::new(Id::from_name("leuco_chunk".to_string()), 1);
+        let recipe_id = Id::from_name("leuco_chunk_production".to_string());
+        research_progress.record_recipe(recipe_id, &[leuco_chunk.clone()]);
+        research_progress.record_recipe(recipe_id, &[leuco_chunk]);
+        assert_eq!(research_progress.recipes_completed(recipe_id), 2);
+
+        let newly_unlocked = research_progress.unlock_available(&manifest, |_| false);
+        assert_eq!(newly_unlocked.len(), 2);
+        assert!(research_progress.is_unlocked(Id::from_name("storage".to_string())));
+        assert!(research_progress.is_unlocked(Id::from_name("bridges".to_string())));
+        assert!(research_progress.structure_unlocked(large_storage, &manifest));
+        assert!(research_progress.terraforming_unlocked(swampy, &manifest));
+
+        // Technologies are only unlocked once
+        assert!(research_progress
+            .unlock_available(&manifest, |_| false)
+            .is_empty());
+    }
+
+    #[test]
+    fn domestication_unlocks_technologies() {
+        let manifest = test_manifest();
+        let mut research_progress = ResearchProgress::default();
+        let husbandry = Id::from_name("husbandry".to_string());
+
+        research_progress.unlock_available(&manifest, |_| false);
+        assert!(!research_progress.is_unlocked(husbandry));
+
+        research_progress.unlock_available(&manifest, |_| true);
+        assert!(research_progress.is_unlocked(husbandry));
+    }
+}
@@ -0,0 +1,214 @@
+//! Defines write-only data for each technology in the tech tree.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
+    construction::terraform::TerraformingTool,
+    crafting::recipe::Recipe,
+    items::item_manifest::Item,
+    organisms::{OrganismId, RawOrganismId},
+    structures::structure_manifest::Structure,
+};
+
+/// The marker type for [`Id<Technology>`](super::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct Technology;
+/// Stores the read-only definitions for all technologies.
+pub type TechnologyManifest = Manifest<Technology, TechnologyData>;
+
+impl TechnologyManifest {
+    /// Returns the technologies that unlock content matching the provided `predicate`.
+    pub(crate) fn unlocked_by(
+        &self,
+        predicate: impl Fn(&Unlocks) -> bool,
+    ) -> impl Iterator<Item = Id<Technology>> + '_ {
+        self.data_map()
+            .iter()
+            .filter(move |(_, data)| predicate(&data.unlocks))
+            .map(|(&technology_id, _)| technology_id)
+    }
+}
+
+/// Data stored in a [`TechnologyManifest`] for each [`Id<Technology>`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TechnologyData {
+    /// The conditions that must all be met before this technology is unlocked.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The content that becomes available once this technology is unlocked.
+    pub unlocks: Unlocks,
+}
+
+/// The unprocessed equivalent of [`TechnologyData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawTechnologyData {
+    /// The conditions that must all be met before this technology is unlocked.
+    pub prerequisites: Vec<RawPrerequisite>,
+    /// The content that becomes available once this technology is unlocked.
+    pub unlocks: RawUnlocks,
+}
+
+impl From<RawTechnologyData> for TechnologyData {
+    fn from(raw: RawTechnologyData) -> Self {
+        TechnologyData {
+            prerequisites: raw.prerequisites.into_iter().map(Into::into).collect(),
+            unlocks: raw.unlocks.into(),
+        }
+    }
+}
+
+/// A condition that must be met to unlock a technology.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prerequisite {
+    /// At least `count` of the provided item must have been produced by crafting.
+    ProduceItem {
+        /// The item that must be produced.
+        item_id: Id<Item>,
+        /// The number of items that must be produced.
+        count: u32,
+    },
+    /// The provided recipe must have been completed at least `count` times.
+    CompleteRecipe {
+        /// The recipe that must be crafted.
+        recipe_id: Id<Recipe>,
+        /// The number of times that the recipe must be crafted.
+        count: u32,
+    },
+    /// The species with the provided prototypical form must be domesticated.
+    DomesticateSpecies(OrganismId),
+    /// Another technology must be unlocked first.
+    Technology(Id<Technology>),
+}
+
+/// The unprocessed equivalent of [`Prerequisite`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawPrerequisite {
+    /// At least `count` of the provided item must have been produced by crafting.
+    ProduceItem {
+        /// The item that must be produced.
+        item: String,
+        /// The number of items that must be produced.
+        count: u32,
+    },
+    /// The provided recipe must have been completed at least `count` times.
+    CompleteRecipe {
+        /// The recipe that must be crafted.
+        recipe: String,
+        /// The number of times that the recipe must be crafted.
+        count: u32,
+    },
+    /// The species with the provided prototypical form must be domesticated.
+    DomesticateSpecies(RawOrganismId),
+    /// Another technology must be unlocked first.
+    Technology(String),
+}
+
+impl From<RawPrerequisite> for Prerequisite {
+    fn from(raw: RawPrerequisite) -> Self {
+        match raw {
+            RawPrerequisite::ProduceItem { item, count } => Prerequisite::ProduceItem {
+                item_id: Id::from_name(item),
+                count,
+            },
+            RawPrerequisite::CompleteRecipe { recipe, count } => Prerequisite::CompleteRecipe {
+                recipe_id: Id::from_name(recipe),
+                count,
+            },
+            RawPrerequisite::DomesticateSpecies(raw_organism_id) => {
+                Prerequisite::DomesticateSpecies(raw_organism_id.into())
+            }
+            RawPrerequisite::Technology(technology) => {
+                Prerequisite::Technology(Id::from_name(technology))
+            }
+        }
+    }
+}
+
+/// The content made available by a technology.
+///
+/// Content that is not unlocked by any technology is available from the start of the game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Unlocks {
+    /// The structures that can be built.
+    pub structures: Vec<Id<Structure>>,
+    /// The recipes that can be selected.
+    pub recipes: Vec<Id<Recipe>>,
+    /// The terraforming tools that can be used.
+    pub(crate) terraforming: Vec<TerraformingTool>,
+}
+
+/// The unprocessed equivalent of [`Unlocks`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RawUnlocks {
+    /// The structures that can be built.
+    pub structures: Vec<String>,
+    /// The recipes that can be selected.
+    pub recipes: Vec<String>,
+    /// The terraforming tools that can be used.
+    pub terraforming: Vec<RawTerraformingTool>,
+}
+
+impl From<RawUnlocks> for Unlocks {
+    fn from(raw: RawUnlocks) -> Self {
+        Unlocks {
+            structures: raw.structures.into_iter().map(Id::from_name).collect(),
+            recipes: raw.recipes.into_iter().map(Id::from_name).collect(),
+            terraforming: raw.terraforming.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// The unprocessed equivalent of [`TerraformingTool`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawTerraformingTool {
+    /// Raise the height of a tile.
+    Raise,
+    /// Lower the height of a tile.
+    Lower,
+    /// Replace the existing soil with the named terrain type.
+    Change(String),
+}
+
+impl From<RawTerraformingTool> for TerraformingTool {
+    fn from(raw: RawTerraformingTool) -> Self {
+        match raw {
+            RawTerraformingTool::Raise => TerraformingTool::Raise,
+            RawTerraformingTool::Lower => TerraformingTool::Lower,
+            RawTerraformingTool::Change(terrain) => {
+                TerraformingTool::Change(Id::from_name(terrain))
+            }
+        }
+    }
+}
+
+/// The [`TechnologyManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "4f6a3d2e-5b1c-4e8f-9a7d-2c3b1e0f6d58"]
+pub struct RawTechnologyManifest {
+    /// The data for each technology.
+    pub technologies: HashMap<String, RawTechnologyData>,
+}
+
+impl IsRawManifest for RawTechnologyManifest {
+    const EXTENSION: &'static str = "technology_manifest.json";
+
+    type Marker = Technology;
+    type Data = TechnologyData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.technologies.clone() {
+            let data = raw_data.into();
+
+            manifest.insert(raw_id, data)
+        }
+
+        manifest
+    }
+}
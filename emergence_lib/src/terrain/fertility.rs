@@ -0,0 +1,276 @@
+//! Soil fertility tracks the nutrients available to living structures on each tile.
+//!
+//! Living structures draw nutrients out of the soil as they craft,
+//! while composting, the remains of dead organisms and decaying litter return them.
+//! Nutrients slowly diffuse between neighboring tiles.
+
+use bevy::{prelude::*, utils::HashMap};
+use core::fmt::Display;
+use derive_more::{Add, AddAssign, Sub, SubAssign};
+use hexx::Hex;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crafting::{
+        item_tags::ItemTag,
+        recipe::{RecipeData, RecipeInput},
+    },
+    geometry::{MapGeometry, VoxelPos},
+    items::{item_manifest::ItemManifest, ItemCount},
+    litter::Litter,
+    organisms::death::OrganismDied,
+    simulation::time::InGameTime,
+};
+
+/// The nutrients available in the soil of a single tile.
+///
+/// This ranges from 0 (completely barren) to 1 (as fertile as soil can be).
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Add,
+    Sub,
+    AddAssign,
+    SubAssign,
+    Serialize,
+    Deserialize,
+)]
+pub struct Fertility(pub f32);
+
+impl Fertility {
+    /// Soil with no nutrients at all.
+    pub const ZERO: Fertility = Fertility(0.);
+
+    /// The most fertile soil possible.
+    pub const MAX: Fertility = Fertility(1.);
+
+    /// The fertility drawn from the soil for each item produced by a living structure.
+    pub const CONSUMED_PER_ITEM: f32 = 0.002;
+
+    /// The fertility returned to the soil for each item that is composted or decays.
+    pub const RETURNED_PER_ITEM: f32 = 0.01;
+
+    /// The fertility returned to the soil when an organism dies.
+    ///
+    /// This is in addition to the nutrients returned as its remains decay.
+    pub const RETURNED_PER_DEATH: f32 = 0.05;
+
+    /// Changes the fertility by `delta`, keeping it between [`Fertility::ZERO`] and [`Fertility::MAX`].
+    pub(crate) fn change(&mut self, delta: f32) {
+        self.0 = (self.0 + delta).clamp(Self::ZERO.0, Self::MAX.0);
+    }
+
+    /// Updates the fertility after a living structure on this tile crafts `recipe`, producing the `produced` items.
+    ///
+    /// Composting recipes return nutrients to the soil, while all other recipes draw nutrients from it.
+    pub(crate) fn record_recipe(&mut self, recipe: &RecipeData, produced: &[ItemCount]) {
+        match recipe.inputs {
+            RecipeInput::Flexible {
+                tag: ItemTag::Compostable,
+                count,
+            } => self.change(count as f32 * Self::RETURNED_PER_ITEM),
+            _ => {
+                let n_produced: u32 = produced.iter().map(|item_count| item_count.count).sum();
+                self.change(-(n_produced as f32) * Self::CONSUMED_PER_ITEM);
+            }
+        }
+    }
+}
+
+impl Default for Fertility {
+    fn default() -> Self {
+        Fertility(0.5)
+    }
+}
+
+impl Display for Fertility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0}%", self.0 * 100.)
+    }
+}
+
+/// The fraction of the difference in fertility between two neighboring tiles that is equalized each day.
+const FERTILITY_DIFFUSION_RATE: f32 = 0.5;
+
+/// The average number of days that it takes for a compostable item lying on the ground to decay.
+const LITTER_DECAY_DAYS: f32 = 2.0;
+
+/// The amount of fertility that should move from `source` to `target` when a `transfer_ratio` of the difference between them diffuses.
+///
+/// Nutrients only ever flow downhill, so this is zero if `target` is at least as fertile as `source`.
+fn fertility_flow(source: Fertility, target: Fertility, transfer_ratio: f32) -> f32 {
+    (source.0 - target.0).max(0.) * transfer_ratio
+}
+
+/// Slowly spreads nutrients from fertile tiles to their less fertile neighbors.
+pub(super) fn diffuse_fertility(
+    mut terrain_query: Query<(&VoxelPos, &mut Fertility)>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+) {
+    // Each tile has six neighbors: cap the transfer to avoid overshooting and oscillating
+    let transfer_ratio = (FERTILITY_DIFFUSION_RATE / in_game_time.seconds_per_day()
+        * time.delta().as_secs_f32())
+    .min(1. / 12.);
+
+    let current: HashMap<Hex, Fertility> = terrain_query
+        .iter()
+        .map(|(voxel_pos, &fertility)| (voxel_pos.hex, fertility))
+        .collect();
+
+    // PERF: it will probably be much faster to store scratch space on components
+    let mut net_change = HashMap::<Hex, f32>::default();
+
+    for (&hex, &fertility) in current.iter() {
+        for maybe_neighbor in map_geometry.adjacent_hexes(hex) {
+            let Some(neighbor) = maybe_neighbor else {
+                continue;
+            };
+
+            let Some(&neighbor_fertility) = current.get(&neighbor) else {
+                continue;
+            };
+
+            let flow = fertility_flow(fertility, neighbor_fertility, transfer_ratio);
+            if flow > 0. {
+                *net_change.entry(hex).or_default() -= flow;
+                *net_change.entry(neighbor).or_default() += flow;
+            }
+        }
+    }
+
+    for (voxel_pos, mut fertility) in terrain_query.iter_mut() {
+        if let Some(&delta) = net_change.get(&voxel_pos.hex) {
+            fertility.change(delta);
+        }
+    }
+}
+
+/// Compostable litter slowly decays, returning its nutrients to the soil beneath it.
+pub(super) fn decay_litter(
+    mut litter_query: Query<(&mut Litter, &VoxelPos)>,
+    mut fertility_query: Query<&mut Fertility>,
+    item_manifest: Res<ItemManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+    in_game_time: Res<InGameTime>,
+) {
+    let rng = &mut thread_rng();
+    let delta_days = time.delta().as_secs_f32() / in_game_time.seconds_per_day();
+    let decay_chance = (delta_days / LITTER_DECAY_DAYS).min(1.);
+
+    for (mut litter, voxel_pos) in litter_query.iter_mut() {
+        let Some(item_id) = litter
+            .contents
+            .iter()
+            .map(|item_slot| item_slot.item_id())
+            .find(|&item_id| item_manifest.has_tag(item_id, ItemTag::Compostable))
+        else {
+            continue;
+        };
+
+        if !rng.gen_bool(decay_chance as f64) {
+            continue;
+        }
+
+        if litter
+            .contents
+            .try_remove_item(&ItemCount::new(item_id, 1))
+            .is_err()
+        {
+            continue;
+        }
+
+        let Ok(terrain_entity) = map_geometry.get_terrain(voxel_pos.hex) else {
+            continue;
+        };
+
+        if let Ok(mut fertility) = fertility_query.get_mut(terrain_entity) {
+            fertility.change(Fertility::RETURNED_PER_ITEM);
+        }
+    }
+}
+
+/// Organisms return nutrients to the soil where they die.
+///
+/// Deaths are sent by commands, so this must run in the [`SimulationEventSet`](crate::simulation::SimulationEventSet).
+pub(super) fn return_nutrients_on_death(
+    mut death_events: EventReader<OrganismDied>,
+    mut fertility_query: Query<&mut Fertility>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for death in death_events.read() {
+        let Ok(terrain_entity) = map_geometry.get_terrain(death.voxel_pos.hex) else {
+            continue;
+        };
+
+        if let Ok(mut fertility) = fertility_query.get_mut(terrain_entity) {
+            fertility.change(Fertility::RETURNED_PER_DEATH);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id,
+        crafting::recipe::{RecipeConditions, RecipeOutput},
+    };
+    use std::time::Duration;
+
+    /// A recipe with the provided `inputs` and no outputs.
+    fn test_recipe(inputs: RecipeInput) -> RecipeData {
+        RecipeData {
+            inputs,
+            outputs: RecipeOutput::EMPTY,
+            craft_time: Duration::from_secs(1),
+            conditions: RecipeConditions::NONE,
+            energy: None,
+        }
+    }
+
+    #[test]
+    fn fertility_is_clamped() {
+        let mut fertility = Fertility(0.5);
+
+        fertility.change(2.);
+        assert_eq!(fertility, Fertility::MAX);
+
+        fertility.change(-3.);
+        assert_eq!(fertility, Fertility::ZERO);
+    }
+
+    #[test]
+    fn composting_replenishes_fertility() {
+        let leaf = ItemCount::new(Id::from_name("acacia_leaf".to_string()), 10);
+
+        let mut fertility = Fertility(0.5);
+        fertility.record_recipe(&test_recipe(RecipeInput::EMPTY), &[leaf.clone()]);
+        assert!(fertility < Fertility(0.5));
+
+        let compost = RecipeInput::Flexible {
+            tag: ItemTag::Compostable,
+            count: 1,
+        };
+        let mut fertility = Fertility(0.5);
+        fertility.record_recipe(&test_recipe(compost), &[leaf]);
+        assert!(fertility > Fertility(0.5));
+    }
+
+    #[test]
+    fn fertility_flows_downhill() {
+        let rich = Fertility(0.8);
+        let poor = Fertility(0.2);
+
+        assert!(fertility_flow(rich, poor, 0.1) > 0.);
+        assert_eq!(fertility_flow(poor, rich, 0.1), 0.);
+        assert_eq!(fertility_flow(rich, rich, 0.1), 0.);
+    }
+}
@@ -13,9 +13,10 @@ use crate::light::shade::{ReceivedLight, Shade};
 use crate::player_interaction::picking::PickableVoxel;
 use crate::player_interaction::selection::ObjectInteraction;
 use crate::signals::Emitter;
-use crate::simulation::SimulationSet;
+use crate::simulation::{SimulationEventSet, SimulationSet};
 use crate::water::{WaterBundle, WaterSet};
 
+use self::fertility::{decay_litter, diffuse_fertility, return_nutrients_on_death, Fertility};
 use self::terrain_assets::TerrainHandles;
 use self::terrain_manifest::{RawTerrainManifest, Terrain, TerrainManifest};
 use crate::litter::{
@@ -23,6 +24,7 @@ use crate::litter::{
     LitterEmitters,
 };
 
+pub mod fertility;
 pub(crate) mod terrain_assets;
 pub mod terrain_manifest;
 
@@ -49,8 +51,14 @@ impl Plugin for TerrainPlugin {
                     set_litter_emitters
                         .after(carry_floating_litter_with_current)
                         .in_set(LitterEmitters),
+                    diffuse_fertility,
+                    decay_litter,
                 )
                     .in_set(SimulationSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                return_nutrients_on_death.in_set(SimulationEventSet),
             );
     }
 }
@@ -78,6 +86,8 @@ pub(crate) struct TerrainBundle {
     received_light: ReceivedLight,
     /// The components used to track the water table at this tile.
     water_bundle: WaterBundle,
+    /// The nutrients available in the soil of this tile.
+    fertility: Fertility,
     /// Any inputs needed to terraform this tile.
     input_inventory: InputInventory,
     /// Any outputs produced by terraforming this tile.
@@ -120,6 +130,7 @@ impl TerrainBundle {
                 soil_water_flow_rate: terrain_data.soil_water_flow_rate,
                 ..Default::default()
             },
+            fertility: terrain_data.soil_fertility,
             input_inventory: InputInventory::NULL,
             output_inventory: OutputInventory::NULL,
             terraforming_action: TerraformingAction::None,
@@ -139,6 +150,7 @@ impl TerrainBundle {
             shade: Shade::default(),
             received_light: ReceivedLight::default(),
             water_bundle: WaterBundle::default(),
+            fertility: Fertility::default(),
             input_inventory: InputInventory::NULL,
             output_inventory: OutputInventory::NULL,
             terraforming_action: TerraformingAction::None,
@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::{loader::IsRawManifest, Manifest},
+    terrain::fertility::Fertility,
     water::{
         water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate},
         SoilWaterCapacity,
@@ -47,6 +48,10 @@ pub struct TerrainData {
     /// This is relative to empty space, which has an evaporation rate of 1.0.
     /// Generally this value should be between 0.05 and 0.5.
     pub soil_water_evaporation_rate: SoilWaterEvaporationRate,
+    /// The fertility of this terrain type when the world is generated.
+    ///
+    /// This should be between 0 and 1.
+    pub soil_fertility: Fertility,
 }
 
 impl Default for TerrainData {
@@ -56,6 +61,7 @@ impl Default for TerrainData {
             soil_water_capacity: SoilWaterCapacity::default(),
             soil_water_flow_rate: SoilWaterFlowRate::default(),
             soil_water_evaporation_rate: SoilWaterEvaporationRate::default(),
+            soil_fertility: Fertility::default(),
         }
     }
 }
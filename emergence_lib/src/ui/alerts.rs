@@ -0,0 +1,206 @@
+//! Shows the [`AlertFeed`], and lets the player jump to alerts or mute them.
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_management::manifest::Id,
+    enum_iter::IterableEnum,
+    player_interaction::{camera::FocusOnSelection, selection::CurrentSelection},
+    simulation::alerts::{AlertFeed, AlertKind, AlertSettings},
+    units::unit_manifest::Unit,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the [`AlertFeed`] and the [`AlertSettings`].
+pub(super) struct AlertFeedPlugin;
+
+impl Plugin for AlertFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_alert_panel).add_systems(
+            Update,
+            (jump_to_alert, toggle_alert_mute, update_alert_panel)
+                .chain()
+                .run_if(in_state(WorldGenState::Complete)),
+        );
+    }
+}
+
+/// The number of alert groups to list.
+const ALERTS_SHOWN: usize = 8;
+
+/// The color of buttons that are not being interacted with.
+const BUTTON_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+
+/// The color of buttons that are being hovered over.
+const HOVERED_BUTTON_COLOR: Color = Color::rgba(0.3, 0.3, 0.3, 0.7);
+
+/// A button that displays the group of alerts at this index in the [`AlertFeed`].
+#[derive(Component, Debug)]
+struct AlertEntry(usize);
+
+/// A button that mutes or unmutes this kind of alert.
+#[derive(Component, Debug)]
+struct MuteToggle(AlertKind);
+
+/// Initializes the alert panel.
+fn spawn_alert_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let button_bundle = ButtonBundle {
+        style: Style {
+            padding: UiRect::all(Val::Px(2.)),
+            margin: UiRect::all(Val::Px(1.)),
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    let alert_panel_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Alerts:", style.clone()));
+
+            for index in 0..ALERTS_SHOWN {
+                parent
+                    .spawn((button_bundle.clone(), AlertEntry(index)))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section("", style.clone()));
+                    });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    for kind in AlertKind::variants() {
+                        row.spawn((button_bundle.clone(), MuteToggle(kind)))
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section("", style.clone()));
+                            });
+                    }
+                });
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(alert_panel_entity);
+}
+
+/// Selects the subject of an alert when it is clicked, and moves the camera to it.
+fn jump_to_alert(
+    entry_query: Query<(&Interaction, &AlertEntry), Changed<Interaction>>,
+    alert_feed: Res<AlertFeed>,
+    unit_query: Query<(), With<Id<Unit>>>,
+    mut current_selection: ResMut<CurrentSelection>,
+    mut focus_events: EventWriter<FocusOnSelection>,
+) {
+    for (interaction, entry) in entry_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(group) = alert_feed.get(entry.0) else {
+            continue;
+        };
+
+        // Units move around, so follow them if they're still alive
+        if unit_query.contains(group.entity) {
+            *current_selection = CurrentSelection::Unit(group.entity);
+        } else {
+            current_selection.select_voxels([group.voxel_pos], false);
+        }
+
+        focus_events.send(FocusOnSelection);
+    }
+}
+
+/// Mutes or unmutes a kind of alert when its button is clicked.
+fn toggle_alert_mute(
+    toggle_query: Query<(&Interaction, &MuteToggle), Changed<Interaction>>,
+    mut alert_settings: ResMut<AlertSettings>,
+) {
+    for (interaction, toggle) in toggle_query.iter() {
+        if *interaction == Interaction::Pressed {
+            alert_settings.toggle_mute(toggle.0);
+        }
+    }
+}
+
+/// Shows the most recent alerts, and which kinds of alerts are muted.
+fn update_alert_panel(
+    mut entry_query: Query<
+        (
+            &AlertEntry,
+            &Interaction,
+            &Children,
+            &mut Style,
+            &mut BackgroundColor,
+        ),
+        Without<MuteToggle>,
+    >,
+    mut toggle_query: Query<(&MuteToggle, &Interaction, &Children, &mut BackgroundColor)>,
+    mut text_query: Query<&mut Text>,
+    alert_feed: Res<AlertFeed>,
+    alert_settings: Res<AlertSettings>,
+) {
+    for (entry, interaction, children, mut style, mut background_color) in entry_query.iter_mut() {
+        *background_color = button_color(interaction).into();
+
+        let Some(group) = alert_feed.get(entry.0) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+
+        if let Ok(mut text) = text_query.get_mut(children[0]) {
+            text.sections[0].value = match group.count {
+                1 => format!("{}", group.kind),
+                count => format!("{} (x{count})", group.kind),
+            };
+        }
+    }
+
+    for (toggle, interaction, children, mut background_color) in toggle_query.iter_mut() {
+        *background_color = button_color(interaction).into();
+
+        if let Ok(mut text) = text_query.get_mut(children[0]) {
+            let state = match alert_settings.is_muted(toggle.0) {
+                true => "muted",
+                false => "on",
+            };
+            text.sections[0].value = format!("{}: {state}", toggle.0);
+        }
+    }
+}
+
+/// The background color of a button in the alert panel.
+fn button_color(interaction: &Interaction) -> Color {
+    match interaction {
+        Interaction::None => BUTTON_COLOR,
+        Interaction::Hovered | Interaction::Pressed => HOVERED_BUTTON_COLOR,
+    }
+}
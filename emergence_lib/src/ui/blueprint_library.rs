@@ -0,0 +1,166 @@
+//! Displays the blueprints that the player has saved, highlighting the one currently selected.
+//!
+//! The selected blueprint can be renamed by typing a new name after [`PlayerAction::RenameBlueprint`] is pressed.
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::{ActionState, ToggleActions};
+
+use crate::player_interaction::{blueprints::BlueprintLibrary, PlayerAction};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// The plugin that adds the blueprint library to the UI.
+pub(super) struct BlueprintLibraryPlugin;
+
+impl Plugin for BlueprintLibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlueprintNamePrompt>()
+            .add_systems(Startup, setup_blueprint_library)
+            .add_systems(Update, (rename_blueprint, update_blueprint_library).chain());
+    }
+}
+
+/// Marker component for the text that lists the available blueprints.
+#[derive(Component)]
+struct BlueprintLibraryText;
+
+/// The name being typed for the selected blueprint.
+#[derive(Resource, Debug, Default)]
+struct BlueprintNamePrompt {
+    /// The original name of the blueprint being renamed, and the new name typed so far.
+    ///
+    /// This is `None` when no blueprint is being renamed.
+    editing: Option<(String, String)>,
+    /// Feedback about the last rename, such as why it failed.
+    message: String,
+}
+
+impl BlueprintNamePrompt {
+    /// The longest name that can be typed.
+    const MAX_LENGTH: usize = 32;
+}
+
+/// Creates the UI needed to display the blueprint library.
+fn setup_blueprint_library(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+) {
+    let left_panel_entity = left_panel_query.single();
+
+    let text_entity = commands
+        .spawn((TextBundle::default(), BlueprintLibraryText))
+        .id();
+    commands.entity(left_panel_entity).add_child(text_entity);
+}
+
+/// Lets the player type a new name for the selected blueprint.
+///
+/// Enter confirms the new name, and Escape (or East on a gamepad) cancels.
+/// All player actions are disabled while typing, and are only re-enabled once the confirming or cancelling input is released,
+/// so that it can't trigger an action of its own.
+fn rename_blueprint(
+    actions: Res<ActionState<PlayerAction>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut prompt: ResMut<BlueprintNamePrompt>,
+    mut blueprint_library: ResMut<BlueprintLibrary>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    let Some((original_name, mut typed_name)) = prompt.editing.clone() else {
+        if actions.just_pressed(PlayerAction::RenameBlueprint) {
+            if let Some(selected) = blueprint_library.selected() {
+                prompt.editing = Some((selected.to_string(), selected.to_string()));
+                prompt.message.clear();
+                toggle_actions.enabled = false;
+            }
+        }
+
+        // Don't type the key that opened the prompt
+        characters.clear();
+        return;
+    };
+
+    let cancelled = keyboard_input.just_released(KeyCode::Escape)
+        || gamepad_input
+            .get_just_released()
+            .any(|button| button.button_type == GamepadButtonType::East);
+
+    if cancelled {
+        prompt.editing = None;
+        toggle_actions.enabled = true;
+        return;
+    }
+
+    if keyboard_input.just_released(KeyCode::Return) {
+        prompt.message = match blueprint_library.rename(&original_name, &typed_name) {
+            Ok(()) => String::new(),
+            Err(error) => error.to_string(),
+        };
+        prompt.editing = None;
+        toggle_actions.enabled = true;
+        return;
+    }
+
+    for character in characters.read() {
+        if !character.char.is_control() && typed_name.len() < BlueprintNamePrompt::MAX_LENGTH {
+            typed_name.push(character.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        typed_name.pop();
+    }
+
+    if prompt.editing.as_ref().map(|(_, name)| name) != Some(&typed_name) {
+        prompt.editing = Some((original_name, typed_name));
+    }
+}
+
+/// Lists the names of all saved blueprints, highlighting the selected one.
+fn update_blueprint_library(
+    mut text_query: Query<&mut Text, With<BlueprintLibraryText>>,
+    blueprint_library: Res<BlueprintLibrary>,
+    prompt: Res<BlueprintNamePrompt>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    if !blueprint_library.is_changed() && !prompt.is_changed() {
+        return;
+    }
+
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let font_size = 20.0;
+    let section = |value: String, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            font: fonts.regular.clone_weak(),
+            font_size,
+            color,
+        },
+    };
+
+    let heading = section("Blueprints\n".to_string(), Color::WHITE);
+
+    let entries = blueprint_library.names().map(|name| match &prompt.editing {
+        Some((original_name, typed_name)) if original_name == name => {
+            section(format!("{typed_name}_\n"), Color::ORANGE)
+        }
+        _ => {
+            let color = match blueprint_library.selected() == Some(name) {
+                true => Color::YELLOW,
+                false => Color::GRAY,
+            };
+            section(format!("{name}\n"), color)
+        }
+    });
+
+    let message = section(prompt.message.clone(), Color::ORANGE_RED);
+
+    text.sections = std::iter::once(heading)
+        .chain(entries)
+        .chain(std::iter::once(message))
+        .collect();
+}
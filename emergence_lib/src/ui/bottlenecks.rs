@@ -0,0 +1,83 @@
+//! Lists the crafting structures whose production is most often blocked.
+
+use bevy::prelude::*;
+
+use crate::{
+    crafting::diagnostics::Bottlenecks, structures::structure_manifest::StructureManifest,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, LeftPanel};
+
+/// Displays the worst [`Bottlenecks`] in the production chain.
+pub(super) struct BottleneckPanelPlugin;
+
+impl Plugin for BottleneckPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_bottleneck_panel)
+            .add_systems(
+                Update,
+                update_bottleneck_panel.run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Marker component for the bottleneck panel.
+#[derive(Component)]
+struct BottleneckPanel;
+
+/// The number of bottlenecks to list.
+const BOTTLENECKS_SHOWN: usize = 5;
+
+/// Initializes the bottleneck panel.
+fn spawn_bottleneck_panel(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let bottleneck_panel_entity = commands
+        .spawn(TextBundle::from_section("", style))
+        .insert(BottleneckPanel)
+        .id();
+
+    let left_panel_entity = left_panel_query.single();
+    commands
+        .entity(left_panel_entity)
+        .add_child(bottleneck_panel_entity);
+}
+
+/// Lists the worst bottlenecks, along with why they are blocked.
+fn update_bottleneck_panel(
+    mut query: Query<&mut Text, With<BottleneckPanel>>,
+    bottlenecks: Res<Bottlenecks>,
+    structure_manifest: Res<StructureManifest>,
+) {
+    if !bottlenecks.is_changed() {
+        return;
+    }
+
+    let mut text = query.single_mut();
+    let mut string = "Bottlenecks:".to_string();
+
+    if bottlenecks.is_empty() {
+        string += "\nNone";
+    }
+
+    for (rank, bottleneck) in bottlenecks.iter().take(BOTTLENECKS_SHOWN).enumerate() {
+        string += &format!(
+            "\n{}. {}: {} ({:.0}% blocked)",
+            rank + 1,
+            structure_manifest.name(bottleneck.structure_id),
+            bottleneck.reason,
+            bottleneck.blocked_fraction * 100.
+        );
+    }
+
+    text.sections[0].value = string;
+}
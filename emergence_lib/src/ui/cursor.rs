@@ -36,7 +36,7 @@ fn set_cursor(
                 // Use the matching icon for the terraforming tool
                 Tool::Terraform(terraforming_tool) => terraforming_icons.get(terraforming_tool),
                 // Ghosts are used instead for structures
-                Tool::Structures(_) => Handle::default(),
+                Tool::Structures(_) | Tool::Blueprint(_) => Handle::default(),
                 // No need to show a custom cursor if we have nothing selected
                 Tool::None => Handle::default(),
             }
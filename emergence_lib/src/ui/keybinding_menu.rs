@@ -0,0 +1,444 @@
+//! A menu for changing keybindings, which are saved to disk as soon as they are changed.
+//!
+//! The menu is navigated using raw inputs rather than [`PlayerAction`]s,
+//! so that it keeps working no matter how the controls have been rebound.
+
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::{DualAxis, VirtualDPad},
+    prelude::{ActionState, ToggleActions},
+    user_input::{InputKind, Modifier, UserInput},
+};
+
+use crate::{
+    graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    player_interaction::{
+        keybindings::{describe_input, is_directional, InputDevice, Keybindings},
+        PlayerAction,
+    },
+};
+
+use super::FiraSansFontFamily;
+
+/// The plugin that adds the keybinding menu to the UI.
+pub(super) struct KeybindingMenuPlugin;
+
+impl Plugin for KeybindingMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeybindingMenu>()
+            .add_systems(Startup, setup_keybinding_menu)
+            .add_systems(
+                Update,
+                (
+                    toggle_keybinding_menu,
+                    capture_rebinding,
+                    navigate_keybinding_menu,
+                    update_keybinding_menu,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The state of the keybinding menu.
+#[derive(Resource, Debug, Default)]
+struct KeybindingMenu {
+    /// Is the menu currently shown?
+    open: bool,
+    /// The index of the selected action.
+    selected: usize,
+    /// The device whose bindings are being edited.
+    device: InputDevice,
+    /// The inputs captured so far for the binding being changed, if the menu is waiting for input.
+    pending: Option<Vec<InputKind>>,
+    /// Feedback about the last change, shown at the bottom of the menu.
+    message: String,
+}
+
+impl KeybindingMenu {
+    /// The number of actions listed at once.
+    const VISIBLE_ROWS: usize = 20;
+}
+
+/// Marker component for the root node of the keybinding menu.
+#[derive(Component)]
+struct KeybindingMenuRoot;
+
+/// Marker component for the text that lists the keybindings.
+#[derive(Component)]
+struct KeybindingMenuText;
+
+/// Creates the (initially hidden) keybinding menu.
+fn setup_keybinding_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(20.),
+                    top: Val::Percent(10.),
+                    width: Val::Percent(60.),
+                    padding: UiRect::all(Val::Px(10.)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0., 0., 0., 0.9)),
+                // Render above the rest of the UI
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            KeybindingMenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((TextBundle::default(), KeybindingMenuText));
+        });
+}
+
+/// Was the provided gamepad `button` just pressed on any gamepad?
+fn gamepad_just_pressed(gamepad_input: &Input<GamepadButton>, button: GamepadButtonType) -> bool {
+    gamepad_input
+        .get_just_pressed()
+        .any(|gamepad_button| gamepad_button.button_type == button)
+}
+
+/// Was the provided gamepad `button` just released on any gamepad?
+fn gamepad_just_released(gamepad_input: &Input<GamepadButton>, button: GamepadButtonType) -> bool {
+    gamepad_input
+        .get_just_released()
+        .any(|gamepad_button| gamepad_button.button_type == button)
+}
+
+/// Opens the menu using [`PlayerAction::ToggleKeybindingMenu`], and closes it with Escape or Start.
+///
+/// All other player actions are disabled while the menu is open.
+/// The menu is only opened or closed once the button is released,
+/// so that the press that closes the menu can't reopen it once player actions are enabled again.
+fn toggle_keybinding_menu(
+    actions: Res<ActionState<PlayerAction>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut menu: ResMut<KeybindingMenu>,
+    mut toggle_actions: ResMut<ToggleActions<PlayerAction>>,
+) {
+    if menu.open {
+        let close_released = keyboard_input.just_released(KeyCode::Escape)
+            || gamepad_just_released(&gamepad_input, GamepadButtonType::Start);
+
+        // Escape and Start cancel the rebinding instead
+        if close_released && menu.pending.is_none() {
+            menu.open = false;
+            menu.message.clear();
+            toggle_actions.enabled = true;
+        }
+    } else if actions.just_released(PlayerAction::ToggleKeybindingMenu) {
+        menu.open = true;
+        toggle_actions.enabled = false;
+    }
+}
+
+/// The modifier corresponding to `key_code`, if it is a modifier key.
+fn modifier(key_code: KeyCode) -> Option<Modifier> {
+    match key_code {
+        KeyCode::AltLeft | KeyCode::AltRight => Some(Modifier::Alt),
+        KeyCode::ControlLeft | KeyCode::ControlRight => Some(Modifier::Control),
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => Some(Modifier::Shift),
+        KeyCode::SuperLeft | KeyCode::SuperRight => Some(Modifier::Win),
+        _ => None,
+    }
+}
+
+/// Reads the keyboard and mouse inputs pressed this frame, returning the new binding once it is complete.
+///
+/// Directional bindings need four keys, which are stored in `captured` until they have all been pressed.
+fn capture_keyboard_and_mouse(
+    keyboard_input: &Input<KeyCode>,
+    mouse_input: &Input<MouseButton>,
+    directional: bool,
+    captured: &mut Vec<InputKind>,
+) -> Option<UserInput> {
+    let held_modifier = keyboard_input.get_pressed().find_map(|&key| modifier(key));
+    let pressed = keyboard_input
+        .get_just_pressed()
+        .find(|&&key| modifier(key).is_none())
+        .map(|&key| InputKind::Keyboard(key))
+        .or_else(|| {
+            mouse_input
+                .get_just_pressed()
+                .next()
+                .map(|&button| InputKind::Mouse(button))
+        });
+
+    if directional {
+        captured.extend(pressed);
+        let [up, down, left, right] = <[InputKind; 4]>::try_from(captured.clone()).ok()?;
+        return Some(UserInput::VirtualDPad(VirtualDPad {
+            up,
+            down,
+            left,
+            right,
+        }));
+    }
+
+    match (pressed, held_modifier) {
+        (Some(input_kind), Some(modifier)) => Some(UserInput::modified(modifier, input_kind)),
+        (Some(input_kind), None) => Some(UserInput::Single(input_kind)),
+        // Modifiers can be bound on their own by pressing and releasing them
+        (None, _) => keyboard_input
+            .get_just_released()
+            .find_map(|&key| modifier(key))
+            .map(|modifier| UserInput::Single(InputKind::Modifier(modifier))),
+    }
+}
+
+/// Reads the gamepad buttons pressed this frame, returning the new binding if any.
+///
+/// Buttons that are already held when another is pressed form a chord with it.
+fn capture_gamepad(gamepad_input: &Input<GamepadButton>, directional: bool) -> Option<UserInput> {
+    let pressed = gamepad_input.get_just_pressed().next()?;
+
+    if directional {
+        // Joysticks are chosen by clicking them in
+        return match pressed.button_type {
+            GamepadButtonType::LeftThumb => Some(DualAxis::left_stick().into()),
+            GamepadButtonType::RightThumb => Some(DualAxis::right_stick().into()),
+            _ => None,
+        };
+    }
+
+    let held: Vec<InputKind> = gamepad_input
+        .get_pressed()
+        .filter(|button| button.gamepad == pressed.gamepad && *button != pressed)
+        .map(|button| InputKind::GamepadButton(button.button_type))
+        .collect();
+
+    match held.is_empty() {
+        true => Some(pressed.button_type.into()),
+        false => Some(UserInput::chord(
+            held.into_iter()
+                .chain([InputKind::GamepadButton(pressed.button_type)]),
+        )),
+    }
+}
+
+/// Binds the next input pressed to the selected action, while the menu is waiting for input.
+fn capture_rebinding(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut menu: ResMut<KeybindingMenu>,
+    mut keybindings: ResMut<Keybindings>,
+) {
+    let Some(mut captured) = menu.pending.clone() else {
+        return;
+    };
+
+    if keyboard_input.just_released(KeyCode::Escape)
+        || gamepad_just_released(&gamepad_input, GamepadButtonType::Start)
+    {
+        menu.pending = None;
+        menu.message = "Rebinding cancelled".to_string();
+        return;
+    }
+
+    // Escape and Start are reserved for cancelling, which happens once they are released
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepad_just_pressed(&gamepad_input, GamepadButtonType::Start)
+    {
+        return;
+    }
+
+    let Some((action, _)) = keybindings.iter().nth(menu.selected) else {
+        menu.pending = None;
+        return;
+    };
+    let action = action.clone();
+    let device = menu.device;
+    let directional = is_directional(&action, device);
+
+    let new_input = match device {
+        InputDevice::KeyboardAndMouse => {
+            capture_keyboard_and_mouse(&keyboard_input, &mouse_input, directional, &mut captured)
+        }
+        InputDevice::Gamepad => capture_gamepad(&gamepad_input, directional),
+    };
+
+    let Some(new_input) = new_input else {
+        if menu.pending.as_ref() != Some(&captured) {
+            menu.pending = Some(captured);
+        }
+        return;
+    };
+
+    let description = describe_input(&new_input);
+    let displaced = keybindings.rebind(&action, device, new_input);
+
+    menu.pending = None;
+    menu.message = match displaced.is_empty() {
+        true => format!("Bound {action:?} to {description}"),
+        false => format!("Bound {action:?} to {description}, unbinding {displaced:?}"),
+    };
+
+    if let Err(error) = keybindings.save() {
+        warn!("Could not save keybindings: {error}");
+    }
+}
+
+/// Moves through the list of actions, and starts or resets rebinding the selected one.
+fn navigate_keybinding_menu(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut menu: ResMut<KeybindingMenu>,
+    mut keybindings: ResMut<Keybindings>,
+) {
+    if !menu.open || menu.pending.is_some() {
+        return;
+    }
+
+    let just_pressed = |key: KeyCode, button: GamepadButtonType| {
+        keyboard_input.just_pressed(key) || gamepad_just_pressed(&gamepad_input, button)
+    };
+
+    let n_actions = keybindings.iter().len();
+
+    if just_pressed(KeyCode::Down, GamepadButtonType::DPadDown) {
+        menu.selected = (menu.selected + 1) % n_actions;
+    }
+
+    if just_pressed(KeyCode::Up, GamepadButtonType::DPadUp) {
+        menu.selected = (menu.selected + n_actions - 1) % n_actions;
+    }
+
+    if just_pressed(KeyCode::Tab, GamepadButtonType::West) {
+        menu.device = menu.device.other();
+    }
+
+    if just_pressed(KeyCode::Return, GamepadButtonType::South) {
+        menu.pending = Some(Vec::new());
+        menu.message.clear();
+    }
+
+    if just_pressed(KeyCode::Back, GamepadButtonType::North) {
+        let Some((action, _)) = keybindings.iter().nth(menu.selected) else {
+            return;
+        };
+        let action = action.clone();
+        let displaced = keybindings.reset(&action);
+
+        menu.message = match displaced.is_empty() {
+            true => format!("Reset {action:?} to its default bindings"),
+            false => format!("Reset {action:?} to its default bindings, unbinding {displaced:?}"),
+        };
+
+        if let Err(error) = keybindings.save() {
+            warn!("Could not save keybindings: {error}");
+        }
+    }
+}
+
+/// Shows or hides the menu, and lists the bindings of each action around the selected one.
+fn update_keybinding_menu(
+    menu: Res<KeybindingMenu>,
+    keybindings: Res<Keybindings>,
+    mut root_query: Query<&mut Style, With<KeybindingMenuRoot>>,
+    mut text_query: Query<&mut Text, With<KeybindingMenuText>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    if !menu.is_changed() && !keybindings.is_changed() {
+        return;
+    }
+
+    let (Ok(mut style), Ok(mut text)) = (root_query.get_single_mut(), text_query.get_single_mut())
+    else {
+        return;
+    };
+
+    style.display = match menu.open {
+        true => Display::Flex,
+        false => Display::None,
+    };
+
+    if !menu.open {
+        return;
+    }
+
+    let font_size = 18.0;
+    let section = |value: String, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            font: fonts.regular.clone_weak(),
+            font_size,
+            color,
+        },
+    };
+
+    let mut sections = vec![
+        section(
+            "Keybindings\n".to_string(),
+            Color::WHITE,
+        ),
+        section(
+            "Up/Down: select, Tab: switch device, Enter: rebind, Backspace: reset, Escape: close\n\n"
+                .to_string(),
+            MENU_NEUTRAL_COLOR,
+        ),
+    ];
+
+    let n_actions = keybindings.iter().len();
+    let first_row = menu
+        .selected
+        .saturating_sub(KeybindingMenu::VISIBLE_ROWS / 2)
+        .min(n_actions.saturating_sub(KeybindingMenu::VISIBLE_ROWS));
+
+    for (index, (action, bindings)) in keybindings
+        .iter()
+        .enumerate()
+        .skip(first_row)
+        .take(KeybindingMenu::VISIBLE_ROWS)
+    {
+        let is_selected = index == menu.selected;
+        let name_color = match is_selected {
+            true => MENU_HIGHLIGHT_COLOR,
+            false => MENU_NEUTRAL_COLOR,
+        };
+        sections.push(section(format!("{action:?}: "), name_color));
+
+        for device in [InputDevice::KeyboardAndMouse, InputDevice::Gamepad] {
+            let is_edited = is_selected && device == menu.device;
+
+            let value = match (&menu.pending, bindings.get(device)) {
+                (Some(captured), _) if is_edited => match is_directional(action, device) {
+                    true if device == InputDevice::Gamepad => "Click a joystick...".to_string(),
+                    true => {
+                        let direction = ["up", "down", "left", "right"]
+                            .get(captured.len())
+                            .unwrap_or(&"right");
+                        format!("Press the key for {direction}...")
+                    }
+                    false => "Press an input...".to_string(),
+                },
+                (_, Some(input)) => describe_input(input),
+                (_, None) => "Unbound".to_string(),
+            };
+
+            let color = if is_edited {
+                Color::YELLOW
+            } else if keybindings.is_conflicted(action, device) {
+                Color::ORANGE_RED
+            } else {
+                MENU_NEUTRAL_COLOR
+            };
+
+            let separator = match device {
+                InputDevice::KeyboardAndMouse => " | ",
+                InputDevice::Gamepad => "\n",
+            };
+
+            sections.push(section(value, color));
+            sections.push(section(separator.to_string(), MENU_NEUTRAL_COLOR));
+        }
+    }
+
+    sections.push(section(format!("\n{}", menu.message), Color::WHITE));
+
+    text.sections = sections;
+}
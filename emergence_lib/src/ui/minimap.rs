@@ -0,0 +1,500 @@
+//! A small top-down view of the whole map, which can be clicked to move the camera.
+//!
+//! The minimap can show any [`OverlayType`], independently of the overlay shown on the map itself.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    ui::RelativeCursorPosition,
+    utils::HashMap,
+};
+use hexx::Hex;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos, MAP_LAYOUT},
+    graphics::{
+        overlay::{OverlayMaterials, OverlayType, TileOverlay},
+        palette::minimap::{BARREN_TERRAIN, CAMERA_VIEW, DEEP_WATER, FERTILE_TERRAIN, STRUCTURE},
+    },
+    items::item_manifest::ItemManifest,
+    player_interaction::camera::{CameraFocus, FocusOnTile},
+    structures::structure_manifest::StructureManifest,
+    terrain::{
+        fertility::Fertility,
+        terrain_manifest::{Terrain, TerrainManifest},
+    },
+    units::unit_manifest::UnitManifest,
+    water::WaterDepth,
+    world_gen::WorldGenState,
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the [`Minimap`].
+pub(super) struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_minimap).add_systems(
+            Update,
+            (
+                recenter_camera_on_click,
+                select_minimap_layer,
+                update_minimap_layer_button,
+                update_minimap,
+            )
+                .run_if(in_state(WorldGenState::Complete))
+                .run_if(resource_exists::<TerrainManifest>()),
+        );
+    }
+}
+
+/// The width and height of the minimap image, in pixels.
+const MINIMAP_SIZE: u32 = 200;
+
+/// How often the minimap is redrawn, in seconds.
+const REFRESH_INTERVAL: f32 = 0.1;
+
+/// The color of the layer button when it is not being interacted with.
+const BUTTON_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+
+/// The color of the layer button when it is being hovered over.
+const HOVERED_BUTTON_COLOR: Color = Color::rgba(0.3, 0.3, 0.3, 0.7);
+
+/// The image that the minimap is drawn to.
+#[derive(Resource, Debug)]
+struct Minimap {
+    /// The handle to the image displayed in the UI.
+    image: Handle<Image>,
+    /// Controls how often the image is redrawn.
+    refresh_timer: Timer,
+    /// The overlay drawn on top of the terrain.
+    ///
+    /// This is chosen on the minimap itself, and does not need to match [`TileOverlay::overlay_type`].
+    layer: OverlayType,
+}
+
+/// Marker component for the UI node that displays the [`Minimap`].
+#[derive(Component, Debug)]
+struct MinimapNode;
+
+/// A button that cycles through the overlays that the [`Minimap`] can display.
+#[derive(Component, Debug)]
+struct MinimapLayerButton;
+
+/// Initializes the minimap.
+fn spawn_minimap(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    mut images: ResMut<Assets<Image>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let size = Extent3d {
+        width: MINIMAP_SIZE,
+        height: MINIMAP_SIZE,
+        depth_or_array_layers: 1,
+    };
+    let image = images.add(Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let minimap_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(MINIMAP_SIZE as f32),
+                        height: Val::Px(MINIMAP_SIZE as f32),
+                        ..default()
+                    },
+                    image: UiImage {
+                        texture: image.clone_weak(),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(Interaction::default())
+                .insert(RelativeCursorPosition::default())
+                .insert(MinimapNode);
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(2.)),
+                        margin: UiRect::all(Val::Px(1.)),
+                        ..default()
+                    },
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                })
+                .insert(MinimapLayerButton)
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section("", style));
+                });
+        })
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(minimap_entity);
+
+    commands.insert_resource(Minimap {
+        image,
+        refresh_timer: Timer::from_seconds(REFRESH_INTERVAL, TimerMode::Repeating),
+        layer: OverlayType::None,
+    });
+}
+
+/// The overlay that the minimap should switch to after `current`.
+///
+/// Individual signal types are too numerous to cycle through,
+/// so the signal type shown on the map (if any) is offered instead.
+fn next_layer(current: OverlayType, main_view: OverlayType) -> OverlayType {
+    let mut layers = vec![OverlayType::None, OverlayType::StrongestSignal];
+    if let OverlayType::Single(..) = main_view {
+        layers.push(main_view);
+    } else if let OverlayType::Single(..) = current {
+        layers.push(current);
+    }
+    layers.extend([
+        OverlayType::DepthToWaterTable,
+        OverlayType::HeightOfWaterTable,
+        OverlayType::VelocityOfWaterTable,
+        OverlayType::NetWater,
+        OverlayType::LightLevel,
+        OverlayType::Fertility,
+    ]);
+
+    // Compare variants, so that a stale signal type still advances to the following layer
+    let index = layers
+        .iter()
+        .position(|layer| std::mem::discriminant(layer) == std::mem::discriminant(&current))
+        .unwrap_or_default();
+
+    layers[(index + 1) % layers.len()]
+}
+
+/// Switches the overlay shown on the minimap when the layer button is clicked.
+fn select_minimap_layer(
+    button_query: Query<&Interaction, (Changed<Interaction>, With<MinimapLayerButton>)>,
+    mut minimap: ResMut<Minimap>,
+    tile_overlay: Res<TileOverlay>,
+) {
+    for interaction in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            minimap.layer = next_layer(minimap.layer, tile_overlay.overlay_type);
+            // Show the new layer right away
+            let duration = minimap.refresh_timer.duration();
+            minimap.refresh_timer.set_elapsed(duration);
+        }
+    }
+}
+
+/// Shows which overlay the minimap is displaying on the layer button.
+fn update_minimap_layer_button(
+    mut button_query: Query<
+        (&Interaction, &Children, &mut BackgroundColor),
+        With<MinimapLayerButton>,
+    >,
+    mut text_query: Query<&mut Text>,
+    minimap: Res<Minimap>,
+    item_manifest: Res<ItemManifest>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+    unit_manifest: Res<UnitManifest>,
+) {
+    for (interaction, children, mut background_color) in button_query.iter_mut() {
+        *background_color = match interaction {
+            Interaction::None => BUTTON_COLOR,
+            Interaction::Hovered | Interaction::Pressed => HOVERED_BUTTON_COLOR,
+        }
+        .into();
+
+        let Ok(mut text) = text_query.get_mut(children[0]) else {
+            continue;
+        };
+
+        let layer_name = match minimap.layer {
+            OverlayType::None => "No overlay".to_string(),
+            OverlayType::Single(signal_type) => signal_type.display(
+                &item_manifest,
+                &structure_manifest,
+                &terrain_manifest,
+                &unit_manifest,
+            ),
+            OverlayType::StrongestSignal => "Strongest signal".to_string(),
+            OverlayType::DepthToWaterTable => "Depth to water table".to_string(),
+            OverlayType::HeightOfWaterTable => "Height of water table".to_string(),
+            OverlayType::VelocityOfWaterTable => "Outgoing lateral water flow".to_string(),
+            OverlayType::NetWater => "Net water flux".to_string(),
+            OverlayType::LightLevel => "Light level".to_string(),
+            OverlayType::Fertility => "Soil fertility".to_string(),
+        };
+
+        let value = format!("Minimap: {layer_name}");
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Half of the width of the square area of the world shown on the minimap, in world units.
+fn half_extent(map_geometry: &MapGeometry) -> f32 {
+    // Flat-topped hexes are sqrt(3) world units tall, and the ocean adds one more ring around the map
+    (map_geometry.radius + 2) as f32 * 3f32.sqrt()
+}
+
+/// Converts a position on the minimap, from (0, 0) in the top-left to (1, 1) in the bottom-right, into a world position.
+///
+/// The returned [`Vec2`] stores the x and z coordinates.
+fn minimap_to_world(normalized: Vec2, half_extent: f32) -> Vec2 {
+    (normalized * 2. - Vec2::ONE) * half_extent
+}
+
+/// Converts a world position (x and z coordinates) into a position on the minimap.
+///
+/// This is the inverse of [`minimap_to_world`].
+fn world_to_minimap(world_xz: Vec2, half_extent: f32) -> Vec2 {
+    (world_xz / half_extent + Vec2::ONE) / 2.
+}
+
+/// Moves the camera to the tile that was clicked on the minimap.
+///
+/// Holding down the mouse button and dragging will continuously move the camera.
+fn recenter_camera_on_click(
+    minimap_query: Query<(&Interaction, &RelativeCursorPosition), With<MinimapNode>>,
+    map_geometry: Res<MapGeometry>,
+    mut focus_events: EventWriter<FocusOnTile>,
+) {
+    for (interaction, relative_cursor_position) in minimap_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let Some(normalized) = relative_cursor_position.normalized else {
+            continue;
+        };
+
+        let world_xz = minimap_to_world(normalized, half_extent(&map_geometry));
+        let hex = MAP_LAYOUT.world_pos_to_hex(world_xz);
+        if map_geometry.is_valid(hex) {
+            focus_events.send(FocusOnTile(hex));
+        }
+    }
+}
+
+/// Redraws the minimap from the current state of the world, outlining the area that the camera can see.
+#[allow(clippy::too_many_arguments)]
+fn update_minimap(
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    terrain_query: Query<(&Id<Terrain>, &VoxelPos, &WaterDepth)>,
+    camera_query: Query<(&Camera, &GlobalTransform, &CameraFocus), With<Camera3d>>,
+    materials: Res<Assets<StandardMaterial>>,
+    overlay_materials: OverlayMaterials,
+    terrain_manifest: Res<TerrainManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time<Real>>,
+) {
+    if !minimap.refresh_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let max_height = terrain_query
+        .iter()
+        .map(|(_, voxel_pos, _)| voxel_pos.height.0)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+
+    // The base map shows the terrain, shaded by height, along with surface water and structures
+    let mut tile_colors: HashMap<Hex, Color> = HashMap::default();
+    for (&terrain_id, &voxel_pos, &water_depth) in terrain_query.iter() {
+        let fertility = terrain_manifest.get(terrain_id).soil_fertility;
+        let mut color = lerp_color(
+            BARREN_TERRAIN,
+            FERTILE_TERRAIN,
+            (fertility.0 / Fertility::MAX.0).clamp(0., 1.),
+        );
+        // Higher ground is brighter
+        let shade = 0.6 + 0.4 * voxel_pos.height.0 as f32 / max_height as f32;
+        color.set_l(color.l() * shade);
+
+        if let WaterDepth::Flooded(..) = water_depth {
+            color = lerp_color(color, DEEP_WATER, 0.7);
+        }
+
+        if map_geometry.get_structure(voxel_pos.above()).is_some() {
+            color = STRUCTURE;
+        }
+
+        tile_colors.insert(voxel_pos.hex, color);
+    }
+
+    // Tint each tile with the layer chosen for the minimap
+    if !minimap.layer.is_none() {
+        for (_, &voxel_pos, _) in terrain_query.iter() {
+            let Some(material_handle) = overlay_materials.get(minimap.layer, voxel_pos) else {
+                continue;
+            };
+
+            let (Some(material), Some(color)) = (
+                materials.get(&material_handle),
+                tile_colors.get_mut(&voxel_pos.hex),
+            ) else {
+                continue;
+            };
+
+            let overlay_color = material.base_color;
+            *color = lerp_color(*color, overlay_color.with_a(1.), overlay_color.a());
+        }
+    }
+
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    let half_extent = half_extent(&map_geometry);
+    for row in 0..MINIMAP_SIZE {
+        for column in 0..MINIMAP_SIZE {
+            let normalized = Vec2::new(column as f32 + 0.5, row as f32 + 0.5) / MINIMAP_SIZE as f32;
+            let hex = MAP_LAYOUT.world_pos_to_hex(minimap_to_world(normalized, half_extent));
+            let color = tile_colors.get(&hex).copied().unwrap_or(DEEP_WATER);
+            set_pixel(&mut image.data, column as i32, row as i32, color);
+        }
+    }
+
+    // Outline the area of the ground that the camera can see
+    let Ok((camera, camera_transform, camera_focus)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let viewport_corners = [
+        Vec2::ZERO,
+        Vec2::new(viewport_size.x, 0.),
+        viewport_size,
+        Vec2::new(0., viewport_size.y),
+    ];
+
+    let ground_corners: Vec<Option<Vec2>> = viewport_corners
+        .iter()
+        .map(|&corner| {
+            let ray = camera.viewport_to_world(camera_transform, corner)?;
+            let distance = ray.intersect_plane(camera_focus.translation(), Vec3::Y)?;
+            let ground_pos = ray.get_point(distance);
+            let normalized = world_to_minimap(Vec2::new(ground_pos.x, ground_pos.z), half_extent);
+            Some(normalized * MINIMAP_SIZE as f32)
+        })
+        .collect();
+
+    for (i, &start) in ground_corners.iter().enumerate() {
+        let end = ground_corners[(i + 1) % ground_corners.len()];
+        // Corners that are above the horizon never reach the ground
+        if let (Some(start), Some(end)) = (start, end) {
+            draw_line(&mut image.data, start, end, CAMERA_VIEW);
+        }
+    }
+}
+
+/// Linearly interpolates between two colors in RGBA space.
+fn lerp_color(start: Color, end: Color, t: f32) -> Color {
+    let start = Vec4::from(start.as_rgba_f32());
+    let end = Vec4::from(end.as_rgba_f32());
+    let [r, g, b, a] = start.lerp(end, t).to_array();
+    Color::rgba(r, g, b, a)
+}
+
+/// Sets the color of the pixel in the minimap image at the provided column and row.
+///
+/// Pixels outside of the image are ignored.
+fn set_pixel(data: &mut [u8], column: i32, row: i32, color: Color) {
+    let size = MINIMAP_SIZE as i32;
+    if !(0..size).contains(&column) || !(0..size).contains(&row) {
+        return;
+    }
+
+    // Each pixel is represented by 4 bytes, in RGBA order
+    let pixel_start = (row * size + column) as usize * 4;
+    data[pixel_start..pixel_start + 4].copy_from_slice(&color.as_rgba_u8());
+}
+
+/// Draws a straight line between two pixel positions in the minimap image.
+fn draw_line(data: &mut [u8], start: Vec2, end: Vec2, color: Color) {
+    // Points far off of the edge of the minimap would take a long time to draw, for no benefit
+    let bound = Vec2::splat(MINIMAP_SIZE as f32);
+    let start = start.clamp(-bound, 2. * bound);
+    let end = end.clamp(-bound, 2. * bound);
+
+    let n_steps = (end - start).abs().max_element().ceil() as u32;
+    for step in 0..=n_steps {
+        let t = step as f32 / n_steps.max(1) as f32;
+        let point = start.lerp(end, t);
+        set_pixel(data, point.x.floor() as i32, point.y.floor() as i32, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimap_and_world_positions_round_trip() {
+        let half_extent = 42.;
+
+        for normalized in [
+            Vec2::ZERO,
+            Vec2::ONE,
+            Vec2::splat(0.5),
+            Vec2::new(0.1, 0.9),
+            Vec2::new(0.75, 0.3),
+        ] {
+            let world_xz = minimap_to_world(normalized, half_extent);
+            let round_tripped = world_to_minimap(world_xz, half_extent);
+            assert!(
+                round_tripped.abs_diff_eq(normalized, 1e-5),
+                "{normalized} became {round_tripped}"
+            );
+        }
+
+        // The center of the minimap is the center of the world
+        assert_eq!(minimap_to_world(Vec2::splat(0.5), half_extent), Vec2::ZERO);
+        assert_eq!(world_to_minimap(Vec2::ZERO, half_extent), Vec2::splat(0.5));
+    }
+
+    #[test]
+    fn layer_button_cycles_through_every_layer() {
+        let mut layer = OverlayType::None;
+        let mut seen = Vec::new();
+        loop {
+            seen.push(layer);
+            layer = next_layer(layer, OverlayType::None);
+            if layer == OverlayType::None {
+                break;
+            }
+        }
+
+        assert_eq!(seen.len(), 8);
+        assert!(seen.contains(&OverlayType::Fertility));
+    }
+}
@@ -5,7 +5,12 @@ use crate::{
     construction::terraform::TerraformingTool,
     structures::structure_manifest::Structure,
     ui::{
+        alerts::AlertFeedPlugin,
+        blueprint_library::BlueprintLibraryPlugin,
+        bottlenecks::BottleneckPanelPlugin,
         cursor::CursorPlugin,
+        keybinding_menu::KeybindingMenuPlugin,
+        minimap::MinimapPlugin,
         overlay::OverlayMenuPlugin,
         production_statistics::ProductionStatisticsPlugin,
         select_structure::SelectStructurePlugin,
@@ -19,7 +24,12 @@ use crate::{
 use bevy::prelude::*;
 use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
 
+mod alerts;
+mod blueprint_library;
+mod bottlenecks;
 mod cursor;
+mod keybinding_menu;
+mod minimap;
 mod overlay;
 mod production_statistics;
 mod select_structure;
@@ -65,7 +75,12 @@ impl Plugin for UiPlugin {
         .add_plugins(StatusPlugin)
         .add_plugins(OverlayMenuPlugin)
         .add_plugins(SelectStructurePlugin)
-        .add_plugins(SelectTerraformingPlugin);
+        .add_plugins(SelectTerraformingPlugin)
+        .add_plugins(BlueprintLibraryPlugin)
+        .add_plugins(BottleneckPanelPlugin)
+        .add_plugins(KeybindingMenuPlugin)
+        .add_plugins(AlertFeedPlugin)
+        .add_plugins(MinimapPlugin);
     }
 }
 
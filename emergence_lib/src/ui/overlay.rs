@@ -74,6 +74,13 @@ fn select_overlay(
             _ => OverlayType::LightLevel,
         };
     }
+
+    if player_actions.just_pressed(PlayerAction::ToggleFertilityOverlay) {
+        tile_overlay.overlay_type = match tile_overlay.overlay_type {
+            OverlayType::Fertility => OverlayType::None,
+            _ => OverlayType::Fertility,
+        };
+    }
 }
 
 /// Creates the UI needed to display the overlay.
@@ -203,6 +210,22 @@ fn update_signal_type_display(
                         color: SignalKind::Demolish.color(),
                     },
                 },
+                TextSection {
+                    value: "Repair\n".to_string(),
+                    style: TextStyle {
+                        font: fonts.regular.clone_weak(),
+                        font_size,
+                        color: SignalKind::Repair.color(),
+                    },
+                },
+                TextSection {
+                    value: "Visit\n".to_string(),
+                    style: TextStyle {
+                        font: fonts.regular.clone_weak(),
+                        font_size,
+                        color: SignalKind::Visit.color(),
+                    },
+                },
                 TextSection {
                     value: "Unit".to_string(),
                     style: TextStyle {
@@ -277,5 +300,17 @@ fn update_signal_type_display(
             // TODO: add a legend for light levels
             legend.texture = Handle::default();
         }
+        OverlayType::Fertility => {
+            text.sections = vec![TextSection {
+                value: "Soil fertility".to_string(),
+                style: TextStyle {
+                    font: fonts.regular.clone_weak(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            }];
+
+            legend.texture = tile_overlay.fertility_legend_image_handle();
+        }
     }
 }
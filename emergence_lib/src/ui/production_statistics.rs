@@ -1,4 +1,6 @@
 //! Displays information about population counts and production over time.
+//!
+//! Recorded history is plotted as line graphs, using the samples stored in the [`StatisticsHistory`].
 
 use bevy::{prelude::*, utils::HashMap};
 
@@ -9,7 +11,11 @@ use crate::{
     items::item_manifest::{Item, ItemManifest},
     light::TotalLight,
     litter::Litter,
-    simulation::{time::InGameTime, weather::CurrentWeather},
+    simulation::{
+        statistics::{StatisticsHistory, StatisticsSample},
+        time::{InGameTime, SimulationSpeed},
+        weather::CurrentWeather,
+    },
     units::{item_interaction::UnitInventory, unit_manifest::Unit},
     water::WaterVolume,
     world_gen::WorldGenState,
@@ -30,10 +36,17 @@ impl Plugin for ProductionStatisticsPlugin {
                 Update,
                 (census, update_item_count).distributive_run_if(in_state(WorldGenState::Complete)),
             )
-            .add_systems(Startup, spawn_production_statistics_menu)
+            .add_systems(
+                Startup,
+                (
+                    spawn_production_statistics_menu,
+                    spawn_statistics_graphs.after(spawn_production_statistics_menu),
+                ),
+            )
             .add_systems(
                 Update,
-                update_production_statistics.run_if(in_state(WorldGenState::Complete)),
+                (update_production_statistics, update_statistics_graphs)
+                    .distributive_run_if(in_state(WorldGenState::Complete)),
             );
     }
 }
@@ -81,6 +94,7 @@ fn spawn_production_statistics_menu(
 fn update_production_statistics(
     mut query: Query<&mut Text, With<ProductionStats>>,
     in_game_time: Res<InGameTime>,
+    simulation_speed: Res<SimulationSpeed>,
     current_weather: Res<CurrentWeather>,
     total_light: Res<TotalLight>,
     water_volume_query: Query<&WaterVolume>,
@@ -96,7 +110,7 @@ fn update_production_statistics(
 
     let average_water_volume = total_water_volume / water_volume_query.iter().len() as f32;
 
-    text.sections[0].value = format!("{}\n", *in_game_time);
+    text.sections[0].value = format!("{}\n{}\n", *in_game_time, *simulation_speed);
     text.sections[1].value = format!("Weather: {}\n", current_weather.get());
     text.sections[2].value = format!("Light: {}\n", *total_light);
     text.sections[3].value = format!("{average_water_volume} average volume of water per tile \n",);
@@ -104,6 +118,152 @@ fn update_production_statistics(
     text.sections[5].value = format!("{}\n", item_count.display(&item_manifest));
 }
 
+/// A value recorded in the [`StatisticsHistory`] that is plotted as a line graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphedMetric {
+    /// The total number of living organisms.
+    Population,
+    /// The number of organisms born since the previous sample.
+    Births,
+    /// The number of organisms that died since the previous sample.
+    Deaths,
+    /// The total volume of water on the map.
+    Water,
+}
+
+impl GraphedMetric {
+    /// All of the metrics that are graphed, in the order that they are displayed.
+    const ALL: [GraphedMetric; 4] = [
+        GraphedMetric::Population,
+        GraphedMetric::Births,
+        GraphedMetric::Deaths,
+        GraphedMetric::Water,
+    ];
+
+    /// The label shown above the graph.
+    fn label(&self) -> &'static str {
+        match self {
+            GraphedMetric::Population => "Population",
+            GraphedMetric::Births => "Births",
+            GraphedMetric::Deaths => "Deaths",
+            GraphedMetric::Water => "Total water",
+        }
+    }
+
+    /// The color of the line.
+    fn color(&self) -> Color {
+        match self {
+            GraphedMetric::Population => Color::WHITE,
+            GraphedMetric::Births => Color::GREEN,
+            GraphedMetric::Deaths => Color::RED,
+            GraphedMetric::Water => Color::CYAN,
+        }
+    }
+
+    /// The value of this metric in the provided `sample`.
+    fn value(&self, sample: &StatisticsSample) -> f32 {
+        match self {
+            GraphedMetric::Population => sample.total_population() as f32,
+            GraphedMetric::Births => sample.births as f32,
+            GraphedMetric::Deaths => sample.deaths as f32,
+            GraphedMetric::Water => sample.total_water,
+        }
+    }
+}
+
+/// The area that a line graph of a [`GraphedMetric`] is drawn in.
+#[derive(Component, Debug)]
+struct StatisticsGraph(GraphedMetric);
+
+/// The width of each statistics graph, in pixels.
+const GRAPH_WIDTH: f32 = 180.;
+
+/// The height of each statistics graph, in pixels.
+const GRAPH_HEIGHT: f32 = 40.;
+
+/// The size of each point that makes up a line graph, in pixels.
+const GRAPH_POINT_SIZE: f32 = 2.;
+
+/// Initializes an empty line graph for each [`GraphedMetric`].
+fn spawn_statistics_graphs(
+    mut commands: Commands,
+    left_panel_query: Query<Entity, With<LeftPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+
+    let left_panel_entity = left_panel_query.single();
+    commands.entity(left_panel_entity).with_children(|parent| {
+        for metric in GraphedMetric::ALL {
+            parent.spawn(TextBundle::from_section(metric.label(), style.clone()));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(GRAPH_WIDTH),
+                        height: Val::Px(GRAPH_HEIGHT),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                },
+                StatisticsGraph(metric),
+            ));
+        }
+    });
+}
+
+/// Redraws the line graphs whenever a new sample is recorded.
+fn update_statistics_graphs(
+    graph_query: Query<(Entity, &StatisticsGraph)>,
+    history: Res<StatisticsHistory>,
+    mut commands: Commands,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    for (graph_entity, graph) in graph_query.iter() {
+        let metric = graph.0;
+        let values: Vec<f32> = history
+            .samples()
+            .map(|sample| metric.value(sample))
+            .collect();
+
+        commands.entity(graph_entity).despawn_descendants();
+
+        // Scale each graph to its own peak, so that small changes are still visible
+        let max_value = values.iter().copied().fold(0., f32::max);
+        let x_step = GRAPH_WIDTH / (values.len().saturating_sub(1).max(1)) as f32;
+
+        commands.entity(graph_entity).with_children(|parent| {
+            for (i, value) in values.iter().enumerate() {
+                let y_fraction = if max_value > 0. {
+                    value / max_value
+                } else {
+                    0.
+                };
+
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(i as f32 * x_step - GRAPH_POINT_SIZE / 2.),
+                        bottom: Val::Px(y_fraction * (GRAPH_HEIGHT - GRAPH_POINT_SIZE)),
+                        width: Val::Px(GRAPH_POINT_SIZE),
+                        height: Val::Px(GRAPH_POINT_SIZE),
+                        ..default()
+                    },
+                    background_color: metric.color().into(),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
 /// Tracks the population of organisms
 #[derive(Debug, Resource, Default)]
 pub(crate) struct Census {
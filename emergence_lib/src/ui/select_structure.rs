@@ -4,11 +4,13 @@ use crate::{
     asset_management::{manifest::Id, AssetState},
     geometry::Facing,
     graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
+    organisms::domestication::Domestication,
     player_interaction::{
         clipboard::{ClipboardData, Tool},
         PlayerAction,
     },
     structures::structure_manifest::{Structure, StructureManifest},
+    technology::{technology_manifest::TechnologyManifest, ResearchProgress},
 };
 
 use itertools::Itertools;
@@ -45,16 +47,28 @@ impl Choice for Id<Structure> {
     const ACTIVATION: PlayerAction = PlayerAction::SelectStructure;
 }
 
-/// Update the set of choices available to build whenever the structure manifest is updated
+/// Update the set of choices available to build whenever the structure manifest is updated,
+/// a species is domesticated or a technology is unlocked
 fn update_structure_choices(
     mut available_choices: ResMut<AvailableChoices<Id<Structure>>>,
     structure_manifest: Res<StructureManifest>,
+    technology_manifest: Res<TechnologyManifest>,
+    domestication: Res<Domestication>,
+    research_progress: Res<ResearchProgress>,
 ) {
-    if structure_manifest.is_changed() {
+    if structure_manifest.is_changed()
+        || technology_manifest.is_changed()
+        || domestication.is_changed()
+        || research_progress.is_changed()
+    {
         // Sort to ensure a stable ordering
         available_choices.choices = structure_manifest
             .prototypes()
             .into_iter()
+            .filter(|&structure_id| domestication.can_build(structure_id, &structure_manifest))
+            .filter(|&structure_id| {
+                research_progress.structure_unlocked(structure_id, &technology_manifest)
+            })
             .sorted()
             .collect();
     }
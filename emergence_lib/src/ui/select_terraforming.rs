@@ -5,6 +5,7 @@ use crate::{
     construction::terraform::TerraformingTool,
     graphics::palette::ui::{MENU_HIGHLIGHT_COLOR, MENU_NEUTRAL_COLOR},
     player_interaction::{clipboard::Tool, PlayerAction},
+    technology::{technology_manifest::TechnologyManifest, ResearchProgress},
     terrain::terrain_manifest::TerrainManifest,
 };
 
@@ -45,12 +46,17 @@ impl Choice for TerraformingTool {
     const ACTIVATION: PlayerAction = PlayerAction::SelectTerraform;
 }
 
-/// Update the set of choices available to build whenever the terrain manifest is updated
+/// Update the set of choices available to build whenever the terrain manifest is updated or a technology is unlocked
 fn update_terraforming_choices(
     mut available_choices: ResMut<AvailableChoices<TerraformingTool>>,
     terrain_manifest: Res<TerrainManifest>,
+    technology_manifest: Res<TechnologyManifest>,
+    research_progress: Res<ResearchProgress>,
 ) {
-    if terrain_manifest.is_changed() {
+    if terrain_manifest.is_changed()
+        || technology_manifest.is_changed()
+        || research_progress.is_changed()
+    {
         available_choices.choices = vec![TerraformingTool::Raise, TerraformingTool::Lower];
 
         // Sort to ensure a stable ordering
@@ -62,6 +68,10 @@ fn update_terraforming_choices(
             .sorted()
             .map(|terrain_id| TerraformingTool::Change(terrain_id));
         available_choices.choices.extend(terrain_choices);
+
+        available_choices.choices.retain(|&terraforming_tool| {
+            research_progress.terraforming_unlocked(terraforming_tool, &technology_manifest)
+        });
     }
 }
 
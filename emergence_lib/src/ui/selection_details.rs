@@ -7,15 +7,16 @@ use crate::{
     crafting::recipe::RecipeManifest,
     geometry::{MapGeometry, VoxelKind},
     items::item_manifest::ItemManifest,
-    player_interaction::{
-        camera::{CameraMode, CameraSettings},
-        selection::CurrentSelection,
-        InteractionSystem,
+    organisms::{
+        disease::disease_manifest::DiseaseManifest,
+        domestication::Domestication,
+        genetics::{GenePool, SelectiveBreeding},
     },
+    player_interaction::{selection::CurrentSelection, InteractionSystem},
     signals::Signals,
     structures::structure_manifest::StructureManifest,
     terrain::terrain_manifest::TerrainManifest,
-    units::unit_manifest::UnitManifest,
+    units::{needs::need_manifest::NeedManifest, unit_manifest::UnitManifest},
     world_gen::WorldGenState,
 };
 
@@ -45,7 +46,6 @@ impl Plugin for SelectionDetailsPlugin {
                     .run_if(in_state(AssetState::FullyLoaded))
                     .run_if(in_state(WorldGenState::Complete)),
             )
-            .add_systems(Update, change_camera_mode.after(update_selection_details))
             .add_systems(
                 Update,
                 update_selection_details.run_if(in_state(AssetState::FullyLoaded)),
@@ -121,21 +121,6 @@ fn populate_selection_panel(
         .add_child(unit_details);
 }
 
-/// Changes the camera mode when the "follow unit" button is pressed.
-fn change_camera_mode(
-    mut camera_query: Query<&mut CameraSettings>,
-    keyboard_input: Res<Input<KeyCode>>,
-) {
-    // FIXME: This should be a button press, not a key press.
-    if keyboard_input.just_pressed(KeyCode::C) {
-        let mut camera_settings = camera_query.single_mut();
-        camera_settings.camera_mode = match camera_settings.camera_mode {
-            CameraMode::FollowUnit => CameraMode::Free,
-            CameraMode::Free => CameraMode::FollowUnit,
-        };
-    }
-}
-
 /// Updates UI elements for selection details panel based on new information.
 fn update_selection_details(
     selection_details: Res<SelectionDetails>,
@@ -181,6 +166,7 @@ fn update_selection_details(
     terrain_manifest: Res<TerrainManifest>,
     recipe_manifest: Res<RecipeManifest>,
     item_manifest: Res<ItemManifest>,
+    need_manifest: Res<NeedManifest>,
 ) {
     let mut parent_visibility = selection_panel_query.single_mut();
     let (mut ghost_structure_style, mut ghost_structure_text) =
@@ -252,6 +238,7 @@ fn update_selection_details(
                 &item_manifest,
                 &structure_manifest,
                 &terrain_manifest,
+                &need_manifest,
             );
         }
         SelectionDetails::None => (),
@@ -311,6 +298,10 @@ fn get_details(
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
     signals: Res<Signals>,
+    gene_pool: Res<GenePool>,
+    selective_breeding: Res<SelectiveBreeding>,
+    domestication: Res<Domestication>,
+    disease_manifest: Res<DiseaseManifest>,
 ) -> Result<(), QueryEntityError> {
     *selection_details = match &*current_selection {
         CurrentSelection::Voxels(selected_voxels) => {
@@ -329,6 +320,8 @@ fn get_details(
                             voxel_pos: *terrain_query_item.voxel_pos,
                             height: terrain_query_item.voxel_pos.height(),
                             depth_to_water_table: *terrain_query_item.water_depth,
+                            fertility: *terrain_query_item.fertility,
+                            research_reserve: terrain_query_item.research_reserve,
                             shade: terrain_query_item.shade.clone(),
                             recieved_light: terrain_query_item.recieved_light.clone(),
                             signals: signals.all_signals_at_position(*terrain_query_item.voxel_pos),
@@ -352,15 +345,25 @@ fn get_details(
                 organism_query
                     .get(voxel_object.entity)
                     .ok()
-                    .map(|query_item| OrganismDetails {
-                        prototypical_form: structure_manifest
+                    .map(|query_item| {
+                        let variety = structure_manifest
                             .get(*structure_query_item.structure_id)
                             .organism_variety.as_ref()
-                            .expect("All structures with organism components must be registered in the manifest as organisms")
-                            .prototypical_form,
-                        lifecycle: query_item.lifecycle.clone(),
-                        energy_pool: query_item.energy_pool.clone(),
-                        oxygen_pool: query_item.oxygen_pool.clone(),
+                            .expect("All structures with organism components must be registered in the manifest as organisms");
+                        let prototypical_form = variety.prototypical_form;
+
+                        OrganismDetails {
+                            prototypical_form,
+                            lifecycle: query_item.lifecycle.clone(),
+                            energy_pool: query_item.energy_pool.clone(),
+                            oxygen_pool: query_item.oxygen_pool.clone(),
+                            genome: query_item.genome.clone(),
+                            gene_distributions: gene_pool.distributions(prototypical_form),
+                            favored_gene: selective_breeding.favored_gene(prototypical_form),
+                            strain: *query_item.strain,
+                            mastery_progress: domestication.mastery_progress(variety),
+                            infections: query_item.infections.display(&disease_manifest),
+                        }
                     });
 
                         SelectionDetails::Structure(StructureDetails {
@@ -374,11 +377,15 @@ fn get_details(
                             input_inventory: structure_query_item.input_inventory.cloned(),
                             output_inventory: structure_query_item.output_inventory.cloned(),
                             crafting_state: structure_query_item.crafting_state.cloned(),
+                            crafting_diagnostics: structure_query_item
+                                .crafting_diagnostics
+                                .cloned(),
                             active_recipe: structure_query_item.active_recipe.cloned(),
                             workers_present: structure_query_item.workers_present.cloned(),
                             vegetative_reproduction: structure_query_item
                                 .vegetative_reproduction
                                 .cloned(),
+                            durability_pool: structure_query_item.durability_pool.cloned(),
                         })
                     }
                     VoxelKind::GhostStructure => {
@@ -401,14 +408,19 @@ fn get_details(
             let unit_query_item = unit_query.get(*unit_entity)?;
             // All units are organisms
             let organism_query_item = organism_query.get(*unit_entity)?;
+            let variety = &unit_manifest.get(*unit_query_item.unit_id).organism_variety;
+            let prototypical_form = variety.prototypical_form;
             let organism_details = OrganismDetails {
-                prototypical_form: unit_manifest
-                    .get(*unit_query_item.unit_id)
-                    .organism_variety
-                    .prototypical_form,
+                prototypical_form,
                 lifecycle: organism_query_item.lifecycle.clone(),
                 energy_pool: organism_query_item.energy_pool.clone(),
                 oxygen_pool: organism_query_item.oxygen_pool.clone(),
+                genome: organism_query_item.genome.clone(),
+                gene_distributions: gene_pool.distributions(prototypical_form),
+                favored_gene: selective_breeding.favored_gene(prototypical_form),
+                strain: *organism_query_item.strain,
+                mastery_progress: domestication.mastery_progress(variety),
+                infections: organism_query_item.infections.display(&disease_manifest),
             };
 
             let unit_data = unit_manifest.get(*unit_query_item.unit_id);
@@ -423,10 +435,12 @@ fn get_details(
                 action: unit_query_item.action.clone(),
                 impatience_pool: unit_query_item.impatience_pool.clone(),
                 age: unit_query_item.age.clone(),
+                needs: unit_query_item.needs.clone(),
                 organism_details,
                 walkable_neighbors: map_geometry
                     .walkable_neighbors(*unit_query_item.voxel_pos)
                     .collect(),
+                decision_trace: unit_query_item.decision_trace.clone(),
             })
         }
         CurrentSelection::None => SelectionDetails::None,
@@ -528,10 +542,18 @@ Construction materials: {construction_materials}
 
 /// Details for organisms
 mod organism_details {
-    use bevy::ecs::query::WorldQuery;
+    use bevy::{ecs::query::WorldQuery, utils::HashMap};
 
     use crate::{
-        organisms::{energy::EnergyPool, lifecycle::Lifecycle, oxygen::OxygenPool, OrganismId},
+        organisms::{
+            disease::Infections,
+            domestication::Strain,
+            energy::EnergyPool,
+            genetics::{Gene, GeneDistribution, Genome},
+            lifecycle::Lifecycle,
+            oxygen::OxygenPool,
+            OrganismId,
+        },
         structures::structure_manifest::StructureManifest,
         units::unit_manifest::UnitManifest,
     };
@@ -545,6 +567,12 @@ mod organism_details {
         pub(super) energy_pool: &'static EnergyPool,
         /// The currrent and max oxygen
         pub(super) oxygen_pool: &'static OxygenPool,
+        /// The heritable traits of this organism
+        pub(super) genome: &'static Genome,
+        /// Whether this organism cooperates with the colony
+        pub(super) strain: &'static Strain,
+        /// The diseases this organism is suffering from or immune to
+        pub(super) infections: &'static Infections,
     }
 
     /// Detailed info about a given organism.
@@ -558,6 +586,18 @@ mod organism_details {
         pub(super) energy_pool: EnergyPool,
         /// The currrent and max oxygen
         pub(super) oxygen_pool: OxygenPool,
+        /// The heritable traits of this organism
+        pub(super) genome: Genome,
+        /// How each gene is expressed across the species of this organism
+        pub(super) gene_distributions: HashMap<Gene, GeneDistribution>,
+        /// The gene that the player is breeding this species for, if any
+        pub(super) favored_gene: Option<Gene>,
+        /// Whether this organism cooperates with the colony
+        pub(super) strain: Strain,
+        /// The current and required biotic mastery, if this species is not yet domesticated
+        pub(super) mastery_progress: Option<(f32, f32)>,
+        /// The diseases this organism is suffering from, formatted for display
+        pub(super) infections: String,
     }
 
     impl OrganismDetails {
@@ -574,13 +614,28 @@ mod organism_details {
 
             let energy_pool = &self.energy_pool;
             let oxygen_pool = &self.oxygen_pool;
+            let genome = self
+                .genome
+                .display(&self.gene_distributions, self.favored_gene);
+            let strain = &self.strain;
+            let infections = &self.infections;
 
-            format!(
+            let base_string = format!(
                 "Prototypical form: {prototypical_form}
 Lifecycle: {lifecycle}
 Energy: {energy_pool}
-Oxygen: {oxygen_pool}"
-            )
+Oxygen: {oxygen_pool}
+Strain: {strain}
+Diseases: {infections}
+Genome: {genome}"
+            );
+
+            match self.mastery_progress {
+                Some((current, required)) => {
+                    format!("{base_string}\nBiotic mastery: {current:.1}/{required:.1}")
+                }
+                None => base_string,
+            }
         }
     }
 }
@@ -594,6 +649,7 @@ mod structure_details {
         asset_management::manifest::Id,
         construction::demolition::MarkedForDemolition,
         crafting::{
+            diagnostics::CraftingDiagnostics,
             inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
             recipe::{ActiveRecipe, RecipeManifest},
             workers::WorkersPresent,
@@ -602,7 +658,10 @@ mod structure_details {
         items::item_manifest::ItemManifest,
         organisms::vegetative_reproduction::VegetativeReproduction,
         signals::Emitter,
-        structures::structure_manifest::{Structure, StructureManifest},
+        structures::{
+            durability::DurabilityPool,
+            structure_manifest::{Structure, StructureManifest},
+        },
         terrain::terrain_manifest::TerrainManifest,
         units::unit_manifest::UnitManifest,
         water::emitters::WaterEmitter,
@@ -627,6 +686,8 @@ mod structure_details {
         pub(crate) active_recipe: Option<&'static ActiveRecipe>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<&'static CraftingState>,
+        /// How crafting has recently been going.
+        pub(crate) crafting_diagnostics: Option<&'static CraftingDiagnostics>,
         /// The workers present at this structure.
         pub(crate) workers_present: Option<&'static WorkersPresent>,
         /// Is this structure marked for removal?
@@ -637,6 +698,8 @@ mod structure_details {
         pub(super) maybe_water_emitter: Option<&'static WaterEmitter>,
         /// The vegetative reproduction strategy, if any.
         pub(crate) vegetative_reproduction: Option<&'static VegetativeReproduction>,
+        /// How worn down this structure is, if it can decay.
+        pub(crate) durability_pool: Option<&'static DurabilityPool>,
     }
 
     /// Detailed info about a given structure.
@@ -664,10 +727,14 @@ mod structure_details {
         pub(crate) active_recipe: Option<ActiveRecipe>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<CraftingState>,
+        /// How crafting has recently been going.
+        pub(crate) crafting_diagnostics: Option<CraftingDiagnostics>,
         /// The number of workers that are presently working on this.
         pub(crate) workers_present: Option<WorkersPresent>,
         /// The vegetative reproduction strategy, if any.
         pub(crate) vegetative_reproduction: Option<VegetativeReproduction>,
+        /// How worn down this structure is, if it can decay.
+        pub(crate) durability_pool: Option<DurabilityPool>,
     }
 
     impl StructureDetails {
@@ -732,6 +799,10 @@ Height: {height}"
                 string += &format!("\nCrafting state: {crafting_state}");
             }
 
+            if let Some(crafting_diagnostics) = &self.crafting_diagnostics {
+                string += &format!("\nRecently: {crafting_diagnostics}");
+            }
+
             if let Some(workers_present) = &self.workers_present {
                 string += &format!("\nWorkers present: {workers_present}");
             }
@@ -748,6 +819,10 @@ Height: {height}"
                 string += &format!("\nVegetative reproduction: {vegetative_reproduction}",);
             }
 
+            if let Some(durability_pool) = &self.durability_pool {
+                string += &format!("\nDurability: {durability_pool}");
+            }
+
             string
         }
     }
@@ -764,9 +839,13 @@ mod terrain_details {
         geometry::{Height, VoxelPos},
         items::item_manifest::ItemManifest,
         light::shade::{ReceivedLight, Shade},
+        organisms::domestication::ResearchReserve,
         signals::LocalSignals,
         structures::structure_manifest::StructureManifest,
-        terrain::terrain_manifest::{Terrain, TerrainManifest},
+        terrain::{
+            fertility::Fertility,
+            terrain_manifest::{Terrain, TerrainManifest},
+        },
         units::unit_manifest::UnitManifest,
         water::WaterDepth,
     };
@@ -786,6 +865,10 @@ mod terrain_details {
         pub(super) terrain_id: &'static Id<Terrain>,
         /// The depth of water on this tile
         pub(super) water_depth: &'static WaterDepth,
+        /// The nutrients available in the soil of this tile
+        pub(super) fertility: &'static Fertility,
+        /// Is this tile part of a research reserve?
+        pub(super) research_reserve: Has<ResearchReserve>,
         /// Any applied terraforming action
         pub(super) maybe_terraforming_details: Option<(
             &'static TerraformingAction,
@@ -838,6 +921,10 @@ Output: {output}"
         pub(super) height: Height,
         /// The distance from the surface to the water table
         pub(super) depth_to_water_table: WaterDepth,
+        /// The nutrients available in the soil of this tile
+        pub(super) fertility: Fertility,
+        /// Is this tile part of a research reserve?
+        pub(super) research_reserve: bool,
         /// The shade of the tile
         pub(super) shade: Shade,
         /// The recieved light of the tile
@@ -864,6 +951,7 @@ Output: {output}"
             let voxel_pos = &self.voxel_pos;
             let height = &self.height;
             let depth_to_water_table = &self.depth_to_water_table;
+            let fertility = &self.fertility;
             let shade = &self.shade;
             let recieved_light = &self.recieved_light;
             let signals = self.signals.display(
@@ -885,11 +973,17 @@ Terrain type: {terrain_type}
 Tile: {voxel_pos}
 Height: {height}
 Water Table: {depth_to_water_table}
+Fertility: {fertility}
 Shade: {shade}
 Current Light: {recieved_light}
 Walkable Neighbors: {walkable_neighbors}"
             );
 
+            let base_string = match self.research_reserve {
+                true => format!("{base_string}\nResearch reserve"),
+                false => base_string,
+            };
+
             if let Some(terraforming_details) = &self.maybe_terraforming_details {
                 let terraforming_details =
                     terraforming_details.display(item_manifest, terrain_manifest);
@@ -915,9 +1009,11 @@ mod unit_details {
             actions::CurrentAction,
             age::Age,
             basic_needs::Diet,
+            decision_trace::DecisionTrace,
             goals::Goal,
             impatience::ImpatiencePool,
             item_interaction::UnitInventory,
+            needs::{need_manifest::NeedManifest, Needs},
             unit_manifest::{Unit, UnitManifest},
         },
     };
@@ -943,6 +1039,10 @@ mod unit_details {
         pub(super) impatience_pool: &'static ImpatiencePool,
         /// The current and max age of this unit.
         pub(super) age: &'static Age,
+        /// How well this unit's other needs are met.
+        pub(super) needs: &'static Needs,
+        /// Why this unit has been doing what it's doing.
+        pub(super) decision_trace: &'static DecisionTrace,
     }
 
     /// Detailed info about a given unit.
@@ -968,8 +1068,12 @@ mod unit_details {
         pub(super) impatience_pool: ImpatiencePool,
         /// The current and max age of this unit.
         pub(super) age: Age,
+        /// How well this unit's other needs are met.
+        pub(super) needs: Needs,
         /// The set of voxels that this unit can walk to
         pub(super) walkable_neighbors: Vec<VoxelPos>,
+        /// Why this unit has been doing what it's doing.
+        pub(super) decision_trace: DecisionTrace,
     }
 
     impl UnitDetails {
@@ -980,6 +1084,7 @@ mod unit_details {
             item_manifest: &ItemManifest,
             structure_manifest: &StructureManifest,
             terrain_manifest: &TerrainManifest,
+            need_manifest: &NeedManifest,
         ) -> String {
             let entity = self.entity;
             let unit_name = unit_manifest.name(self.unit_id);
@@ -991,6 +1096,7 @@ mod unit_details {
                 structure_manifest,
                 terrain_manifest,
                 unit_manifest,
+                need_manifest,
             );
             let action = &self.action.display(item_manifest);
             let impatience_pool = &self.impatience_pool;
@@ -998,12 +1104,22 @@ mod unit_details {
                 .organism_details
                 .display(structure_manifest, unit_manifest);
             let age = &self.age;
+            let needs = self.needs.display(need_manifest);
             let walkable_neighbors = self
                 .walkable_neighbors
                 .iter()
                 .map(|neighbor| format!("{}", neighbor))
                 .collect::<Vec<_>>()
                 .join("\n ");
+            let decision_trace = self.decision_trace.display(
+                DecisionTrace::DECISIONS_SHOWN,
+                false,
+                item_manifest,
+                structure_manifest,
+                terrain_manifest,
+                unit_manifest,
+                need_manifest,
+            );
 
             format!(
                 "Entity: {entity:?}
@@ -1011,12 +1127,15 @@ Unit type: {unit_name}
 Tile: {voxel_pos}
 Walkable Neighbors: {walkable_neighbors}
 Diet: {diet}
+Needs: {needs}
 Holding: {held_item}
 Goal: {goal}
 Action: {action}
 Impatience: {impatience_pool}
 Age: {age}
-{organism_details}"
+{organism_details}
+Recent decisions:
+{decision_trace}"
             )
         }
     }
@@ -192,6 +192,9 @@ impl FromWorld for Icons<GoalKind> {
             asset_server.load("icons/goals/demolish.png"),
         );
         map.insert(GoalKind::Eat, asset_server.load("icons/goals/eat.png"));
+        map.insert(GoalKind::Satisfy, asset_server.load("icons/goals/eat.png"));
+        // Hunting is just a special way of eating
+        map.insert(GoalKind::Hunt, asset_server.load("icons/goals/eat.png"));
         map.insert(GoalKind::Fetch, asset_server.load("icons/goals/fetch.png"));
         map.insert(
             GoalKind::Remove,
@@ -203,6 +206,10 @@ impl FromWorld for Icons<GoalKind> {
             asset_server.load("icons/goals/wander.png"),
         );
         map.insert(GoalKind::Work, asset_server.load("icons/goals/work.png"));
+        // Repairing is just a special kind of work
+        map.insert(GoalKind::Repair, asset_server.load("icons/goals/work.png"));
+        // Visiting partners is a form of work, as far as the colony is concerned
+        map.insert(GoalKind::Visit, asset_server.load("icons/goals/work.png"));
         map.insert(
             GoalKind::Breathe,
             asset_server.load("icons/goals/breathe.png"),
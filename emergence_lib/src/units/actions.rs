@@ -1,7 +1,10 @@
 //! What are units currently doing?
 
 use bevy::{
-    ecs::{query::WorldQuery, system::SystemParam},
+    ecs::{
+        query::{Has, WorldQuery},
+        system::SystemParam,
+    },
     prelude::*,
     utils::Duration,
 };
@@ -25,48 +28,73 @@ use crate::{
     geometry::{Facing, Height, MapGeometry, RotationDirection, VoxelPos},
     items::{errors::AddOneItemError, item_manifest::ItemManifest, ItemCount},
     litter::{Litter, LitterCommandsExt},
-    organisms::{energy::EnergyPool, lifecycle::Lifecycle},
+    organisms::{
+        death::{CauseOfDeath, DeathCommandsExt},
+        energy::EnergyPool,
+        genetics::{Gene, Genome},
+        lifecycle::Lifecycle,
+        symbiosis::{PartnerQuery, Visited},
+        Organism,
+    },
     signals::{SignalType, Signals},
-    structures::{commands::StructureCommandsExt, structure_manifest::Structure},
+    structures::{
+        commands::StructureCommandsExt,
+        durability::{DurabilityPool, RepairQuery},
+        structure_manifest::{Structure, StructureManifest},
+    },
     terrain::terrain_manifest::{Terrain, TerrainManifest},
     water::WaterDepth,
 };
 
 use super::{
     goals::Goal,
+    hunting::{HuntingCommandsExt, PreyQuery},
     impatience::ImpatiencePool,
     item_interaction::UnitInventory,
+    needs::{
+        need_manifest::{NeedCondition, NeedManifest, NeedSatisfier},
+        Needs,
+    },
     unit_manifest::{Unit, UnitManifest},
 };
 
 /// Ticks the timer for each [`CurrentAction`].
-pub(super) fn advance_action_timer(mut units_query: Query<&mut CurrentAction>, time: Res<Time>) {
+pub(super) fn advance_action_timer(
+    mut units_query: Query<(&mut CurrentAction, &Genome)>,
+    time: Res<Time>,
+) {
     let delta = time.delta();
 
-    for mut current_action in units_query.iter_mut() {
+    for (mut current_action, genome) in units_query.iter_mut() {
+        // Faster units finish moving sooner
+        let delta = match current_action.action {
+            UnitAction::MoveForward => delta.mul_f32(genome.get(Gene::WalkingSpeed)),
+            _ => delta,
+        };
+
         current_action.timer.tick(delta);
     }
 }
 
 /// Choose the unit's action for this turn
 pub(super) fn choose_actions(
-    mut units_query: Query<
-        (
-            &VoxelPos,
-            &Facing,
-            &Goal,
-            &mut CurrentAction,
-            &UnitInventory,
-        ),
-        With<Id<Unit>>,
-    >,
+    mut units_query: Query<(
+        Entity,
+        &Id<Unit>,
+        &VoxelPos,
+        &Facing,
+        &Goal,
+        &mut CurrentAction,
+        &UnitInventory,
+    )>,
     // We shouldn't be dropping off new stuff at structures that are about to be destroyed!
     input_inventory_query: Query<&InputInventory, Without<MarkedForDemolition>>,
     // But we can take their items away
     output_inventory_query: Query<&OutputInventory>,
     storage_inventory_query: Query<&StorageInventory>,
     workplace_query: WorkplaceQuery,
-    demolition_query: DemolitionQuery,
+    structure_targets: StructureTargetQuery,
+    prey_query: PreyQuery,
     map_geometry: Res<MapGeometry>,
     signals: Res<Signals>,
     terrain_query: Query<&Id<Terrain>>,
@@ -74,13 +102,27 @@ pub(super) fn choose_actions(
     water_depth_query: Query<&WaterDepth>,
     terrain_manifest: Res<TerrainManifest>,
     item_manifest: Res<ItemManifest>,
+    structure_manifest: Res<StructureManifest>,
+    need_manifest: Res<NeedManifest>,
 ) {
     let rng = &mut thread_rng();
 
-    for (&unit_pos, facing, goal, mut current_action, unit_inventory) in units_query.iter_mut() {
+    for (unit_entity, &unit_id, &unit_pos, facing, goal, mut current_action, unit_inventory) in
+        units_query.iter_mut()
+    {
         if current_action.finished() {
             let previous_action = current_action.action.clone();
 
+            // Needs that are met by items or structures are pursued just like food or symbiotic partners
+            let goal = &match *goal {
+                Goal::Satisfy(need_id) => match need_manifest.get(need_id).satisfier {
+                    NeedSatisfier::Item(item_kind) => Goal::Eat(item_kind),
+                    NeedSatisfier::Structure(structure_id) => Goal::Visit(structure_id),
+                    NeedSatisfier::Condition(_) => goal.clone(),
+                },
+                _ => goal.clone(),
+            };
+
             *current_action = match goal {
                 // Drop whatever you're holding before wandering further
                 Goal::Wander { .. } => match unit_inventory.held_item {
@@ -194,7 +236,77 @@ pub(super) fn choose_actions(
                     *structure_id,
                     unit_pos,
                     facing,
-                    &demolition_query,
+                    &structure_targets.demolition,
+                    &signals,
+                    rng,
+                    &item_manifest,
+                    &terrain_query,
+                    &terrain_manifest,
+                    &map_geometry,
+                ),
+                Goal::Repair(structure_id) => {
+                    let repair_material = structure_manifest
+                        .get(*structure_id)
+                        .durability
+                        .as_ref()
+                        .and_then(|durability_data| durability_data.repair_material);
+
+                    let held_item = unit_inventory.held_item;
+
+                    // If we're holding the wrong thing, drop it.
+                    if held_item.is_some() && held_item != repair_material {
+                        CurrentAction::abandon(
+                            previous_action,
+                            unit_pos,
+                            unit_inventory,
+                            &map_geometry,
+                            &terrain_manifest,
+                            &terrain_query,
+                            rng,
+                        )
+                    // Go grab the materials needed to make repairs
+                    } else if let (Some(repair_material), None) = (repair_material, held_item) {
+                        let item_kind = ItemKind::Single(repair_material);
+
+                        CurrentAction::find(
+                            unit_inventory,
+                            item_kind,
+                            DeliveryMode::PickUp,
+                            Purpose::Instrumental,
+                            unit_pos,
+                            facing,
+                            &Goal::Fetch(item_kind),
+                            &input_inventory_query,
+                            &output_inventory_query,
+                            &storage_inventory_query,
+                            &litter_query,
+                            &signals,
+                            rng,
+                            &item_manifest,
+                            &terrain_query,
+                            &terrain_manifest,
+                            &map_geometry,
+                        )
+                    } else {
+                        CurrentAction::find_repair_site(
+                            *structure_id,
+                            unit_pos,
+                            facing,
+                            &structure_targets.repair,
+                            &signals,
+                            rng,
+                            &item_manifest,
+                            &terrain_query,
+                            &terrain_manifest,
+                            &map_geometry,
+                        )
+                    }
+                }
+                Goal::Visit(structure_id) => CurrentAction::find_partner(
+                    *structure_id,
+                    unit_pos,
+                    facing,
+                    &structure_targets.partner,
                     &signals,
                     rng,
                     &item_manifest,
@@ -212,6 +324,44 @@ pub(super) fn choose_actions(
                     &terrain_manifest,
                     &map_geometry,
                 ),
+                Goal::Hunt(prey_id) => CurrentAction::hunt(
+                    *prey_id,
+                    unit_entity,
+                    unit_pos,
+                    facing,
+                    &prey_query,
+                    &signals,
+                    rng,
+                    &item_manifest,
+                    &terrain_query,
+                    &terrain_manifest,
+                    &map_geometry,
+                ),
+                Goal::Satisfy(need_id) => match need_manifest.get(*need_id).satisfier {
+                    NeedSatisfier::Condition(NeedCondition::Company) => {
+                        CurrentAction::seek_company(
+                            unit_id,
+                            previous_action,
+                            unit_pos,
+                            facing,
+                            &signals,
+                            rng,
+                            &item_manifest,
+                            &terrain_query,
+                            &terrain_manifest,
+                            &map_geometry,
+                        )
+                    }
+                    // Shelter can't be sensed from afar, but structures are common, so just look around
+                    _ => CurrentAction::wander(
+                        previous_action,
+                        unit_pos,
+                        &map_geometry,
+                        &terrain_query,
+                        &terrain_manifest,
+                        rng,
+                    ),
+                },
                 Goal::Breathe => CurrentAction::find_oxygen(
                     unit_pos,
                     facing,
@@ -251,19 +401,23 @@ pub(super) fn start_actions(
 /// Exhaustively handles the cleanup for each planned action
 pub(super) fn finish_actions(
     mut unit_query: Query<ActionDataQuery>,
-    mut inventory_query: Query<
+    mut inventory_query: Query<(
         AnyOf<(
             &mut InputInventory,
             &mut OutputInventory,
             &mut StorageInventory,
             &mut Litter,
         )>,
-    >,
+        Option<&Genome>,
+    )>,
     mut workplace_query: Query<(&CraftingState, &mut WorkersPresent)>,
     // This must be compatible with unit_query
-    structure_query: Query<&VoxelPos, (With<Id<Structure>>, Without<Goal>)>,
+    structure_query: Query<(&VoxelPos, &Id<Structure>, Has<Organism>), Without<Goal>>,
+    mut durability_query: Query<(&Id<Structure>, &mut DurabilityPool)>,
     item_manifest: Res<ItemManifest>,
     unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    need_manifest: Res<NeedManifest>,
     signals: Res<Signals>,
     map_geometry: Res<MapGeometry>,
     mut commands: Commands,
@@ -289,11 +443,16 @@ pub(super) fn finish_actions(
                     item_kind,
                     output_entity,
                 } => {
+                    let previous_goal = unit.goal.clone();
+
                     if let Ok((
-                        _,
-                        mut maybe_output_inventory,
-                        mut maybe_storage_inventory,
-                        mut maybe_litter,
+                        (
+                            _,
+                            mut maybe_output_inventory,
+                            mut maybe_storage_inventory,
+                            mut maybe_litter,
+                        ),
+                        maybe_genome,
                     )) = inventory_query.get_mut(*output_entity)
                     {
                         *unit.goal = match unit.unit_inventory.held_item {
@@ -340,7 +499,22 @@ pub(super) fn finish_actions(
                                     match transfer_result {
                                         Ok(()) => {
                                             unit.unit_inventory.held_item = Some(item_id);
-                                            if signals.detectable(
+                                            // Only the outputs of an organism are its offspring
+                                            unit.unit_inventory.held_item_parent =
+                                                match (&maybe_output_inventory, &maybe_litter) {
+                                                    (Some(_), _) => maybe_genome.cloned(),
+                                                    (_, Some(litter)) => {
+                                                        litter.parent_genome.clone()
+                                                    }
+                                                    _ => None,
+                                                };
+                                            if let Goal::Repair(structure_id) = previous_goal {
+                                                // We picked this up to patch up a structure, so go do that
+                                                Goal::Repair(structure_id)
+                                            } else if let Goal::Satisfy(need_id) = previous_goal {
+                                                // We picked this up to meet one of our needs, so go use it
+                                                Goal::Satisfy(need_id)
+                                            } else if signals.detectable(
                                                 SignalType::item_signal_types(
                                                     *item_kind,
                                                     item_manifest,
@@ -373,7 +547,7 @@ pub(super) fn finish_actions(
                     item_kind,
                     input_entity,
                 } => {
-                    if let Ok((maybe_input_inventory, _, maybe_storage_inventory, _)) =
+                    if let Ok(((maybe_input_inventory, _, maybe_storage_inventory, _), _)) =
                         inventory_query.get_mut(*input_entity)
                     {
                         *unit.goal = match unit.unit_inventory.held_item {
@@ -458,22 +632,102 @@ pub(super) fn finish_actions(
                     }
                 }
                 UnitAction::Demolish { structure_entity } => {
-                    if let Ok(&structure_tile_pos) = structure_query.get(*structure_entity) {
-                        // FIXME: this doesn't work for structures that don't cover the origin of their footprint
+                    if let Ok((&structure_tile_pos, _, is_organism)) =
+                        structure_query.get(*structure_entity)
+                    {
                         // TODO: this should probably take time and use work?
-                        commands.despawn_structure(structure_tile_pos);
+                        if is_organism {
+                            commands.kill_organism(*structure_entity, CauseOfDeath::Demolition);
+                        } else {
+                            // FIXME: this doesn't work for structures that don't cover the origin of their footprint
+                            commands.despawn_structure(structure_tile_pos);
+                        }
                     }
 
                     // Whether we succeeded or failed, pick something else to do
                     *unit.goal = Goal::default();
                 }
+                UnitAction::Repair { structure_entity } => {
+                    if let Ok((&structure_id, mut durability_pool)) =
+                        durability_query.get_mut(*structure_entity)
+                    {
+                        if let Some(durability_data) =
+                            &structure_manifest.get(structure_id).durability
+                        {
+                            let has_materials = match durability_data.repair_material {
+                                Some(repair_material) => {
+                                    unit.unit_inventory.held_item == Some(repair_material)
+                                }
+                                None => true,
+                            };
+
+                            if has_materials {
+                                // The materials are used up in the repair
+                                if durability_data.repair_material.is_some() {
+                                    unit.unit_inventory.held_item = None;
+                                }
+
+                                let proposed =
+                                    durability_pool.current() + durability_data.repair_amount;
+                                durability_pool.set_current(proposed);
+                            }
+                        }
+                    }
+
+                    // Whether we succeeded or failed, pick something else to do
+                    *unit.goal = Goal::default();
+                }
+                UnitAction::Visit { structure_entity } => {
+                    let maybe_structure_id = structure_query
+                        .get(*structure_entity)
+                        .ok()
+                        .map(|(_, &structure_id, _)| structure_id);
+
+                    // Units keep visiting until their need is satiated, so the goal is left as is
+                    if let Goal::Satisfy(need_id) = *unit.goal {
+                        let need_data = need_manifest.get(need_id);
+
+                        if let Some(structure_id) = maybe_structure_id {
+                            if need_data.satisfier == NeedSatisfier::Structure(structure_id) {
+                                unit.needs.restore(need_id, need_data.restoration);
+                            }
+                        }
+                    } else {
+                        if let Some(structure_id) = maybe_structure_id {
+                            let maybe_visit_bonus = structure_manifest
+                                .get(structure_id)
+                                .symbiosis
+                                .as_ref()
+                                .and_then(|symbiosis| symbiosis.visit_bonus.as_ref());
+
+                            if let Some(visit_bonus) = maybe_visit_bonus {
+                                commands
+                                    .entity(*structure_entity)
+                                    .insert(Visited::new(visit_bonus));
+                            }
+                        }
+
+                        // Whether we succeeded or failed, pick something else to do
+                        *unit.goal = Goal::default();
+                    }
+                }
                 UnitAction::Eat => {
                     if let Some(held_item) = unit.unit_inventory.held_item {
                         let unit_data = unit_manifest.get(*unit.unit_id);
 
                         let diet = &unit_data.diet;
 
-                        if diet.item_kind().matches(held_item, item_manifest) {
+                        // Items consumed to meet a need don't provide any energy
+                        if let Goal::Satisfy(need_id) = *unit.goal {
+                            let need_data = need_manifest.get(need_id);
+
+                            if let NeedSatisfier::Item(item_kind) = need_data.satisfier {
+                                if item_kind.matches(held_item, item_manifest) {
+                                    unit.unit_inventory.held_item = None;
+                                    unit.needs.restore(need_id, need_data.restoration);
+                                }
+                            }
+                        } else if diet.item_kind().matches(held_item, item_manifest) {
                             unit.unit_inventory.held_item = None;
 
                             let proposed = unit.energy_pool.current() + diet.energy();
@@ -482,9 +736,14 @@ pub(super) fn finish_actions(
                         }
                     }
                 }
+                UnitAction::Attack { prey_entity } => {
+                    // Energy is transferred once the prey is actually caught
+                    commands.catch_prey(unit.entity, *prey_entity);
+                }
                 UnitAction::Abandon => {
                     if let Some(held_item) = unit.unit_inventory.held_item {
-                        commands.spawn_litter(*unit.voxel_pos, held_item);
+                        let parent_genome = unit.unit_inventory.held_item_parent.take();
+                        commands.spawn_litter(*unit.voxel_pos, held_item, parent_genome);
                         unit.unit_inventory.held_item = None;
                     } else {
                         unit.impatience.increment();
@@ -517,6 +776,8 @@ pub(super) struct ActionDataQuery {
     voxel_pos: &'static mut VoxelPos,
     /// How much energy the unit has
     energy_pool: &'static mut EnergyPool,
+    /// How well the unit's other needs are met
+    needs: &'static mut Needs,
     /// How frustrated this unit is about not being able to progress towards its goal
     impatience: &'static mut ImpatiencePool,
     /// The direction this unit is facing
@@ -553,6 +814,16 @@ pub(super) enum UnitAction {
         /// The structure to work at.
         structure_entity: Entity,
     },
+    /// Attempt to repair the provided `structure_entity`
+    Repair {
+        /// The structure to repair.
+        structure_entity: Entity,
+    },
+    /// Visit the provided `structure_entity`, which this unit has a symbiotic relationship with
+    Visit {
+        /// The structure to visit.
+        structure_entity: Entity,
+    },
     /// Spin left or right.
     Spin {
         /// The direction to turn in.
@@ -562,6 +833,11 @@ pub(super) enum UnitAction {
     MoveForward,
     /// Eats one of the currently held object
     Eat,
+    /// Attempt to catch and eat the provided `prey_entity`
+    Attack {
+        /// The unit being hunted.
+        prey_entity: Entity,
+    },
     /// Abandon whatever you are currently holding, dropping it on the ground
     Abandon,
 }
@@ -606,9 +882,16 @@ impl UnitAction {
             UnitAction::Demolish { structure_entity } => {
                 format!("Demolishing {structure_entity:?}")
             }
+            UnitAction::Repair { structure_entity } => {
+                format!("Repairing {structure_entity:?}")
+            }
+            UnitAction::Visit { structure_entity } => {
+                format!("Visiting {structure_entity:?}")
+            }
             UnitAction::Spin { rotation_direction } => format!("Spinning {rotation_direction}"),
             UnitAction::MoveForward => "Moving forward".to_string(),
             UnitAction::Eat => "Eating".to_string(),
+            UnitAction::Attack { prey_entity } => format!("Attacking {prey_entity:?}"),
             UnitAction::Abandon => "Abandoning held object".to_string(),
         }
     }
@@ -622,7 +905,10 @@ impl UnitAction {
             UnitAction::Abandon => 0.2,
             UnitAction::Work { .. } => 0.1,
             UnitAction::Demolish { .. } => 0.1,
+            UnitAction::Repair { .. } => 0.5,
+            UnitAction::Visit { .. } => 0.5,
             UnitAction::Eat => 0.3,
+            UnitAction::Attack { .. } => 0.5,
             UnitAction::Idle => 0.1,
             UnitAction::Spin { .. } => 0.1,
             UnitAction::MoveForward => 0.3,
@@ -676,6 +962,11 @@ impl CurrentAction {
         &self.action
     }
 
+    /// Did this action just start?
+    pub(super) fn just_started(&self) -> bool {
+        self.just_started
+    }
+
     /// Have we waited long enough to perform this action?
     pub(super) fn finished(&self) -> bool {
         self.timer.finished()
@@ -932,6 +1223,187 @@ impl CurrentAction {
         }
     }
 
+    /// Attempt to find a damaged structure of type `structure_id` to repair
+    fn find_repair_site(
+        structure_id: Id<Structure>,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        repair_query: &RepairQuery,
+        signals: &Signals,
+        rng: &mut ThreadRng,
+        item_manifest: &ItemManifest,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let ahead = unit_pos.neighbor(facing.direction);
+        if let Some(repair_site) =
+            repair_query.needs_repair(unit_pos, ahead, structure_id, map_geometry)
+        {
+            CurrentAction::repair(repair_site)
+        } else if let Some(repair_site) =
+            repair_query.needs_repair(unit_pos, unit_pos, structure_id, map_geometry)
+        {
+            CurrentAction::repair(repair_site)
+        } else {
+            let mut repair_sites: Vec<(Entity, VoxelPos)> = Vec::new();
+
+            for neighbor in unit_pos.reachable_neighbors() {
+                if let Some(repair_site) =
+                    repair_query.needs_repair(unit_pos, neighbor, structure_id, map_geometry)
+                {
+                    repair_sites.push((repair_site, neighbor));
+                }
+            }
+
+            if let Some(chosen_repair_site) = repair_sites.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    chosen_repair_site.1,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else if let Some(upstream) = signals.upstream(
+                unit_pos,
+                &Goal::Repair(structure_id),
+                item_manifest,
+                map_geometry,
+            ) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    upstream,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else {
+                CurrentAction::idle()
+            }
+        }
+    }
+
+    /// Attempt to find a structure of type `structure_id` that is waiting for a visit from its partners
+    fn find_partner(
+        structure_id: Id<Structure>,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        partner_query: &PartnerQuery,
+        signals: &Signals,
+        rng: &mut ThreadRng,
+        item_manifest: &ItemManifest,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let ahead = unit_pos.neighbor(facing.direction);
+        if let Some(partner) =
+            partner_query.awaiting_visit(unit_pos, ahead, structure_id, map_geometry)
+        {
+            CurrentAction::visit(partner)
+        } else if let Some(partner) =
+            partner_query.awaiting_visit(unit_pos, unit_pos, structure_id, map_geometry)
+        {
+            CurrentAction::visit(partner)
+        } else {
+            let mut partners: Vec<(Entity, VoxelPos)> = Vec::new();
+
+            for neighbor in unit_pos.reachable_neighbors() {
+                if let Some(partner) =
+                    partner_query.awaiting_visit(unit_pos, neighbor, structure_id, map_geometry)
+                {
+                    partners.push((partner, neighbor));
+                }
+            }
+
+            if let Some(chosen_partner) = partners.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    chosen_partner.1,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else if let Some(upstream) = signals.upstream(
+                unit_pos,
+                &Goal::Visit(structure_id),
+                item_manifest,
+                map_geometry,
+            ) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    upstream,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else {
+                CurrentAction::idle()
+            }
+        }
+    }
+
+    /// Attempt to catch a unit of type `prey_id`, following its scent if none are in reach
+    fn hunt(
+        prey_id: Id<Unit>,
+        predator: Entity,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        prey_query: &PreyQuery,
+        signals: &Signals,
+        rng: &mut ThreadRng,
+        item_manifest: &ItemManifest,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        map_geometry: &MapGeometry,
+    ) -> CurrentAction {
+        let ahead = unit_pos.neighbor(facing.direction);
+        if let Some(prey) = prey_query.find_prey(predator, unit_pos, ahead, prey_id) {
+            CurrentAction::attack(prey)
+        } else if let Some(prey) = prey_query.find_prey(predator, unit_pos, unit_pos, prey_id) {
+            CurrentAction::attack(prey)
+        } else {
+            let mut prey_positions: Vec<VoxelPos> = Vec::new();
+
+            for neighbor in unit_pos.reachable_neighbors() {
+                if prey_query
+                    .find_prey(predator, unit_pos, neighbor, prey_id)
+                    .is_some()
+                {
+                    prey_positions.push(neighbor);
+                }
+            }
+
+            if let Some(&prey_pos) = prey_positions.choose(rng) {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    prey_pos,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else if let Some(upstream) =
+                signals.upstream(unit_pos, &Goal::Hunt(prey_id), item_manifest, map_geometry)
+            {
+                CurrentAction::move_or_spin(
+                    unit_pos,
+                    upstream,
+                    facing,
+                    terrain_query,
+                    terrain_manifest,
+                    map_geometry,
+                )
+            } else {
+                CurrentAction::idle()
+            }
+        }
+    }
+
     /// Spins 60 degrees left or right.
     pub(super) fn spin(rotation_direction: RotationDirection) -> Self {
         CurrentAction::new(UnitAction::Spin { rotation_direction })
@@ -1098,6 +1570,21 @@ impl CurrentAction {
         CurrentAction::new(UnitAction::Demolish { structure_entity })
     }
 
+    /// Repair the specified structure
+    pub(super) fn repair(structure_entity: Entity) -> Self {
+        CurrentAction::new(UnitAction::Repair { structure_entity })
+    }
+
+    /// Visit the specified symbiotic partner
+    pub(super) fn visit(structure_entity: Entity) -> Self {
+        CurrentAction::new(UnitAction::Visit { structure_entity })
+    }
+
+    /// Attack the specified unit
+    pub(super) fn attack(prey_entity: Entity) -> Self {
+        CurrentAction::new(UnitAction::Attack { prey_entity })
+    }
+
     /// Drops the currently held item on the ground.
     ///
     /// If we cannot, wander around instead.
@@ -1185,6 +1672,45 @@ impl CurrentAction {
         CurrentAction::idle()
     }
 
+    /// Seek out other units of the same species, following their [`SignalType::Unit`] signal.
+    ///
+    /// If no other units can be detected, wander instead.
+    fn seek_company(
+        unit_id: Id<Unit>,
+        previous_action: UnitAction,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        signals: &Signals,
+        rng: &mut ThreadRng,
+        item_manifest: &ItemManifest,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        map_geometry: &MapGeometry,
+    ) -> Self {
+        // Avoiding crowds follows the same signal, just in the opposite direction
+        if let Some(upstream) =
+            signals.upstream(unit_pos, &Goal::Avoid(unit_id), item_manifest, map_geometry)
+        {
+            CurrentAction::move_or_spin(
+                unit_pos,
+                upstream,
+                facing,
+                terrain_query,
+                terrain_manifest,
+                map_geometry,
+            )
+        } else {
+            CurrentAction::wander(
+                previous_action,
+                unit_pos,
+                map_geometry,
+                terrain_query,
+                terrain_manifest,
+                rng,
+            )
+        }
+    }
+
     /// Attempts to move to shallower water.
     fn find_oxygen(
         current_tile: VoxelPos,
@@ -1231,6 +1757,19 @@ impl CurrentAction {
     }
 }
 
+/// The queries needed to find structures that units can demolish, repair or visit.
+///
+/// These are grouped together to keep [`choose_actions`] within Bevy's limit on system parameters.
+#[derive(SystemParam)]
+pub(super) struct StructureTargetQuery<'w, 's> {
+    /// Structures that need to be demolished.
+    demolition: DemolitionQuery<'w, 's>,
+    /// Structures that need to be repaired.
+    repair: RepairQuery<'w, 's>,
+    /// Structures that are waiting for their symbiotic partners to visit.
+    partner: PartnerQuery<'w, 's>,
+}
+
 /// A query about the [`CraftingState`] of a structure that might need work done.
 #[derive(SystemParam)]
 pub(crate) struct WorkplaceQuery<'w, 's> {
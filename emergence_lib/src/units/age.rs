@@ -9,7 +9,13 @@ use bevy::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::simulation::time::{Days, InGameTime};
+use crate::{
+    organisms::{
+        death::{CauseOfDeath, DeathCommandsExt},
+        disease::{disease_manifest::DiseaseManifest, Infections},
+    },
+    simulation::time::{Days, InGameTime},
+};
 
 /// The age of a unit, in in-game days.
 #[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -55,20 +61,27 @@ impl Display for Age {
 }
 
 /// Advances the age of all units by the elapsed time and kills them if they are too old.
+///
+/// Diseases that shorten lifespans cause infected units to age faster.
 pub(super) fn aging(
     mut commands: Commands,
     time: Res<Time>,
     in_game_time: Res<InGameTime>,
-    mut query: Query<(&mut Age, Entity)>,
+    disease_manifest: Res<DiseaseManifest>,
+    mut query: Query<(&mut Age, Entity, Option<&Infections>)>,
 ) {
     let delta_time = time.delta().as_secs_f32();
     let delta_days = Days(delta_time / in_game_time.seconds_per_day());
 
-    for (mut age, entity) in query.iter_mut() {
-        age.current += delta_days;
+    for (mut age, entity, maybe_infections) in query.iter_mut() {
+        let lifespan =
+            maybe_infections.map_or(1.0, |infections| infections.lifespan(&disease_manifest));
+
+        // Guard against diseases that would otherwise cause instant death
+        age.current += delta_days / lifespan.max(f32::EPSILON);
 
         if age.current > age.max {
-            commands.entity(entity).despawn_recursive();
+            commands.kill_organism(entity, CauseOfDeath::OldAge);
         }
     }
 }
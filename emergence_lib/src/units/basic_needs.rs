@@ -6,11 +6,13 @@ use serde::{Deserialize, Serialize};
 use crate::{
     asset_management::manifest::Id,
     crafting::item_tags::ItemKind,
+    geometry::VoxelPos,
     items::item_manifest::{Item, ItemManifest},
     organisms::{
         energy::{Energy, EnergyPool},
         oxygen::OxygenPool,
     },
+    signals::{SignalStrength, SignalType, Signals},
 };
 
 use super::{
@@ -26,6 +28,10 @@ pub struct Diet {
     item_kind: ItemKind,
     /// The amount of energy restored per item destroyed
     energy: Energy,
+    /// The species of unit that can be hunted for food.
+    ///
+    /// When hunted, the prey's remaining energy is transferred to the predator.
+    prey: Vec<Id<Unit>>,
 }
 
 impl Diet {
@@ -35,6 +41,7 @@ impl Diet {
         Diet {
             item_kind: ItemKind::Single(Id::from_name(item_name.to_string())),
             energy: Energy(10.),
+            prey: Vec::new(),
         }
     }
 }
@@ -46,6 +53,8 @@ pub struct RawDiet {
     item: String,
     /// The amount of energy restored per item destroyed
     energy: Energy,
+    /// The names of the species of unit that can be hunted for food
+    prey: Option<Vec<String>>,
 }
 
 impl RawDiet {
@@ -54,8 +63,15 @@ impl RawDiet {
         Self {
             item: item.to_string(),
             energy: Energy(energy),
+            prey: None,
         }
     }
+
+    /// Allows units with this diet to hunt the provided species of unit.
+    pub fn with_prey(mut self, prey: &[&str]) -> Self {
+        self.prey = Some(prey.iter().map(|name| name.to_string()).collect());
+        self
+    }
 }
 
 impl From<RawDiet> for Diet {
@@ -63,6 +79,12 @@ impl From<RawDiet> for Diet {
         Diet {
             item_kind: ItemKind::Single(Id::from_name(raw_diet.item)),
             energy: raw_diet.energy,
+            prey: raw_diet
+                .prey
+                .unwrap_or_default()
+                .into_iter()
+                .map(Id::from_name)
+                .collect(),
         }
     }
 }
@@ -73,6 +95,7 @@ impl Diet {
         Diet {
             item_kind: ItemKind::Single(item),
             energy,
+            prey: Vec::new(),
         }
     }
 
@@ -86,6 +109,25 @@ impl Diet {
         self.energy
     }
 
+    /// Can units with this diet hunt units of the provided species?
+    pub(crate) fn is_prey(&self, unit_id: Id<Unit>) -> bool {
+        self.prey.contains(&unit_id)
+    }
+
+    /// Returns the species of prey whose signal is strongest at `voxel_pos`, if any can be detected.
+    pub(super) fn strongest_prey_signal(
+        &self,
+        voxel_pos: VoxelPos,
+        signals: &Signals,
+    ) -> Option<Id<Unit>> {
+        self.prey
+            .iter()
+            .map(|&prey_id| (prey_id, signals.get(SignalType::Unit(prey_id), voxel_pos)))
+            .filter(|(_, signal_strength)| *signal_strength > SignalStrength::ZERO)
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(prey_id, _)| prey_id)
+    }
+
     /// Pretty formatting for this type
     pub(crate) fn display(&self, item_manifest: &ItemManifest) -> String {
         format!(
@@ -109,12 +151,15 @@ pub(super) fn check_for_oxygen(mut unit_query: Query<(&mut Goal, &OxygenPool)>)
     }
 }
 
-/// Swaps the goal to [`Goal::Eat`] when energy is low
+/// Swaps the goal to [`Goal::Eat`] or [`Goal::Hunt`] when energy is low
+///
+/// Units will hunt if they can detect any of their prey nearby, and otherwise look for food items.
 pub(super) fn check_for_hunger(
-    mut unit_query: Query<(&mut Goal, &EnergyPool, &Id<Unit>, &UnitInventory)>,
+    mut unit_query: Query<(&mut Goal, &EnergyPool, &Id<Unit>, &UnitInventory, &VoxelPos)>,
     unit_manifest: Res<UnitManifest>,
+    signals: Res<Signals>,
 ) {
-    for (mut goal, energy_pool, unit_id, unit_inventory) in unit_query.iter_mut() {
+    for (mut goal, energy_pool, unit_id, unit_inventory, &voxel_pos) in unit_query.iter_mut() {
         if energy_pool.is_hungry() {
             // Make sure to put down any item we're holding before eating
             if let Some(item) = unit_inventory.held_item {
@@ -124,11 +169,49 @@ pub(super) fn check_for_hunger(
             }
 
             let diet = &unit_manifest.get(*unit_id).diet;
-            *goal = Goal::Eat(diet.item_kind);
-        } else if matches!(*goal, Goal::Eat(..)) && energy_pool.is_satiated() {
+            *goal = match diet.strongest_prey_signal(voxel_pos, &signals) {
+                Some(prey_id) => Goal::Hunt(prey_id),
+                None => Goal::Eat(diet.item_kind),
+            };
+        } else if matches!(*goal, Goal::Eat(..) | Goal::Hunt(..)) && energy_pool.is_satiated() {
             *goal = Goal::Wander {
                 remaining_actions: None,
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::DiscreteHeight;
+    use hexx::Hex;
+
+    #[test]
+    fn prey_are_converted_from_raw_diet() {
+        let diet: Diet = RawDiet::new("acacia_leaf", 10.).with_prey(&["ant"]).into();
+
+        assert!(diet.is_prey(Id::from_name("ant".to_string())));
+        assert!(!diet.is_prey(Id::from_name("basket_crab".to_string())));
+    }
+
+    #[test]
+    fn strongest_prey_signal_is_chosen() {
+        let diet: Diet = RawDiet::new("acacia_leaf", 10.)
+            .with_prey(&["ant", "basket_crab"])
+            .into();
+        let ant = Id::from_name("ant".to_string());
+        let crab = Id::from_name("basket_crab".to_string());
+        let voxel_pos = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(0),
+        };
+
+        let mut signals = Signals::default();
+        assert_eq!(diet.strongest_prey_signal(voxel_pos, &signals), None);
+
+        signals.add_signal(SignalType::Unit(ant), voxel_pos, SignalStrength::new(1.));
+        signals.add_signal(SignalType::Unit(crab), voxel_pos, SignalStrength::new(5.));
+        assert_eq!(diet.strongest_prey_signal(voxel_pos, &signals), Some(crab));
+    }
+}
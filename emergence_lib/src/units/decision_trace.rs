@@ -0,0 +1,480 @@
+//! Records why units make the choices that they do, so that surprising behavior can be diagnosed.
+//!
+//! Each unit keeps a [`DecisionTrace`] of its most recent goal decisions.
+//! Each decision stores the signals and candidate goals that were considered,
+//! along with the actions taken in pursuit of the chosen goal and any growing impatience.
+//! The trace of the selected unit is shown in the UI, and can be exported to disk as text.
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    asset_management::manifest::Id,
+    items::item_manifest::ItemManifest,
+    player_interaction::{selection::CurrentSelection, PlayerAction},
+    signals::LocalSignals,
+    simulation::time::InGameTime,
+    structures::structure_manifest::StructureManifest,
+    terrain::terrain_manifest::TerrainManifest,
+    utils::persistence::{write_file, PersistenceError},
+};
+
+use super::{
+    actions::{CurrentAction, UnitAction},
+    goals::Goal,
+    impatience::ImpatiencePool,
+    needs::need_manifest::NeedManifest,
+    unit_manifest::{Unit, UnitManifest},
+};
+
+/// The most recent decisions made by a unit, oldest first.
+#[derive(Component, Debug, Default, Clone)]
+pub(crate) struct DecisionTrace {
+    /// The recorded decisions.
+    decisions: VecDeque<Decision>,
+    /// The impatience of the unit when it was last checked.
+    last_impatience: Option<ImpatiencePool>,
+}
+
+impl DecisionTrace {
+    /// The maximum number of decisions that are kept.
+    const MAX_DECISIONS: usize = 10;
+
+    /// The maximum number of actions that are kept for each decision.
+    const MAX_ACTIONS: usize = 16;
+
+    /// The number of decisions shown in the UI.
+    pub(crate) const DECISIONS_SHOWN: usize = 3;
+
+    /// The directory where traces are exported, relative to the working directory.
+    const EXPORT_DIRECTORY: &'static str = "decision_traces";
+
+    /// Records that a new goal was chosen, discarding the oldest decision if the trace is full.
+    ///
+    /// Repeated decisions to keep wandering are merged together,
+    /// so they don't push more interesting decisions out of the trace.
+    pub(super) fn record_decision(
+        &mut self,
+        day: f32,
+        reason: DecisionReason,
+        goal: Goal,
+        deliberation: Option<Deliberation>,
+    ) {
+        if let Some(previous) = self.decisions.back_mut() {
+            if previous.reason == reason
+                && matches!(previous.goal, Goal::Wander { .. })
+                && matches!(goal, Goal::Wander { .. })
+            {
+                previous.day = day;
+                previous.goal = goal;
+                previous.deliberation = deliberation;
+                previous.repeats += 1;
+                return;
+            }
+        }
+
+        while self.decisions.len() >= DecisionTrace::MAX_DECISIONS {
+            self.decisions.pop_front();
+        }
+
+        self.decisions.push_back(Decision {
+            day,
+            reason,
+            goal,
+            deliberation,
+            repeats: 0,
+            actions: VecDeque::new(),
+            n_actions: 0,
+            impatience: Vec::new(),
+        });
+    }
+
+    /// Records an action taken in pursuit of the current goal.
+    fn record_action(&mut self, action: UnitAction) {
+        let Some(decision) = self.decisions.back_mut() else {
+            return;
+        };
+
+        if decision.actions.len() >= DecisionTrace::MAX_ACTIONS {
+            decision.actions.pop_front();
+        }
+        decision.actions.push_back(action);
+        decision.n_actions += 1;
+    }
+
+    /// Records the unit's impatience if it has grown since it was last checked.
+    fn record_impatience(&mut self, impatience: &ImpatiencePool) {
+        let has_grown = self
+            .last_impatience
+            .as_ref()
+            .map_or(impatience.current() > 0, |last| {
+                impatience.current() > last.current()
+            });
+
+        if has_grown {
+            if let Some(decision) = self.decisions.back_mut() {
+                decision.impatience.push(impatience.clone());
+            }
+        }
+
+        self.last_impatience = Some(impatience.clone());
+    }
+
+    /// Has the unit's goal changed since the last recorded decision?
+    ///
+    /// Wandering is treated as a single goal, no matter how many actions remain.
+    fn goal_changed(&self, goal: &Goal) -> bool {
+        match self.decisions.back() {
+            Some(decision) => match (&decision.goal, goal) {
+                (Goal::Wander { .. }, Goal::Wander { .. }) => false,
+                (previous, current) => previous != current,
+            },
+            None => true,
+        }
+    }
+
+    /// Pretty formatting for this type.
+    ///
+    /// Only the most recent `max_decisions` are shown, newest first.
+    /// If `verbose` is true, the signals present for each decision and all recorded actions are included.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn display(
+        &self,
+        max_decisions: usize,
+        verbose: bool,
+        item_manifest: &ItemManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        unit_manifest: &UnitManifest,
+        need_manifest: &NeedManifest,
+    ) -> String {
+        if self.decisions.is_empty() {
+            return "No decisions recorded".to_string();
+        }
+
+        let display_goal = |goal: &Goal| {
+            goal.display(
+                item_manifest,
+                structure_manifest,
+                terrain_manifest,
+                unit_manifest,
+                need_manifest,
+            )
+        };
+
+        let mut string = String::new();
+        for decision in self.decisions.iter().rev().take(max_decisions) {
+            string += &format!(
+                "[Day {:.2}] {}: {}",
+                decision.day,
+                decision.reason,
+                display_goal(&decision.goal)
+            );
+            if decision.repeats > 0 {
+                string += &format!(" (x{})", decision.repeats + 1);
+            }
+            string += "\n";
+
+            if let Some(deliberation) = &decision.deliberation {
+                let candidates = deliberation
+                    .candidates
+                    .iter()
+                    .map(|(goal, weight)| {
+                        format!(
+                            "{} ({:.0}%)",
+                            display_goal(goal),
+                            deliberation.share(*weight) * 100.
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                string += &match candidates.is_empty() {
+                    true => "  Considered: nothing\n".to_string(),
+                    false => format!("  Considered: {}\n", candidates.join(", ")),
+                };
+
+                if verbose {
+                    let signals = deliberation.signals.display(
+                        item_manifest,
+                        structure_manifest,
+                        terrain_manifest,
+                        unit_manifest,
+                    );
+                    string += "  Signals:\n";
+                    for line in signals.lines() {
+                        string += &format!("    {line}\n");
+                    }
+                }
+            }
+
+            if !decision.impatience.is_empty() {
+                let impatience = decision
+                    .impatience
+                    .iter()
+                    .map(|impatience_pool| impatience_pool.to_string())
+                    .collect::<Vec<_>>();
+                string += &format!("  Impatience: {}\n", impatience.join(", "));
+            }
+
+            let actions: Vec<String> = match verbose {
+                true => decision
+                    .actions
+                    .iter()
+                    .map(|action| action.display(item_manifest))
+                    .collect(),
+                false => decision
+                    .actions
+                    .back()
+                    .map(|action| action.display(item_manifest))
+                    .into_iter()
+                    .collect(),
+            };
+            string += &format!(
+                "  Actions ({}): {}\n",
+                decision.n_actions,
+                actions.join(", ")
+            );
+        }
+
+        string
+    }
+
+    /// Writes the formatted `contents` of a trace to disk, named after the unit that it belongs to.
+    ///
+    /// Returns the path of the file written.
+    fn export(
+        unit_name: &str,
+        entity: Entity,
+        contents: &str,
+    ) -> Result<PathBuf, PersistenceError> {
+        let path = PathBuf::from(DecisionTrace::EXPORT_DIRECTORY)
+            .join(format!("{unit_name}_{}.txt", entity.index()));
+        write_file(&path, contents)?;
+
+        Ok(path)
+    }
+}
+
+/// A single choice of goal, and what happened while pursuing it.
+#[derive(Debug, Clone)]
+struct Decision {
+    /// The in-game day on which the decision was made.
+    day: f32,
+    /// Why a new goal was chosen.
+    reason: DecisionReason,
+    /// The goal that was chosen.
+    goal: Goal,
+    /// What was considered when choosing the goal, if the unit looked at the signals around it.
+    deliberation: Option<Deliberation>,
+    /// The number of times that this same decision was made again, immediately afterwards.
+    repeats: u32,
+    /// The most recent actions taken in pursuit of this goal.
+    actions: VecDeque<UnitAction>,
+    /// The total number of actions taken in pursuit of this goal.
+    n_actions: usize,
+    /// The unit's impatience each time it grew while pursuing this goal.
+    impatience: Vec<ImpatiencePool>,
+}
+
+/// Why a unit chose a new goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DecisionReason {
+    /// The unit finished wandering, and looked at the signals around it to pick something to do.
+    FinishedWandering,
+    /// The unit ran out of patience, and abandoned its previous goal.
+    OutOfPatience,
+    /// The goal was changed for another reason, such as completing the previous goal or becoming hungry.
+    GoalChanged,
+}
+
+impl core::fmt::Display for DecisionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            DecisionReason::FinishedWandering => "Finished wandering",
+            DecisionReason::OutOfPatience => "Out of patience",
+            DecisionReason::GoalChanged => "Goal changed",
+        };
+
+        write!(f, "{str}")
+    }
+}
+
+/// What a unit considered when choosing a new goal based on the signals around it.
+#[derive(Debug, Clone)]
+pub(super) struct Deliberation {
+    /// All of the signals at the unit's position.
+    pub(super) signals: LocalSignals,
+    /// The goals that could have been chosen, along with the relative weight of each.
+    pub(super) candidates: Vec<(Goal, f32)>,
+}
+
+impl Deliberation {
+    /// The fraction of the total weight of all candidates that `weight` makes up.
+    ///
+    /// If no candidate had any weight, nothing could have been chosen, so this is zero.
+    fn share(&self, weight: f32) -> f32 {
+        let total_weight: f32 = self.candidates.iter().map(|(_, weight)| weight).sum();
+
+        match total_weight > 0. {
+            true => weight / total_weight,
+            false => 0.,
+        }
+    }
+}
+
+/// Records each unit's impatience, before it can be reset by choosing a new goal.
+pub(super) fn record_impatience(mut unit_query: Query<(&ImpatiencePool, &mut DecisionTrace)>) {
+    for (impatience_pool, mut decision_trace) in unit_query.iter_mut() {
+        decision_trace.record_impatience(impatience_pool);
+    }
+}
+
+/// Records each unit's newly chosen actions, along with any goals that were changed outside of [`choose_goal`](super::goals::choose_goal).
+pub(super) fn record_actions(
+    mut unit_query: Query<(&Goal, &CurrentAction, &mut DecisionTrace)>,
+    in_game_time: Res<InGameTime>,
+) {
+    for (goal, current_action, mut decision_trace) in unit_query.iter_mut() {
+        if decision_trace.goal_changed(goal) {
+            decision_trace.record_decision(
+                in_game_time.elapsed_days(),
+                DecisionReason::GoalChanged,
+                goal.clone(),
+                None,
+            );
+        }
+
+        if current_action.just_started() {
+            decision_trace.record_action(current_action.action().clone());
+        }
+    }
+}
+
+/// Writes the decision trace of the selected unit to disk when prompted by player input.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn export_decision_trace(
+    player_actions: Res<ActionState<PlayerAction>>,
+    current_selection: Res<CurrentSelection>,
+    unit_query: Query<(&Id<Unit>, &DecisionTrace)>,
+    item_manifest: Res<ItemManifest>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+    unit_manifest: Res<UnitManifest>,
+    need_manifest: Res<NeedManifest>,
+) {
+    if !player_actions.just_pressed(PlayerAction::ExportDecisionTrace) {
+        return;
+    }
+
+    let CurrentSelection::Unit(unit_entity) = *current_selection else {
+        warn!("Select a unit to export its decision trace.");
+        return;
+    };
+
+    let Ok((&unit_id, decision_trace)) = unit_query.get(unit_entity) else {
+        return;
+    };
+
+    let contents = decision_trace.display(
+        DecisionTrace::MAX_DECISIONS,
+        true,
+        &item_manifest,
+        &structure_manifest,
+        &terrain_manifest,
+        &unit_manifest,
+        &need_manifest,
+    );
+
+    match DecisionTrace::export(unit_manifest.name(unit_id), unit_entity, &contents) {
+        Ok(path) => info!("Exported decision trace to {path:?}"),
+        Err(error) => error!("Failed to export decision trace: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_is_bounded() {
+        let mut trace = DecisionTrace::default();
+
+        for day in 0..DecisionTrace::MAX_DECISIONS + 5 {
+            trace.record_decision(day as f32, DecisionReason::GoalChanged, Goal::Breathe, None);
+        }
+
+        assert_eq!(trace.decisions.len(), DecisionTrace::MAX_DECISIONS);
+        assert_eq!(trace.decisions.front().unwrap().day, 5.);
+
+        for _ in 0..DecisionTrace::MAX_ACTIONS + 3 {
+            trace.record_action(UnitAction::MoveForward);
+        }
+
+        let latest = trace.decisions.back().unwrap();
+        assert_eq!(latest.actions.len(), DecisionTrace::MAX_ACTIONS);
+        assert_eq!(latest.n_actions, DecisionTrace::MAX_ACTIONS + 3);
+    }
+
+    #[test]
+    fn wandering_counts_as_a_single_goal() {
+        let mut trace = DecisionTrace::default();
+        assert!(trace.goal_changed(&Goal::default()));
+
+        trace.record_decision(
+            0.,
+            DecisionReason::FinishedWandering,
+            Goal::Wander {
+                remaining_actions: Some(3),
+            },
+            None,
+        );
+
+        assert!(!trace.goal_changed(&Goal::Wander {
+            remaining_actions: Some(2),
+        }));
+        assert!(trace.goal_changed(&Goal::Breathe));
+
+        // Deciding to keep wandering doesn't fill up the trace
+        trace.record_decision(1., DecisionReason::FinishedWandering, Goal::default(), None);
+        assert_eq!(trace.decisions.len(), 1);
+        assert_eq!(trace.decisions.back().unwrap().repeats, 1);
+    }
+
+    #[test]
+    fn only_growing_impatience_is_recorded() {
+        let mut trace = DecisionTrace::default();
+        trace.record_decision(0., DecisionReason::GoalChanged, Goal::Breathe, None);
+
+        let mut impatience_pool = ImpatiencePool::new(3);
+        trace.record_impatience(&impatience_pool);
+        impatience_pool.increment();
+        trace.record_impatience(&impatience_pool);
+        trace.record_impatience(&impatience_pool);
+        impatience_pool.reset();
+        trace.record_impatience(&impatience_pool);
+
+        let recorded = &trace.decisions.back().unwrap().impatience;
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].current(), 1);
+    }
+
+    #[test]
+    fn candidates_without_weight_have_no_share() {
+        use crate::{geometry::VoxelPos, signals::Signals};
+
+        let signals = Signals::default().all_signals_at_position(VoxelPos::default());
+        let deliberation = Deliberation {
+            signals: signals.clone(),
+            candidates: vec![(Goal::Breathe, 0.), (Goal::default(), 0.)],
+        };
+        assert_eq!(deliberation.share(0.), 0.);
+
+        let deliberation = Deliberation {
+            signals,
+            candidates: vec![(Goal::Breathe, 1.), (Goal::default(), 3.)],
+        };
+        assert_eq!(deliberation.share(1.), 0.25);
+    }
+}
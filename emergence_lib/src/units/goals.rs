@@ -11,13 +11,17 @@ use crate::construction::ghosts::WorkplaceId;
 use crate::crafting::item_tags::ItemKind;
 use crate::geometry::VoxelPos;
 use crate::items::item_manifest::ItemManifest;
+use crate::organisms::domestication::Strain;
 use crate::signals::{SignalType, Signals};
+use crate::simulation::time::InGameTime;
 use crate::structures::structure_manifest::{Structure, StructureManifest};
 use crate::terrain::terrain_manifest::TerrainManifest;
 
 use super::actions::{DeliveryMode, Purpose};
+use super::decision_trace::{DecisionReason, DecisionTrace, Deliberation};
 use super::impatience::ImpatiencePool;
 use super::item_interaction::UnitInventory;
+use super::needs::need_manifest::{Need, NeedManifest};
 use super::unit_manifest::{Unit, UnitManifest};
 use super::WanderingBehavior;
 
@@ -56,12 +60,20 @@ pub(crate) enum Goal {
     Work(WorkplaceId),
     /// Attempting to destroy a structure.
     Demolish(Id<Structure>),
+    /// Attempting to repair a damaged structure.
+    Repair(Id<Structure>),
+    /// Attempting to visit a structure that this unit has a symbiotic relationship with.
+    Visit(Id<Structure>),
     /// Attempting to feed self.
     Eat(ItemKind),
+    /// Attempting to satisfy one of this unit's other needs.
+    Satisfy(Id<Need>),
     /// Attempting to get to oxygen.
     Breathe,
     /// Trying to avoid a specific unit.
     Avoid(Id<Unit>),
+    /// Attempting to catch and eat a unit of the provided species.
+    Hunt(Id<Unit>),
 }
 
 /// The data-less version of [`Goal`].
@@ -81,10 +93,18 @@ pub(crate) enum GoalKind {
     Work,
     /// Attempting to destroy a structure.
     Demolish,
+    /// Attempting to repair a damaged structure.
+    Repair,
+    /// Attempting to visit a structure that this unit has a symbiotic relationship with.
+    Visit,
     /// Attempting to feed self.
     Eat,
+    /// Attempting to satisfy one of this unit's other needs.
+    Satisfy,
     /// Trying to avoid a specific unit.
     Avoid,
+    /// Attempting to catch and eat a specific unit.
+    Hunt,
     /// Trying to get to oxygen.
     Breathe,
 }
@@ -99,8 +119,12 @@ impl From<&Goal> for GoalKind {
             Goal::Store(_) => GoalKind::Store,
             Goal::Work(_) => GoalKind::Work,
             Goal::Demolish(_) => GoalKind::Demolish,
+            Goal::Repair(_) => GoalKind::Repair,
+            Goal::Visit(_) => GoalKind::Visit,
             Goal::Eat(_) => GoalKind::Eat,
+            Goal::Satisfy(_) => GoalKind::Satisfy,
             Goal::Avoid(_) => GoalKind::Avoid,
+            Goal::Hunt(_) => GoalKind::Hunt,
             Goal::Breathe => GoalKind::Breathe,
         }
     }
@@ -126,6 +150,8 @@ impl TryFrom<SignalType> for Goal {
             SignalType::Pull(item_kind) => Ok(Goal::Fetch(item_kind)),
             SignalType::Work(structure_id) => Ok(Goal::Work(structure_id)),
             SignalType::Demolish(structure_id) => Ok(Goal::Demolish(structure_id)),
+            SignalType::Repair(structure_id) => Ok(Goal::Repair(structure_id)),
+            SignalType::Visit(structure_id) => Ok(Goal::Visit(structure_id)),
             SignalType::Contains(_) => Err(()),
             SignalType::Stores(_) => Err(()),
             SignalType::Unit(unit) => Ok(Goal::Avoid(unit)),
@@ -144,8 +170,13 @@ impl Goal {
             Goal::Store(_) => Some(DeliveryMode::DropOff),
             Goal::Work(_) => None,
             Goal::Demolish(_) => None,
+            Goal::Repair(_) => None,
+            Goal::Visit(_) => None,
             Goal::Eat(_) => Some(DeliveryMode::PickUp),
+            // Depends on what satisfies the need
+            Goal::Satisfy(_) => None,
             Goal::Avoid(_) => None,
+            Goal::Hunt(_) => None,
             Goal::Breathe => None,
         }
     }
@@ -160,9 +191,13 @@ impl Goal {
             Goal::Store(_) => Purpose::Instrumental,
             Goal::Work(_) => Purpose::Intrinsic,
             Goal::Demolish(_) => Purpose::Intrinsic,
+            Goal::Repair(_) => Purpose::Intrinsic,
+            Goal::Visit(_) => Purpose::Intrinsic,
             Goal::Eat(_) => Purpose::Instrumental,
+            Goal::Satisfy(_) => Purpose::Instrumental,
             Goal::Breathe => Purpose::Instrumental,
             Goal::Avoid(_) => Purpose::Instrumental,
+            Goal::Hunt(_) => Purpose::Instrumental,
         }
     }
 
@@ -173,6 +208,7 @@ impl Goal {
         structure_manifest: &StructureManifest,
         terrain_manifest: &TerrainManifest,
         unit_manifest: &UnitManifest,
+        need_manifest: &NeedManifest,
     ) -> String {
         match self {
             Goal::Wander { remaining_actions } => format!(
@@ -192,8 +228,16 @@ impl Goal {
             Goal::Demolish(structure) => {
                 format!("Demolish {}", structure_manifest.name(*structure))
             }
+            Goal::Repair(structure) => {
+                format!("Repair {}", structure_manifest.name(*structure))
+            }
+            Goal::Visit(structure) => {
+                format!("Visit {}", structure_manifest.name(*structure))
+            }
             Goal::Eat(item_kind) => format!("Eat {}", item_manifest.name_of_kind(*item_kind)),
+            Goal::Satisfy(need) => format!("Satisfy {}", need_manifest.name(*need)),
             Goal::Avoid(unit) => format!("Avoid {}", unit_manifest.name(*unit)),
+            Goal::Hunt(unit) => format!("Hunt {}", unit_manifest.name(*unit)),
             Goal::Breathe => "Breathe".to_string(),
         }
     }
@@ -207,18 +251,34 @@ pub(super) fn choose_goal(
         &mut ImpatiencePool,
         &UnitInventory,
         &Id<Unit>,
+        &Strain,
+        &mut DecisionTrace,
     )>,
     unit_manifest: Res<UnitManifest>,
     item_manifest: Res<ItemManifest>,
+    structure_manifest: Res<StructureManifest>,
     signals: Res<Signals>,
+    in_game_time: Res<InGameTime>,
 ) {
     let rng = &mut thread_rng();
+    let today = in_game_time.elapsed_days();
 
-    for (&voxel_pos, mut goal, mut impatience_pool, unit_inventory, &unit_id) in
-        units_query.iter_mut()
+    for (
+        &voxel_pos,
+        mut goal,
+        mut impatience_pool,
+        unit_inventory,
+        &unit_id,
+        &strain,
+        mut decision_trace,
+    ) in units_query.iter_mut()
     {
+        let mut decision_reason = None;
+
         // If we're out of patience, give up and choose a new goal
         if impatience_pool.is_full() {
+            decision_reason = Some(DecisionReason::OutOfPatience);
+
             // If you're holding something, try to put it away nicely
             *goal = if let Some(held_item) = unit_inventory.held_item {
                 match &*goal {
@@ -247,17 +307,31 @@ pub(super) fn choose_goal(
 
         if let Goal::Wander { remaining_actions } = *goal {
             let wandering_behavior = &unit_manifest.get(unit_id).wandering_behavior;
-            *goal = compute_new_goal(
+            let (new_goal, maybe_deliberation) = compute_new_goal(
                 unit_id,
+                strain,
                 remaining_actions,
                 voxel_pos,
                 wandering_behavior,
                 rng,
                 &signals,
+                &unit_manifest,
+                &structure_manifest,
             );
+            *goal = new_goal;
+
+            if maybe_deliberation.is_some() {
+                decision_reason.get_or_insert(DecisionReason::FinishedWandering);
+            }
 
             // Reset impatience when we choose a new goal
             impatience_pool.reset();
+
+            if let Some(reason) = decision_reason {
+                decision_trace.record_decision(today, reason, goal.clone(), maybe_deliberation);
+            }
+        } else if let Some(reason) = decision_reason {
+            decision_trace.record_decision(today, reason, goal.clone(), None);
         }
     }
 }
@@ -266,14 +340,19 @@ pub(super) fn choose_goal(
 ///
 // By default, goals are reset to wandering when completed.
 /// If anything fails, just keep wandering for now.
+///
+/// If the signals were used to choose a new goal, what was considered is returned as well.
 fn compute_new_goal(
     unit_id: Id<Unit>,
+    strain: Strain,
     mut remaining_actions: Option<u16>,
     voxel_pos: VoxelPos,
     wandering_behavior: &WanderingBehavior,
     rng: &mut ThreadRng,
     signals: &Signals,
-) -> Goal {
+    unit_manifest: &UnitManifest,
+    structure_manifest: &StructureManifest,
+) -> (Goal, Option<Deliberation>) {
     // When we first get a wandering goal, pick a number of actions to take before picking a new goal.
     if remaining_actions.is_none() {
         let number_of_actions = wandering_behavior.sample(rng);
@@ -283,9 +362,10 @@ fn compute_new_goal(
     // If we have actions left while wandering, use them up before picking a new goal.
     if let Some(n) = remaining_actions {
         if n != 0 {
-            return Goal::Wander {
+            let goal = Goal::Wander {
                 remaining_actions: Some(n - 1),
             };
+            return (goal, None);
         }
     }
 
@@ -293,28 +373,121 @@ fn compute_new_goal(
     let current_signals = signals.all_signals_at_position(voxel_pos);
     let mut goal_relevant_signals = current_signals.goal_relevant_signals();
 
-    // Only try to avoid units of the same type
+    // Wild units ignore the signals that coordinate the colony
+    goal_relevant_signals.retain(|(signal_type, _)| strain.responds_to(**signal_type));
+
+    // Only try to avoid units of the same type, or those that might eat us
     goal_relevant_signals.retain(|(signal_type, _)| {
         if let SignalType::Unit(signal_unit_id) = signal_type {
-            *signal_unit_id == unit_id
+            *signal_unit_id == unit_id || unit_manifest.get(*signal_unit_id).diet.is_prey(unit_id)
         } else {
             true
         }
     });
 
-    if let Ok(goal_weights) = WeightedIndex::new(
-        goal_relevant_signals
-            .iter()
-            .map(|(_type, strength)| strength.value()),
-    ) {
-        let selected_goal_index = goal_weights.sample(rng);
-        if let Some(selected_signal) = goal_relevant_signals.get(selected_goal_index) {
-            let selected_signal_type = *selected_signal.0;
-            selected_signal_type.try_into().unwrap()
+    // Only visit structures that we have a symbiotic relationship with
+    goal_relevant_signals.retain(|(signal_type, _)| {
+        if let SignalType::Visit(structure_id) = signal_type {
+            structure_manifest
+                .get(*structure_id)
+                .symbiosis
+                .as_ref()
+                .is_some_and(|symbiosis| symbiosis.is_partner(unit_id))
         } else {
-            Goal::Wander { remaining_actions }
+            true
         }
-    } else {
-        Goal::Wander { remaining_actions }
+    });
+
+    let candidates: Vec<(Goal, f32)> = goal_relevant_signals
+        .into_iter()
+        .map(|(&signal_type, strength)| (signal_type.try_into().unwrap(), strength.value()))
+        .collect();
+
+    let goal = match WeightedIndex::new(candidates.iter().map(|(_goal, weight)| *weight)) {
+        Ok(goal_weights) => candidates[goal_weights.sample(rng)].0.clone(),
+        Err(_) => Goal::Wander { remaining_actions },
+    };
+
+    let deliberation = Deliberation {
+        signals: current_signals,
+        candidates,
+    };
+
+    (goal, Some(deliberation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SignalStrength;
+    use crate::units::basic_needs::{Diet, RawDiet};
+    use crate::units::unit_manifest::UnitData;
+
+    /// A manifest where "spiders" hunt "ants", and "beetles" are harmless.
+    fn unit_manifest() -> UnitManifest {
+        let mut unit_manifest = UnitManifest::new();
+        for name in ["ant", "beetle"] {
+            unit_manifest.insert(
+                name.to_string(),
+                UnitData::simple(name, Diet::simple("acacia_leaf")),
+            );
+        }
+        let spider_diet: Diet = RawDiet::new("acacia_leaf", 10.).with_prey(&["ant"]).into();
+        unit_manifest.insert(
+            "spider".to_string(),
+            UnitData::simple("spider", spider_diet),
+        );
+        unit_manifest
+    }
+
+    /// The goal chosen by an ant that has finished wandering, with only the provided unit signal nearby.
+    fn goal_near(signal_unit: &str) -> Goal {
+        let ant = Id::from_name("ant".to_string());
+        let mut signals = Signals::default();
+        signals.add_signal(
+            SignalType::Unit(Id::from_name(signal_unit.to_string())),
+            VoxelPos::ZERO,
+            SignalStrength::new(1.),
+        );
+
+        let (goal, _deliberation) = compute_new_goal(
+            ant,
+            Strain::Wild,
+            Some(0),
+            VoxelPos::ZERO,
+            &WanderingBehavior::default(),
+            &mut thread_rng(),
+            &signals,
+            &unit_manifest(),
+            &StructureManifest::new(),
+        );
+
+        goal
+    }
+
+    #[test]
+    fn prey_flee_from_predators() {
+        assert_eq!(
+            goal_near("spider"),
+            Goal::Avoid(Id::from_name("spider".to_string()))
+        );
+    }
+
+    #[test]
+    fn units_avoid_their_own_kind() {
+        assert_eq!(
+            goal_near("ant"),
+            Goal::Avoid(Id::from_name("ant".to_string()))
+        );
+    }
+
+    #[test]
+    fn harmless_units_are_ignored() {
+        assert_eq!(
+            goal_near("beetle"),
+            Goal::Wander {
+                remaining_actions: Some(0)
+            }
+        );
     }
 }
@@ -0,0 +1,220 @@
+//! Predators hunt other units for food, absorbing their remaining energy.
+
+use bevy::{
+    ecs::system::{Command, SystemParam},
+    prelude::*,
+};
+use leafwing_abilities::prelude::Pool;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{Height, VoxelPos},
+    organisms::{
+        death::{CauseOfDeath, KillOrganismCommand},
+        energy::EnergyPool,
+        lifecycle::Lifecycle,
+    },
+};
+
+use super::unit_manifest::Unit;
+
+/// A query for the units that can be hunted.
+#[derive(SystemParam)]
+pub(crate) struct PreyQuery<'w, 's> {
+    /// The contained query type.
+    query: Query<'w, 's, (Entity, &'static VoxelPos, &'static Id<Unit>)>,
+}
+
+impl<'w, 's> PreyQuery<'w, 's> {
+    /// Is there a unit of type `prey_id` at `target` that can be caught by `predator` from `current`?
+    ///
+    /// If so, returns `Some(matching_prey_entity)`.
+    pub(crate) fn find_prey(
+        &self,
+        predator: Entity,
+        current: VoxelPos,
+        target: VoxelPos,
+        prey_id: Id<Unit>,
+    ) -> Option<Entity> {
+        // This is only a viable target if the unit can reach it!
+        if current.abs_height_diff(target) > Height::MAX_STEP {
+            return None;
+        }
+
+        // PERF: units are not indexed by position, so we have to scan them all
+        self.query
+            .iter()
+            .find(|(entity, &voxel_pos, &unit_id)| {
+                *entity != predator && voxel_pos == target && unit_id == prey_id
+            })
+            .map(|(entity, ..)| entity)
+    }
+}
+
+/// An extension trait for [`Commands`] for hunting.
+pub(crate) trait HuntingCommandsExt {
+    /// The `predator` attempts to kill and eat the `prey`.
+    ///
+    /// This fails if the prey has already died, or is no longer adjacent to the predator.
+    fn catch_prey(&mut self, predator: Entity, prey: Entity);
+}
+
+impl HuntingCommandsExt for Commands<'_, '_> {
+    fn catch_prey(&mut self, predator: Entity, prey: Entity) {
+        self.add(CatchPreyCommand { predator, prey });
+    }
+}
+
+/// A [`Command`] used to kill prey and transfer its energy to the predator.
+struct CatchPreyCommand {
+    /// The unit doing the hunting.
+    predator: Entity,
+    /// The unit being hunted.
+    prey: Entity,
+}
+
+impl Command for CatchPreyCommand {
+    fn apply(self, world: &mut World) {
+        // The prey may have been caught by someone else, or died of natural causes
+        let Some(prey) = world.get_entity(self.prey) else {
+            return;
+        };
+        let (Some(&prey_pos), Some(prey_energy_pool)) =
+            (prey.get::<VoxelPos>(), prey.get::<EnergyPool>())
+        else {
+            return;
+        };
+        let prey_energy = prey_energy_pool.current();
+
+        let Some(mut predator) = world.get_entity_mut(self.predator) else {
+            return;
+        };
+        let Some(&predator_pos) = predator.get::<VoxelPos>() else {
+            return;
+        };
+
+        // The prey got away!
+        if predator_pos.hex.unsigned_distance_to(prey_pos.hex) > 1 {
+            return;
+        }
+
+        if let Some(mut energy_pool) = predator.get_mut::<EnergyPool>() {
+            let proposed = energy_pool.current() + prey_energy;
+            energy_pool.set_current(proposed);
+        }
+
+        if let Some(mut lifecycle) = predator.get_mut::<Lifecycle>() {
+            lifecycle.record_energy_gained(prey_energy);
+        }
+
+        KillOrganismCommand {
+            entity: self.prey,
+            cause: CauseOfDeath::Predation,
+        }
+        .apply(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::Hex;
+
+    use super::*;
+    use crate::{
+        geometry::DiscreteHeight,
+        organisms::{death::OrganismDied, energy::Energy, OrganismId},
+        units::{
+            basic_needs::{Diet, RawDiet},
+            unit_manifest::{UnitData, UnitManifest},
+        },
+    };
+
+    /// Creates a world where "spiders" hunt "ants".
+    fn world_with_spiders_and_ants() -> World {
+        let mut world = World::new();
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData::simple("ant", Diet::simple("acacia_leaf")),
+        );
+        let spider_diet: Diet = RawDiet::new("acacia_leaf", 10.).with_prey(&["ant"]).into();
+        unit_manifest.insert(
+            "spider".to_string(),
+            UnitData::simple("spider", spider_diet),
+        );
+        world.insert_resource(unit_manifest);
+
+        world.init_resource::<Events<OrganismDied>>();
+
+        world
+    }
+
+    /// Spawns a unit of the provided type at `hex`, with `energy` of a possible 100.
+    fn spawn_unit(world: &mut World, name: &str, hex: Hex, energy: f32) -> Entity {
+        let mut energy_pool = EnergyPool::simple(100.);
+        energy_pool.set_current(Energy(energy));
+
+        world
+            .spawn((
+                Id::<Unit>::from_name(name.to_string()),
+                VoxelPos {
+                    hex,
+                    height: DiscreteHeight(1),
+                },
+                energy_pool,
+            ))
+            .id()
+    }
+
+    #[test]
+    fn caught_prey_is_killed_and_eaten() {
+        let mut world = world_with_spiders_and_ants();
+        let predator = spawn_unit(&mut world, "spider", Hex::ZERO, 10.);
+        let prey = spawn_unit(&mut world, "ant", Hex::new(1, 0), 25.);
+
+        CatchPreyCommand { predator, prey }.apply(&mut world);
+
+        assert!(world.get_entity(prey).is_none());
+        let predator_energy = world.get::<EnergyPool>(predator).unwrap().current();
+        assert_eq!(predator_energy, Energy(35.));
+
+        let events = world.resource::<Events<OrganismDied>>();
+        let deaths: Vec<&OrganismDied> = events.get_reader().read(events).collect();
+        assert_eq!(deaths.len(), 1);
+        assert_eq!(deaths[0].entity, prey);
+        assert_eq!(
+            deaths[0].organism_id,
+            OrganismId::Unit(Id::from_name("ant".to_string()))
+        );
+        assert_eq!(deaths[0].cause, CauseOfDeath::Predation);
+    }
+
+    #[test]
+    fn distant_prey_escapes() {
+        let mut world = world_with_spiders_and_ants();
+        let predator = spawn_unit(&mut world, "spider", Hex::ZERO, 10.);
+        let prey = spawn_unit(&mut world, "ant", Hex::new(2, 0), 25.);
+
+        CatchPreyCommand { predator, prey }.apply(&mut world);
+
+        assert!(world.get_entity(prey).is_some());
+        let predator_energy = world.get::<EnergyPool>(predator).unwrap().current();
+        assert_eq!(predator_energy, Energy(10.));
+        assert!(world.resource::<Events<OrganismDied>>().is_empty());
+    }
+
+    #[test]
+    fn prey_that_has_already_died_cannot_be_eaten() {
+        let mut world = world_with_spiders_and_ants();
+        let predator = spawn_unit(&mut world, "spider", Hex::ZERO, 10.);
+        let prey = spawn_unit(&mut world, "ant", Hex::new(1, 0), 25.);
+        world.despawn(prey);
+
+        CatchPreyCommand { predator, prey }.apply(&mut world);
+
+        let predator_energy = world.get::<EnergyPool>(predator).unwrap().current();
+        assert_eq!(predator_energy, Energy(10.));
+        assert!(world.resource::<Events<OrganismDied>>().is_empty());
+    }
+}
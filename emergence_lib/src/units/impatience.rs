@@ -21,6 +21,11 @@ impl ImpatiencePool {
         ImpatiencePool { current: 0, max }
     }
 
+    /// The current impatience of this unit.
+    pub(super) fn current(&self) -> u8 {
+        self.current
+    }
+
     /// Is this unit out of patience?
     pub(super) fn is_full(&self) -> bool {
         self.current >= self.max
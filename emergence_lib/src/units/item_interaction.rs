@@ -5,13 +5,20 @@ use bevy::prelude::*;
 use crate::{
     asset_management::manifest::Id,
     items::item_manifest::{Item, ItemManifest},
+    organisms::genetics::Genome,
 };
 
 /// The item(s) that a unit is carrying.
 #[derive(Component, Default, Clone, Debug, Deref, DerefMut)]
 pub(crate) struct UnitInventory {
     /// The single item the unit is currently holding
+    #[deref]
     pub(crate) held_item: Option<Id<Item>>,
+    /// The genome of the organism that produced the held item, if known.
+    ///
+    /// This is updated whenever an item is picked up,
+    /// so that seeds inherit from the organism that produced them.
+    pub(crate) held_item_parent: Option<Genome>,
 }
 
 impl UnitInventory {
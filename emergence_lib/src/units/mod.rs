@@ -18,21 +18,26 @@ use serde::{Deserialize, Serialize};
 use self::{
     actions::CurrentAction,
     age::Age,
+    decision_trace::DecisionTrace,
     goals::Goal,
     impatience::ImpatiencePool,
     item_interaction::UnitInventory,
+    needs::{need_manifest::RawNeedManifest, Needs},
     unit_assets::UnitHandles,
     unit_manifest::{RawUnitManifest, Unit, UnitData},
 };
 
-use crate::organisms::OrganismBundle;
+use crate::organisms::{domestication::Strain, genetics::Genome, OrganismBundle};
 
 pub(crate) mod actions;
 pub mod age;
 pub mod basic_needs;
+pub(crate) mod decision_trace;
 pub(crate) mod goals;
+pub(crate) mod hunting;
 pub(crate) mod impatience;
 pub(crate) mod item_interaction;
+pub mod needs;
 pub(crate) mod unit_assets;
 pub mod unit_manifest;
 
@@ -88,12 +93,16 @@ pub(crate) struct UnitBundle {
     impatience: ImpatiencePool,
     /// What is the unit currently doing.
     current_action: CurrentAction,
+    /// Why the unit has been doing what it's doing.
+    decision_trace: DecisionTrace,
     /// What is the unit currently holding, if anything?
     held_item: UnitInventory,
     /// What signals is this unit emitting?
     emitter: Emitter,
     /// The current and max age of the unit.
     age: Age,
+    /// How well the unit's needs beyond food and oxygen are met.
+    needs: Needs,
     /// Organism data
     organism_bundle: OrganismBundle,
     /// Makes units pickable
@@ -114,10 +123,13 @@ impl UnitBundle {
     /// Initializes a new unit.
     ///
     /// It will be just born, and full.
+    /// Its stats are scaled by the provided `genome`.
     pub(crate) fn newborn(
         unit_id: Id<Unit>,
         voxel_pos: VoxelPos,
         unit_data: UnitData,
+        genome: Genome,
+        strain: Strain,
         unit_handles: &UnitHandles,
     ) -> Self {
         let scene_handle = unit_handles.scenes.get(&unit_id).unwrap();
@@ -127,8 +139,9 @@ impl UnitBundle {
             voxel_pos,
             facing: Facing::default(),
             current_goal: Goal::default(),
-            impatience: ImpatiencePool::new(unit_data.max_impatience),
+            impatience: ImpatiencePool::new(genome.max_impatience(unit_data.max_impatience)),
             current_action: CurrentAction::default(),
+            decision_trace: DecisionTrace::default(),
             held_item: UnitInventory::default(),
             emitter: Emitter {
                 signals: vec![(
@@ -136,10 +149,13 @@ impl UnitBundle {
                     SignalStrength::new(Self::UNIT_EMITTER_STRENGTH),
                 )],
             },
-            age: Age::newborn(unit_data.max_age),
+            age: Age::newborn(genome.max_age(unit_data.max_age)),
+            needs: Needs::new(&unit_data.needs),
             organism_bundle: OrganismBundle::new(
-                unit_data.organism_variety.energy_pool,
+                genome.energy_pool(&unit_data.organism_variety.energy_pool),
                 unit_data.organism_variety.lifecycle,
+                genome,
+                strain,
             ),
             raycast_mesh: RaycastMesh::default(),
             mesh: unit_handles.picking_mesh.clone_weak(),
@@ -154,6 +170,8 @@ impl UnitBundle {
     //// Generates a randomized unit.
     ///
     /// This is used for world generation.
+    /// Its genome is sampled from the natural variation of its species,
+    /// and species that have not yet been domesticated start out wild.
     pub(crate) fn randomized(
         unit_id: Id<Unit>,
         voxel_pos: VoxelPos,
@@ -162,17 +180,20 @@ impl UnitBundle {
         rng: &mut impl Rng,
     ) -> Self {
         let scene_handle = unit_handles.scenes.get(&unit_id).unwrap();
-        let mut energy_pool = unit_data.organism_variety.energy_pool;
+        let genome = Genome::sample(unit_data.organism_variety.genetic_variation, rng);
+        let mut energy_pool = genome.energy_pool(&unit_data.organism_variety.energy_pool);
         energy_pool.randomize(rng);
-        let age = Age::randomized(rng, unit_data.max_age);
+        let age = Age::randomized(rng, genome.max_age(unit_data.max_age));
+        let strain = Strain::initial(&unit_data.organism_variety);
 
         UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
             current_goal: Goal::default(),
-            impatience: ImpatiencePool::new(unit_data.max_impatience),
+            impatience: ImpatiencePool::new(genome.max_impatience(unit_data.max_impatience)),
             current_action: CurrentAction::default(),
+            decision_trace: DecisionTrace::default(),
             held_item: UnitInventory::default(),
             emitter: Emitter {
                 signals: vec![(
@@ -181,7 +202,13 @@ impl UnitBundle {
                 )],
             },
             age,
-            organism_bundle: OrganismBundle::new(energy_pool, unit_data.organism_variety.lifecycle),
+            needs: Needs::new(&unit_data.needs),
+            organism_bundle: OrganismBundle::new(
+                energy_pool,
+                unit_data.organism_variety.lifecycle,
+                genome,
+                strain,
+            ),
             raycast_mesh: RaycastMesh::default(),
             mesh: unit_handles.picking_mesh.clone_weak(),
             scene_bundle: SceneBundle {
@@ -200,17 +227,20 @@ impl UnitBundle {
         rng: &mut impl Rng,
     ) -> Self {
         let scene_handle = Handle::default();
-        let mut energy_pool = unit_data.organism_variety.energy_pool;
+        let genome = Genome::sample(unit_data.organism_variety.genetic_variation, rng);
+        let mut energy_pool = genome.energy_pool(&unit_data.organism_variety.energy_pool);
         energy_pool.randomize(rng);
-        let age = Age::randomized(rng, unit_data.max_age);
+        let age = Age::randomized(rng, genome.max_age(unit_data.max_age));
+        let strain = Strain::initial(&unit_data.organism_variety);
 
         UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
             current_goal: Goal::default(),
-            impatience: ImpatiencePool::new(unit_data.max_impatience),
+            impatience: ImpatiencePool::new(genome.max_impatience(unit_data.max_impatience)),
             current_action: CurrentAction::default(),
+            decision_trace: DecisionTrace::default(),
             held_item: UnitInventory::default(),
             emitter: Emitter {
                 signals: vec![(
@@ -219,7 +249,13 @@ impl UnitBundle {
                 )],
             },
             age,
-            organism_bundle: OrganismBundle::new(energy_pool, unit_data.organism_variety.lifecycle),
+            needs: Needs::new(&unit_data.needs),
+            organism_bundle: OrganismBundle::new(
+                energy_pool,
+                unit_data.organism_variety.lifecycle,
+                genome,
+                strain,
+            ),
             raycast_mesh: RaycastMesh::default(),
             mesh: Handle::default(),
             scene_bundle: SceneBundle {
@@ -249,6 +285,7 @@ pub struct UnitsPlugin;
 impl Plugin for UnitsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ManifestPlugin::<RawUnitManifest>::new())
+            .add_plugins(ManifestPlugin::<RawNeedManifest>::new())
             .add_asset_collection::<UnitHandles>()
             .add_systems(
                 FixedUpdate,
@@ -268,6 +305,18 @@ impl Plugin for UnitsPlugin {
                         .in_set(UnitSystem::ChooseNewAction)
                         .after(UnitSystem::Act)
                         .after(UnitSystem::ChooseGoal),
+                    // Impatience is reset when a new goal is chosen, so it must be recorded first
+                    decision_trace::record_impatience
+                        .after(UnitSystem::Act)
+                        .before(UnitSystem::ChooseGoal),
+                    decision_trace::record_actions.after(UnitSystem::ChooseNewAction),
+                    needs::decay_needs,
+                    needs::satisfy_needs_from_conditions.after(needs::decay_needs),
+                    needs::check_for_needs
+                        .after(needs::satisfy_needs_from_conditions)
+                        .after(UnitSystem::ChooseGoal)
+                        // Food and oxygen are more pressing, so they should overwrite
+                        .before(basic_needs::check_for_hunger),
                     basic_needs::check_for_hunger
                         // Avoid a delay
                         .before(UnitSystem::ChooseNewAction)
@@ -278,6 +327,7 @@ impl Plugin for UnitsPlugin {
                     age::aging,
                 )
                     .in_set(SimulationSet),
-            );
+            )
+            .add_systems(Update, decision_trace::export_decision_trace);
     }
 }
@@ -0,0 +1,367 @@
+//! Needs beyond food and oxygen, such as rest, water or company.
+//!
+//! Each unit type declares which needs it has in the [`UnitManifest`](super::unit_manifest::UnitManifest),
+//! while the needs themselves are defined in the [`NeedManifest`].
+//! Needs slowly decay over time, and once they fall below their warning threshold,
+//! units will switch to [`Goal::Satisfy`] until the need is satiated once again.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::item_tags::ItemKind,
+    geometry::{MapGeometry, VoxelPos},
+};
+
+use self::need_manifest::{Need, NeedCondition, NeedManifest, NeedSatisfier};
+
+use super::{goals::Goal, item_interaction::UnitInventory, unit_manifest::Unit};
+
+pub mod need_manifest;
+
+/// How well each of a unit's needs are currently met.
+///
+/// Each need is stored as a fraction of its maximum, between 0 and 1.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Needs {
+    /// The current fraction of each need.
+    satisfaction: HashMap<Id<Need>, f32>,
+}
+
+impl Needs {
+    /// Creates a new [`Needs`] component, with every need fully satisfied.
+    pub(crate) fn new(needs: &[Id<Need>]) -> Self {
+        Needs {
+            satisfaction: needs.iter().map(|&need_id| (need_id, 1.0)).collect(),
+        }
+    }
+
+    /// The current fraction of the provided need, if this unit has it.
+    pub(crate) fn get(&self, need_id: Id<Need>) -> Option<f32> {
+        self.satisfaction.get(&need_id).copied()
+    }
+
+    /// Restores the provided need by `amount`, up to its maximum.
+    pub(crate) fn restore(&mut self, need_id: Id<Need>, amount: f32) {
+        if let Some(current) = self.satisfaction.get_mut(&need_id) {
+            *current = (*current + amount).clamp(0., 1.);
+        }
+    }
+
+    /// Reduces each need according to its decay rate.
+    fn decay(&mut self, delta_time: f32, need_manifest: &NeedManifest) {
+        for (&need_id, current) in self.satisfaction.iter_mut() {
+            let decay = need_manifest.get(need_id).decay_per_second * delta_time;
+            *current = (*current - decay).clamp(0., 1.);
+        }
+    }
+
+    /// Is the provided need met well enough that the unit can stop seeking it?
+    ///
+    /// Needs that this unit does not have are always satiated.
+    pub(crate) fn is_satiated(&self, need_id: Id<Need>, need_manifest: &NeedManifest) -> bool {
+        self.get(need_id).map_or(true, |current| {
+            current >= need_manifest.get(need_id).satiation_threshold
+        })
+    }
+
+    /// Returns the need that has fallen furthest below its warning threshold, if any.
+    pub(crate) fn most_urgent(&self, need_manifest: &NeedManifest) -> Option<Id<Need>> {
+        self.satisfaction
+            .iter()
+            .map(|(&need_id, &current)| {
+                (
+                    need_id,
+                    need_manifest.get(need_id).warning_threshold - current,
+                )
+            })
+            .filter(|(_, deficit)| *deficit > 0.)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(need_id, _)| need_id)
+    }
+
+    /// Pretty formatting for this type
+    pub(crate) fn display(&self, need_manifest: &NeedManifest) -> String {
+        if self.satisfaction.is_empty() {
+            return "None".to_string();
+        }
+
+        let mut needs: Vec<String> = self
+            .satisfaction
+            .iter()
+            .map(|(&need_id, &current)| {
+                format!("{}: {:.0}%", need_manifest.name(need_id), current * 100.)
+            })
+            .collect();
+        // Keep the order stable from frame to frame
+        needs.sort();
+
+        needs.join(", ")
+    }
+}
+
+/// Drains each unit's needs over time.
+pub(super) fn decay_needs(
+    mut unit_query: Query<&mut Needs>,
+    need_manifest: Res<NeedManifest>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    for mut needs in unit_query.iter_mut() {
+        needs.decay(delta_time, &need_manifest);
+    }
+}
+
+/// Restores any needs whose [`NeedCondition`] is currently met.
+pub(super) fn satisfy_needs_from_conditions(
+    mut unit_query: Query<(&Id<Unit>, &VoxelPos, &mut Needs)>,
+    need_manifest: Res<NeedManifest>,
+    map_geometry: Res<MapGeometry>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta().as_secs_f32();
+
+    let mut population: HashMap<(Id<Unit>, VoxelPos), usize> = HashMap::new();
+    for (&unit_id, &voxel_pos, _) in unit_query.iter() {
+        *population.entry((unit_id, voxel_pos)).or_default() += 1;
+    }
+
+    for (&unit_id, &voxel_pos, mut needs) in unit_query.iter_mut() {
+        let met_needs: Vec<(Id<Need>, f32)> = needs
+            .satisfaction
+            .keys()
+            .filter_map(|&need_id| {
+                let need_data = need_manifest.get(need_id);
+                let NeedSatisfier::Condition(condition) = need_data.satisfier else {
+                    return None;
+                };
+
+                let is_met = match condition {
+                    NeedCondition::Company => {
+                        // This includes the voxel that the unit is standing in, so don't count ourselves
+                        let nearby = voxel_pos
+                            .reachable_neighbors()
+                            .into_iter()
+                            .filter_map(|neighbor| population.get(&(unit_id, neighbor)))
+                            .sum::<usize>();
+                        nearby > 1
+                    }
+                    NeedCondition::Shelter => [voxel_pos, voxel_pos.above()]
+                        .into_iter()
+                        .chain(voxel_pos.all_neighbors())
+                        .any(|nearby| map_geometry.get_structure(nearby).is_some()),
+                };
+
+                is_met.then_some((need_id, need_data.restoration * delta_time))
+            })
+            .collect();
+
+        for (need_id, amount) in met_needs {
+            needs.restore(need_id, amount);
+        }
+    }
+}
+
+/// Swaps the goal to [`Goal::Satisfy`] when a need falls below its warning threshold.
+///
+/// Once the need is satiated, the unit goes back to wandering.
+pub(super) fn check_for_needs(
+    mut unit_query: Query<(&mut Goal, &Needs, &UnitInventory)>,
+    need_manifest: Res<NeedManifest>,
+) {
+    for (mut goal, needs, unit_inventory) in unit_query.iter_mut() {
+        if let Goal::Satisfy(need_id) = *goal {
+            if needs.is_satiated(need_id, &need_manifest) {
+                *goal = Goal::Wander {
+                    remaining_actions: None,
+                }
+            }
+            continue;
+        }
+
+        // Food and oxygen are more pressing than any other need
+        if matches!(*goal, Goal::Eat(..) | Goal::Hunt(..) | Goal::Breathe) {
+            continue;
+        }
+
+        let Some(need_id) = needs.most_urgent(&need_manifest) else {
+            continue;
+        };
+
+        // Make sure to put down any item we're holding first, unless it's what we need
+        if let Some(item) = unit_inventory.held_item {
+            let satisfier = need_manifest.get(need_id).satisfier;
+            if *goal == Goal::Store(ItemKind::Single(item))
+                && satisfier != NeedSatisfier::Item(ItemKind::Single(item))
+            {
+                continue;
+            }
+        }
+
+        *goal = Goal::Satisfy(need_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::need_manifest::{NeedData, RawNeedManifest};
+    use super::*;
+    use crate::{
+        asset_management::manifest::{loader::IsRawManifest, Manifest},
+        geometry::Facing,
+        items::item_manifest::RawItemManifest,
+        structures::{structure_manifest::RawStructureManifest, Footprint},
+        units::unit_manifest::RawUnitManifest,
+    };
+
+    /// Creates a manifest with a single need, named "rest".
+    fn rest_manifest() -> (NeedManifest, Id<Need>) {
+        let mut need_manifest = NeedManifest::new();
+        need_manifest.insert(
+            "rest".to_string(),
+            NeedData {
+                satisfier: NeedSatisfier::Condition(NeedCondition::Shelter),
+                decay_per_second: 0.1,
+                warning_threshold: 0.3,
+                satiation_threshold: 0.8,
+                restoration: 0.5,
+            },
+        );
+
+        (need_manifest, Id::from_name("rest".to_string()))
+    }
+
+    #[test]
+    fn needs_decay_to_warning_threshold() {
+        let (need_manifest, rest) = rest_manifest();
+        let mut needs = Needs::new(&[rest]);
+
+        assert_eq!(needs.get(rest), Some(1.0));
+        assert_eq!(needs.most_urgent(&need_manifest), None);
+
+        needs.decay(5.0, &need_manifest);
+        assert_eq!(needs.most_urgent(&need_manifest), None);
+
+        needs.decay(3.0, &need_manifest);
+        assert_eq!(needs.most_urgent(&need_manifest), Some(rest));
+        assert!(!needs.is_satiated(rest, &need_manifest));
+
+        // Needs can't go below zero
+        needs.decay(100.0, &need_manifest);
+        assert_eq!(needs.get(rest), Some(0.0));
+    }
+
+    #[test]
+    fn restoring_needs_satiates_them() {
+        let (need_manifest, rest) = rest_manifest();
+        let mut needs = Needs::new(&[rest]);
+
+        needs.decay(10.0, &need_manifest);
+        needs.restore(rest, 0.5);
+        assert!(!needs.is_satiated(rest, &need_manifest));
+
+        needs.restore(rest, 0.5);
+        assert!(needs.is_satiated(rest, &need_manifest));
+        assert_eq!(needs.get(rest), Some(1.0));
+    }
+
+    /// Reads and processes a manifest from the base game's assets.
+    fn base_game_manifest<M: IsRawManifest>() -> Manifest<M::Marker, M::Data> {
+        let path = format!(
+            "{}/../emergence_game/assets/manifests/base_game.{}",
+            env!("CARGO_MANIFEST_DIR"),
+            M::EXTENSION
+        );
+        let bytes = std::fs::read(&path).unwrap_or_else(|error| panic!("{path}: {error}"));
+        serde_json::from_slice::<M>(&bytes).unwrap().process()
+    }
+
+    /// Is a basket crab's `need_id` restored, when standing in a world arranged to meet `condition`?
+    fn condition_restores_need(need_id: Id<Need>, condition: NeedCondition) -> bool {
+        let mut app = App::new();
+        let mut map_geometry = MapGeometry::new(&mut app.world, 1);
+        let need_manifest = base_game_manifest::<RawNeedManifest>();
+        let crab = Id::<Unit>::from_name("basket_crab".to_string());
+        let voxel_pos = VoxelPos::ZERO.above();
+        let neighbor = voxel_pos.all_neighbors()[0];
+
+        match condition {
+            NeedCondition::Company => {
+                app.world.spawn((crab, neighbor, Needs::new(&[need_id])));
+            }
+            NeedCondition::Shelter => {
+                map_geometry
+                    .add_structure(
+                        neighbor,
+                        Facing::default(),
+                        &Footprint::single(),
+                        false,
+                        false,
+                        false,
+                        Entity::from_bits(42),
+                    )
+                    .unwrap();
+            }
+        }
+
+        let mut needs = Needs::new(&[need_id]);
+        needs.decay(f32::MAX, &need_manifest);
+        let unit = app.world.spawn((crab, voxel_pos, needs)).id();
+
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+
+        app.insert_resource(map_geometry)
+            .insert_resource(need_manifest)
+            .insert_resource(time)
+            .add_systems(Update, satisfy_needs_from_conditions);
+        app.update();
+
+        app.world.get::<Needs>(unit).unwrap().get(need_id).unwrap() > 0.
+    }
+
+    #[test]
+    fn base_game_crabs_can_satisfy_every_need() {
+        let need_manifest = base_game_manifest::<RawNeedManifest>();
+        let unit_manifest = base_game_manifest::<RawUnitManifest>();
+        let item_manifest = base_game_manifest::<RawItemManifest>();
+        let structure_manifest = base_game_manifest::<RawStructureManifest>();
+
+        let crab = Id::<Unit>::from_name("basket_crab".to_string());
+        let crab_needs = &unit_manifest.get(crab).needs;
+        assert!(!crab_needs.is_empty());
+
+        for &need_id in crab_needs {
+            let can_be_satisfied = match need_manifest.get(need_id).satisfier {
+                NeedSatisfier::Item(item_kind) => item_manifest
+                    .variants()
+                    .into_iter()
+                    .any(|item_id| item_kind.matches(item_id, &item_manifest)),
+                NeedSatisfier::Structure(structure_id) => {
+                    structure_manifest.data_map().contains_key(&structure_id)
+                }
+                NeedSatisfier::Condition(condition) => condition_restores_need(need_id, condition),
+            };
+
+            assert!(
+                can_be_satisfied,
+                "Basket crabs cannot satisfy their need for {}",
+                need_manifest.name(need_id)
+            );
+        }
+    }
+
+    #[test]
+    fn missing_needs_are_ignored() {
+        let (need_manifest, rest) = rest_manifest();
+        let mut needs = Needs::default();
+
+        needs.restore(rest, 0.5);
+        assert_eq!(needs.get(rest), None);
+        assert!(needs.is_satiated(rest, &need_manifest));
+        assert_eq!(needs.display(&need_manifest), "None");
+    }
+}
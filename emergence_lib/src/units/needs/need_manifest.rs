@@ -0,0 +1,155 @@
+//! Defines write-only data for each need.
+
+use bevy::{
+    asset::Asset,
+    reflect::{Reflect, TypePath, TypeUuid},
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::{loader::IsRawManifest, Id, Manifest},
+    crafting::item_tags::ItemKind,
+    structures::structure_manifest::Structure,
+};
+
+/// The marker type for [`Id<Need>`](super::Id).
+#[derive(Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct Need;
+/// Stores the read-only definitions for all needs.
+pub type NeedManifest = Manifest<Need, NeedData>;
+
+impl NeedManifest {
+    /// Can any need be satisfied by visiting a structure of type `structure_id`?
+    pub(crate) fn satisfied_by_structure(&self, structure_id: Id<Structure>) -> bool {
+        self.data_map()
+            .values()
+            .any(|data| data.satisfier == NeedSatisfier::Structure(structure_id))
+    }
+}
+
+/// Data stored in a [`NeedManifest`] for each [`Id<Need>`].
+///
+/// Needs are tracked as a fraction of their maximum, between 0 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeedData {
+    /// What this need can be satisfied by.
+    pub satisfier: NeedSatisfier,
+    /// The fraction of this need lost each second.
+    pub decay_per_second: f32,
+    /// Units will seek to satisfy this need once it falls below this fraction.
+    pub warning_threshold: f32,
+    /// Units will stop seeking to satisfy this need once it rises above this fraction.
+    pub satiation_threshold: f32,
+    /// The fraction of this need that is restored.
+    ///
+    /// Items and structures restore this much each time they are used,
+    /// while conditions restore this much each second that they are met.
+    pub restoration: f32,
+}
+
+/// The unprocessed equivalent of [`NeedData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawNeedData {
+    /// What this need can be satisfied by.
+    pub satisfier: RawNeedSatisfier,
+    /// The fraction of this need lost each second.
+    pub decay_per_second: f32,
+    /// Units will seek to satisfy this need once it falls below this fraction.
+    pub warning_threshold: f32,
+    /// Units will stop seeking to satisfy this need once it rises above this fraction.
+    pub satiation_threshold: f32,
+    /// The fraction of this need that is restored.
+    pub restoration: f32,
+}
+
+impl From<RawNeedData> for NeedData {
+    fn from(raw: RawNeedData) -> Self {
+        assert!(
+            raw.warning_threshold <= raw.satiation_threshold,
+            "Need warning threshold ({}) must not exceed its satiation threshold ({})",
+            raw.warning_threshold,
+            raw.satiation_threshold
+        );
+
+        NeedData {
+            satisfier: raw.satisfier.into(),
+            decay_per_second: raw.decay_per_second,
+            warning_threshold: raw.warning_threshold,
+            satiation_threshold: raw.satiation_threshold,
+            restoration: raw.restoration,
+        }
+    }
+}
+
+/// The means by which a need can be satisfied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NeedSatisfier {
+    /// Consuming a single item of this kind.
+    Item(ItemKind),
+    /// Visiting a structure of this type.
+    Structure(Id<Structure>),
+    /// Being in a particular situation.
+    Condition(NeedCondition),
+}
+
+/// The unprocessed equivalent of [`NeedSatisfier`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawNeedSatisfier {
+    /// The name of the item that must be consumed.
+    Item(String),
+    /// The name of the structure that must be visited.
+    Structure(String),
+    /// The situation that the unit must be in.
+    Condition(NeedCondition),
+}
+
+impl From<RawNeedSatisfier> for NeedSatisfier {
+    fn from(raw: RawNeedSatisfier) -> Self {
+        match raw {
+            RawNeedSatisfier::Item(item) => {
+                NeedSatisfier::Item(ItemKind::Single(Id::from_name(item)))
+            }
+            RawNeedSatisfier::Structure(structure) => {
+                NeedSatisfier::Structure(Id::from_name(structure))
+            }
+            RawNeedSatisfier::Condition(condition) => NeedSatisfier::Condition(condition),
+        }
+    }
+}
+
+/// A situation that gradually satisfies a need while a unit is in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NeedCondition {
+    /// Another unit of the same species is within reach.
+    Company,
+    /// A structure is directly overhead, or the unit is inside or next to one.
+    Shelter,
+}
+
+/// The [`NeedManifest`] as seen in the manifest file.
+#[derive(Asset, Debug, Clone, Serialize, Deserialize, TypeUuid, TypePath, PartialEq)]
+#[uuid = "e2a7c5d1-9f3b-4c8e-a6d4-7b1f0e3c9a52"]
+pub struct RawNeedManifest {
+    /// The data for each need.
+    pub needs: HashMap<String, RawNeedData>,
+}
+
+impl IsRawManifest for RawNeedManifest {
+    const EXTENSION: &'static str = "need_manifest.json";
+
+    type Marker = Need;
+    type Data = NeedData;
+
+    fn process(&self) -> Manifest<Self::Marker, Self::Data> {
+        let mut manifest = Manifest::new();
+
+        for (raw_id, raw_data) in self.needs.clone() {
+            let data = raw_data.into();
+
+            manifest.insert(raw_id, data)
+        }
+
+        manifest
+    }
+}
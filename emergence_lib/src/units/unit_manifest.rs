@@ -8,13 +8,13 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    asset_management::manifest::loader::IsRawManifest,
+    asset_management::manifest::{loader::IsRawManifest, Id},
     organisms::{OrganismVariety, RawOrganismVariety},
     simulation::time::Days,
     units::{basic_needs::Diet, WanderingBehavior},
 };
 
-use super::{basic_needs::RawDiet, Manifest};
+use super::{basic_needs::RawDiet, needs::need_manifest::Need, Manifest};
 
 /// The marker type for [`Id<Unit>`](super::Id).
 #[derive(Reflect, Clone, Copy, PartialEq, Eq)]
@@ -37,6 +37,8 @@ pub struct UnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// The needs beyond food and oxygen that units of this type must satisfy.
+    pub needs: Vec<Id<Need>>,
 }
 
 impl UnitData {
@@ -49,6 +51,7 @@ impl UnitData {
             max_impatience: 10,
             max_age: Days(10.0),
             wandering_behavior: WanderingBehavior::default(),
+            needs: Vec::new(),
         }
     }
 }
@@ -68,6 +71,8 @@ pub struct RawUnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// The names of the needs beyond food and oxygen that units of this type must satisfy.
+    pub needs: Option<Vec<String>>,
 }
 
 impl From<RawUnitData> for UnitData {
@@ -84,6 +89,12 @@ impl From<RawUnitData> for UnitData {
             max_impatience: raw.max_impatience,
             max_age: Days(raw.max_age),
             wandering_behavior: raw.wandering_behavior,
+            needs: raw
+                .needs
+                .unwrap_or_default()
+                .into_iter()
+                .map(Id::from_name)
+                .collect(),
         }
     }
 }
@@ -3,3 +3,4 @@
 pub mod curves;
 pub mod fallible_commands;
 pub mod noise;
+pub mod persistence;
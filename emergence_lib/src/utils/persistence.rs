@@ -0,0 +1,121 @@
+//! Reading and writing the files that persist player data between sessions, such as keybindings and blueprints.
+//!
+//! Data is stored as JSON, and default locations are relative to the working directory.
+
+use std::path::Path;
+
+use bevy::log::warn;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// An error produced when reading or writing persisted data.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PersistenceError {
+    /// An [IO](std::io) Error
+    #[error("Could not access file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [serde_json](serde_json) Error
+    #[error("Could not parse file: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+impl PersistenceError {
+    /// Was the file missing?
+    ///
+    /// This is expected when nothing has been saved yet.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, PersistenceError::Io(error) if error.kind() == std::io::ErrorKind::NotFound)
+    }
+}
+
+/// Reads a value stored as JSON in the file at `path`.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, PersistenceError> {
+    let bytes = std::fs::read(path)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Writes `value` as JSON to the file at `path`, creating any missing directories.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), PersistenceError> {
+    let json = serde_json::to_string_pretty(value)?;
+    write_file(path, json)
+}
+
+/// Writes `contents` to the file at `path`, creating any missing directories.
+pub fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), PersistenceError> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Logs a warning if loading `description` from `path` failed.
+///
+/// Missing files are not reported, as nothing may have been saved yet.
+pub fn warn_on_load_error(result: Result<(), PersistenceError>, description: &str, path: &Path) {
+    match result {
+        Ok(()) => (),
+        Err(error) if error.is_not_found() => (),
+        Err(error) => warn!(
+            "Could not load {description} from {}: {error}",
+            path.display()
+        ),
+    }
+}
+
+/// A uniquely named directory in the system's temporary directory, which is deleted when dropped.
+#[cfg(test)]
+pub(crate) struct TemporaryDirectory {
+    /// The path to the directory.
+    path: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl TemporaryDirectory {
+    /// Reserves a directory for the test called `name`.
+    ///
+    /// The directory itself is only created once something is written to it.
+    pub(crate) fn new(name: &str) -> Self {
+        TemporaryDirectory {
+            path: std::env::temp_dir().join(format!("emergence_{name}_{}", std::process::id())),
+        }
+    }
+
+    /// The path to the directory.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path to the file called `file_name` inside of this directory.
+    pub(crate) fn join(&self, file_name: &str) -> std::path::PathBuf {
+        self.path.join(file_name)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TemporaryDirectory {
+    fn drop(&mut self) {
+        // The directory may never have been created
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trips_through_disk() {
+        let directory = TemporaryDirectory::new("persistence_test");
+        let path = directory.join("nested/values.json");
+
+        write_json(&path, &vec![1, 2, 3]).unwrap();
+        let values: Vec<u32> = read_json(&path).unwrap();
+        assert_eq!(values, vec![1, 2, 3]);
+
+        let missing = read_json::<Vec<u32>>(&directory.join("missing.json"));
+        assert!(missing.unwrap_err().is_not_found());
+    }
+}
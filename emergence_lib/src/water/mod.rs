@@ -12,6 +12,7 @@ use derive_more::{Add, AddAssign, Sub, SubAssign};
 use serde::{Deserialize, Serialize};
 
 use crate::geometry::{VoxelPos, MAP_LAYOUT};
+use crate::simulation::alerts::Alert;
 use crate::simulation::time::Days;
 use crate::{
     asset_management::manifest::Id,
@@ -21,7 +22,7 @@ use crate::{
     structures::structure_manifest::StructureManifest,
 };
 
-use self::ocean::{tides, Ocean, TideSettings};
+use self::ocean::{tides, warn_of_high_tide, Ocean, TideSettings};
 use self::water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate};
 use self::{
     emitters::{add_water_emitters, produce_water_from_emitters},
@@ -180,6 +181,15 @@ impl Plugin for WaterPlugin {
                 .chain()
                 .in_set(WaterSet::VerticalWaterMovement),
         )
+        .add_systems(
+            FixedUpdate,
+            warn_of_high_tide
+                .after(tides)
+                // Like draw_water_from_roots, fail silently to allow for integration testing of water behavior.
+                .run_if(resource_exists::<StructureManifest>())
+                .run_if(resource_exists::<Events<Alert>>())
+                .in_set(SimulationSet),
+        )
         .add_systems(
             FixedUpdate,
             // It is important that the computed height of the water is accurate before we start moving it around.
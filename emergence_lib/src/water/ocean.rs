@@ -2,8 +2,12 @@
 
 use bevy::prelude::*;
 
-use crate::geometry::Height;
+use crate::asset_management::manifest::Id;
+use crate::construction::ghosts::{Ghost, Preview};
+use crate::geometry::{Height, MapGeometry, VoxelPos};
+use crate::simulation::alerts::{Alert, AlertKind};
 use crate::simulation::time::{Days, InGameTime};
+use crate::structures::structure_manifest::{Structure, StructureManifest};
 
 use super::WaterConfig;
 
@@ -18,6 +22,20 @@ pub struct TideSettings {
     pub minimum: Height,
 }
 
+impl TideSettings {
+    /// The height of the ocean after `time` in-game days have elapsed.
+    fn height_at(&self, time: f32) -> Height {
+        // The factor of TAU compensates for the natural period of the sine function.
+        let scaled_time = time * std::f32::consts::TAU / self.period.0;
+
+        // The sine function can have a range of [-1, 1],
+        // so at its lowest point we are subtracting the amplitude.
+        // To ensure that the lowest point is at the minimum water level,
+        // we add the minimum water level to the amplitude before applying the sine component.
+        self.minimum + self.amplitude + self.amplitude * scaled_time.sin()
+    }
+}
+
 /// Stores data about the current state of the ocean.
 #[derive(Resource, Debug, Default)]
 pub struct Ocean {
@@ -39,16 +57,53 @@ pub(super) fn tides(
     water_config: Res<WaterConfig>,
 ) {
     let time = in_game_time.elapsed_days();
-    let settings = water_config.tide_settings;
-
-    // The factor of TAU compensates for the natural period of the sine function.
-    let scaled_time = time * std::f32::consts::TAU / settings.period.0;
-
-    // The sine function can have a range of [-1, 1],
-    // so at its lowest point we are subtracting the amplitude.
-    // To ensure that the lowest point is at the minimum water level,
-    // we add the minimum water level to the amplitude before applying the sine component.
-    let tide_height =
-        settings.minimum + settings.amplitude + settings.amplitude * scaled_time.sin();
-    ocean.height = tide_height;
+    ocean.height = water_config.tide_settings.height_at(time);
+}
+
+/// Warns the player when the rising tide is about to submerge a structure on the coast.
+pub(super) fn warn_of_high_tide(
+    ocean: Res<Ocean>,
+    in_game_time: Res<InGameTime>,
+    water_config: Res<WaterConfig>,
+    structure_query: Query<(Entity, &VoxelPos, &Id<Structure>), (Without<Ghost>, Without<Preview>)>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+    mut alerts: EventWriter<Alert>,
+) {
+    /// How far in advance the player is warned.
+    const WARNING_TIME: Days = Days(0.05);
+
+    let predicted_height = water_config
+        .tide_settings
+        .height_at(in_game_time.elapsed_days() + WARNING_TIME.0);
+
+    // Only warn while the tide is coming in
+    if predicted_height <= ocean.height() {
+        return;
+    }
+
+    for (entity, &voxel_pos, &structure_id) in structure_query.iter() {
+        // Only structures next to the ocean are flooded directly by the tide
+        let is_coastal = map_geometry
+            .adjacent_hexes(voxel_pos.hex)
+            .iter()
+            .any(Option::is_none);
+        if !is_coastal {
+            continue;
+        }
+
+        let Ok(terrain_height) = map_geometry.get_height(voxel_pos.hex) else {
+            continue;
+        };
+        let footprint = &structure_manifest.get(structure_id).footprint;
+        let structure_top = Height::from(terrain_height) + Height::from(footprint.max_height());
+
+        if ocean.height() <= structure_top && structure_top < predicted_height {
+            alerts.send(Alert {
+                kind: AlertKind::HighTide,
+                entity,
+                voxel_pos,
+            });
+        }
+    }
 }
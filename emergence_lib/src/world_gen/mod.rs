@@ -161,6 +161,7 @@ impl GenerationConfig {
 
         let mut unit_chances: HashMap<Id<Unit>, f32> = HashMap::new();
         unit_chances.insert(Id::from_name("basket_crab".to_string()), 1e-2);
+        unit_chances.insert(Id::from_name("salt_shell".to_string()), 1e-3);
 
         let mut structure_chances: HashMap<Id<Structure>, f32> = HashMap::new();
         structure_chances.insert(Id::from_name("ant_hive".to_string()), 1e-3);
@@ -12,18 +12,29 @@ use emergence_lib::{
     items::item_manifest::{RawItemData, RawItemManifest},
     light::Illuminance,
     organisms::{
+        carrying_capacity::CarryingCapacity,
+        disease::disease_manifest::{RawDiseaseData, RawDiseaseEffects, RawDiseaseManifest},
         energy::{Energy, EnergyPool},
         lifecycle::{RawLifePath, RawLifecycle},
+        symbiosis::{RawSymbiosisData, VisitBonus},
         vegetative_reproduction::RawVegetativeReproduction,
         RawOrganismId, RawOrganismVariety,
     },
     structures::{
+        durability::RawDurabilityData,
         structure_manifest::{RawStructureData, RawStructureKind, RawStructureManifest},
         Footprint,
     },
-    terrain::terrain_manifest::{RawTerrainManifest, TerrainData},
+    technology::technology_manifest::{
+        RawPrerequisite, RawTechnologyData, RawTechnologyManifest, RawTerraformingTool, RawUnlocks,
+    },
+    terrain::{
+        fertility::Fertility,
+        terrain_manifest::{RawTerrainManifest, TerrainData},
+    },
     units::{
         basic_needs::RawDiet,
+        needs::need_manifest::{NeedCondition, RawNeedData, RawNeedManifest, RawNeedSatisfier},
         unit_manifest::{RawUnitData, RawUnitManifest},
         WanderingBehavior,
     },
@@ -94,6 +105,7 @@ fn can_serialize_terrain_manifest() {
                 soil_water_capacity: SoilWaterCapacity(0.3),
                 soil_water_flow_rate: SoilWaterFlowRate(0.1),
                 soil_water_evaporation_rate: SoilWaterEvaporationRate(0.2),
+                soil_fertility: Fertility(0.6),
             },
         )]),
     };
@@ -121,6 +133,9 @@ fn can_serialize_unit_manifest() {
                         prototypical_form: RawOrganismId::unit("ant"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(100.),
+                        remains: Some(HashMap::from_iter([("ant_carcass".to_string(), 1)])),
+                        genetic_variation: Some(0.2),
+                        mastery_required: None,
                     },
                     diet: RawDiet::new("leuco_chunk", 50.),
                     max_impatience: 10,
@@ -130,6 +145,7 @@ fn can_serialize_unit_manifest() {
                         (16, 0.1),
                     ]),
                     max_age: 10.,
+                    needs: None,
                 },
             ),
             (
@@ -139,11 +155,15 @@ fn can_serialize_unit_manifest() {
                         prototypical_form: RawOrganismId::unit("test_unit"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(50.),
+                        remains: None,
+                        genetic_variation: None,
+                        mastery_required: Some(10.),
                     },
-                    diet: RawDiet::new("acacia_leaf", 0.),
+                    diet: RawDiet::new("acacia_leaf", 0.).with_prey(&["ant"]),
                     max_impatience: 0,
                     wandering_behavior: WanderingBehavior::from_iter([(0, 0.7), (16, 0.1)]),
                     max_age: 0.2,
+                    needs: Some(vec!["rest".to_string()]),
                 },
             ),
         ]),
@@ -204,10 +224,29 @@ fn can_serialize_recipe_manifest() {
                     conditions: Some(RecipeConditions {
                         workers_required: 2,
                         allowable_light_range: None,
+                        min_fertility: None,
                     }),
                     energy: None,
                 },
             ),
+            (
+                "reed_production".to_string(),
+                RawRecipeData {
+                    inputs: RawRecipeInput::empty(),
+                    outputs: HashMap::from_iter([("reed".to_string(), 1.)]),
+                    craft_time: 5.,
+                    conditions: Some(RecipeConditions {
+                        workers_required: 0,
+                        allowable_light_range: Some(Threshold::new(
+                            Illuminance::DimlyLit,
+                            Illuminance::BrightlyLit,
+                        )),
+                        // Only grows in rich soil
+                        min_fertility: Some(Fertility(0.05)),
+                    }),
+                    energy: Some(Energy(10.)),
+                },
+            ),
         ]),
     };
 
@@ -234,6 +273,9 @@ fn can_serialize_structure_manifest() {
                         prototypical_form: RawOrganismId::structure("leuco"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(100.),
+                        remains: None,
+                        genetic_variation: None,
+                        mastery_required: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("leuco_chunk_production"),
@@ -248,6 +290,16 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: Some(RawSymbiosisData {
+                        partners: vec!["ant".to_string()],
+                        visit_bonus: Some(VisitBonus {
+                            yield_multiplier: 1.5,
+                            duration: 0.5,
+                        }),
+                        partner_energy_regen: Some(2.),
+                    }),
                 },
             ),
             (
@@ -265,6 +317,9 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: true,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
             (
@@ -279,6 +334,9 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
             (
@@ -292,6 +350,9 @@ fn can_serialize_structure_manifest() {
                             time_required: Some(1.),
                         }]),
                         energy_pool: EnergyPool::simple(75.),
+                        remains: None,
+                        genetic_variation: None,
+                        mastery_required: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("acacia_leaf_production"),
@@ -306,6 +367,9 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
             (
@@ -315,6 +379,9 @@ fn can_serialize_structure_manifest() {
                         prototypical_form: RawOrganismId::structure("acacia"),
                         lifecycle: RawLifecycle::STATIC,
                         energy_pool: EnergyPool::simple(300.),
+                        remains: None,
+                        genetic_variation: None,
+                        mastery_required: None,
                     }),
                     kind: RawStructureKind::Crafting {
                         starting_recipe: RawActiveRecipe::new("acacia_leaf_production"),
@@ -334,6 +401,12 @@ fn can_serialize_structure_manifest() {
                         period: 10.,
                         energy_threshold: 30.,
                     }),
+                    carrying_capacity: Some(CarryingCapacity {
+                        radius: 2,
+                        max_density: 4,
+                    }),
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
             (
@@ -353,6 +426,9 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
             (
@@ -373,6 +449,38 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: Some(RawDurabilityData {
+                        max: 100.,
+                        decay_rate: 0.1,
+                        repair_material: Some("leuco_chunk".to_string()),
+                        repair_amount: 25.,
+                    }),
+                    symbiosis: None,
+                },
+            ),
+            (
+                "large_storage".to_string(),
+                RawStructureData {
+                    organism_variety: None,
+                    kind: RawStructureKind::Storage {
+                        max_slot_count: 6,
+                        reserved_for: None,
+                    },
+                    construction_strategy: RawConstructionStrategy::Upgrade {
+                        from: "storage".to_string(),
+                        work: Some(10.),
+                        materials: HashMap::from_iter([("leuco_chunk".to_string(), 2)]),
+                    },
+                    max_workers: 6,
+                    footprint: Some(Footprint::single()),
+                    root_zone: None,
+                    can_walk_on_roof: false,
+                    can_walk_through: false,
+                    vegetative_reproduction: None,
+                    carrying_capacity: None,
+                    durability: None,
+                    symbiosis: None,
                 },
             ),
         ]),
@@ -388,3 +496,157 @@ fn can_serialize_structure_manifest() {
     // Check that the deserialized version is the same as the original
     assert_eq!(raw_structure_manifest, deserialized);
 }
+
+#[test]
+fn can_serialize_technology_manifest() {
+    // Create a new raw technology manifest
+    let raw_technology_manifest = RawTechnologyManifest {
+        technologies: HashMap::from_iter(vec![
+            (
+                "bulk_storage".to_string(),
+                RawTechnologyData {
+                    prerequisites: vec![RawPrerequisite::ProduceItem {
+                        item: "leuco_chunk".to_string(),
+                        count: 20,
+                    }],
+                    unlocks: RawUnlocks {
+                        structures: vec!["large_storage".to_string()],
+                        recipes: Vec::new(),
+                        terraforming: Vec::new(),
+                    },
+                },
+            ),
+            (
+                "wetland_engineering".to_string(),
+                RawTechnologyData {
+                    prerequisites: vec![
+                        RawPrerequisite::Technology("bulk_storage".to_string()),
+                        RawPrerequisite::CompleteRecipe {
+                            recipe: "acacia_leaf_production".to_string(),
+                            count: 10,
+                        },
+                        RawPrerequisite::DomesticateSpecies(RawOrganismId::unit("salt_shell")),
+                    ],
+                    unlocks: RawUnlocks {
+                        structures: Vec::new(),
+                        recipes: vec!["tide_weed_production".to_string()],
+                        terraforming: vec![
+                            RawTerraformingTool::Raise,
+                            RawTerraformingTool::Change("swampy".to_string()),
+                        ],
+                    },
+                },
+            ),
+        ]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_technology_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawTechnologyManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_technology_manifest, deserialized);
+}
+
+#[test]
+fn can_serialize_disease_manifest() {
+    // Create a new raw disease manifest
+    let raw_disease_manifest = RawDiseaseManifest {
+        diseases: HashMap::from_iter(vec![
+            (
+                "leuco_blight".to_string(),
+                RawDiseaseData {
+                    hosts: vec![RawOrganismId::structure("leuco")],
+                    transmission_rate: 0.3,
+                    outbreak_rate: 0.01,
+                    recovery_rate: 0.2,
+                    immunity_duration: 5.0,
+                    effects: RawDiseaseEffects {
+                        work_speed: Some(0.5),
+                        ..Default::default()
+                    },
+                },
+            ),
+            (
+                "shell_mites".to_string(),
+                RawDiseaseData {
+                    hosts: vec![
+                        RawOrganismId::unit("basket_crab"),
+                        RawOrganismId::unit("salt_shell"),
+                    ],
+                    transmission_rate: 0.5,
+                    outbreak_rate: 0.005,
+                    recovery_rate: 0.25,
+                    immunity_duration: 3.0,
+                    effects: RawDiseaseEffects {
+                        energy_drain: Some(0.5),
+                        work_speed: None,
+                        lifespan: Some(0.75),
+                    },
+                },
+            ),
+        ]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_disease_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawDiseaseManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_disease_manifest, deserialized);
+}
+
+#[test]
+fn can_serialize_need_manifest() {
+    // Create a new raw need manifest
+    let raw_need_manifest = RawNeedManifest {
+        needs: HashMap::from_iter(vec![
+            (
+                "thirst".to_string(),
+                RawNeedData {
+                    satisfier: RawNeedSatisfier::Item("water".to_string()),
+                    decay_per_second: 0.002,
+                    warning_threshold: 0.3,
+                    satiation_threshold: 0.9,
+                    restoration: 0.5,
+                },
+            ),
+            (
+                "rest".to_string(),
+                RawNeedData {
+                    satisfier: RawNeedSatisfier::Structure("tide_weed".to_string()),
+                    decay_per_second: 0.001,
+                    warning_threshold: 0.2,
+                    satiation_threshold: 0.8,
+                    restoration: 0.25,
+                },
+            ),
+            (
+                "company".to_string(),
+                RawNeedData {
+                    satisfier: RawNeedSatisfier::Condition(NeedCondition::Company),
+                    decay_per_second: 0.003,
+                    warning_threshold: 0.25,
+                    satiation_threshold: 0.75,
+                    restoration: 0.02,
+                },
+            ),
+        ]),
+    };
+
+    // Serialize it
+    let serialized = serde_json::to_string(&raw_need_manifest).unwrap();
+    println!("{}", &serialized);
+
+    // Deserialize it
+    let deserialized: RawNeedManifest = serde_json::from_str(&serialized).unwrap();
+
+    // Check that the deserialized version is the same as the original
+    assert_eq!(raw_need_manifest, deserialized);
+}